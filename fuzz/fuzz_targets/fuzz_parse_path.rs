@@ -0,0 +1,20 @@
+//! Feeds arbitrary input to the parser for path data.
+//!
+//! Run with `cargo fuzz run fuzz_parse_path` from the `fuzz` directory.  The
+//! seed corpus in `corpus/fuzz_parse_path` has the path data from the test
+//! fixtures, plus some degenerate cases.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use rsvg_internals::parse_and_serialize_path;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(path_str) = std::str::from_utf8(data) {
+        if let Some(serialized) = parse_and_serialize_path(path_str) {
+            // Non-finite coordinates don't serialize to valid path data, so
+            // we can only check that parsing the result doesn't panic.
+            let _ = parse_and_serialize_path(&serialized);
+        }
+    }
+});
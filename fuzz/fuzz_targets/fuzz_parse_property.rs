@@ -0,0 +1,19 @@
+//! Feeds arbitrary input to the parser for every CSS property.
+//!
+//! Run with `cargo fuzz run fuzz_parse_property` from the `fuzz` directory.  The
+//! seed corpus in `corpus/fuzz_parse_property` has the values from the property
+//! tests and from the `style` attributes in the test fixtures.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use rsvg_internals::{parse_and_serialize_property, PROPERTY_NAMES};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(value) = std::str::from_utf8(data) {
+        for name in PROPERTY_NAMES {
+            // Valid values get serialized too, so that code is exercised as well.
+            let _ = parse_and_serialize_property(name, value);
+        }
+    }
+});
@@ -593,13 +593,14 @@ impl CHandle {
 
         match Url::parse(&url) {
             Ok(u) => {
-                rsvg_log!("setting base_uri to \"{}\"", u.as_str());
+                rsvg_log!(Debug, "setting base_uri to \"{}\"", u.as_str());
                 let mut inner = self.inner.borrow_mut();
                 inner.base_url.set(u);
             }
 
             Err(e) => {
                 rsvg_log!(
+                    Warn,
                     "not setting base_uri to \"{}\" since it is invalid: {}",
                     url,
                     e
@@ -1333,7 +1334,7 @@ pub unsafe extern "C" fn rsvg_rust_handle_render_cairo_sub(
         Ok(()) => true.to_glib(),
 
         Err(e) => {
-            rsvg_log!("could not render: {}", e);
+            rsvg_log!(Error, "could not render: {}", e);
             false.to_glib()
         }
     }
@@ -1356,7 +1357,7 @@ pub unsafe extern "C" fn rsvg_rust_handle_get_pixbuf_sub(
     match rhandle.get_pixbuf_sub(id.as_ref().map(String::as_str)) {
         Ok(pixbuf) => pixbuf.to_glib_full(),
         Err(e) => {
-            rsvg_log!("could not render: {}", e);
+            rsvg_log!(Error, "could not render: {}", e);
             ptr::null_mut()
         }
     }
@@ -1394,7 +1395,7 @@ pub unsafe extern "C" fn rsvg_rust_handle_get_dimensions_sub(
         }
 
         Err(e) => {
-            rsvg_log!("could not get dimensions: {}", e);
+            rsvg_log!(Error, "could not get dimensions: {}", e);
             *dimension_data = RsvgDimensionData::empty();
             false.to_glib()
         }
@@ -1430,7 +1431,7 @@ pub unsafe extern "C" fn rsvg_rust_handle_get_position_sub(
             p.x = 0;
             p.y = 0;
 
-            rsvg_log!("could not get position: {}", e);
+            rsvg_log!(Error, "could not get position: {}", e);
             false.to_glib()
         }
     }
@@ -1923,7 +1924,7 @@ pub(crate) fn set_gerror(err: *mut *mut glib_sys::GError, code: u32, msg: &str)
         //
         // See https://gitlab.gnome.org/GNOME/gtk/issues/2294 for an example of code that
         // passed a NULL GError and so we had no easy way to see what was wrong.
-        rsvg_log!("{}", msg);
+        rsvg_log!(Error, "{}", msg);
 
         glib_sys::g_set_error_literal(
             err,
@@ -1431,6 +1431,10 @@ rsvg_handle_get_geometry_for_element (RsvgHandle     *handle,
  * The `element_viewport` gives the position and size at which the named element will
  * be rendered.  FIXME: mention proportional scaling.
  *
+ * A <literal>symbol</literal> element gets rendered as if it were referenced
+ * by a <literal>use</literal> whose position and size are those of the
+ * @element_viewport.
+ *
  * API ordering: This function must be called on a fully-loaded @handle.  See
  * the section <ulink url="#API-ordering">API ordering</ulink> for details.
  *
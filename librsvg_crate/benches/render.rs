@@ -0,0 +1,272 @@
+#[macro_use]
+extern crate criterion;
+use criterion::{black_box, Criterion, ParameterizedBenchmark, Throughput};
+
+use librsvg::{CairoRenderer, Loader, SvgHandle};
+use std::io::Cursor;
+
+const SURFACE_SIDE: i32 = 512;
+
+fn load(svg: &str) -> SvgHandle {
+    Loader::new()
+        .read(Cursor::new(svg.to_string().into_bytes()), None)
+        .unwrap()
+}
+
+fn render(handle: &SvgHandle) {
+    let surface =
+        cairo::ImageSurface::create(cairo::Format::ARgb32, SURFACE_SIDE, SURFACE_SIDE).unwrap();
+    let cr = cairo::Context::new(&surface);
+
+    CairoRenderer::new(handle)
+        .render_document(
+            &cr,
+            &cairo::Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: f64::from(SURFACE_SIDE),
+                height: f64::from(SURFACE_SIDE),
+            },
+        )
+        .unwrap();
+}
+
+fn document(body: &str) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{side}" height="{side}">{body}</svg>"#,
+        side = SURFACE_SIDE,
+        body = body
+    )
+}
+
+/// Coordinates of the cell `i` in a grid of `n` cells that covers the surface.
+fn grid_cell(i: u32, n: u32) -> (f64, f64, f64) {
+    let cols = (f64::from(n).sqrt().ceil() as u32).max(1);
+    let size = f64::from(SURFACE_SIDE) / f64::from(cols);
+
+    (f64::from(i % cols) * size, f64::from(i / cols) * size, size)
+}
+
+fn shapes_svg(n: u32) -> String {
+    let body: String = (0..n)
+        .map(|i| {
+            let (x, y, size) = grid_cell(i, n);
+
+            if i % 2 == 0 {
+                format!(
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="blue" stroke="black"/>"#,
+                    x,
+                    y,
+                    size,
+                    size / 2.0
+                )
+            } else {
+                format!(
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="lime" stroke="black"/>"#,
+                    x + size / 2.0,
+                    y + size / 2.0,
+                    size / 2.0
+                )
+            }
+        })
+        .collect();
+
+    document(&body)
+}
+
+fn filter_chain_svg(n: u32) -> String {
+    let primitives: String = (0..n)
+        .map(|i| match i % 4 {
+            0 => r#"<feGaussianBlur stdDeviation="2"/>"#,
+            1 => r#"<feOffset dx="3" dy="3"/>"#,
+            2 => r#"<feColorMatrix type="hueRotate" values="45"/>"#,
+            _ => r#"<feComposite in2="SourceGraphic" operator="over"/>"#,
+        })
+        .collect();
+
+    document(&format!(
+        r#"<filter id="filter">{}</filter>
+           <rect x="64" y="64" width="384" height="384" fill="blue" filter="url(#filter)"/>"#,
+        primitives
+    ))
+}
+
+// A grid of cells filled with linear and radial gradients that have many
+// stops, and with a mesh gradient of 2x2 patches.
+fn gradient_grid_svg(n: u32) -> String {
+    let stops: String = (0..16)
+        .map(|i| {
+            format!(
+                r#"<stop offset="{}" stop-color="rgb({}, 0, {})"/>"#,
+                f64::from(i) / 15.0,
+                i * 16,
+                255 - i * 16
+            )
+        })
+        .collect();
+
+    let cells: String = (0..n)
+        .map(|i| {
+            let (x, y, size) = grid_cell(i, n);
+            let gradient = match i % 3 {
+                0 => "linear",
+                1 => "radial",
+                _ => "mesh",
+            };
+
+            format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="url(#{})"/>"#,
+                x, y, size, size, gradient
+            )
+        })
+        .collect();
+
+    document(&format!(
+        r#"<linearGradient id="linear">{stops}</linearGradient>
+           <radialGradient id="radial">{stops}</radialGradient>
+           <meshgradient id="mesh" x="0" y="0">
+             <meshrow>
+               <meshpatch>
+                 <stop path="l 0.5,0" stop-color="black"/>
+                 <stop path="l 0,0.5" stop-color="red"/>
+                 <stop path="l -0.5,0" stop-color="blue"/>
+                 <stop path="l 0,-0.5" stop-color="lime"/>
+               </meshpatch>
+               <meshpatch>
+                 <stop path="c 0.25,-0.1 0.25,0.1 0.5,0"/>
+                 <stop path="l 0,0.5" stop-color="yellow"/>
+                 <stop path="l -0.5,0" stop-color="magenta"/>
+               </meshpatch>
+             </meshrow>
+             <meshrow>
+               <meshpatch>
+                 <stop path="l 0,0.5"/>
+                 <stop path="l -0.5,0" stop-color="cyan"/>
+                 <stop path="l 0,-0.5" stop-color="white"/>
+               </meshpatch>
+               <meshpatch>
+                 <stop path="l 0,0.5"/>
+                 <stop path="l -0.5,0" stop-color="gray"/>
+               </meshpatch>
+             </meshrow>
+           </meshgradient>
+           {cells}"#,
+        stops = stops,
+        cells = cells
+    ))
+}
+
+fn text_svg(n: u32) -> String {
+    let body: String = (0..n)
+        .map(|i| {
+            let (x, y, size) = grid_cell(i, n);
+
+            format!(
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}">Hello, <tspan font-weight="bold">world</tspan>!</text>"#,
+                x,
+                y + size,
+                size / 4.0
+            )
+        })
+        .collect();
+
+    document(&body)
+}
+
+fn long_path_svg(n: u32) -> String {
+    let mut d = String::from("M 0 0");
+
+    for i in 0..n {
+        let x = f64::from(i % 512);
+        let y = f64::from((i * 7) % 512);
+
+        d.push_str(&match i % 4 {
+            0 => format!(" L {} {}", x, y),
+            1 => format!(" C {} {} {} {} {} {}", x, 0, 0, y, x, y),
+            2 => format!(" a 10 20 30 1 0 {} {}", -x / 10.0, y / 10.0),
+            _ => format!(" q {} {} {} {}", x / 2.0, y / 2.0, x, y),
+        });
+    }
+
+    document(&format!(r#"<path d="{}"/>"#, d))
+}
+
+// The filter region of the flood is a square of the given side in the corner
+// of the document; the output of the flood only needs to cover that region.
+fn flood_svg(side: u32) -> String {
+    document(&format!(
+        r#"<filter id="flood" filterUnits="userSpaceOnUse" x="0" y="0" width="{side}" height="{side}">
+             <feFlood flood-color="red" flood-opacity="0.5"/>
+           </filter>
+           <rect width="{side}" height="{side}" filter="url(#flood)"/>"#,
+        side = side
+    ))
+}
+
+fn bench_render(c: &mut Criterion, name: &str, make_svg: fn(u32) -> String, sizes: Vec<u32>) {
+    c.bench(
+        name,
+        ParameterizedBenchmark::new(
+            name,
+            move |b, &n| {
+                let handle = load(&make_svg(n));
+
+                b.iter(|| render(black_box(&handle)))
+            },
+            sizes,
+        )
+        .throughput(|&n| Throughput::Elements(n)),
+    );
+}
+
+fn bench_shapes(c: &mut Criterion) {
+    bench_render(c, "render shapes", shapes_svg, vec![16, 256, 4096]);
+}
+
+fn bench_filter_chain(c: &mut Criterion) {
+    bench_render(c, "render filter chain", filter_chain_svg, vec![1, 4, 16]);
+}
+
+fn bench_gradients(c: &mut Criterion) {
+    bench_render(
+        c,
+        "render gradients",
+        gradient_grid_svg,
+        vec![16, 256, 1024],
+    );
+}
+
+fn bench_flood(c: &mut Criterion) {
+    bench_render(c, "render flood", flood_svg, vec![64, 512]);
+}
+
+fn bench_text(c: &mut Criterion) {
+    bench_render(c, "render text", text_svg, vec![1, 16, 256]);
+}
+
+fn bench_path_parsing(c: &mut Criterion) {
+    c.bench(
+        "load long path",
+        ParameterizedBenchmark::new(
+            "load long path",
+            |b, &n| {
+                let svg = long_path_svg(n);
+
+                b.iter(|| load(black_box(&svg)))
+            },
+            vec![100, 1000, 10000],
+        )
+        .throughput(|&n| Throughput::Bytes(long_path_svg(n).len() as u32)),
+    );
+}
+
+criterion_group!(
+    benches,
+    bench_shapes,
+    bench_filter_chain,
+    bench_gradients,
+    bench_flood,
+    bench_text,
+    bench_path_parsing
+);
+criterion_main!(benches);
@@ -153,15 +153,20 @@
 use glib::prelude::*;
 use url::Url;
 
+use std::cell::RefCell;
+use std::io::Read;
 use std::path::Path;
+use std::rc::Rc;
+use std::sync::{Arc, Weak};
 
 use gio::{Cancellable, FileExt};
 
 use rsvg_internals::{Dpi, Handle, LoadOptions};
 
 pub use rsvg_internals::{
-    DefsLookupErrorKind, HrefError, Length as InternalLength, LengthUnit, LoadingError,
-    RenderingError, RsvgLength as Length,
+    AriaAttributes, DefsLookupErrorKind, Diagnostic, HrefError, Length as InternalLength,
+    LengthUnit, LinkTarget, LoadingError, RenderingError, RsvgLength as Length, SerializeError,
+    Severity, SourceLocation, SvgElementType,
 };
 
 /// Builder for loading an [`SvgHandle`][SvgHandle].
@@ -177,6 +182,8 @@ pub use rsvg_internals::{
 pub struct Loader {
     unlimited_size: bool,
     keep_image_data: bool,
+    normalize_unicode: bool,
+    keep_attributes: bool,
 }
 
 impl Loader {
@@ -190,6 +197,14 @@ impl Loader {
     /// surfaces that support including image data in compressed
     /// formats, like PDF.
     ///
+    /// * [`normalize_unicode`](#method.without_unicode_normalization) defaults to
+    /// `true`, so that text content gets normalized to Unicode NFC.
+    ///
+    /// * [`keep_attributes`](#method.keep_attributes) defaults to `false`.  You only
+    /// need this to serialize the document with [`SvgHandle::to_svg_string`].
+    ///
+    /// [`SvgHandle::to_svg_string`]: struct.SvgHandle.html#method.to_svg_string
+    ///
     /// # Example:
     ///
     /// ```
@@ -203,6 +218,8 @@ impl Loader {
         Loader {
             unlimited_size: false,
             keep_image_data: false,
+            normalize_unicode: true,
+            keep_attributes: false,
         }
     }
 
@@ -266,6 +283,52 @@ impl Loader {
         self
     }
 
+    /// Keeps the character data of text elements in its original Unicode normalization form.
+    ///
+    /// By default, librsvg normalizes text content to Unicode NFC, so that text
+    /// gets the same glyphs no matter whether it was written with precomposed
+    /// characters or with combining marks.  Call this to turn that off.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use librsvg;
+    ///
+    /// let svg_handle = librsvg::Loader::new()
+    ///     .without_unicode_normalization()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    /// ```
+    pub fn without_unicode_normalization(mut self) -> Self {
+        self.normalize_unicode = false;
+        self
+    }
+
+    /// Keeps a copy of the attributes of each element as they were in the XML.
+    ///
+    /// Normally, elements only keep the values that were parsed from their
+    /// attributes.  Call this if you need to write the document back out with
+    /// [`SvgHandle::to_svg_string`].
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use librsvg;
+    ///
+    /// let svg_handle = librsvg::Loader::new()
+    ///     .keep_attributes()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// let svg_xml = svg_handle.to_svg_string().unwrap();
+    /// ```
+    ///
+    /// [`SvgHandle::to_svg_string`]: struct.SvgHandle.html#method.to_svg_string
+    pub fn keep_attributes(mut self) -> Self {
+        self.keep_attributes = true;
+        self
+    }
+
     /// Reads an SVG document from `path`.
     ///
     /// # Example:
@@ -339,14 +402,98 @@ impl Loader {
             None
         };
 
+        self.load(stream.as_ref(), base_url, cancellable.map(|c| c.as_ref()))
+    }
+
+    /// Reads an SVG document from any implementor of `std::io::Read`.
+    ///
+    /// This is useful if your data does not come from a file or a `gio::InputStream`,
+    /// for example, if it is in memory or comes from a network library.  The data is
+    /// parsed as it gets read, without reading all of it into memory first; for this,
+    /// the `reader` gets wrapped in a `gio::InputStream`, which is why it must be
+    /// `Send` and `'static`.
+    ///
+    /// The `base_url`, if it is not `None`, is used to resolve relative URLs in the
+    /// SVG document, for example, those of raster images.
+    ///
+    /// I/O errors from the `reader` are returned as `LoadingError::Glib` values,
+    /// just like the errors from the other loading functions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let file = std::fs::File::open("example.svg").unwrap();
+    ///
+    /// let svg_handle = librsvg::Loader::new()
+    ///     .read(file, None)
+    ///     .unwrap();
+    /// ```
+    pub fn read<R: Read + Send + 'static>(
+        self,
+        reader: R,
+        base_url: Option<&str>,
+    ) -> Result<SvgHandle, LoadingError> {
+        let base_url = parse_base_url(base_url)?;
+        let stream = gio::ReadInputStream::new(reader);
+
+        self.load(stream.upcast_ref(), base_url, None)
+    }
+
+    /// Reads SVG data from any implementor of `std::io::Read`, to be loaded
+    /// independently in several threads.
+    ///
+    /// An [`SvgHandle`] cannot be sent to another thread; see its documentation
+    /// for the reasons.  Instead, this returns a [`SharedSvgData`], which holds
+    /// the data and the loading options of this `Loader`, and which can be
+    /// cloned and sent to other threads.  Each thread then parses the data into
+    /// its own `SvgHandle`.
+    ///
+    /// The data is not parsed here, so XML errors are only reported when the
+    /// `SharedSvgData` is used.
+    ///
+    /// [`SvgHandle`]: struct.SvgHandle.html
+    /// [`SharedSvgData`]: struct.SharedSvgData.html
+    pub fn read_shared<R: Read>(
+        self,
+        reader: R,
+        base_url: Option<&str>,
+    ) -> Result<SharedSvgData, LoadingError> {
+        let base_url = parse_base_url(base_url)?;
+        let data = read_to_vec(reader)?;
+
+        Ok(SharedSvgData(Arc::new(SharedSvgDataInner {
+            data,
+            base_url,
+            unlimited_size: self.unlimited_size,
+            keep_image_data: self.keep_image_data,
+            normalize_unicode: self.normalize_unicode,
+            keep_attributes: self.keep_attributes,
+        })))
+    }
+
+    fn read_bytes(self, data: &[u8], base_url: Option<Url>) -> Result<SvgHandle, LoadingError> {
+        let bytes = glib::Bytes::from(data);
+        let stream = gio::MemoryInputStream::new_from_bytes(&bytes);
+
+        self.load(stream.upcast_ref(), base_url, None)
+    }
+
+    fn load(
+        self,
+        stream: &gio::InputStream,
+        base_url: Option<Url>,
+        cancellable: Option<&Cancellable>,
+    ) -> Result<SvgHandle, LoadingError> {
         let load_options = LoadOptions::new(base_url)
             .with_unlimited_size(self.unlimited_size)
-            .keep_image_data(self.keep_image_data);
+            .keep_image_data(self.keep_image_data)
+            .normalize_unicode(self.normalize_unicode)
+            .keep_attributes(self.keep_attributes);
 
         Ok(SvgHandle(Handle::from_stream(
             &load_options,
-            stream.as_ref(),
-            cancellable.map(|c| c.as_ref()),
+            stream,
+            cancellable,
         )?))
     }
 }
@@ -355,10 +502,145 @@ fn url_from_file(file: &gio::File) -> Result<Url, LoadingError> {
     Ok(Url::parse(&file.get_uri()).map_err(|_| LoadingError::BadUrl)?)
 }
 
+fn parse_base_url(base_url: Option<&str>) -> Result<Option<Url>, LoadingError> {
+    if let Some(base_url) = base_url {
+        Ok(Some(
+            Url::parse(base_url).map_err(|_| LoadingError::BadUrl)?,
+        ))
+    } else {
+        Ok(None)
+    }
+}
+
+fn read_to_vec<R: Read>(mut reader: R) -> Result<Vec<u8>, LoadingError> {
+    let mut data = Vec::new();
+
+    reader.read_to_end(&mut data).map_err(|e| {
+        LoadingError::Glib(glib::Error::new(gio::IOErrorEnum::Failed, &e.to_string()))
+    })?;
+
+    Ok(data)
+}
+
+/// SVG data that can be shared among threads.
+///
+/// Since an [`SvgHandle`] cannot be sent to another thread, use this to
+/// render the same document from several threads, for example, from a
+/// thread pool.  You can create it with [`Loader::read_shared`].
+///
+/// Cloning a `SharedSvgData` is cheap, as the data is reference-counted.
+///
+/// # Example
+///
+/// ```
+/// use std::thread;
+///
+/// let file = std::fs::File::open("example.svg").unwrap();
+///
+/// let data = librsvg::Loader::new()
+///     .read_shared(file, None)
+///     .unwrap();
+///
+/// let threads: Vec<_> = (0..2)
+///     .map(|_| {
+///         let data = data.clone();
+///
+///         thread::spawn(move || {
+///             data.with_handle(|svg_handle| {
+///                 librsvg::CairoRenderer::new(svg_handle).intrinsic_dimensions()
+///             })
+///             .unwrap()
+///         })
+///     })
+///     .collect();
+///
+/// for t in threads {
+///     t.join().unwrap();
+/// }
+/// ```
+///
+/// [`SvgHandle`]: struct.SvgHandle.html
+/// [`Loader::read_shared`]: struct.Loader.html#method.read_shared
+#[derive(Clone)]
+pub struct SharedSvgData(Arc<SharedSvgDataInner>);
+
+struct SharedSvgDataInner {
+    data: Vec<u8>,
+    base_url: Option<Url>,
+    unlimited_size: bool,
+    keep_image_data: bool,
+    normalize_unicode: bool,
+    keep_attributes: bool,
+}
+
+thread_local! {
+    // Handles loaded by SharedSvgData::with_handle() in the current thread.  Entries
+    // whose SharedSvgData has been dropped are removed the next time it gets called.
+    static THREAD_HANDLES: RefCell<Vec<(Weak<SharedSvgDataInner>, Rc<SvgHandle>)>> =
+        RefCell::new(Vec::new());
+}
+
+impl SharedSvgData {
+    /// Loads a new [`SvgHandle`] from the data.
+    ///
+    /// [`SvgHandle`]: struct.SvgHandle.html
+    pub fn load(&self) -> Result<SvgHandle, LoadingError> {
+        let inner = &self.0;
+
+        let loader = Loader {
+            unlimited_size: inner.unlimited_size,
+            keep_image_data: inner.keep_image_data,
+            normalize_unicode: inner.normalize_unicode,
+            keep_attributes: inner.keep_attributes,
+        };
+
+        loader.read_bytes(&inner.data, inner.base_url.clone())
+    }
+
+    /// Calls `f` with an [`SvgHandle`] for the data that belongs to the current thread.
+    ///
+    /// The handle is loaded the first time this is called in each thread, and is
+    /// reused afterwards, so that a thread pool only parses the data once per thread.
+    ///
+    /// [`SvgHandle`]: struct.SvgHandle.html
+    pub fn with_handle<T, F: FnOnce(&SvgHandle) -> T>(&self, f: F) -> Result<T, LoadingError> {
+        let handle = THREAD_HANDLES.with(|handles| {
+            let mut handles = handles.borrow_mut();
+
+            handles.retain(|(data, _)| data.upgrade().is_some());
+
+            let existing = handles
+                .iter()
+                .find(|(data, _)| data.upgrade().map_or(false, |d| Arc::ptr_eq(&d, &self.0)))
+                .map(|(_, handle)| handle.clone());
+
+            if let Some(handle) = existing {
+                Ok(handle)
+            } else {
+                let handle = Rc::new(self.load()?);
+                handles.push((Arc::downgrade(&self.0), handle.clone()));
+                Ok(handle)
+            }
+        })?;
+
+        // The thread's handles are not borrowed here, so f() may use other SharedSvgData.
+        Ok(f(&handle))
+    }
+}
+
 /// Handle used to hold SVG data in memory.
 ///
 /// You can create this from one of the `read` methods in
 /// [`Loader`](#struct.Loader.html).
+///
+/// An `SvgHandle` is neither `Send` nor `Sync`, so it cannot be shared among
+/// threads.  The document tree uses reference-counted nodes with interior mutability,
+/// and the raster images it references are kept as Cairo surfaces, which are not
+/// thread-safe either; making all of that thread-safe would add locking to every
+/// node access during rendering.  If you need to render the same document from
+/// several threads, use a [`SharedSvgData`] to load a separate handle in each thread.
+///
+/// [`SharedSvgData`]: struct.SharedSvgData.html
 pub struct SvgHandle(Handle);
 
 impl SvgHandle {
@@ -373,6 +655,85 @@ impl SvgHandle {
         self.0.has_sub(id)
     }
 
+    /// Returns the text of the `<title>` element of the document, if there is one.
+    ///
+    /// This is the `<title>` that is a direct child of the toplevel `<svg>` element.
+    /// Whitespace in the text is collapsed to single spaces.
+    pub fn title(&self) -> Option<String> {
+        // Looking up the toplevel element cannot fail
+        self.0.get_title(None).unwrap_or(None)
+    }
+
+    /// Returns the text of the `<desc>` element of the document, if there is one.
+    ///
+    /// This is the `<desc>` that is a direct child of the toplevel `<svg>` element.
+    /// Whitespace in the text is collapsed to single spaces.
+    pub fn desc(&self) -> Option<String> {
+        self.0.get_desc(None).unwrap_or(None)
+    }
+
+    /// Returns the text of the `<title>` child of the element with the specified `id`.
+    ///
+    /// The `id` must be a plain fragment identifier like `#foo`, as in
+    /// [`has_element_with_id`](#method.has_element_with_id).
+    pub fn element_title(&self, id: &str) -> Result<Option<String>, RenderingError> {
+        self.0.get_title(Some(id))
+    }
+
+    /// Returns the text of the `<desc>` child of the element with the specified `id`.
+    ///
+    /// The `id` must be a plain fragment identifier like `#foo`, as in
+    /// [`has_element_with_id`](#method.has_element_with_id).
+    pub fn element_desc(&self, id: &str) -> Result<Option<String>, RenderingError> {
+        self.0.get_desc(Some(id))
+    }
+
+    /// Returns the type of the element with the specified `id`.
+    ///
+    /// As in [`has_element_with_id`](#method.has_element_with_id), the `id` must be
+    /// a plain fragment identifier like `#foo`.  Returns `Ok(None)` if there is
+    /// no such element.
+    pub fn element_type(&self, id: &str) -> Result<Option<SvgElementType>, RenderingError> {
+        self.0.get_element_type(id)
+    }
+
+    /// Returns the ARIA attributes of the element with the specified `id`.
+    ///
+    /// As in [`has_element_with_id`](#method.has_element_with_id), the `id` must be
+    /// a plain fragment identifier like `#foo`.  Returns `Ok(None)` if there is
+    /// no such element.
+    pub fn aria_attributes(&self, id: &str) -> Result<Option<AriaAttributes>, RenderingError> {
+        self.0.get_aria_attributes(id)
+    }
+
+    /// Returns the ids of the document's elements in keyboard navigation order.
+    ///
+    /// Elements with a `tabindex` come first, in ascending order of its value and
+    /// with ties in document order.  They are followed by the `<a>` elements without
+    /// a `tabindex`, in document order.  Elements without an `id` are not included.
+    /// The ids are returned without a leading `#`.
+    pub fn tab_order(&self) -> Vec<String> {
+        self.0.get_tab_order()
+    }
+
+    /// Serializes the document back to SVG XML.
+    ///
+    /// The handle must have been loaded with [`Loader::keep_attributes`]; otherwise
+    /// this returns [`SerializeError::AttributesNotKept`].
+    ///
+    /// Elements are written out with the attributes they had in the original
+    /// document, so that loading the result renders the same as this handle.
+    /// Stylesheets that were not in a `<style>` element, like the ones from
+    /// `xml-stylesheet` processing instructions or from [`set_stylesheet`],
+    /// are not included.
+    ///
+    /// [`Loader::keep_attributes`]: struct.Loader.html#method.keep_attributes
+    /// [`SerializeError::AttributesNotKept`]: enum.SerializeError.html#variant.AttributesNotKept
+    /// [`set_stylesheet`]: #method.set_stylesheet
+    pub fn to_svg_string(&self) -> Result<String, SerializeError> {
+        self.0.to_svg_string()
+    }
+
     /// Sets a CSS stylesheet to use for an SVG document.
     ///
     /// During the CSS cascade, the specified stylesheet will be used
@@ -384,6 +745,104 @@ impl SvgHandle {
     pub fn set_stylesheet(&mut self, css: &str) -> Result<(), LoadingError> {
         self.0.set_stylesheet(css)
     }
+
+    /// Overrides the style of the element with the specified `id`.
+    ///
+    /// The `declarations` are a list of CSS declarations like in a `style` attribute,
+    /// for example `fill: red; stroke-width: 2`.  They are applied with a "User"
+    /// [origin] after the element's own `style` attribute, so they take precedence over
+    /// the document's styles except for `!important` declarations.  Calling this again
+    /// for the same `id` replaces the previous override.
+    ///
+    /// This is useful to theme an SVG at rendering time without modifying its source.
+    /// Returns an error if any of the declarations is invalid; in that case the
+    /// previous override for the element is kept.
+    ///
+    /// [origin]: https://drafts.csswg.org/css-cascade-3/#cascading-origins
+    pub fn set_element_style_override(
+        &mut self,
+        id: &str,
+        declarations: &str,
+    ) -> Result<(), LoadingError> {
+        self.0.set_element_style_override(id, declarations)
+    }
+
+    /// Removes all the overrides set with [`set_element_style_override`].
+    ///
+    /// [`set_element_style_override`]: #method.set_element_style_override
+    pub fn clear_style_overrides(&mut self) {
+        self.0.clear_style_overrides()
+    }
+
+    /// Returns the non-fatal problems found while loading and rendering the SVG.
+    ///
+    /// Things like invalid property values are not errors for SVG: they are simply
+    /// ignored.  This function lets you find out about them.  Problems found while
+    /// loading come first, followed by the ones from each rendering call.
+    ///
+    /// The collected diagnostics are cleared, so calling this function again only
+    /// returns the problems found since the previous call.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        self.0.take_diagnostics()
+    }
+
+    /// Returns the natural size of the SVG document, as specified in its toplevel `<svg>`
+    /// element.
+    ///
+    /// Unlike [`CairoRenderer::intrinsic_dimensions`], this does not need a renderer,
+    /// since the lengths are not resolved to pixels.
+    ///
+    /// [`CairoRenderer::intrinsic_dimensions`]: struct.CairoRenderer.html#method.intrinsic_dimensions
+    pub fn natural_size(&self) -> NaturalSize {
+        let d = self.0.get_intrinsic_dimensions();
+
+        NaturalSize {
+            width: d.width.map(|l| NaturalDimension::from(Length::from(l))),
+            height: d.height.map(|l| NaturalDimension::from(Length::from(l))),
+            vbox: d.vbox.map(|v| cairo::Rectangle::from(v.0)),
+        }
+    }
+}
+
+/// One of the dimensions of the natural size of an SVG document, in CSS terms.
+///
+/// Note that a `width="100"` without units is in user units, which are reported
+/// as `LengthUnit::Px`, while `width="10cm"` is a physical length.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NaturalDimension {
+    /// A length with units, like `10cm` or `100px`.
+    Length(f64, LengthUnit),
+
+    /// A percentage of the size of the viewport; for example, `50%` is `Percentage(50.0)`.
+    Percentage(f64),
+}
+
+impl From<Length> for NaturalDimension {
+    fn from(l: Length) -> NaturalDimension {
+        match l.unit {
+            // Percentages are stored as fractions.
+            LengthUnit::Percent => NaturalDimension::Percentage(l.length * 100.0),
+            unit => NaturalDimension::Length(l.length, unit),
+        }
+    }
+}
+
+/// The natural size of an SVG document, from the `width`, `height`, and `viewBox`
+/// attributes of its toplevel `<svg>` element.
+///
+/// You can obtain this from [`SvgHandle::natural_size`].
+///
+/// [`SvgHandle::natural_size`]: struct.SvgHandle.html#method.natural_size
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NaturalSize {
+    /// `width` attribute of the `<svg>`, if present
+    pub width: Option<NaturalDimension>,
+
+    /// `height` attribute of the `<svg>`, if present
+    pub height: Option<NaturalDimension>,
+
+    /// `viewBox` attribute of the `<svg>`, if present
+    pub vbox: Option<cairo::Rectangle>,
 }
 
 /// Can render an `SvgHandle` to a Cairo context.
@@ -485,6 +944,77 @@ impl<'a> CairoRenderer<'a> {
         self.handle.0.render_document(cr, viewport, self.dpi, false)
     }
 
+    /// Renders the whole SVG document fitted to a viewport, one tile at a time
+    ///
+    /// This is useful for very large output sizes, where a single surface for the
+    /// whole image would not fit in memory.  The output image extends from (0, 0) to
+    /// the bottom-right corner of the `viewport`; it gets divided into tiles of
+    /// `tile_size` × `tile_size` pixels, and the tiles on the right and bottom edges
+    /// may be smaller than that.
+    ///
+    /// Each tile is rendered independently into its own `cairo::ImageSurface`, and
+    /// then `callback` gets called with the column and row of the tile, and the
+    /// surface with its pixels.  The tile at column `col` and row `row` starts at
+    /// pixel `(col * tile_size, row * tile_size)` of the output image.
+    ///
+    /// Filter effects that sample pixels beyond the bounds of a tile, like
+    /// blurs, may produce visible seams between tiles.
+    ///
+    /// Returns `RenderingError::Cairo` with `cairo::Status::InvalidSize` if
+    /// `tile_size` is not positive.
+    pub fn render_document_tiled<F>(
+        &self,
+        viewport: &cairo::Rectangle,
+        tile_size: i32,
+        mut callback: F,
+    ) -> Result<(), RenderingError>
+    where
+        F: FnMut(i32, i32, &cairo::ImageSurface),
+    {
+        if tile_size <= 0 {
+            return Err(RenderingError::Cairo(cairo::Status::InvalidSize));
+        }
+
+        let max_size = f64::from(std::i32::MAX);
+        let width = (viewport.x + viewport.width).ceil().max(0.0).min(max_size) as i32;
+        let height = (viewport.y + viewport.height).ceil().max(0.0).min(max_size) as i32;
+
+        // Rounds up without overflowing for sizes near i32::MAX
+        let num_tiles = |size: i32| {
+            if size > 0 {
+                (size - 1) / tile_size + 1
+            } else {
+                0
+            }
+        };
+
+        let cols = num_tiles(width);
+        let rows = num_tiles(height);
+
+        for row in 0..rows {
+            for col in 0..cols {
+                let x = col * tile_size;
+                let y = row * tile_size;
+
+                let tile = cairo::ImageSurface::create(
+                    cairo::Format::ARgb32,
+                    tile_size.min(width - x),
+                    tile_size.min(height - y),
+                )?;
+
+                {
+                    let cr = cairo::Context::new(&tile);
+                    cr.translate(-f64::from(x), -f64::from(y));
+                    self.render_document(&cr, viewport)?;
+                }
+
+                callback(col, row, &tile);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Computes the (ink_rect, logical_rect) of an SVG element, as if
     /// the SVG were rendered to a specific viewport.
     ///
@@ -520,6 +1050,17 @@ impl<'a> CairoRenderer<'a> {
             .map(|(i, l)| (i.into(), l.into()))
     }
 
+    /// Returns the hyperlinks from the `<a>` elements in the SVG, in document order.
+    ///
+    /// The bounding box of each link is computed as if the SVG were rendered to the
+    /// specified `viewport`, like in [`geometry_for_layer`], so that callers can
+    /// find out which link was clicked.
+    ///
+    /// [`geometry_for_layer`]: #method.geometry_for_layer
+    pub fn links(&self, viewport: &cairo::Rectangle) -> Result<Vec<LinkTarget>, RenderingError> {
+        self.handle.0.get_links(viewport, self.dpi, false)
+    }
+
     /// Renders a single SVG element in the same place as for a whole SVG document
     ///
     /// This is equivalent to `render_document`, but renders only a single element and its
@@ -603,6 +1144,9 @@ impl<'a> CairoRenderer<'a> {
     /// The `element_viewport` gives the position and size at which the named element will
     /// be rendered.  FIXME: mention proportional scaling.
     ///
+    /// A `<symbol>` element gets rendered as if it were referenced by a `<use>`
+    /// whose position and size are those of the `element_viewport`.
+    ///
     /// The `cr` must be in a `cairo::Status::Success` state, or this function
     /// will not render anything, and instead will return
     /// `RenderingError::Cairo` with the `cr`'s current error state.
@@ -1,10 +1,19 @@
 use cairo;
-use librsvg::{CairoRenderer, DefsLookupErrorKind, HrefError, RenderingError};
+use librsvg::{
+    AriaAttributes, CairoRenderer, DefsLookupErrorKind, HrefError, LinkTarget, Loader,
+    LoadingError, RenderingError, SerializeError, Severity, SvgElementType, SvgHandle,
+};
 
+use std::io::{self, Cursor, Read};
+use std::thread;
+
+use rsvg_internals::limits;
+use rsvg_internals::surface_utils::iterators::Pixels;
 use rsvg_internals::surface_utils::shared_surface::{SharedImageSurface, SurfaceType};
+use rsvg_internals::surface_utils::Pixel;
 
 mod utils;
-use self::utils::{compare_to_surface, load_svg};
+use self::utils::{compare_to_surface, fixture_dir, load_svg, render_document, SurfaceSize};
 
 #[test]
 fn has_element_with_id_works() {
@@ -41,6 +50,111 @@ fn has_element_with_id_works() {
     );
 }
 
+#[test]
+fn element_type_works() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     xmlns:foo="http://example.com/foo" id="root">
+  <defs id="defs">
+    <linearGradient id="gradient"/>
+    <filter id="filter">
+      <feGaussianBlur id="blur" stdDeviation="2"/>
+    </filter>
+  </defs>
+  <g id="group">
+    <rect id="rect" width="10" height="10"/>
+    <circle id="circle" r="5"/>
+    <path id="path" d="M 0 0 L 10 10"/>
+    <text id="text">hello</text>
+  </g>
+  <use id="use" xlink:href="#rect"/>
+  <title id="title">not supported</title>
+  <foo:bar id="foreign"/>
+</svg>
+"##,
+    );
+
+    let expected = [
+        ("#root", SvgElementType::Svg),
+        ("#defs", SvgElementType::Defs),
+        ("#gradient", SvgElementType::LinearGradient),
+        ("#filter", SvgElementType::Filter),
+        ("#blur", SvgElementType::FeGaussianBlur),
+        ("#group", SvgElementType::G),
+        ("#rect", SvgElementType::Rect),
+        ("#circle", SvgElementType::Circle),
+        ("#path", SvgElementType::Path),
+        ("#text", SvgElementType::Text),
+        ("#use", SvgElementType::Use),
+        ("#title", SvgElementType::Unknown),
+        ("#foreign", SvgElementType::Unknown),
+    ];
+
+    for &(id, element_type) in expected.iter() {
+        assert_eq!(svg.element_type(id).unwrap(), Some(element_type), "{}", id);
+    }
+
+    assert_eq!(svg.element_type("#nonexistent").unwrap(), None);
+
+    assert_eq!(
+        svg.element_type("not a fragment"),
+        Err(RenderingError::InvalidId(
+            DefsLookupErrorKind::CannotLookupExternalReferences
+        ))
+    );
+}
+
+#[test]
+fn title_and_desc_work() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg">
+  <title>My SVG</title>
+  <desc>
+    A drawing with
+    a rectangle
+  </desc>
+  <rect id="rect" width="10" height="10">
+    <title>A rectangle</title>
+  </rect>
+  <circle id="circle" r="5"/>
+</svg>
+"#,
+    );
+
+    assert_eq!(svg.title(), Some("My SVG".to_string()));
+    assert_eq!(svg.desc(), Some("A drawing with a rectangle".to_string()));
+
+    assert_eq!(
+        svg.element_title("#rect").unwrap(),
+        Some("A rectangle".to_string())
+    );
+    assert_eq!(svg.element_desc("#rect").unwrap(), None);
+    assert_eq!(svg.element_title("#circle").unwrap(), None);
+
+    assert_eq!(
+        svg.element_title("#nonexistent"),
+        Err(RenderingError::InvalidId(DefsLookupErrorKind::NotFound))
+    );
+}
+
+#[test]
+fn no_title_or_desc() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg">
+  <g>
+    <title>Not the document's title</title>
+  </g>
+</svg>
+"#,
+    );
+
+    assert_eq!(svg.title(), None);
+    assert_eq!(svg.desc(), None);
+}
+
 #[test]
 fn render_layer() {
     let svg = load_svg(
@@ -174,6 +288,218 @@ fn untransformed_element() {
     compare_to_surface(&output_surf, &reference_surf, "untransformed_element");
 }
 
+#[test]
+fn render_symbol_element() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="300" height="300">
+  <symbol id="sym" viewBox="0 0 10 10">
+    <rect x="0" y="0" width="5" height="10" fill="#00ff00"/>
+    <circle cx="7.5" cy="5" r="2.5" fill="#0000ff"/>
+  </symbol>
+  <use id="use" xlink:href="#sym" x="100" y="50" width="100" height="100"/>
+</svg>
+"##,
+    );
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let viewport = cairo::Rectangle {
+        x: 100.0,
+        y: 50.0,
+        width: 100.0,
+        height: 100.0,
+    };
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output);
+        renderer.render_element(&cr, Some("#sym"), &viewport)
+    };
+
+    let output_surf = res
+        .and_then(|_| Ok(SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap()))
+        .unwrap();
+
+    let reference = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&reference);
+        let document_viewport = cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 300.0,
+            height: 300.0,
+        };
+
+        renderer.render_layer(&cr, Some("#use"), &document_viewport)
+    };
+
+    let reference_surf = res
+        .and_then(|_| Ok(SharedImageSurface::wrap(reference, SurfaceType::SRgb).unwrap()))
+        .unwrap();
+
+    compare_to_surface(&output_surf, &reference_surf, "render_symbol_element");
+}
+
+#[test]
+fn render_document_in_tiles() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="150" height="100">
+  <rect x="10" y="10" width="100" height="50" fill="#00ff00" stroke="#000000" stroke-width="4"/>
+  <circle cx="100" cy="70" r="40" fill="#0000ff" fill-opacity="0.5"/>
+</svg>
+"##,
+    );
+
+    let viewport = cairo::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 150.0,
+        height: 100.0,
+    };
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 150, 100).unwrap();
+    let mut tiles = Vec::new();
+
+    {
+        let cr = cairo::Context::new(&output);
+
+        CairoRenderer::new(&svg)
+            .render_document_tiled(&viewport, 64, |col, row, tile| {
+                tiles.push((col, row, tile.get_width(), tile.get_height()));
+
+                cr.set_source_surface(tile, f64::from(col * 64), f64::from(row * 64));
+                cr.paint();
+            })
+            .unwrap();
+    }
+
+    assert_eq!(
+        tiles,
+        vec![
+            (0, 0, 64, 64),
+            (1, 0, 64, 64),
+            (2, 0, 22, 64),
+            (0, 1, 64, 36),
+            (1, 1, 64, 36),
+            (2, 1, 22, 36),
+        ]
+    );
+
+    let output_surf = SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap();
+
+    let reference_surf = render_document(&svg, SurfaceSize(150, 100), |_| (), viewport).unwrap();
+
+    compare_to_surface(&output_surf, &reference_surf, "render_document_in_tiles");
+}
+
+#[test]
+fn render_document_tiled_rejects_invalid_tile_size() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect width="100" height="100"/>
+</svg>
+"##,
+    );
+
+    let viewport = cairo::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 100.0,
+        height: 100.0,
+    };
+
+    let renderer = CairoRenderer::new(&svg);
+
+    for &tile_size in &[0, -64] {
+        let mut num_tiles = 0;
+
+        assert_eq!(
+            renderer.render_document_tiled(&viewport, tile_size, |_, _, _| num_tiles += 1),
+            Err(RenderingError::Cairo(cairo::Status::InvalidSize))
+        );
+        assert_eq!(num_tiles, 0);
+    }
+}
+
+#[test]
+fn stroke_ink_rect_encloses_caps_and_joins() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="300" height="300">
+  <g fill="none" stroke="#000000" stroke-width="16">
+    <path id="butt_miter" d="M 20 80 L 50 20 L 80 80" stroke-linecap="butt" stroke-linejoin="miter"/>
+    <path id="butt_round" d="M 120 80 L 150 20 L 180 80" stroke-linecap="butt" stroke-linejoin="round"/>
+    <path id="butt_bevel" d="M 220 80 L 250 20 L 280 80" stroke-linecap="butt" stroke-linejoin="bevel"/>
+    <path id="round_miter" d="M 20 180 L 50 120 L 80 180" stroke-linecap="round" stroke-linejoin="miter"/>
+    <path id="round_round" d="M 120 180 L 150 120 L 180 180" stroke-linecap="round" stroke-linejoin="round"/>
+    <path id="round_bevel" d="M 220 180 L 250 120 L 280 180" stroke-linecap="round" stroke-linejoin="bevel"/>
+    <path id="square_miter" d="M 20 280 L 50 220 L 80 280" stroke-linecap="square" stroke-linejoin="miter"/>
+    <path id="square_round" d="M 120 280 Q 150 200 180 280" stroke-linecap="square" stroke-linejoin="round"/>
+    <path id="square_bevel" d="M 220 280 L 250 220 L 280 280" stroke-linecap="square" stroke-linejoin="bevel"/>
+  </g>
+</svg>
+"##,
+    );
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let viewport = cairo::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 300.0,
+        height: 300.0,
+    };
+
+    for id in &[
+        "#butt_miter",
+        "#butt_round",
+        "#butt_bevel",
+        "#round_miter",
+        "#round_round",
+        "#round_bevel",
+        "#square_miter",
+        "#square_round",
+        "#square_bevel",
+    ] {
+        let (ink_r, _) = renderer.geometry_for_layer(Some(id), &viewport).unwrap();
+
+        let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+
+        {
+            let cr = cairo::Context::new(&output);
+            renderer.render_layer(&cr, Some(id), &viewport).unwrap();
+        }
+
+        let output_surf = SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap();
+
+        for (x, y, pixel) in Pixels::new(&output_surf) {
+            if pixel.a == 0 {
+                continue;
+            }
+
+            // The pixel's square must touch the ink rectangle
+            let (x, y) = (f64::from(x), f64::from(y));
+
+            assert!(
+                x + 1.0 > ink_r.x
+                    && x < ink_r.x + ink_r.width
+                    && y + 1.0 > ink_r.y
+                    && y < ink_r.y + ink_r.height,
+                "pixel ({}, {}) of {} is outside its ink rectangle {:?}",
+                x,
+                y,
+                id,
+                ink_r
+            );
+        }
+    }
+}
+
 #[test]
 fn set_stylesheet() {
     // This has a rectangle which we style from a user-supplied stylesheet.
@@ -222,3 +548,688 @@ fn set_stylesheet() {
 
     compare_to_surface(&output_surf, &reference_surf, "set_stylesheet");
 }
+
+#[test]
+fn element_style_override() {
+    let mut svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <style>
+    rect { fill: blue; }
+  </style>
+  <rect id="foo" x="0" y="0" width="50" height="100" style="fill: black;"/>
+  <rect id="bar" x="50" y="0" width="50" height="100"/>
+</svg>
+"##,
+    );
+
+    let render = |svg: &SvgHandle| {
+        let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+        {
+            let cr = cairo::Context::new(&output);
+            let viewport = cairo::Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 100.0,
+            };
+
+            CairoRenderer::new(svg)
+                .render_document(&cr, &viewport)
+                .unwrap();
+        }
+
+        let surf = SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap();
+        (surf.get_pixel(25, 50), surf.get_pixel(75, 50))
+    };
+
+    let (foo, bar) = render(&svg);
+    assert_eq!((foo.r, foo.g, foo.b), (0, 0, 0));
+    assert_eq!((bar.r, bar.g, bar.b), (0, 0, 255));
+
+    svg.set_element_style_override("foo", "fill: #00ff00")
+        .expect("should be valid declarations");
+
+    let (foo, bar) = render(&svg);
+    assert_eq!((foo.r, foo.g, foo.b), (0, 255, 0));
+    assert_eq!((bar.r, bar.g, bar.b), (0, 0, 255));
+
+    assert!(svg
+        .set_element_style_override("foo", "fill: #00ff00; stroke: furlong")
+        .is_err());
+
+    svg.clear_style_overrides();
+
+    let (foo, bar) = render(&svg);
+    assert_eq!((foo.r, foo.g, foo.b), (0, 0, 0));
+    assert_eq!((bar.r, bar.g, bar.b), (0, 0, 255));
+}
+
+#[test]
+fn circular_use_is_an_error() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="100" height="100">
+  <use id="loop" xlink:href="#loop"/>
+</svg>
+"##,
+    );
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output);
+        let viewport = cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        };
+
+        renderer.render_document(&cr, &viewport)
+    };
+
+    match res {
+        Err(RenderingError::CircularReference) => (),
+        _ => panic!("expected a circular reference error"),
+    }
+}
+
+fn render_result(svg: &SvgHandle) -> Result<(), RenderingError> {
+    let renderer = CairoRenderer::new(svg);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let cr = cairo::Context::new(&output);
+    let viewport = cairo::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 100.0,
+        height: 100.0,
+    };
+
+    renderer.render_document(&cr, &viewport)
+}
+
+/// Creates a document with `depth` nested `<use>` elements, counting the one
+/// outside the `<defs>`.
+fn use_chain(depth: usize) -> SvgHandle {
+    let mut defs = String::from(r#"<rect id="use0" width="10" height="10"/>"#);
+
+    for i in 1..depth {
+        defs.push_str(&format!(
+            r##"<use id="use{}" xlink:href="#use{}"/>"##,
+            i,
+            i - 1
+        ));
+    }
+
+    let svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="100" height="100">
+  <defs>{}</defs>
+  <use xlink:href="#use{}"/>
+</svg>
+"##,
+        defs,
+        depth - 1
+    );
+
+    load_svg(svg.as_bytes())
+}
+
+#[test]
+fn use_chain_at_the_nesting_limit_renders() {
+    let svg = use_chain(limits::MAX_REFERENCE_DEPTH);
+
+    assert!(render_result(&svg).is_ok());
+}
+
+#[test]
+fn use_chain_past_the_nesting_limit_is_an_error() {
+    let svg = use_chain(limits::MAX_REFERENCE_DEPTH + 1);
+
+    match render_result(&svg) {
+        Err(RenderingError::InstancingLimit) => (),
+        r => panic!("expected a nesting error, got {:?}", r),
+    }
+}
+
+#[test]
+fn gradient_chain_past_the_nesting_limit_is_an_error() {
+    // The first gradient has a chain of MAX_REFERENCE_DEPTH + 1 fallbacks.
+    let last = limits::MAX_REFERENCE_DEPTH + 1;
+
+    let mut gradients = String::new();
+
+    for i in 0..last {
+        gradients.push_str(&format!(
+            r##"<linearGradient id="gradient{}" xlink:href="#gradient{}"/>"##,
+            i,
+            i + 1
+        ));
+    }
+
+    let svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="100" height="100">
+  {}
+  <linearGradient id="gradient{}">
+    <stop offset="0" stop-color="black"/>
+  </linearGradient>
+  <rect width="10" height="10" fill="url(#gradient0)"/>
+</svg>
+"##,
+        gradients, last
+    );
+
+    match render_result(&load_svg(svg.as_bytes())) {
+        Err(RenderingError::InstancingLimit) => (),
+        r => panic!("expected a nesting error, got {:?}", r),
+    }
+}
+
+#[test]
+fn links_have_hrefs_and_bounding_boxes() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="100" height="100">
+  <a xlink:href="#details" xlink:title="Details">
+    <rect x="10" y="10" width="20" height="20"/>
+  </a>
+  <a href="https://example.com/" xlink:href="ignored.svg" target="_blank">
+    <rect x="50" y="40" width="30" height="40"/>
+  </a>
+  <a>
+    <rect x="0" y="0" width="5" height="5"/>
+  </a>
+  <rect id="details" x="0" y="90" width="10" height="10"/>
+</svg>
+"##,
+    );
+
+    let renderer = CairoRenderer::new(&svg);
+    let links = renderer
+        .links(&cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        })
+        .unwrap();
+
+    assert_eq!(
+        links,
+        vec![
+            LinkTarget {
+                element_id: Some("details".to_string()),
+                href: "#details".to_string(),
+                target: None,
+                title: Some("Details".to_string()),
+                bounding_box: cairo::Rectangle {
+                    x: 10.0,
+                    y: 10.0,
+                    width: 20.0,
+                    height: 20.0,
+                },
+            },
+            LinkTarget {
+                element_id: None,
+                href: "https://example.com/".to_string(),
+                target: Some("_blank".to_string()),
+                title: None,
+                bounding_box: cairo::Rectangle {
+                    x: 50.0,
+                    y: 40.0,
+                    width: 30.0,
+                    height: 40.0,
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn links_are_measured_in_viewport_coordinates() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="100" height="100" viewBox="0 0 50 50">
+  <g transform="translate(10, 5)">
+    <a xlink:href="#shown">
+      <rect x="0" y="0" width="10" height="10"/>
+    </a>
+  </g>
+  <g display="none">
+    <a xlink:href="#hidden">
+      <rect x="0" y="0" width="10" height="10"/>
+    </a>
+  </g>
+</svg>
+"##,
+    );
+
+    let renderer = CairoRenderer::new(&svg);
+    let bounding_boxes: Vec<_> = renderer
+        .links(&cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        })
+        .unwrap()
+        .into_iter()
+        .map(|link| link.bounding_box)
+        .collect();
+
+    assert_eq!(
+        bounding_boxes,
+        vec![
+            cairo::Rectangle {
+                x: 20.0,
+                y: 10.0,
+                width: 20.0,
+                height: 20.0,
+            },
+            cairo::Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: 0.0,
+                height: 0.0,
+            },
+        ]
+    );
+}
+
+#[test]
+fn aria_attributes_are_available_by_element_id() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="50">
+  <g id="button" role="button" aria-label="My button" aria-describedby="help">
+    <rect x="0" y="0" width="10" height="10"/>
+  </g>
+  <text id="help" aria-hidden="true">Press to continue</text>
+  <rect id="plain" x="0" y="0" width="10" height="10"/>
+</svg>
+"#,
+    );
+
+    assert_eq!(
+        svg.aria_attributes("#button").unwrap(),
+        Some(AriaAttributes {
+            role: Some("button".to_string()),
+            label: Some("My button".to_string()),
+            describedby: Some("help".to_string()),
+            hidden: None,
+        })
+    );
+
+    assert_eq!(
+        svg.aria_attributes("#help").unwrap().unwrap().hidden,
+        Some(true)
+    );
+
+    assert_eq!(
+        svg.aria_attributes("#plain").unwrap(),
+        Some(AriaAttributes::default())
+    );
+
+    assert_eq!(svg.aria_attributes("#nonexistent").unwrap(), None);
+}
+
+#[test]
+fn tab_order_follows_tabindex_then_document_order() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="50">
+  <rect id="untabbed" x="0" y="0" width="10" height="10"/>
+  <a id="link"><rect x="0" y="0" width="10" height="10"/></a>
+  <rect id="third" x="0" y="0" width="10" height="10" tabindex="7"/>
+  <g id="negative" tabindex="-2">
+    <rect x="0" y="0" width="10" height="10" tabindex="1"/>
+    <rect id="first" x="0" y="0" width="10" height="10" tabindex="3"/>
+  </g>
+  <rect id="zero" x="0" y="0" width="10" height="10" tabindex="0"/>
+  <rect id="fourth" x="0" y="0" width="10" height="10" tabindex="7"/>
+  <rect id="invalid" x="0" y="0" width="10" height="10" tabindex="foo"/>
+</svg>
+"#,
+    );
+
+    assert_eq!(
+        svg.tab_order(),
+        vec!["negative", "zero", "first", "third", "fourth", "link"]
+    );
+}
+
+#[test]
+fn invalid_property_produces_one_warning() {
+    let mut svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="50">
+  <rect x="10" y="10" width="30" height="30" style="fill: bogus; stroke: black"/>
+</svg>
+"#,
+    );
+
+    let diagnostics = svg.take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert!(diagnostics[0].location.is_some());
+
+    assert!(svg.take_diagnostics().is_empty());
+}
+
+fn style_fill_at_center(svg: &SvgHandle) -> Pixel {
+    render_document(
+        svg,
+        SurfaceSize(50, 50),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 50.0,
+            height: 50.0,
+        },
+    )
+    .unwrap()
+    .get_pixel(25, 25)
+}
+
+#[test]
+fn style_without_type_is_css() {
+    let mut svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="50">
+  <style>rect { fill: lime; }</style>
+  <rect x="0" y="0" width="50" height="50"/>
+</svg>
+"#,
+    );
+
+    assert!(svg.take_diagnostics().is_empty());
+    assert_eq!(style_fill_at_center(&svg).g, 255);
+}
+
+#[test]
+fn style_with_text_css_type_is_applied() {
+    let mut svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="50">
+  <style type="text/css">rect { fill: lime; }</style>
+  <rect x="0" y="0" width="50" height="50"/>
+</svg>
+"#,
+    );
+
+    assert!(svg.take_diagnostics().is_empty());
+    assert_eq!(style_fill_at_center(&svg).g, 255);
+}
+
+#[test]
+fn style_with_unsupported_type_is_ignored() {
+    let mut svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="50">
+  <style type="text/xsl">rect { fill: lime; }</style>
+  <rect x="0" y="0" width="50" height="50"/>
+</svg>
+"#,
+    );
+
+    let diagnostics = svg.take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.contains("type attribute"));
+
+    // The rectangle keeps the default black fill.
+    let pixel = style_fill_at_center(&svg);
+    assert_eq!((pixel.r, pixel.g, pixel.b, pixel.a), (0, 0, 0, 255));
+}
+
+#[test]
+fn loads_from_reader() {
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="50">
+  <rect id="foo" x="10" y="10" width="30" height="30"/>
+</svg>
+"#
+    .to_vec();
+
+    let svg = Loader::new().read(Cursor::new(data), None).unwrap();
+
+    assert!(svg.has_element_with_id("#foo").unwrap());
+
+    let renderer = CairoRenderer::new(&svg);
+    let (ink_r, _) = renderer.geometry_for_element(Some("#foo")).unwrap();
+
+    assert_eq!(
+        ink_r,
+        cairo::Rectangle {
+            x: 10.0,
+            y: 10.0,
+            width: 30.0,
+            height: 30.0,
+        }
+    );
+}
+
+// Returns the data one byte at a time, so that the parser has to keep asking for more.
+struct OneByteReader(Cursor<Vec<u8>>);
+
+impl Read for OneByteReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(1);
+        self.0.read(&mut buf[..len])
+    }
+}
+
+#[test]
+fn loads_from_reader_in_small_pieces() {
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="50">
+  <rect id="foo" x="10" y="10" width="30" height="30"/>
+</svg>
+"#
+    .to_vec();
+
+    let svg = Loader::new()
+        .read(OneByteReader(Cursor::new(data)), None)
+        .unwrap();
+
+    assert!(svg.has_element_with_id("#foo").unwrap());
+}
+
+struct FailingReader;
+
+impl Read for FailingReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::Other, "read failed"))
+    }
+}
+
+#[test]
+fn read_errors_are_loading_errors() {
+    match Loader::new().read(FailingReader, None) {
+        Err(LoadingError::Glib(_)) => (),
+        _ => panic!("expected an I/O error"),
+    }
+}
+
+#[test]
+fn invalid_base_url_for_reader_is_an_error() {
+    match Loader::new().read(Cursor::new(Vec::new()), Some("not a url")) {
+        Err(LoadingError::BadUrl) => (),
+        _ => panic!("expected a BadUrl error"),
+    }
+}
+
+#[test]
+fn xml_base_changes_base_for_relative_image_hrefs() {
+    let svg = Loader::new()
+        .read_path(fixture_dir().join("xml-base").join("document.svg"))
+        .unwrap();
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(100, 50),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 50.0,
+        },
+    )
+    .unwrap();
+
+    let image_in_group = output_surf.get_pixel(10, 25);
+    assert_eq!(
+        (
+            image_in_group.r,
+            image_in_group.g,
+            image_in_group.b,
+            image_in_group.a
+        ),
+        (0, 255, 0, 255)
+    );
+
+    let image_outside_group = output_surf.get_pixel(40, 25);
+    assert_eq!(image_outside_group.a, 0);
+
+    let fe_image = output_surf.get_pixel(70, 25);
+    assert_eq!(
+        (fe_image.r, fe_image.g, fe_image.b, fe_image.a),
+        (0, 255, 0, 255)
+    );
+}
+
+fn render_pixels(svg: &librsvg::SvgHandle) -> Vec<Pixel> {
+    let output = render_document(
+        svg,
+        SurfaceSize(50, 50),
+        |_| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 50.0,
+            height: 50.0,
+        },
+    )
+    .unwrap();
+
+    (0..50)
+        .flat_map(|y| (0..50).map(move |x| (x, y)))
+        .map(|(x, y)| output.get_pixel(x, y))
+        .collect()
+}
+
+#[test]
+fn shared_data_renders_the_same_in_several_threads() {
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="50">
+  <linearGradient id="gradient">
+    <stop offset="0" stop-color="lime"/>
+    <stop offset="1" stop-color="blue"/>
+  </linearGradient>
+  <circle cx="25" cy="25" r="20" fill="url(#gradient)" stroke="black" stroke-width="2"/>
+</svg>
+"#
+    .to_vec();
+
+    let shared = Loader::new().read_shared(Cursor::new(data), None).unwrap();
+
+    let reference = render_pixels(&shared.load().unwrap());
+
+    let threads: Vec<_> = (0..2)
+        .map(|_| {
+            let shared = shared.clone();
+
+            thread::spawn(move || {
+                // Render twice, to exercise the per-thread handle
+                let first = shared.with_handle(render_pixels).unwrap();
+                let second = shared.with_handle(render_pixels).unwrap();
+                assert_eq!(first, second);
+                first
+            })
+        })
+        .collect();
+
+    for t in threads {
+        assert_eq!(t.join().unwrap(), reference);
+    }
+}
+
+#[test]
+fn serialized_svg_renders_the_same() {
+    let svg = Loader::new()
+        .keep_attributes()
+        .read(
+            Cursor::new(
+                &br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="50" height="50">
+  <style type="text/css">
+    .shape > circle { fill: url(#gradient); }
+  </style>
+  <defs>
+    <linearGradient id="gradient" gradientTransform="rotate(45 0.5 0.5)">
+      <stop offset="0" stop-color="lime"/>
+      <stop offset="1" stop-color="blue"/>
+    </linearGradient>
+    <rect id="square" width="10" height="10" style="fill: red; stroke: black"/>
+  </defs>
+  <g class="shape" transform="translate(5, 5)">
+    <circle cx="20" cy="20" r="15" stroke="black" stroke-width="2"/>
+  </g>
+  <use xlink:href="#square" x="35" y="35"/>
+  <text x="2" y="10" font-size="8" xml:space="preserve">a &lt; b &amp;  c</text>
+</svg>
+"##[..],
+            ),
+            None,
+        )
+        .unwrap();
+
+    let serialized = svg.to_svg_string().unwrap();
+
+    let reloaded = Loader::new()
+        .read(Cursor::new(serialized.into_bytes()), None)
+        .unwrap();
+
+    assert_eq!(render_pixels(&reloaded), render_pixels(&svg));
+}
+
+#[test]
+fn serializing_needs_the_attributes_to_be_kept() {
+    let svg = load_svg(br#"<svg xmlns="http://www.w3.org/2000/svg"/>"#);
+
+    assert_eq!(svg.to_svg_string(), Err(SerializeError::AttributesNotKept));
+}
+
+#[test]
+fn serialization_gives_clashing_prefixes_different_names() {
+    let svg = Loader::new()
+        .keep_attributes()
+        .read(
+            Cursor::new(
+                &br#"<svg xmlns="http://www.w3.org/2000/svg">
+  <g xmlns:a="http://example.com/one" a:foo="1"/>
+  <g xmlns:a="http://example.com/two" a:foo="2"/>
+</svg>"#[..],
+            ),
+            None,
+        )
+        .unwrap();
+
+    let serialized = svg.to_svg_string().unwrap();
+
+    assert!(serialized.contains(r#"xmlns:a="http://example.com/one""#));
+    assert!(serialized.contains(r#"xmlns:ns1="http://example.com/two""#));
+    assert!(serialized.contains(r#"a:foo="1""#));
+    assert!(serialized.contains(r#"ns1:foo="2""#));
+}
@@ -0,0 +1,1013 @@
+use cairo;
+
+use librsvg::{RenderingError, Severity, SvgHandle};
+use rsvg_internals::surface_utils::Pixel;
+
+mod utils;
+
+use self::utils::{compare_to_surface, load_svg, render_document, SurfaceSize};
+
+#[test]
+fn diffuse_lighting_highlights_slope_facing_the_light() {
+    // The bump map is a dome with its top at the center; the light comes from the right.
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <radialGradient id="dome">
+      <stop offset="0" stop-color="black" stop-opacity="1"/>
+      <stop offset="1" stop-color="black" stop-opacity="0"/>
+    </radialGradient>
+    <filter id="light" filterUnits="userSpaceOnUse" x="0" y="0" width="100" height="100"
+            color-interpolation-filters="sRGB">
+      <feDiffuseLighting surfaceScale="20" lighting-color="white">
+        <feDistantLight azimuth="0" elevation="30"/>
+      </feDiffuseLighting>
+    </filter>
+  </defs>
+  <rect width="100" height="100" fill="url(#dome)" filter="url(#light)"/>
+</svg>
+"#,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(100, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        },
+    )
+    .unwrap();
+
+    let left = output_surf.get_pixel(30, 50);
+    let right = output_surf.get_pixel(70, 50);
+    let top = output_surf.get_pixel(50, 30);
+    let bottom = output_surf.get_pixel(50, 70);
+
+    assert!(
+        i32::from(right.r) > i32::from(left.r) + 100,
+        "left: {:?}, right: {:?}",
+        left,
+        right
+    );
+
+    // The light is in the plane of the horizontal axis, so the result is symmetric about it;
+    // allow for the quantization of the gradient's alpha values.
+    assert!(
+        (i32::from(top.r) - i32::from(bottom.r)).abs() <= 5,
+        "top: {:?}, bottom: {:?}",
+        top,
+        bottom
+    );
+}
+
+#[test]
+fn point_light_highlight_is_under_the_light() {
+    // A flat bump map is brightest right under a point light.
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="light" filterUnits="userSpaceOnUse" x="0" y="0" width="100" height="100"
+            color-interpolation-filters="sRGB">
+      <feDiffuseLighting lighting-color="white">
+        <fePointLight x="30" y="70" z="5"/>
+      </feDiffuseLighting>
+    </filter>
+  </defs>
+  <rect width="100" height="100" filter="url(#light)"/>
+</svg>
+"#,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(100, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        },
+    )
+    .unwrap();
+
+    let mut brightest = (0, 0, 0);
+    for y in 0..100 {
+        for x in 0..100 {
+            let r = output_surf.get_pixel(x, y).r;
+            if r > brightest.2 {
+                brightest = (x, y, r);
+            }
+        }
+    }
+
+    assert_eq!((brightest.0, brightest.1), (30, 70));
+}
+
+#[test]
+fn spot_light_only_lights_inside_its_cone() {
+    let centered = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="light" filterUnits="userSpaceOnUse" x="0" y="0" width="100" height="100"
+            color-interpolation-filters="sRGB">
+      <feDiffuseLighting lighting-color="white">
+        <feSpotLight x="50" y="50" z="50" pointsAtX="50" pointsAtY="50" pointsAtZ="0"
+                     limitingConeAngle="20"/>
+      </feDiffuseLighting>
+    </filter>
+  </defs>
+  <rect width="100" height="100" filter="url(#light)"/>
+</svg>
+"#,
+    );
+
+    let off_center = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="light" filterUnits="userSpaceOnUse" x="0" y="0" width="100" height="100"
+            color-interpolation-filters="sRGB">
+      <feDiffuseLighting lighting-color="white">
+        <feSpotLight x="50" y="50" z="50" pointsAtX="80" pointsAtY="50" pointsAtZ="0"
+                     limitingConeAngle="20"/>
+      </feDiffuseLighting>
+    </filter>
+  </defs>
+  <rect width="100" height="100" filter="url(#light)"/>
+</svg>
+"#,
+    );
+
+    let viewport = cairo::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 100.0,
+        height: 100.0,
+    };
+
+    let centered_surf =
+        render_document(&centered, SurfaceSize(100, 100), |_cr| (), viewport).unwrap();
+    let off_center_surf =
+        render_document(&off_center, SurfaceSize(100, 100), |_cr| (), viewport).unwrap();
+
+    // (80, 50) is about 31 degrees away from the light's axis when it points
+    // straight down, which is outside the 20 degree cone.
+    assert!(centered_surf.get_pixel(50, 50).r > 200);
+    assert!(centered_surf.get_pixel(80, 50).r < 16);
+
+    assert!(off_center_surf.get_pixel(50, 50).r < 16);
+    assert!(off_center_surf.get_pixel(80, 50).r > 150);
+}
+
+#[test]
+fn convolve_matrix_preserve_alpha_keeps_source_alpha() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <defs>
+    <filter id="emboss" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40"
+            color-interpolation-filters="sRGB">
+      <feConvolveMatrix order="3" kernelMatrix="-2 -1 0 -1 1 1 0 1 2" preserveAlpha="true"/>
+    </filter>
+  </defs>
+  <g filter="url(#emboss)">
+    <rect x="5" y="5" width="20" height="20" fill="red" fill-opacity="0.5"/>
+    <rect x="15" y="15" width="20" height="20" fill="blue" fill-opacity="0.8"/>
+  </g>
+</svg>
+"#,
+    );
+
+    let unfiltered = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <rect x="5" y="5" width="20" height="20" fill="red" fill-opacity="0.5"/>
+  <rect x="15" y="15" width="20" height="20" fill="blue" fill-opacity="0.8"/>
+</svg>
+"#,
+    );
+
+    let viewport = cairo::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 40.0,
+        height: 40.0,
+    };
+
+    let output_surf = render_document(&svg, SurfaceSize(40, 40), |_cr| (), viewport).unwrap();
+    let reference_surf =
+        render_document(&unfiltered, SurfaceSize(40, 40), |_cr| (), viewport).unwrap();
+
+    let mut changed_color = false;
+
+    for y in 0..40 {
+        for x in 0..40 {
+            let output = output_surf.get_pixel(x, y);
+            let reference = reference_surf.get_pixel(x, y);
+
+            assert_eq!(output.a, reference.a, "alpha differs at ({}, {})", x, y);
+
+            if (output.r, output.g, output.b) != (reference.r, reference.g, reference.b) {
+                changed_color = true;
+            }
+        }
+    }
+
+    // The kernel is not the identity, so it must have done something to the colors.
+    assert!(changed_color);
+}
+
+#[test]
+fn convolve_matrix_kernel_unit_length_spreads_kernel() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="average" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feConvolveMatrix order="3" kernelMatrix="1 1 1 1 1 1 1 1 1" kernelUnitLength="1"/>
+  </filter>
+  <rect x="10" y="10" width="20" height="20" fill="black" filter="url(#average)"/>
+</svg>
+"#,
+    );
+
+    let scaled = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="average" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feConvolveMatrix order="3" kernelMatrix="1 1 1 1 1 1 1 1 1" kernelUnitLength="2"/>
+  </filter>
+  <rect x="10" y="10" width="20" height="20" fill="black" filter="url(#average)"/>
+</svg>
+"#,
+    );
+
+    // With a kernel unit of one pixel, the average only reaches one pixel
+    // beyond the edge of the square; with two pixels, it reaches further.
+    assert_eq!(render_to_pixel(&svg, 31, 20).a, 0);
+    assert!(render_to_pixel(&scaled, 31, 20).a > 16);
+}
+
+#[test]
+fn empty_filter_region_produces_warning() {
+    let mut svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <defs>
+    <filter id="empty" x="50%" width="0%">
+      <feFlood flood-color="lime"/>
+    </filter>
+  </defs>
+  <rect width="40" height="40" fill="red" filter="url(#empty)"/>
+</svg>
+"#,
+    );
+
+    assert!(svg.take_diagnostics().is_empty());
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(40, 40),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 40.0,
+            height: 40.0,
+        },
+    )
+    .unwrap();
+
+    // The element is not rendered at all.
+    assert_eq!(output_surf.get_pixel(20, 20).a, 0);
+
+    let diagnostics = svg.take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert!(diagnostics[0]
+        .message
+        .contains("empty filter effects region"));
+}
+
+#[test]
+fn invalid_filter_units_produce_error() {
+    let mut svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="bad" filterUnits="bogus" primitiveUnits="userSpaceOnUse"/>
+</svg>
+"#,
+    );
+
+    let diagnostics = svg.take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert!(diagnostics[0].message.contains("filterUnits"));
+}
+
+fn render_40x40(svg: &SvgHandle) -> Result<(), RenderingError> {
+    render_document(
+        svg,
+        SurfaceSize(40, 40),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 40.0,
+            height: 40.0,
+        },
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn fe_image_referencing_its_own_filter_is_a_circular_reference() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="40" height="40">
+  <filter id="a">
+    <feImage xlink:href="#a"/>
+  </filter>
+  <rect width="40" height="40" filter="url(#a)"/>
+</svg>
+"##,
+    );
+
+    match render_40x40(&svg) {
+        Err(RenderingError::CircularReference) => (),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn fe_image_referencing_the_filtered_element_is_a_circular_reference() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="40" height="40">
+  <filter id="f">
+    <feImage xlink:href="#r"/>
+  </filter>
+  <rect id="r" width="40" height="40" filter="url(#f)"/>
+</svg>
+"##,
+    );
+
+    match render_40x40(&svg) {
+        Err(RenderingError::CircularReference) => (),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn use_inside_the_element_it_references_is_a_circular_reference() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="40" height="40">
+  <g id="g">
+    <rect width="10" height="10"/>
+    <use xlink:href="#g" x="10"/>
+  </g>
+</svg>
+"##,
+    );
+
+    match render_40x40(&svg) {
+        Err(RenderingError::CircularReference) => (),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn primitive_overrides_color_interpolation_filters_of_filter() {
+    // The filter asks for linearRGB, but the blur overrides it with sRGB.
+    let overridden = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="blur" color-interpolation-filters="linearRGB">
+    <feGaussianBlur stdDeviation="3" color-interpolation-filters="sRGB"/>
+  </filter>
+  <g filter="url(#blur)">
+    <rect x="0" y="0" width="20" height="40" fill="red"/>
+    <rect x="20" y="0" width="20" height="40" fill="blue"/>
+  </g>
+</svg>
+"##,
+    );
+
+    let srgb = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="blur" color-interpolation-filters="sRGB">
+    <feGaussianBlur stdDeviation="3"/>
+  </filter>
+  <g filter="url(#blur)">
+    <rect x="0" y="0" width="20" height="40" fill="red"/>
+    <rect x="20" y="0" width="20" height="40" fill="blue"/>
+  </g>
+</svg>
+"##,
+    );
+
+    let linear_rgb = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="blur" color-interpolation-filters="linearRGB">
+    <feGaussianBlur stdDeviation="3"/>
+  </filter>
+  <g filter="url(#blur)">
+    <rect x="0" y="0" width="20" height="40" fill="red"/>
+    <rect x="20" y="0" width="20" height="40" fill="blue"/>
+  </g>
+</svg>
+"##,
+    );
+
+    let viewport = cairo::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 40.0,
+        height: 40.0,
+    };
+
+    let render = |svg| render_document(svg, SurfaceSize(40, 40), |_cr| (), viewport).unwrap();
+
+    let overridden_surf = render(&overridden);
+    let srgb_surf = render(&srgb);
+    let linear_rgb_surf = render(&linear_rgb);
+
+    compare_to_surface(
+        &overridden_surf,
+        &srgb_surf,
+        "primitive_overrides_color_interpolation_filters_of_filter",
+    );
+
+    // Make sure that the test is meaningful: blending in linearRGB looks different.
+    assert_ne!(
+        overridden_surf.get_pixel(20, 20),
+        linear_rgb_surf.get_pixel(20, 20)
+    );
+}
+
+#[test]
+fn color_interpolation_filters_defaults_to_linear_rgb() {
+    let unstyled = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="blur">
+    <feGaussianBlur stdDeviation="3"/>
+  </filter>
+  <g filter="url(#blur)">
+    <rect x="0" y="0" width="20" height="40" fill="red"/>
+    <rect x="20" y="0" width="20" height="40" fill="blue"/>
+  </g>
+</svg>
+"##,
+    );
+
+    let linear_rgb = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="blur">
+    <feGaussianBlur stdDeviation="3" color-interpolation-filters="linearRGB"/>
+  </filter>
+  <g filter="url(#blur)">
+    <rect x="0" y="0" width="20" height="40" fill="red"/>
+    <rect x="20" y="0" width="20" height="40" fill="blue"/>
+  </g>
+</svg>
+"##,
+    );
+
+    let srgb = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="blur">
+    <feGaussianBlur stdDeviation="3" color-interpolation-filters="sRGB"/>
+  </filter>
+  <g filter="url(#blur)">
+    <rect x="0" y="0" width="20" height="40" fill="red"/>
+    <rect x="20" y="0" width="20" height="40" fill="blue"/>
+  </g>
+</svg>
+"##,
+    );
+
+    let viewport = cairo::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 40.0,
+        height: 40.0,
+    };
+
+    let render = |svg| render_document(svg, SurfaceSize(40, 40), |_cr| (), viewport).unwrap();
+
+    let unstyled_surf = render(&unstyled);
+
+    compare_to_surface(
+        &unstyled_surf,
+        &render(&linear_rgb),
+        "color_interpolation_filters_defaults_to_linear_rgb",
+    );
+
+    assert_ne!(
+        unstyled_surf.get_pixel(20, 20),
+        render(&srgb).get_pixel(20, 20)
+    );
+}
+
+fn render_to_pixel(svg: &SvgHandle, x: u32, y: u32) -> Pixel {
+    render_document(
+        svg,
+        SurfaceSize(40, 40),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 40.0,
+            height: 40.0,
+        },
+    )
+    .unwrap()
+    .get_pixel(x, y)
+}
+
+#[test]
+fn unknown_result_name_falls_back_to_default_input() {
+    // The first primitive gets the SourceGraphic; the last one gets the
+    // result of the primitive before it.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="first" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feOffset in="nonexistent" dx="0" dy="0"/>
+  </filter>
+  <filter id="last" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feFlood flood-color="blue"/>
+    <feOffset in="nonexistent" dx="0" dy="0"/>
+  </filter>
+  <rect x="0" y="0" width="20" height="40" fill="lime" filter="url(#first)"/>
+  <rect x="20" y="0" width="20" height="40" fill="lime" filter="url(#last)"/>
+</svg>
+"##,
+    );
+
+    assert_eq!(
+        render_to_pixel(&svg, 10, 20),
+        Pixel {
+            r: 0,
+            g: 255,
+            b: 0,
+            a: 255
+        }
+    );
+    assert_eq!(
+        render_to_pixel(&svg, 30, 20),
+        Pixel {
+            r: 0,
+            g: 0,
+            b: 255,
+            a: 255
+        }
+    );
+}
+
+#[test]
+fn source_alpha_is_a_black_silhouette_of_the_source() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="filter" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feFlood flood-color="red" result="flood-red"/>
+    <feComposite in="SourceAlpha" in2="flood-red" operator="in"/>
+  </filter>
+  <g filter="url(#filter)">
+    <rect x="0" y="0" width="20" height="20" fill="lime"/>
+    <rect x="20" y="0" width="20" height="20" fill="lime" fill-opacity="0.5"/>
+  </g>
+</svg>
+"##,
+    );
+
+    assert_eq!(
+        render_to_pixel(&svg, 10, 10),
+        Pixel {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255
+        }
+    );
+
+    let half = render_to_pixel(&svg, 30, 10);
+    assert_eq!((half.r, half.g, half.b), (0, 0, 0));
+    assert!(half.a >= 127 && half.a <= 128);
+
+    assert_eq!(
+        render_to_pixel(&svg, 10, 30),
+        Pixel {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0
+        }
+    );
+}
+
+#[test]
+fn primitives_can_add_color_to_alpha_only_inputs() {
+    // SourceAlpha has no color, but the color matrix and the arithmetic
+    // composite turn it white.  The blur and the blend must see that color.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="matrix">
+    <feColorMatrix in="SourceAlpha" type="matrix"
+                   values="0 0 0 0 1  0 0 0 0 1  0 0 0 0 1  0 0 0 1 0"/>
+    <feGaussianBlur stdDeviation="1"/>
+    <feBlend in2="SourceGraphic" mode="multiply"/>
+  </filter>
+  <filter id="arithmetic">
+    <feComposite in="SourceAlpha" in2="SourceAlpha" operator="arithmetic" k4="1"/>
+    <feGaussianBlur stdDeviation="1"/>
+    <feBlend in2="SourceGraphic" mode="multiply"/>
+  </filter>
+  <rect x="0" y="0" width="20" height="40" fill="lime" filter="url(#matrix)"/>
+  <rect x="20" y="0" width="20" height="40" fill="lime" filter="url(#arithmetic)"/>
+</svg>
+"##,
+    );
+
+    let lime = Pixel {
+        r: 0,
+        g: 255,
+        b: 0,
+        a: 255,
+    };
+
+    assert_eq!(render_to_pixel(&svg, 10, 20), lime);
+    assert_eq!(render_to_pixel(&svg, 30, 20), lime);
+}
+
+#[test]
+fn gaussian_blur_with_two_std_deviations_blurs_each_axis_separately() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="filter" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feGaussianBlur stdDeviation="4 1"/>
+  </filter>
+  <rect x="15" y="15" width="10" height="10" fill="black" filter="url(#filter)"/>
+</svg>
+"##,
+    );
+
+    // Both pixels are at the same distance from the edge of the square.
+    let horizontal = render_to_pixel(&svg, 29, 20);
+    let vertical = render_to_pixel(&svg, 20, 29);
+
+    assert!(horizontal.a > vertical.a + 16);
+}
+
+#[test]
+fn flood_color_current_color_is_taken_from_filtered_element() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="flood" color="red" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feFlood flood-color="currentColor"/>
+  </filter>
+  <rect x="0" y="0" width="40" height="40" style="color: blue" filter="url(#flood)"/>
+</svg>
+"##,
+    );
+
+    assert_eq!(
+        render_to_pixel(&svg, 20, 20),
+        Pixel {
+            r: 0,
+            g: 0,
+            b: 255,
+            a: 255
+        }
+    );
+}
+
+#[test]
+fn primitive_percentages_refer_to_filter_region() {
+    // The filter region goes from 8 to 28; the flood covers 10% to 90% of that, from 10 to 26.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="flood" filterUnits="userSpaceOnUse" x="8" y="8" width="20" height="20">
+    <feFlood flood-color="lime" x="10%" y="10%" width="80%" height="80%"/>
+  </filter>
+  <rect x="0" y="0" width="40" height="40" filter="url(#flood)"/>
+</svg>
+"##,
+    );
+
+    let transparent = Pixel {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 0,
+    };
+
+    let lime = Pixel {
+        r: 0,
+        g: 255,
+        b: 0,
+        a: 255,
+    };
+
+    assert_eq!(render_to_pixel(&svg, 9, 18), transparent);
+    assert_eq!(render_to_pixel(&svg, 10, 18), lime);
+    assert_eq!(render_to_pixel(&svg, 25, 18), lime);
+    assert_eq!(render_to_pixel(&svg, 26, 18), transparent);
+    assert_eq!(render_to_pixel(&svg, 18, 9), transparent);
+    assert_eq!(render_to_pixel(&svg, 18, 25), lime);
+    assert_eq!(render_to_pixel(&svg, 18, 26), transparent);
+}
+
+#[test]
+fn lighting_color_current_color_is_taken_from_filtered_element() {
+    // A flat surface lit from straight above gets the full lighting color.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="light" color="red" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feDiffuseLighting lighting-color="currentColor">
+      <feDistantLight azimuth="0" elevation="90"/>
+    </feDiffuseLighting>
+  </filter>
+  <rect x="0" y="0" width="40" height="40" style="color: blue" filter="url(#light)"/>
+</svg>
+"##,
+    );
+
+    assert_eq!(
+        render_to_pixel(&svg, 20, 20),
+        Pixel {
+            r: 0,
+            g: 0,
+            b: 255,
+            a: 255
+        }
+    );
+}
+
+#[test]
+fn background_image_accumulates_everything_below() {
+    // The filter multiplies a white source by the background image, so the
+    // output shows the background where there is one, and white elsewhere.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="bg" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feBlend in="BackgroundImage" in2="SourceGraphic" mode="multiply"/>
+  </filter>
+  <rect x="0" y="0" width="40" height="40" fill="red"/>
+  <g enable-background="accumulate">
+    <rect x="0" y="0" width="20" height="40" fill="lime"/>
+    <rect x="0" y="0" width="40" height="40" fill="white" filter="url(#bg)"/>
+  </g>
+</svg>
+"##,
+    );
+
+    assert_eq!(
+        render_to_pixel(&svg, 10, 20),
+        Pixel {
+            r: 0,
+            g: 255,
+            b: 0,
+            a: 255
+        }
+    );
+    assert_eq!(
+        render_to_pixel(&svg, 30, 20),
+        Pixel {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255
+        }
+    );
+}
+
+#[test]
+fn enable_background_new_starts_a_new_background_image() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="bg" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feBlend in="BackgroundImage" in2="SourceGraphic" mode="multiply"/>
+  </filter>
+  <rect x="0" y="0" width="40" height="40" fill="red"/>
+  <g enable-background="new">
+    <rect x="0" y="0" width="20" height="40" fill="lime"/>
+    <rect x="0" y="0" width="40" height="40" fill="white" filter="url(#bg)"/>
+  </g>
+</svg>
+"##,
+    );
+
+    assert_eq!(
+        render_to_pixel(&svg, 10, 20),
+        Pixel {
+            r: 0,
+            g: 255,
+            b: 0,
+            a: 255
+        }
+    );
+
+    // The red rectangle is outside the new background, so it does not
+    // show up in BackgroundImage.
+    assert_eq!(
+        render_to_pixel(&svg, 30, 20),
+        Pixel {
+            r: 255,
+            g: 255,
+            b: 255,
+            a: 255
+        }
+    );
+}
+
+#[test]
+fn background_alpha_has_only_the_alpha_of_the_background() {
+    // BackgroundAlpha is black where the lime rectangle is, and transparent
+    // elsewhere, so the red rectangle below the group shows through there.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="bg" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feComposite in="BackgroundAlpha" in2="SourceGraphic" operator="in"/>
+  </filter>
+  <rect x="0" y="0" width="40" height="40" fill="red"/>
+  <g enable-background="new">
+    <rect x="0" y="0" width="20" height="40" fill="lime"/>
+    <rect x="0" y="0" width="40" height="40" fill="white" filter="url(#bg)"/>
+  </g>
+</svg>
+"##,
+    );
+
+    assert_eq!(
+        render_to_pixel(&svg, 10, 20),
+        Pixel {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255
+        }
+    );
+    assert_eq!(
+        render_to_pixel(&svg, 30, 20),
+        Pixel {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255
+        }
+    );
+}
+
+#[test]
+fn default_filter_region_with_object_bounding_box_units() {
+    // The default region is -10%/-10%/120%/120% of the 20x20 bounding box,
+    // so the flood covers 8..32 on each axis.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="flood">
+    <feFlood flood-color="lime"/>
+  </filter>
+  <rect x="10" y="10" width="20" height="20" filter="url(#flood)"/>
+</svg>
+"##,
+    );
+
+    let lime = Pixel {
+        r: 0,
+        g: 255,
+        b: 0,
+        a: 255,
+    };
+
+    let transparent = Pixel {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 0,
+    };
+
+    assert_eq!(render_to_pixel(&svg, 8, 20), lime);
+    assert_eq!(render_to_pixel(&svg, 31, 20), lime);
+    assert_eq!(render_to_pixel(&svg, 6, 20), transparent);
+    assert_eq!(render_to_pixel(&svg, 33, 20), transparent);
+}
+
+#[test]
+fn default_filter_region_with_user_space_units_refers_to_viewport() {
+    // With userSpaceOnUse, the default -10%/-10%/120%/120% are percentages
+    // of the viewport, not of the element's bounding box.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="flood" filterUnits="userSpaceOnUse">
+    <feFlood flood-color="lime"/>
+  </filter>
+  <rect x="10" y="10" width="20" height="20" filter="url(#flood)"/>
+</svg>
+"##,
+    );
+
+    let lime = Pixel {
+        r: 0,
+        g: 255,
+        b: 0,
+        a: 255,
+    };
+
+    assert_eq!(render_to_pixel(&svg, 0, 0), lime);
+    assert_eq!(render_to_pixel(&svg, 39, 39), lime);
+}
+
+#[test]
+fn flood_result_covers_the_whole_surface_for_the_next_filter() {
+    // The first filter only floods its small region; the second one moves
+    // that flood outside of the first region.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="flood" filterUnits="userSpaceOnUse" x="0" y="0" width="10" height="10">
+    <feFlood flood-color="lime"/>
+  </filter>
+  <filter id="move" filterUnits="userSpaceOnUse" x="0" y="0" width="40" height="40">
+    <feOffset dx="20" dy="20"/>
+  </filter>
+  <rect x="0" y="0" width="40" height="40" filter="url(#flood) url(#move)"/>
+</svg>
+"##,
+    );
+
+    assert_eq!(
+        render_to_pixel(&svg, 25, 25),
+        Pixel {
+            r: 0,
+            g: 255,
+            b: 0,
+            a: 255
+        }
+    );
+    assert_eq!(render_to_pixel(&svg, 5, 5).a, 0);
+}
+
+#[test]
+fn tile_of_standard_input_is_the_filter_region() {
+    // The tile for SourceGraphic is the whole filter effects region, so tiling
+    // it over the region reproduces the source graphic there.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="tile" filterUnits="userSpaceOnUse" x="10" y="10" width="20" height="20">
+    <feTile in="SourceGraphic"/>
+  </filter>
+  <g filter="url(#tile)">
+    <rect x="0" y="0" width="20" height="40" fill="lime"/>
+    <rect x="20" y="0" width="20" height="40" fill="blue"/>
+  </g>
+</svg>
+"##,
+    );
+
+    let lime = Pixel {
+        r: 0,
+        g: 255,
+        b: 0,
+        a: 255,
+    };
+    let blue = Pixel {
+        r: 0,
+        g: 0,
+        b: 255,
+        a: 255,
+    };
+
+    assert_eq!(render_to_pixel(&svg, 15, 15), lime);
+    assert_eq!(render_to_pixel(&svg, 25, 25), blue);
+    assert_eq!(render_to_pixel(&svg, 5, 5).a, 0);
+    assert_eq!(render_to_pixel(&svg, 35, 35).a, 0);
+}
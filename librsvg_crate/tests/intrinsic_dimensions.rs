@@ -2,7 +2,7 @@ use cairo;
 
 use librsvg::{
     CairoRenderer, DefsLookupErrorKind, HrefError, IntrinsicDimensions, Length, LengthUnit,
-    RenderingError,
+    NaturalDimension, NaturalSize, RenderingError,
 };
 
 mod utils;
@@ -50,6 +50,47 @@ fn has_intrinsic_dimensions() {
     );
 }
 
+#[test]
+fn natural_size_keeps_units() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10cm" height="20" viewBox="0 0 100 200"/>
+"#,
+    );
+
+    assert_eq!(
+        svg.natural_size(),
+        NaturalSize {
+            width: Some(NaturalDimension::Length(10.0, LengthUnit::Cm)),
+            height: Some(NaturalDimension::Length(20.0, LengthUnit::Px)),
+            vbox: Some(cairo::Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 200.0,
+            }),
+        }
+    );
+}
+
+#[test]
+fn natural_size_with_percentages() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50%"/>
+"#,
+    );
+
+    assert_eq!(
+        svg.natural_size(),
+        NaturalSize {
+            width: Some(NaturalDimension::Percentage(50.0)),
+            height: None,
+            vbox: None,
+        }
+    );
+}
+
 #[test]
 fn root_geometry_with_percent_viewport() {
     let svg = load_svg(
@@ -148,6 +189,65 @@ fn layer_geometry_viewport_viewbox() {
     assert_eq!((ink_r, logical_r), (rect, rect));
 }
 
+#[test]
+fn layer_geometry_ignores_paint_and_effects() {
+    // Computing geometries doesn't paint anything, so paint servers,
+    // masks, and filters don't need to be evaluated; the bounding boxes
+    // only depend on the element's geometry and stroke.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="blur">
+      <feGaussianBlur stdDeviation="5"/>
+    </filter>
+    <mask id="mask">
+      <rect x="0" y="0" width="100" height="100" fill="white"/>
+    </mask>
+    <pattern id="pattern" width="4" height="4" patternUnits="userSpaceOnUse">
+      <rect width="2" height="2" fill="black"/>
+    </pattern>
+  </defs>
+  <rect id="foo" x="10" y="20" width="30" height="40" fill="url(#pattern)"
+        stroke="black" stroke-width="10" filter="url(#blur)" mask="url(#mask)"/>
+</svg>
+"##,
+    );
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let viewport = cairo::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 100.0,
+        height: 100.0,
+    };
+
+    let (ink_r, logical_r) = renderer
+        .geometry_for_layer(Some("#foo"), &viewport)
+        .unwrap();
+
+    assert_eq!(
+        ink_r,
+        cairo::Rectangle {
+            x: 5.0,
+            y: 15.0,
+            width: 40.0,
+            height: 50.0,
+        }
+    );
+
+    assert_eq!(
+        logical_r,
+        cairo::Rectangle {
+            x: 10.0,
+            y: 20.0,
+            width: 30.0,
+            height: 40.0,
+        }
+    );
+}
+
 #[test]
 fn layer_geometry_for_nonexistent_element() {
     let svg = load_svg(
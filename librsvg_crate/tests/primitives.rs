@@ -2,6 +2,8 @@ use cairo;
 
 mod utils;
 
+use librsvg::Severity;
+use rsvg_internals::surface_utils::iterators::Pixels;
 use rsvg_internals::surface_utils::shared_surface::{SharedImageSurface, SurfaceType};
 
 use self::utils::{compare_to_surface, load_svg, render_document, SurfaceSize};
@@ -367,3 +369,1628 @@ fn nested_masks() {
 
     compare_to_surface(&output_surf, &reference_surf, "nested_masks");
 }
+
+#[test]
+fn gradient_color_interpolation() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="20">
+  <defs>
+    <linearGradient id="srgb" color-interpolation="sRGB">
+      <stop offset="0" stop-color="black"/>
+      <stop offset="1" stop-color="white"/>
+    </linearGradient>
+    <linearGradient id="linear" color-interpolation="linearRGB">
+      <stop offset="0" stop-color="black"/>
+      <stop offset="1" stop-color="white"/>
+    </linearGradient>
+  </defs>
+  <rect x="0" y="0" width="100" height="10" fill="url(#srgb)"/>
+  <rect x="0" y="10" width="100" height="10" fill="url(#linear)"/>
+</svg>
+"#,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(100, 20),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 20.0,
+        },
+    )
+    .unwrap();
+
+    let srgb_midpoint = output_surf.get_pixel(50, 5);
+    let linear_midpoint = output_surf.get_pixel(50, 15);
+
+    // Interpolating in linear light yields a brighter midpoint than
+    // interpolating in sRGB; roughly 188 versus 128.
+    assert!(srgb_midpoint.r < 140);
+    assert!(linear_midpoint.r > 175);
+    assert_eq!(srgb_midpoint.a, 255);
+    assert_eq!(linear_midpoint.a, 255);
+}
+
+#[test]
+fn linear_gradient_reflect_mirrors_at_tile_boundaries() {
+    // Both rows should render the same reflected gradient; the second one gets
+    // its half-width tile from gradientTransform instead of from x2.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="20">
+  <defs>
+    <linearGradient id="plain" x1="0" x2="0.5" spreadMethod="reflect">
+      <stop offset="0" stop-color="black"/>
+      <stop offset="1" stop-color="white"/>
+    </linearGradient>
+    <linearGradient id="transformed" x1="0" x2="1" spreadMethod="reflect"
+                    gradientTransform="scale(0.5 1)">
+      <stop offset="0" stop-color="black"/>
+      <stop offset="1" stop-color="white"/>
+    </linearGradient>
+  </defs>
+  <rect x="0" y="0" width="100" height="10" fill="url(#plain)"/>
+  <rect x="0" y="10" width="100" height="10" fill="url(#transformed)"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(100, 20),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 20.0,
+        },
+    )
+    .unwrap();
+
+    for &y in &[5, 15] {
+        // The gradient reaches white in the middle and goes back to black.
+        assert!(output_surf.get_pixel(0, y).r < 10);
+        assert!(output_surf.get_pixel(49, y).r > 245);
+        assert!(output_surf.get_pixel(99, y).r < 10);
+
+        for x in 0..50 {
+            let left = output_surf.get_pixel(x, y);
+            let right = output_surf.get_pixel(99 - x, y);
+            assert!((i32::from(left.r) - i32::from(right.r)).abs() <= 2);
+        }
+    }
+
+    for x in 0..100 {
+        let plain = output_surf.get_pixel(x, 5);
+        let transformed = output_surf.get_pixel(x, 15);
+        assert!((i32::from(plain.r) - i32::from(transformed.r)).abs() <= 2);
+    }
+}
+
+#[test]
+fn radial_gradient_reflect_mirrors_at_tile_boundaries() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <radialGradient id="gradient" r="0.5" spreadMethod="reflect"
+                    gradientTransform="translate(0.5 0.5) scale(0.5) translate(-0.5 -0.5)">
+      <stop offset="0" stop-color="black"/>
+      <stop offset="1" stop-color="white"/>
+    </radialGradient>
+  </defs>
+  <rect x="0" y="0" width="100" height="100" fill="url(#gradient)"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    // The gradient's circle has a radius of 25 pixels; past it, the colors
+    // run back from white to black.
+    for d in 0..25 {
+        let inside = output_surf.get_pixel(50 + d, 50);
+        let outside = output_surf.get_pixel(99 - d, 50);
+        assert!((i32::from(inside.r) - i32::from(outside.r)).abs() <= 2);
+        assert_eq!(outside.a, 255);
+    }
+}
+
+#[test]
+fn shape_rendering_geometric_precision() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <path d="M 10 90 C 10 -20, 90 -20, 90 90 S 60 60, 10 90 Z" fill="black"/>
+  <path d="M 110 90 C 110 -20, 190 -20, 190 90 S 160 60, 110 90 Z" fill="black"
+        shape-rendering="geometricPrecision"/>
+</svg>
+"#,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(200, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 200.0,
+            height: 100.0,
+        },
+    )
+    .unwrap();
+
+    // Both halves of the image have the same curve; the right one is flattened
+    // with a finer tolerance, so the antialiased edges should differ somewhere.
+    let mut num_different = 0;
+
+    for y in 0..100 {
+        for x in 0..100 {
+            let left = output_surf.get_pixel(x, y);
+            let right = output_surf.get_pixel(x + 100, y);
+
+            if left != right {
+                num_different += 1;
+            }
+        }
+    }
+
+    assert!(num_different > 0);
+}
+
+#[test]
+fn text_rendering_modes_differ() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <text x="10" y="40" font-family="sans-serif" font-size="20"
+        text-rendering="optimizeSpeed">Hello</text>
+  <text x="10" y="90" font-family="sans-serif" font-size="20"
+        text-rendering="geometricPrecision">Hello</text>
+</svg>
+"#,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(200, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 200.0,
+            height: 100.0,
+        },
+    )
+    .unwrap();
+
+    // optimizeSpeed turns off antialiasing, so its glyphs only have fully opaque
+    // or fully transparent pixels; geometricPrecision has partially-covered ones.
+    let mut speed_has_partial = false;
+    let mut precision_has_partial = false;
+
+    for y in 0..50 {
+        for x in 0..200 {
+            let speed = output_surf.get_pixel(x, y);
+            let precision = output_surf.get_pixel(x, y + 50);
+
+            speed_has_partial |= speed.a != 0 && speed.a != 255;
+            precision_has_partial |= precision.a != 0 && precision.a != 255;
+        }
+    }
+
+    assert!(!speed_has_partial);
+    assert!(precision_has_partial);
+}
+
+#[test]
+fn filter_functions_match_equivalent_filter_element() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="30" y="30" width="40" height="40" fill="rgb(0, 128, 255)"
+        style="filter: blur(5px) brightness(0.8)"/>
+</svg>
+"#,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(100, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        },
+    )
+    .unwrap();
+
+    let reference = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="filter" filterUnits="userSpaceOnUse" x="0" y="0" width="100" height="100">
+      <feGaussianBlur stdDeviation="5"/>
+      <feComponentTransfer>
+        <feFuncR type="linear" slope="0.8"/>
+        <feFuncG type="linear" slope="0.8"/>
+        <feFuncB type="linear" slope="0.8"/>
+      </feComponentTransfer>
+    </filter>
+  </defs>
+  <rect x="30" y="30" width="40" height="40" fill="rgb(0, 128, 255)" filter="url(#filter)"/>
+</svg>
+"#,
+    );
+
+    let reference_surf = render_document(
+        &reference,
+        SurfaceSize(100, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        },
+    )
+    .unwrap();
+
+    compare_to_surface(
+        &output_surf,
+        &reference_surf,
+        "filter_functions_match_equivalent_filter_element",
+    );
+}
+
+#[test]
+fn backdrop_filter_blurs_background() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="0" y="0" width="50" height="100" fill="black"/>
+  <rect x="50" y="0" width="50" height="100" fill="white"/>
+  <rect x="25" y="25" width="50" height="50" fill="white" fill-opacity="0"
+        style="backdrop-filter: blur(10px)"/>
+</svg>
+"#,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(100, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        },
+    )
+    .unwrap();
+
+    // Outside the overlay, the black/white edge stays sharp.
+    assert_eq!(output_surf.get_pixel(48, 10).r, 0);
+    assert_eq!(output_surf.get_pixel(52, 10).r, 255);
+
+    // Behind the overlay, the edge is blurred.
+    let left = output_surf.get_pixel(48, 50);
+    let right = output_surf.get_pixel(52, 50);
+    assert!(left.r > 32);
+    assert!(right.r < 224);
+    assert_eq!(left.a, 255);
+    assert_eq!(right.a, 255);
+}
+
+#[test]
+fn nested_svg_overflow_auto_clips() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <svg x="25" y="25" width="50" height="50" overflow="auto">
+    <rect x="-25" y="-25" width="100" height="100" fill="black"/>
+  </svg>
+</svg>
+"#,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(100, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        },
+    )
+    .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf);
+
+        cr.rectangle(25.0, 25.0, 50.0, 50.0);
+        cr.set_source_rgba(0.0, 0.0, 0.0, 1.0);
+        cr.fill();
+    }
+
+    let reference_surf = SharedImageSurface::wrap(reference_surf, SurfaceType::SRgb).unwrap();
+
+    compare_to_surface(
+        &output_surf,
+        &reference_surf,
+        "nested_svg_overflow_auto_clips",
+    );
+}
+
+#[test]
+fn nested_svg_overflow_clip_clips() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <svg x="25" y="25" width="50" height="50" style="overflow: clip">
+    <rect x="-25" y="-25" width="100" height="100" fill="blue"/>
+  </svg>
+</svg>
+"#,
+    );
+
+    compare_to_surface(
+        &render_100x100(&svg),
+        &reference_square(25.0, 25.0, 50.0),
+        "nested_svg_overflow_clip_clips",
+    );
+}
+
+#[test]
+fn use_of_symbol_inherits_from_use_not_from_symbol_ancestors() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="100" height="100">
+  <defs>
+    <g fill="blue">
+      <symbol id="square">
+        <rect x="25" y="25" width="50" height="50"/>
+      </symbol>
+    </g>
+  </defs>
+  <use xlink:href="#square" fill="red"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(100, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        },
+    )
+    .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf);
+
+        cr.rectangle(25.0, 25.0, 50.0, 50.0);
+        cr.set_source_rgba(1.0, 0.0, 0.0, 1.0);
+        cr.fill();
+    }
+
+    let reference_surf = SharedImageSurface::wrap(reference_surf, SurfaceType::SRgb).unwrap();
+
+    compare_to_surface(
+        &output_surf,
+        &reference_surf,
+        "use_of_symbol_inherits_from_use_not_from_symbol_ancestors",
+    );
+}
+
+#[test]
+fn use_does_not_propagate_non_inherited_properties() {
+    // If the opacity of the <use> were copied to the referenced element, it
+    // would be applied twice and the result would be 25% opaque.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="100" height="100">
+  <defs>
+    <rect id="square" x="25" y="25" width="50" height="50"/>
+  </defs>
+  <use xlink:href="#square" fill="lime" opacity="0.5"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(100, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        },
+    )
+    .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf);
+
+        cr.rectangle(25.0, 25.0, 50.0, 50.0);
+        cr.set_source_rgba(0.0, 1.0, 0.0, 0.5);
+        cr.fill();
+    }
+
+    let reference_surf = SharedImageSurface::wrap(reference_surf, SurfaceType::SRgb).unwrap();
+
+    compare_to_surface(
+        &output_surf,
+        &reference_surf,
+        "use_does_not_propagate_non_inherited_properties",
+    );
+}
+
+fn render_100x100(svg: &librsvg::SvgHandle) -> SharedImageSurface {
+    render_document(
+        svg,
+        SurfaceSize(100, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        },
+    )
+    .unwrap()
+}
+
+fn reference_square(x: f64, y: f64, side: f64) -> SharedImageSurface {
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf);
+
+        cr.rectangle(x, y, side, side);
+        cr.set_source_rgba(0.0, 0.0, 1.0, 1.0);
+        cr.fill();
+    }
+
+    SharedImageSurface::wrap(reference_surf, SurfaceType::SRgb).unwrap()
+}
+
+#[test]
+fn symbol_viewbox_is_scaled_to_use_size() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="100" height="100">
+  <symbol id="square" viewBox="0 0 100 100">
+    <rect x="20" y="20" width="60" height="60" fill="blue"/>
+  </symbol>
+  <use xlink:href="#square" x="10" y="10" width="50" height="50"/>
+</svg>
+"##,
+    );
+
+    compare_to_surface(
+        &render_100x100(&svg),
+        &reference_square(20.0, 20.0, 30.0),
+        "symbol_viewbox_is_scaled_to_use_size",
+    );
+}
+
+#[test]
+fn symbol_size_is_used_if_use_has_no_size() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="100" height="100">
+  <symbol id="square" viewBox="0 0 100 100" width="50" height="50">
+    <rect x="20" y="20" width="60" height="60" fill="blue"/>
+  </symbol>
+  <use xlink:href="#square" x="10" y="10"/>
+</svg>
+"##,
+    );
+
+    compare_to_surface(
+        &render_100x100(&svg),
+        &reference_square(20.0, 20.0, 30.0),
+        "symbol_size_is_used_if_use_has_no_size",
+    );
+}
+
+#[test]
+fn use_size_overrides_symbol_size() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="100" height="100">
+  <symbol id="square" viewBox="0 0 100 100" width="50" height="50">
+    <rect x="20" y="20" width="60" height="60" fill="blue"/>
+  </symbol>
+  <use xlink:href="#square" width="100" height="100"/>
+</svg>
+"##,
+    );
+
+    compare_to_surface(
+        &render_100x100(&svg),
+        &reference_square(20.0, 20.0, 60.0),
+        "use_size_overrides_symbol_size",
+    );
+}
+
+fn ink_rect(svg: &librsvg::SvgHandle, id: &str) -> cairo::Rectangle {
+    let (ink_r, _) = librsvg::CairoRenderer::new(svg)
+        .geometry_for_element(Some(id))
+        .unwrap();
+    ink_r
+}
+
+#[test]
+fn xml_space_preserve_keeps_leading_spaces() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <text id="default" x="10" y="40" font-size="20">   AB</text>
+  <text id="preserve" x="10" y="80" font-size="20" xml:space="preserve">   AB</text>
+</svg>
+"#,
+    );
+
+    let default = ink_rect(&svg, "#default");
+    let preserve = ink_rect(&svg, "#preserve");
+
+    assert!(preserve.x > default.x + 5.0);
+    assert!((preserve.width - default.width).abs() < 1.0);
+}
+
+#[test]
+fn xml_space_of_use_applies_to_instanced_text() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="200" height="100">
+  <defs>
+    <text id="text" x="10" y="40" font-size="20">   AB</text>
+  </defs>
+  <use id="default" xlink:href="#text"/>
+  <use id="preserve" xlink:href="#text" xml:space="preserve"/>
+</svg>
+"##,
+    );
+
+    // Query the elements more than once, to check that the normalized text
+    // of one instance doesn't leak into the other one.
+    let default = ink_rect(&svg, "#default");
+    let preserve = ink_rect(&svg, "#preserve");
+
+    assert!(preserve.x > default.x + 5.0);
+    assert_eq!(ink_rect(&svg, "#default"), default);
+    assert_eq!(ink_rect(&svg, "#preserve"), preserve);
+}
+
+#[test]
+fn path_length_scales_stroke_dasharray() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <line x1="0" y1="25" x2="100" y2="25" pathLength="10"
+        stroke="blue" stroke-width="50" stroke-dasharray="5"/>
+</svg>
+"#,
+    );
+
+    compare_to_surface(
+        &render_100x100(&svg),
+        &reference_square(0.0, 0.0, 50.0),
+        "path_length_scales_stroke_dasharray",
+    );
+}
+
+#[test]
+fn zero_path_length_is_ignored() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <line x1="0" y1="25" x2="100" y2="25" pathLength="0"
+        stroke="blue" stroke-width="50" stroke-dasharray="50"/>
+</svg>
+"#,
+    );
+
+    compare_to_surface(
+        &render_100x100(&svg),
+        &reference_square(0.0, 0.0, 50.0),
+        "zero_path_length_is_ignored",
+    );
+}
+
+#[test]
+fn font_size_adjust_changes_text_size() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="400" height="200">
+  <text id="plain" x="10" y="50" font-family="sans-serif" font-size="20">xxxx</text>
+  <text id="small" x="10" y="100" font-family="sans-serif" font-size="20" font-size-adjust="0.25">xxxx</text>
+  <text id="large" x="10" y="150" font-family="sans-serif" font-size="20" font-size-adjust="1.0">xxxx</text>
+</svg>
+"#,
+    );
+
+    let plain = ink_rect(&svg, "#plain");
+    let small = ink_rect(&svg, "#small");
+    let large = ink_rect(&svg, "#large");
+
+    // The x-height is 5 and 20 pixels, respectively
+    assert!((small.height - 5.0).abs() < 1.5);
+    assert!((large.height - 20.0).abs() < 1.5);
+
+    assert!(small.width < plain.width);
+    assert!(large.width > plain.width);
+}
+
+fn render_200x100(svg: &librsvg::SvgHandle) -> SharedImageSurface {
+    render_document(
+        svg,
+        SurfaceSize(200, 100),
+        |_cr| (),
+        cairo::Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 200.0,
+            height: 100.0,
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn unicode_bidi_override_reverses_latin_text() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <text x="10" y="50" font-family="sans-serif" font-size="30"
+        direction="rtl" unicode-bidi="bidi-override">ABC</text>
+</svg>
+"#,
+    );
+
+    let reference = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <text x="10" y="50" font-family="sans-serif" font-size="30">CBA</text>
+</svg>
+"#,
+    );
+
+    compare_to_surface(
+        &render_200x100(&svg),
+        &render_200x100(&reference),
+        "unicode_bidi_override_reverses_latin_text",
+    );
+}
+
+#[test]
+fn unicode_bidi_embed_lays_out_neutrals_in_embedding_direction() {
+    // In a right-to-left embedding, the trailing "!" goes to the left of the Latin run
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <text x="10" y="50" font-family="sans-serif" font-size="30"
+        direction="rtl" unicode-bidi="embed">AB!</text>
+</svg>
+"#,
+    );
+
+    let reference = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <text x="10" y="50" font-family="sans-serif" font-size="30">!AB</text>
+</svg>
+"#,
+    );
+
+    compare_to_surface(
+        &render_200x100(&svg),
+        &render_200x100(&reference),
+        "unicode_bidi_embed_lays_out_neutrals_in_embedding_direction",
+    );
+}
+
+#[test]
+fn vertical_text_flows_downwards() {
+    let svg = load_svg(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"200\" height=\"400\">
+  <text id=\"short\" x=\"50\" y=\"20\" font-size=\"30\" writing-mode=\"vertical-rl\">日</text>
+  <text id=\"long\" x=\"100\" y=\"20\" font-size=\"30\" writing-mode=\"vertical-rl\">日本語</text>
+  <text id=\"lr\" x=\"150\" y=\"20\" font-size=\"30\" writing-mode=\"vertical-lr\">日本語</text>
+</svg>
+"
+        .as_bytes(),
+    );
+
+    let short = ink_rect(&svg, "#short");
+    let long = ink_rect(&svg, "#long");
+    let lr = ink_rect(&svg, "#lr");
+
+    // Both start at the same y, but the longer one extends further down
+    assert!((short.y - long.y).abs() < 5.0);
+    assert!(long.y + long.height > short.y + short.height + 30.0);
+    assert!(long.height > long.width);
+
+    // Lines are not stacked in SVG text, so both vertical modes look the same
+    assert!((long.y - lr.y).abs() < 0.1);
+    assert!((long.height - lr.height).abs() < 0.1);
+}
+
+#[test]
+fn text_anchor_applies_along_vertical_axis() {
+    let svg = load_svg(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"200\" height=\"400\">
+  <text id=\"start\" x=\"50\" y=\"200\" font-size=\"30\" writing-mode=\"vertical-rl\">日本語</text>
+  <text id=\"middle\" x=\"100\" y=\"200\" font-size=\"30\" writing-mode=\"vertical-rl\"
+        text-anchor=\"middle\">日本語</text>
+  <text id=\"end\" x=\"150\" y=\"200\" font-size=\"30\" writing-mode=\"vertical-rl\"
+        text-anchor=\"end\">日本語</text>
+</svg>
+"
+        .as_bytes(),
+    );
+
+    let start = ink_rect(&svg, "#start");
+    let middle = ink_rect(&svg, "#middle");
+    let end = ink_rect(&svg, "#end");
+
+    assert!(start.y >= 195.0);
+    assert!((middle.y + middle.height / 2.0 - 200.0).abs() < 10.0);
+    assert!(end.y + end.height <= 205.0);
+
+    // The anchor only moves the text vertically
+    assert!((start.x + 50.0 - middle.x).abs() < 0.1);
+    assert!((middle.x + 50.0 - end.x).abs() < 0.1);
+}
+
+fn assert_fully_transparent(surf: &SharedImageSurface) {
+    for y in 0..surf.height() as u32 {
+        for x in 0..surf.width() as u32 {
+            assert_eq!(
+                surf.get_pixel(x, y).a,
+                0,
+                "pixel at ({}, {}) is not transparent",
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[test]
+fn display_none_group_renders_nothing() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <marker id="marker" markerWidth="20" markerHeight="20" markerUnits="userSpaceOnUse">
+      <rect x="0" y="0" width="20" height="20" fill="blue"/>
+    </marker>
+  </defs>
+  <g display="none">
+    <rect x="10" y="10" width="80" height="80" fill="blue"/>
+    <path d="M 20 20 L 80 80" stroke="blue" marker-start="url(#marker)" marker-end="url(#marker)"/>
+    <g display="inline">
+      <rect x="10" y="10" width="80" height="80" fill="blue"/>
+    </g>
+  </g>
+</svg>
+"##,
+    );
+
+    assert_fully_transparent(&render_100x100(&svg));
+}
+
+#[test]
+fn defs_children_are_not_rendered_directly() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs display="inline" style="display: inline !important; visibility: visible">
+    <rect id="square" x="10" y="10" width="80" height="80" fill="blue"
+          display="inline" visibility="visible"/>
+    <g display="inline">
+      <rect x="10" y="10" width="80" height="80" fill="blue"/>
+    </g>
+  </defs>
+</svg>
+"##,
+    );
+
+    assert_fully_transparent(&render_100x100(&svg));
+
+    // The same element can still be instanced from outside the defs.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="100" height="100">
+  <defs display="inline">
+    <rect id="square" x="10" y="10" width="80" height="80" fill="blue"/>
+  </defs>
+  <use xlink:href="#square"/>
+</svg>
+"##,
+    );
+
+    compare_to_surface(
+        &render_100x100(&svg),
+        &reference_square(10.0, 10.0, 80.0),
+        "defs_children_are_not_rendered_directly",
+    );
+}
+
+#[test]
+fn use_of_display_none_element_renders_nothing() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="100" height="100">
+  <rect id="hidden" x="10" y="10" width="80" height="80" fill="blue" display="none"/>
+  <g display="none">
+    <rect id="inside-hidden-group" x="10" y="10" width="80" height="80" fill="blue"/>
+  </g>
+  <use xlink:href="#hidden"/>
+  <use xlink:href="#inside-hidden-group" display="none"/>
+</svg>
+"##,
+    );
+
+    assert_fully_transparent(&render_100x100(&svg));
+}
+
+#[test]
+fn display_none_tspan_takes_no_space() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <text id="with-hidden" x="10" y="40" font-size="20">A<tspan display="none">BBBB</tspan>C</text>
+  <text id="without" x="10" y="80" font-size="20">AC</text>
+</svg>
+"#,
+    );
+
+    let with_hidden = ink_rect(&svg, "#with-hidden");
+    let without = ink_rect(&svg, "#without");
+
+    assert!((with_hidden.x - without.x).abs() < 0.1);
+    assert!((with_hidden.width - without.width).abs() < 0.1);
+}
+
+#[test]
+fn hatch_with_rotation_paints_diagonal_stripes() {
+    // With rotate="45" the hatch lines run along x + y = constant, and they are
+    // repeated every 20 * sqrt(2) ≈ 28.28 units along the x + y axis.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <hatch id="hatch" hatchUnits="userSpaceOnUse" pitch="20" rotate="45">
+      <hatchpath stroke="black" stroke-width="4"/>
+    </hatch>
+  </defs>
+  <rect x="0" y="0" width="100" height="100" fill="url(#hatch)"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    // Pixel centers are at (x + 0.5, y + 0.5), so they lie on x + y + 1 = constant.
+    for &(sum, opaque) in &[(28, true), (42, false), (57, true), (71, false)] {
+        for x in (sum - 90).max(5)..(sum - 5).min(95) {
+            let y = sum - 1 - x;
+            let pixel = output_surf.get_pixel(x as u32, y as u32);
+
+            if opaque {
+                assert!(
+                    pixel.a > 200,
+                    "pixel at ({}, {}) should be in a stripe",
+                    x,
+                    y
+                );
+            } else {
+                assert!(
+                    pixel.a < 10,
+                    "pixel at ({}, {}) should be between stripes",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn hatch_path_is_repeated_by_its_own_height() {
+    // The hatch path is only 5 units tall, so it needs to be repeated four
+    // times within each 20-unit tile to make up a continuous line.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <hatch id="hatch" hatchUnits="userSpaceOnUse" pitch="20">
+      <hatchpath d="M 10 0 L 10 5" stroke="black" stroke-width="2"/>
+    </hatch>
+  </defs>
+  <rect x="0" y="0" width="100" height="100" fill="url(#hatch)"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    for y in 0..100 {
+        assert!(
+            output_surf.get_pixel(10, y).a > 200,
+            "pixel at (10, {}) should be in a hatch line",
+            y
+        );
+        assert_eq!(output_surf.get_pixel(0, y).a, 0);
+    }
+}
+
+#[test]
+fn mesh_gradient_interpolates_between_corner_colors() {
+    // A 2x2 mesh where red goes from 0 to 255 left to right, green goes from 0
+    // to 255 top to bottom, and blue is only present at the center vertex.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <meshgradient id="mesh" x="0" y="0" gradientUnits="userSpaceOnUse">
+      <meshrow>
+        <meshpatch>
+          <stop path="l 50,0" stop-color="rgb(0,0,0)"/>
+          <stop path="l 0,50" stop-color="rgb(128,0,0)"/>
+          <stop path="l -50,0" stop-color="rgb(128,128,255)"/>
+          <stop path="l 0,-50" stop-color="rgb(0,128,0)"/>
+        </meshpatch>
+        <meshpatch>
+          <stop path="l 50,0"/>
+          <stop path="l 0,50" stop-color="rgb(255,0,0)"/>
+          <stop path="l -50,0" stop-color="rgb(255,128,0)"/>
+        </meshpatch>
+      </meshrow>
+      <meshrow>
+        <meshpatch>
+          <stop path="l 0,50"/>
+          <stop path="l -50,0" stop-color="rgb(128,255,0)"/>
+          <stop path="l 0,-50" stop-color="rgb(0,255,0)"/>
+        </meshpatch>
+        <meshpatch>
+          <stop path="l 0,50"/>
+          <stop path="l -50,0" stop-color="rgb(255,255,0)"/>
+        </meshpatch>
+      </meshrow>
+    </meshgradient>
+  </defs>
+  <rect x="0" y="0" width="100" height="100" fill="url(#mesh)"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    let close = |a: u8, b: u8| (i32::from(a) - i32::from(b)).abs() <= 8;
+
+    for &(x, y, r, g, b) in &[
+        (0, 0, 0, 0, 0),
+        (99, 0, 255, 0, 0),
+        (0, 99, 0, 255, 0),
+        (99, 99, 255, 255, 0),
+        (50, 50, 128, 128, 255),
+    ] {
+        let pixel = output_surf.get_pixel(x, y);
+        assert!(
+            close(pixel.r, r) && close(pixel.g, g) && close(pixel.b, b) && pixel.a == 255,
+            "unexpected color {:?} at ({}, {})",
+            pixel,
+            x,
+            y
+        );
+    }
+
+    // No jumps between neighboring pixels, not even across patch boundaries
+    for i in 0..100 {
+        for j in 1..100 {
+            for &(p, q) in &[
+                (output_surf.get_pixel(j - 1, i), output_surf.get_pixel(j, i)),
+                (output_surf.get_pixel(i, j - 1), output_surf.get_pixel(i, j)),
+            ] {
+                assert!(close(p.r, q.r) && close(p.g, q.g) && close(p.b, q.b));
+            }
+        }
+    }
+}
+
+#[test]
+fn stroke_dasharray_none_is_solid() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <g stroke="black" stroke-width="10" stroke-dasharray="5">
+    <path d="M 10 15 L 90 15"/>
+    <path d="M 10 40 L 90 40" stroke-dasharray="none"/>
+    <path d="M 10 65 L 90 65" stroke-dasharray="0"/>
+    <path d="M 10 90 L 90 90" stroke-dasharray="0 0"/>
+  </g>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    // Inherited dashes leave gaps
+    assert!((10..90).any(|x| output_surf.get_pixel(x, 15).a == 0));
+
+    // "none" and dash arrays that add up to zero produce a solid stroke
+    for &y in &[40, 65, 90] {
+        for x in 10..90 {
+            assert_eq!(
+                output_surf.get_pixel(x, y).a,
+                255,
+                "gap in stroke at ({}, {})",
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[test]
+fn baseline_shift_percentage_refers_to_font_size() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <text id="default" x="10" y="50" font-size="20px">X</text>
+  <text id="shifted" x="110" y="50" font-size="20px" baseline-shift="50%">X</text>
+</svg>
+"#,
+    );
+
+    let default = ink_rect(&svg, "#default");
+    let shifted = ink_rect(&svg, "#shifted");
+
+    assert!((default.y - shifted.y - 10.0).abs() < 0.1);
+    assert!((default.height - shifted.height).abs() < 0.1);
+}
+
+#[test]
+fn letter_spacing_normal_adds_no_spacing() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="300" height="200">
+  <text id="default" x="10" y="40" font-size="20">AVAVAV</text>
+  <text id="normal" x="10" y="80" font-size="20" letter-spacing="normal">AVAVAV</text>
+  <text id="zero" x="10" y="120" font-size="20" letter-spacing="0">AVAVAV</text>
+  <text id="spaced" x="10" y="160" font-size="20" letter-spacing="10">AVAVAV</text>
+</svg>
+"#,
+    );
+
+    let default = ink_rect(&svg, "#default");
+    let normal = ink_rect(&svg, "#normal");
+    let zero = ink_rect(&svg, "#zero");
+    let spaced = ink_rect(&svg, "#spaced");
+
+    assert!((normal.width - default.width).abs() < 0.1);
+    assert!((zero.width - normal.width).abs() < 1.0);
+    assert!(spaced.width > normal.width + 40.0);
+}
+
+#[test]
+fn polyline_with_invalid_points_renders_points_before_error() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <polyline points="10,50 90,50 90,BAD 10,90" fill="none" stroke="black" stroke-width="10"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    for x in 10..90 {
+        assert_eq!(output_surf.get_pixel(x, 50).a, 255);
+    }
+
+    assert_eq!(output_surf.get_pixel(50, 80).a, 0);
+}
+
+#[test]
+fn shapes_with_negative_dimensions_are_in_error() {
+    let mut svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="10" y="10" width="-50" height="50" fill="blue"/>
+  <rect x="10" y="10" width="50" height="-50" fill="blue"/>
+  <circle cx="50" cy="50" r="-20" fill="blue"/>
+  <ellipse cx="50" cy="50" rx="-20" ry="10" fill="blue"/>
+  <ellipse cx="50" cy="50" rx="20" ry="-10" fill="blue"/>
+</svg>
+"##,
+    );
+
+    let diagnostics = svg.take_diagnostics();
+    assert_eq!(diagnostics.len(), 5);
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+
+    assert_fully_transparent(&render_100x100(&svg));
+}
+
+#[test]
+fn svg_with_negative_dimensions_is_in_error() {
+    let mut svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <svg width="-50" height="50">
+    <rect width="50" height="50" fill="blue"/>
+  </svg>
+  <svg width="50" height="-50">
+    <rect width="50" height="50" fill="blue"/>
+  </svg>
+  <svg width="50" height="50" viewBox="0 0 -50 50">
+    <rect width="50" height="50" fill="blue"/>
+  </svg>
+</svg>
+"##,
+    );
+
+    let diagnostics = svg.take_diagnostics();
+    assert_eq!(diagnostics.len(), 3);
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+
+    assert_fully_transparent(&render_100x100(&svg));
+}
+
+#[test]
+fn svg_with_zero_dimensions_is_not_rendered() {
+    // These are not errors, but they disable rendering of the element, even
+    // when its overflow is visible.
+    let mut svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <svg width="0" height="50" overflow="visible">
+    <rect width="50" height="50" fill="blue"/>
+  </svg>
+  <svg width="50" height="0" overflow="visible">
+    <rect width="50" height="50" fill="blue"/>
+  </svg>
+  <svg width="50" height="50" viewBox="0 0 0 50" overflow="visible">
+    <rect width="50" height="50" fill="blue"/>
+  </svg>
+</svg>
+"##,
+    );
+
+    assert!(svg.take_diagnostics().is_empty());
+    assert_fully_transparent(&render_100x100(&svg));
+
+    let toplevel = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="0" height="0">
+  <rect width="50" height="50" fill="blue"/>
+</svg>
+"##,
+    );
+
+    assert_fully_transparent(&render_100x100(&toplevel));
+}
+
+#[test]
+fn line_markers_are_oriented_along_the_line() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <marker id="arrow" markerWidth="10" markerHeight="10" refX="0" refY="5"
+            orient="auto" markerUnits="userSpaceOnUse">
+      <rect x="0" y="0" width="10" height="10" fill="#0000ff"/>
+    </marker>
+  </defs>
+  <line x1="50" y1="10" x2="50" y2="90" stroke="black" stroke-width="2"
+        marker-start="url(#arrow)" marker-end="url(#arrow)"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    // The line points down, so the markers' x axis is rotated to point down as well:
+    // each marker covers x in [45, 55] and 10 units in y starting from its endpoint.
+    for &(x, y) in &[(46, 11), (53, 18), (46, 91), (53, 98)] {
+        let pixel = output_surf.get_pixel(x, y);
+        assert_eq!(
+            (pixel.r, pixel.g, pixel.b, pixel.a),
+            (0, 0, 255, 255),
+            "no marker at ({}, {})",
+            x,
+            y
+        );
+    }
+
+    // Where unrotated markers would be drawn
+    for &(x, y) in &[(58, 6), (58, 86)] {
+        assert_eq!(output_surf.get_pixel(x, y).a, 0);
+    }
+}
+
+#[test]
+fn ellipses_with_zero_radii_are_not_rendered() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <g fill="blue" stroke="black" stroke-width="10" stroke-linecap="round">
+    <ellipse cx="50" cy="50" rx="0" ry="30"/>
+    <ellipse cx="50" cy="50" rx="30" ry="0"/>
+    <ellipse cx="50" cy="50" rx="0" ry="0"/>
+    <circle cx="50" cy="50" r="0"/>
+  </g>
+</svg>
+"##,
+    );
+
+    assert_fully_transparent(&render_100x100(&svg));
+}
+
+#[test]
+fn clip_path_with_text_clips_to_glyph_outlines() {
+    let clipped = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="100" height="100">
+  <defs>
+    <text id="letter" x="50" y="80" font-size="80" text-anchor="middle">R</text>
+    <clipPath id="text_clip" clipPathUnits="userSpaceOnUse">
+      <text x="50" y="80" font-size="80" text-anchor="middle">R</text>
+    </clipPath>
+    <clipPath id="use_clip" clipPathUnits="userSpaceOnUse">
+      <use xlink:href="#letter"/>
+    </clipPath>
+  </defs>
+  <rect x="0" y="0" width="100" height="100" fill="#0000ff" clip-path="url(#text_clip)"/>
+  <rect x="0" y="0" width="100" height="100" fill="#0000ff" clip-path="url(#use_clip)"/>
+</svg>
+"##,
+    );
+
+    let reference = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <text x="50" y="80" font-size="80" text-anchor="middle" fill="#0000ff">R</text>
+  <text x="50" y="80" font-size="80" text-anchor="middle" fill="#0000ff">R</text>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&clipped);
+    let reference_surf = render_100x100(&reference);
+
+    // The glyph must cover some pixels, but not its whole bounding box
+    assert!(Pixels::new(&output_surf).any(|(_, _, p)| p.a == 255));
+    assert_eq!(output_surf.get_pixel(1, 1).a, 0);
+
+    compare_to_surface(
+        &output_surf,
+        &reference_surf,
+        "clip_path_with_text_clips_to_glyph_outlines",
+    );
+}
+
+#[test]
+fn clip_rule_determines_the_clip_region() {
+    // The star's inner pentagon is outside the clip region with evenodd, and
+    // inside with nonzero.  The fill-rule of the clip path's children is ignored.
+    let evenodd = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <clipPath id="evenodd">
+    <path d="M50,5 L79,95 L2,40 L98,40 L21,95 Z" clip-rule="evenodd" fill-rule="nonzero"/>
+  </clipPath>
+  <rect width="100" height="100" fill="blue" clip-path="url(#evenodd)"/>
+</svg>
+"##,
+    );
+
+    let nonzero = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <clipPath id="nonzero">
+    <path d="M50,5 L79,95 L2,40 L98,40 L21,95 Z" clip-rule="nonzero" fill-rule="evenodd"/>
+  </clipPath>
+  <rect width="100" height="100" fill="blue" clip-path="url(#nonzero)"/>
+</svg>
+"##,
+    );
+
+    let evenodd_surf = render_100x100(&evenodd);
+    let nonzero_surf = render_100x100(&nonzero);
+
+    // A point in the top arm of the star.
+    assert_eq!(evenodd_surf.get_pixel(50, 20).a, 255);
+    assert_eq!(nonzero_surf.get_pixel(50, 20).a, 255);
+
+    // A point in the inner pentagon.
+    assert_eq!(evenodd_surf.get_pixel(50, 55).a, 0);
+    assert_eq!(nonzero_surf.get_pixel(50, 55).a, 255);
+}
+
+#[test]
+fn mask_with_image_uses_image_luminance() {
+    // The image is 4x4 pixels; its left half is black and its right half is white.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="100" height="100">
+  <mask id="mask">
+    <image x="0" y="0" width="100" height="100" preserveAspectRatio="none"
+           xlink:href="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAQAAAAECAIAAAAmkwkpAAAAEUlEQVR4nGNgAIP/YMBAHAcAJioX6a0EP9wAAAAASUVORK5CYII="/>
+  </mask>
+  <rect x="0" y="0" width="100" height="100" fill="#ff0000" mask="url(#mask)"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    for y in &[10, 50, 90] {
+        assert_eq!(output_surf.get_pixel(30, *y).a, 0);
+
+        let pixel = output_surf.get_pixel(70, *y);
+        assert_eq!((pixel.r, pixel.g, pixel.b, pixel.a), (255, 0, 0, 255));
+    }
+}
+
+#[test]
+fn pattern_overflow_visible_bleeds_into_neighboring_tiles() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <pattern id="hidden" x="0" y="0" width="20" height="20" patternUnits="userSpaceOnUse">
+    <circle cx="5" cy="5" r="8" fill="#0000ff"/>
+  </pattern>
+  <pattern id="visible" x="0" y="0" width="20" height="20" patternUnits="userSpaceOnUse"
+           overflow="visible">
+    <circle cx="5" cy="5" r="8" fill="#0000ff"/>
+  </pattern>
+  <rect x="0" y="0" width="100" height="50" fill="url(#hidden)"/>
+  <rect x="0" y="50" width="100" height="50" fill="url(#visible)"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    // Inside the circle in its own tile
+    assert_eq!(output_surf.get_pixel(25, 25).a, 255);
+    assert_eq!(output_surf.get_pixel(25, 65).a, 255);
+
+    // Where the circle sticks out of the left and top sides of its tile
+    for &(x, y) in &[(38, 25), (25, 38)] {
+        assert_eq!(output_surf.get_pixel(x, y).a, 0);
+        assert_eq!(output_surf.get_pixel(x, y + 40).a, 255);
+    }
+}
+
+#[test]
+fn use_x_y_are_applied_after_transform() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="100" height="100">
+  <defs>
+    <path id="shape" d="M 0 0 L 20 0 L 0 10 z" fill="#0000ff"/>
+  </defs>
+  <use xlink:href="#shape" x="10" y="20"/>
+  <use xlink:href="#shape" x="10" y="20" transform="translate(40, 0) scale(2)"/>
+</svg>
+"##,
+    );
+
+    let reference = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <g transform="translate(10, 20)">
+    <path d="M 0 0 L 20 0 L 0 10 z" fill="#0000ff"/>
+  </g>
+  <g transform="translate(40, 0) scale(2) translate(10, 20)">
+    <path d="M 0 0 L 20 0 L 0 10 z" fill="#0000ff"/>
+  </g>
+</svg>
+"##,
+    );
+
+    compare_to_surface(
+        &render_100x100(&svg),
+        &render_100x100(&reference),
+        "use_x_y_are_applied_after_transform",
+    );
+}
+
+#[test]
+fn user_space_gradient_uses_transformed_user_space() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <linearGradient id="gradient" gradientUnits="userSpaceOnUse" x1="0" y1="0" x2="50" y2="0">
+    <stop offset="0" stop-color="#000000"/>
+    <stop offset="1" stop-color="#ffffff"/>
+  </linearGradient>
+  <g transform="scale(2)">
+    <rect x="0" y="0" width="50" height="50" fill="url(#gradient)"/>
+  </g>
+</svg>
+"##,
+    );
+
+    let reference = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <linearGradient id="gradient" gradientUnits="userSpaceOnUse" x1="0" y1="0" x2="100" y2="0">
+    <stop offset="0" stop-color="#000000"/>
+    <stop offset="1" stop-color="#ffffff"/>
+  </linearGradient>
+  <rect x="0" y="0" width="100" height="100" fill="url(#gradient)"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    // The gradient is stretched along with the rectangle, instead of being
+    // repeated/padded after half of it.
+    assert!(output_surf.get_pixel(75, 50).r < 224);
+
+    compare_to_surface(
+        &output_surf,
+        &render_100x100(&reference),
+        "user_space_gradient_uses_transformed_user_space",
+    );
+}
+
+#[test]
+fn bounding_box_gradient_on_zero_height_element_paints_average_stop_color() {
+    // A horizontal line has a zero-height bounding box, so the gradient's
+    // objectBoundingBox units cannot be used for its stroke.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <linearGradient id="gradient">
+    <stop offset="0" stop-color="#ff0000"/>
+    <stop offset="1" stop-color="#0000ff"/>
+  </linearGradient>
+  <line x1="10" y1="50" x2="90" y2="50" stroke="url(#gradient)" stroke-width="20"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    for &x in &[20, 50, 80] {
+        let pixel = output_surf.get_pixel(x, 50);
+
+        assert!(pixel.r > 120 && pixel.r < 135);
+        assert_eq!(pixel.g, 0);
+        assert!(pixel.b > 120 && pixel.b < 135);
+        assert_eq!(pixel.a, 255);
+    }
+}
+
+#[test]
+fn bounding_box_gradient_on_zero_height_element_uses_fallback_color() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <linearGradient id="gradient">
+    <stop offset="0" stop-color="#0000ff"/>
+    <stop offset="1" stop-color="#00ff00"/>
+  </linearGradient>
+  <line x1="10" y1="50" x2="90" y2="50" stroke="url(#gradient) #ff0000" stroke-width="20"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    let pixel = output_surf.get_pixel(50, 50);
+    assert_eq!((pixel.r, pixel.g, pixel.b, pixel.a), (255, 0, 0, 255));
+}
+
+#[test]
+fn average_stop_color_ignores_color_of_transparent_stops() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <linearGradient id="gradient">
+    <stop offset="0" stop-color="#ff0000"/>
+    <stop offset="1" stop-color="#0000ff" stop-opacity="0"/>
+  </linearGradient>
+  <line x1="10" y1="50" x2="90" y2="50" stroke="url(#gradient)" stroke-width="20"/>
+</svg>
+"##,
+    );
+
+    let output_surf = render_100x100(&svg);
+
+    // Half-transparent red, with premultiplied alpha.
+    let pixel = output_surf.get_pixel(50, 50);
+    assert!(pixel.a > 120 && pixel.a < 135);
+    assert!(pixel.r >= pixel.a - 1 && pixel.r <= pixel.a + 1);
+    assert_eq!(pixel.g, 0);
+    assert_eq!(pixel.b, 0);
+}
+
+// Sum of the channel differences between two adjacent pixel columns.
+fn column_difference(surf: &SharedImageSurface, x: u32) -> u32 {
+    let diff = |a: u8, b: u8| u32::from(if a > b { a - b } else { b - a });
+
+    (0..surf.height() as u32)
+        .map(|y| {
+            let p = surf.get_pixel(x, y);
+            let q = surf.get_pixel(x + 1, y);
+
+            diff(p.r, q.r) + diff(p.g, q.g) + diff(p.b, q.b) + diff(p.a, q.a)
+        })
+        .sum()
+}
+
+macro_rules! turbulence_tiles_svg {
+    ($stitch_tiles:expr) => {
+        concat!(
+            r##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <filter id="filter" filterUnits="userSpaceOnUse" x="0" y="0" width="50" height="50">
+    <feTurbulence baseFrequency="0.05" numOctaves="1" stitchTiles=""##,
+            $stitch_tiles,
+            r##""/>
+  </filter>
+  <pattern id="pattern" patternUnits="userSpaceOnUse" width="50" height="50">
+    <rect width="50" height="50" filter="url(#filter)"/>
+  </pattern>
+  <rect width="100" height="100" fill="url(#pattern)"/>
+</svg>
+"##
+        )
+        .as_bytes()
+    };
+}
+
+#[test]
+fn turbulence_stitch_tiles_makes_seams_continuous() {
+    let stitched = render_100x100(&load_svg(turbulence_tiles_svg!("stitch")));
+    let not_stitched = render_100x100(&load_svg(turbulence_tiles_svg!("noStitch")));
+
+    // Columns 49 and 50 are the right and left edges of adjacent tiles.
+    let stitched_seam = column_difference(&stitched, 49);
+    let stitched_interior = column_difference(&stitched, 24);
+
+    assert!(stitched_seam <= 2 * stitched_interior + 50);
+    assert!(stitched_seam < column_difference(&not_stitched, 49));
+}
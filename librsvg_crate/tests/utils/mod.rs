@@ -19,8 +19,8 @@ use self::compare_surfaces::compare_surfaces;
 
 pub use self::compare_surfaces::BufferDiff;
 
-pub fn load_svg(input: &'static [u8]) -> SvgHandle {
-    let bytes = glib::Bytes::from_static(input);
+pub fn load_svg(input: &[u8]) -> SvgHandle {
+    let bytes = glib::Bytes::from(input);
     let stream = gio::MemoryInputStream::new_from_bytes(&bytes);
 
     Loader::new()
@@ -1,8 +1,9 @@
 //! CSS angle values.
 
 use std::f64::consts::*;
+use std::fmt;
 
-use cssparser::{Parser, Token};
+use cssparser::{Parser, ToCss, Token};
 
 use crate::error::*;
 use crate::parsers::{finite_f32, Parse};
@@ -56,6 +57,17 @@ impl Angle {
     }
 }
 
+impl ToCss for Angle {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        // The value was parsed as an f32 and then converted to radians, so
+        // write it back with the same precision.
+        write!(dest, "{}rad", self.0 as f32)
+    }
+}
+
 // angle:
 // https://www.w3.org/TR/SVG/types.html#DataTypeAngle
 //
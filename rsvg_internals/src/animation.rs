@@ -0,0 +1,683 @@
+//! SMIL animation elements: `set`, and `animateMotion` with `mpath`.
+
+use cssparser::{Parser, ParserInput, Token};
+use markup5ever::{expanded_name, local_name, namespace_url, ns, LocalName, QualName};
+use std::f64::consts::PI;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::allowed_url::Fragment;
+use crate::angle::Angle;
+use crate::document::AcquiredNodes;
+use crate::element::{Draw, Element, ElementResult, SetAttributes};
+use crate::error::*;
+use crate::node::{Node, NodeBorrow};
+use crate::parsers::{finite_f32, Parse, ParseValue};
+use crate::path_builder::{Path, PathBuilder};
+use crate::path_parser;
+use crate::properties::{parse_property, ParsedProperty};
+use crate::property_bag::PropertyBag;
+use crate::transform::Transform;
+
+/// A SMIL clock value, like `2s` or `500ms`.
+///
+/// Only timecount values are supported; full and partial clock values like
+/// `01:30` are not.  A number without units is in seconds.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ClockValue(pub Duration);
+
+impl Parse for ClockValue {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<ClockValue, ParseError<'i>> {
+        let loc = parser.current_source_location();
+
+        let token = parser.next()?;
+
+        let seconds = match *token {
+            Token::Number { value, .. } => {
+                f64::from(finite_f32(value).map_err(|e| loc.new_custom_error(e))?)
+            }
+
+            Token::Dimension {
+                value, ref unit, ..
+            } => {
+                let value = f64::from(finite_f32(value).map_err(|e| loc.new_custom_error(e))?);
+
+                match unit.as_ref() {
+                    "h" => value * 3600.0,
+                    "min" => value * 60.0,
+                    "s" => value,
+                    "ms" => value / 1000.0,
+                    _ => return Err(loc.new_unexpected_token_error(token.clone())),
+                }
+            }
+
+            _ => return Err(loc.new_unexpected_token_error(token.clone())),
+        };
+
+        if seconds < 0.0 {
+            return Err(loc.new_custom_error(ValueErrorKind::value_error(
+                "clock value must be non-negative",
+            )));
+        }
+
+        // Duration::from_secs_f64() panics for values that don't fit in a Duration
+        if !seconds.is_finite() || seconds >= std::u64::MAX as f64 {
+            return Err(
+                loc.new_custom_error(ValueErrorKind::value_error("clock value is too large"))
+            );
+        }
+
+        Ok(ClockValue(Duration::from_secs_f64(seconds)))
+    }
+}
+
+/// The timing attributes that are common to all animation elements.
+#[derive(Default)]
+struct Timing {
+    begin: Duration,
+
+    /// `None` means an indefinite duration.
+    dur: Option<Duration>,
+}
+
+impl Timing {
+    /// Parses `begin` and `dur`; returns `Ok(false)` for other attributes.
+    fn parse_attribute(&mut self, attr: &QualName, value: &str) -> Result<bool, ElementError> {
+        match attr.expanded() {
+            expanded_name!("", "begin") => {
+                let ClockValue(begin) = attr.parse(value)?;
+                self.begin = begin;
+            }
+            expanded_name!("", "dur") => {
+                self.dur = if value.trim() == "indefinite" {
+                    None
+                } else {
+                    let ClockValue(dur) = attr.parse(value)?;
+                    Some(dur)
+                };
+            }
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Whether the animation is active at time `t` of the document timeline.
+    ///
+    /// The active interval includes its beginning but not its end.
+    fn is_active_at(&self, t: Duration) -> bool {
+        if t < self.begin {
+            return false;
+        }
+
+        match self.dur {
+            // An end time that is too far away to represent is never reached
+            Some(dur) => self.begin.checked_add(dur).map_or(true, |end| t < end),
+            None => true,
+        }
+    }
+
+    /// Returns how far into its duration the animation is at time `t`, from 0 to 1.
+    fn progress_at(&self, t: Duration) -> f64 {
+        match self.dur {
+            Some(dur) if t > self.begin && dur > Duration::from_secs(0) => {
+                let elapsed = (t - self.begin).as_secs_f64();
+                (elapsed / dur.as_secs_f64()).min(1.0)
+            }
+
+            _ => 0.0,
+        }
+    }
+}
+
+/// Node for the `<set>` element
+///
+/// This sets a property to the value in the `to` attribute during the active
+/// interval of the element, which starts at `begin` and lasts for `dur`.
+#[derive(Default)]
+pub struct Set {
+    attribute_name: Option<QualName>,
+    to: Option<ParsedProperty>,
+    timing: Timing,
+}
+
+impl SetAttributes for Set {
+    fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
+        let mut to = None;
+
+        for (attr, value) in pbag.iter() {
+            if self.timing.parse_attribute(&attr, value)? {
+                continue;
+            }
+
+            match attr.expanded() {
+                expanded_name!("", "attributeName") => {
+                    self.attribute_name =
+                        Some(QualName::new(None, ns!(), LocalName::from(value.trim())))
+                }
+                expanded_name!("", "to") => to = Some((attr, value)),
+                _ => (),
+            }
+        }
+
+        // The value in "to" can only be parsed once we know which property it is for.
+        if let (Some(name), Some((attr, value))) = (self.attribute_name.as_ref(), to) {
+            let mut input = ParserInput::new(value);
+            let mut parser = Parser::new(&mut input);
+
+            match parse_property(name, &mut parser, false) {
+                Ok(prop) => self.to = Some(prop),
+
+                // Only properties can be animated for now; ignore other attributes.
+                Err(ParseError {
+                    kind: cssparser::ParseErrorKind::Custom(ValueErrorKind::UnknownProperty),
+                    ..
+                }) => rsvg_log!(
+                    Warn,
+                    "(set element for unsupported attribute {})",
+                    name.local
+                ),
+
+                Err(e) => return Err(e).attribute(attr),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Draw for Set {}
+
+impl Set {
+    /// Whether the element is active at time `t` of the document timeline.
+    ///
+    /// The active interval includes its beginning but not its end.
+    pub fn is_active_at(&self, t: Duration) -> bool {
+        self.timing.is_active_at(t)
+    }
+
+    /// Returns the property value that the element sets at time `t`, or `None`
+    /// if the element is not active then.
+    pub fn apply_at(&self, t: Duration) -> Option<ParsedProperty> {
+        if self.is_active_at(t) {
+            self.to.clone()
+        } else {
+            None
+        }
+    }
+}
+
+/// A list of numbers separated by semicolons, as in `keyTimes="0; 0.5; 1"`.
+#[derive(Debug, Clone, PartialEq)]
+struct SemicolonList(Vec<f64>);
+
+impl Parse for SemicolonList {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<SemicolonList, ParseError<'i>> {
+        let mut v = vec![f64::parse(parser)?];
+
+        while !parser.is_exhausted() {
+            // Allow a trailing semicolon
+            parser.expect_semicolon()?;
+            if parser.is_exhausted() {
+                break;
+            }
+
+            v.push(f64::parse(parser)?);
+        }
+
+        Ok(SemicolonList(v))
+    }
+}
+
+fn validate_key_times(list: SemicolonList) -> Result<SemicolonList, ValueErrorKind> {
+    let SemicolonList(ref v) = list;
+
+    if v[0] != 0.0 {
+        return Err(ValueErrorKind::value_error("keyTimes must start with 0"));
+    }
+
+    if v.windows(2).any(|w| w[0] > w[1]) || v[v.len() - 1] > 1.0 {
+        return Err(ValueErrorKind::value_error(
+            "keyTimes must be increasing values between 0 and 1",
+        ));
+    }
+
+    Ok(list)
+}
+
+fn validate_key_points(list: SemicolonList) -> Result<SemicolonList, ValueErrorKind> {
+    if list.0.iter().any(|&p| p < 0.0 || p > 1.0) {
+        Err(ValueErrorKind::value_error(
+            "keyPoints must be between 0 and 1",
+        ))
+    } else {
+        Ok(list)
+    }
+}
+
+/// The `rotate` attribute of `<animateMotion>`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum MotionRotate {
+    /// Rotate along the direction of the path
+    Auto,
+
+    /// Rotate along the opposite direction of the path
+    AutoReverse,
+
+    /// Rotate by a fixed angle
+    Angle(Angle),
+}
+
+impl Default for MotionRotate {
+    fn default() -> MotionRotate {
+        MotionRotate::Angle(Angle::new(0.0))
+    }
+}
+
+impl Parse for MotionRotate {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<MotionRotate, ParseError<'i>> {
+        if parser
+            .try_parse(|p| p.expect_ident_matching("auto"))
+            .is_ok()
+        {
+            Ok(MotionRotate::Auto)
+        } else if parser
+            .try_parse(|p| p.expect_ident_matching("auto-reverse"))
+            .is_ok()
+        {
+            Ok(MotionRotate::AutoReverse)
+        } else {
+            Ok(MotionRotate::Angle(Angle::parse(parser)?))
+        }
+    }
+}
+
+/// Node for the `<animateMotion>` element
+///
+/// This moves the element along a motion path, which comes from an `<mpath>`
+/// child or from the `path` attribute.
+#[derive(Default)]
+pub struct AnimateMotion {
+    timing: Timing,
+    path: Option<Rc<Path>>,
+    key_times: Option<Vec<f64>>,
+    key_points: Option<Vec<f64>>,
+    rotate: MotionRotate,
+}
+
+impl SetAttributes for AnimateMotion {
+    fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
+        for (attr, value) in pbag.iter() {
+            if self.timing.parse_attribute(&attr, value)? {
+                continue;
+            }
+
+            match attr.expanded() {
+                expanded_name!("", "path") => {
+                    let mut builder = PathBuilder::new();
+                    if let Err(e) = path_parser::parse_path_into_builder(value, &mut builder) {
+                        // Creating a partial path is OK per the spec, as for <path>
+                        rsvg_log!(Warn, "could not parse path: {}", e);
+                    }
+                    self.path = Some(Rc::new(builder.into_path()));
+                }
+                expanded_name!("", "rotate") => self.rotate = attr.parse(value)?,
+                _ => match &*attr.local {
+                    "keyTimes" => {
+                        let SemicolonList(v) =
+                            attr.parse_and_validate(value, validate_key_times)?;
+                        self.key_times = Some(v);
+                    }
+                    "keyPoints" => {
+                        let SemicolonList(v) =
+                            attr.parse_and_validate(value, validate_key_points)?;
+                        self.key_points = Some(v);
+                    }
+                    _ => (),
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Draw for AnimateMotion {}
+
+impl AnimateMotion {
+    /// Whether the element is active at time `t` of the document timeline.
+    pub fn is_active_at(&self, t: Duration) -> bool {
+        self.timing.is_active_at(t)
+    }
+
+    /// Returns the motion path for the `<animateMotion>` element in `node`.
+    ///
+    /// An `<mpath>` child that references a `<path>` element takes precedence
+    /// over the `path` attribute.
+    pub fn motion_path(&self, node: &Node, acquired_nodes: &mut AcquiredNodes) -> Option<Rc<Path>> {
+        for child in node.children().filter(|c| c.is_element()) {
+            if let Element::Mpath(ref mpath) = *child.borrow_element() {
+                if let Some(ref link) = mpath.link {
+                    match acquired_nodes.acquire(link) {
+                        Ok(acquired) => {
+                            if let Element::Path(ref p) = *acquired.get().borrow_element() {
+                                return p.get_path();
+                            }
+
+                            rsvg_log!(Warn, "element referenced by mpath is not a path");
+                        }
+
+                        Err(e) => rsvg_log!(Warn, "could not acquire mpath reference: {}", e),
+                    }
+                }
+            }
+        }
+
+        self.path.clone()
+    }
+
+    /// Computes the transformation that moves the element along `path` at time `t`.
+    ///
+    /// This is the position at the fraction of the duration that corresponds to `t`,
+    /// or at the start of the path before `begin`, and at the end of the path after
+    /// the animation ends.  Use `is_active_at` to find whether the transformation
+    /// should apply at all.
+    pub fn transform_at(&self, path: &Path, t: Duration) -> Transform {
+        let fraction = self.key_point_at(self.timing.progress_at(t));
+
+        match path.point_at_length(path.path_length() * fraction) {
+            Some(((x, y), direction)) => {
+                let angle = match self.rotate {
+                    MotionRotate::Auto => direction,
+                    MotionRotate::AutoReverse => Angle::new(direction.radians() + PI),
+                    MotionRotate::Angle(a) => a,
+                };
+
+                Transform::new_rotate(angle).post_translate(x, y)
+            }
+
+            None => Transform::identity(),
+        }
+    }
+
+    /// Maps the progress of the animation to a fraction of the length of the
+    /// path, with the `keyTimes` and `keyPoints` attributes if they are present.
+    fn key_point_at(&self, progress: f64) -> f64 {
+        match (&self.key_times, &self.key_points) {
+            (Some(times), Some(points)) if times.len() == points.len() && times.len() > 1 => {
+                for (t, p) in times.windows(2).zip(points.windows(2)) {
+                    if progress <= t[1] {
+                        let span = t[1] - t[0];
+
+                        return if span > 0.0 {
+                            p[0] + (p[1] - p[0]) * (progress - t[0]) / span
+                        } else {
+                            p[1]
+                        };
+                    }
+                }
+
+                points[points.len() - 1]
+            }
+
+            _ => progress,
+        }
+    }
+}
+
+/// Node for the `<mpath>` element, which references the motion path for its
+/// parent `<animateMotion>`.
+#[derive(Default)]
+pub struct Mpath {
+    link: Option<Fragment>,
+}
+
+impl SetAttributes for Mpath {
+    fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
+        for (attr, value) in pbag.iter() {
+            if let expanded_name!(xlink "href") = attr.expanded() {
+                self.link = Some(Fragment::parse(value).attribute(attr)?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Draw for Mpath {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::SpecifiedValue;
+    use crate::property_defs::Visibility;
+    use std::ffi::CString;
+    use std::ptr;
+
+    fn with_attributes<T: SetAttributes + Default>(
+        attrs: &[(&str, &str)],
+    ) -> Result<T, ElementError> {
+        let attrs: Vec<(CString, CString)> = attrs
+            .iter()
+            .map(|(name, value)| (CString::new(*name).unwrap(), CString::new(*value).unwrap()))
+            .collect();
+
+        let mut v: Vec<*const libc::c_char> = Vec::new();
+
+        for (name, value) in &attrs {
+            let value_start = value.as_ptr();
+            let value_end = unsafe { value_start.add(value.as_bytes().len()) };
+
+            v.push(name.as_ptr());
+            v.push(ptr::null()); // prefix
+            v.push(ptr::null()); // uri
+            v.push(value_start);
+            v.push(value_end);
+        }
+
+        let pbag = unsafe { PropertyBag::new_from_xml2_attributes(attrs.len(), v.as_ptr()) };
+
+        let mut elt = T::default();
+        elt.set_attributes(&pbag).map(|_| elt)
+    }
+
+    fn set_with_attributes(attrs: &[(&str, &str)]) -> Result<Set, ElementError> {
+        with_attributes(attrs)
+    }
+
+    #[test]
+    fn parses_clock_values() {
+        assert_eq!(
+            ClockValue::parse_str("2"),
+            Ok(ClockValue(Duration::from_secs(2)))
+        );
+        assert_eq!(
+            ClockValue::parse_str("1.5s"),
+            Ok(ClockValue(Duration::from_millis(1500)))
+        );
+        assert_eq!(
+            ClockValue::parse_str("250ms"),
+            Ok(ClockValue(Duration::from_millis(250)))
+        );
+        assert_eq!(
+            ClockValue::parse_str("2min"),
+            Ok(ClockValue(Duration::from_secs(120)))
+        );
+        assert_eq!(
+            ClockValue::parse_str("1h"),
+            Ok(ClockValue(Duration::from_secs(3600)))
+        );
+
+        assert!(ClockValue::parse_str("").is_err());
+        assert!(ClockValue::parse_str("-1s").is_err());
+        assert!(ClockValue::parse_str("1px").is_err());
+        assert!(ClockValue::parse_str("foo").is_err());
+    }
+
+    #[test]
+    fn huge_clock_values_are_in_error() {
+        assert!(ClockValue::parse_str("1e20s").is_err());
+        assert!(ClockValue::parse_str("1e30h").is_err());
+        assert!(ClockValue::parse_str("1e38min").is_err());
+        assert!(set_with_attributes(&[("begin", "1e20s")]).is_err());
+        assert!(set_with_attributes(&[("dur", "1e30h")]).is_err());
+    }
+
+    #[test]
+    fn end_past_the_largest_duration_is_never_reached() {
+        let set = set_with_attributes(&[
+            ("attributeName", "visibility"),
+            ("to", "hidden"),
+            ("begin", "1e19s"),
+            ("dur", "1e19s"),
+        ])
+        .unwrap();
+
+        assert!(!set.is_active_at(Duration::from_secs(0)));
+        let max = Duration::from_secs(std::u64::MAX);
+
+        assert!(set.is_active_at(Duration::from_secs(10_000_000_000_000_000_000)));
+        assert!(set.is_active_at(max));
+
+        let motion =
+            with_attributes::<AnimateMotion>(&[("begin", "1e19s"), ("dur", "1e19s")]).unwrap();
+        assert!(motion.is_active_at(max));
+        assert!(motion.timing.progress_at(max) <= 1.0);
+    }
+
+    #[test]
+    fn set_is_active_between_begin_and_end() {
+        let set = set_with_attributes(&[
+            ("attributeName", "visibility"),
+            ("to", "hidden"),
+            ("begin", "1s"),
+            ("dur", "2s"),
+        ])
+        .unwrap();
+
+        assert!(!set.is_active_at(Duration::from_secs(0)));
+        assert!(set.is_active_at(Duration::from_secs(1)));
+        assert!(set.is_active_at(Duration::from_millis(1500)));
+        assert!(!set.is_active_at(Duration::from_secs(3)));
+        assert!(!set.is_active_at(Duration::from_millis(3500)));
+
+        assert!(set.apply_at(Duration::from_secs(0)).is_none());
+        assert!(set.apply_at(Duration::from_millis(3500)).is_none());
+
+        match set.apply_at(Duration::from_millis(1500)) {
+            Some(ParsedProperty::Visibility(SpecifiedValue::Specified(Visibility::Hidden))) => (),
+            _ => panic!("expected visibility to be set to hidden"),
+        }
+    }
+
+    #[test]
+    fn set_without_dur_stays_active() {
+        let set = set_with_attributes(&[
+            ("attributeName", "visibility"),
+            ("to", "hidden"),
+            ("begin", "1s"),
+        ])
+        .unwrap();
+
+        assert!(!set.is_active_at(Duration::from_millis(500)));
+        assert!(set.is_active_at(Duration::from_secs(1000)));
+
+        let set =
+            set_with_attributes(&[("attributeName", "visibility"), ("to", "hidden")]).unwrap();
+        assert!(set.is_active_at(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn set_with_invalid_value_is_in_error() {
+        assert!(set_with_attributes(&[("attributeName", "visibility"), ("to", "foo")]).is_err());
+        assert!(set_with_attributes(&[("begin", "foo")]).is_err());
+    }
+
+    #[test]
+    fn set_for_non_property_attribute_sets_nothing() {
+        let set = set_with_attributes(&[("attributeName", "x"), ("to", "10")]).unwrap();
+        assert!(set.apply_at(Duration::from_secs(0)).is_none());
+    }
+
+    fn motion_with_attributes(attrs: &[(&str, &str)]) -> AnimateMotion {
+        let mut all = vec![("path", "M 0 0 L 100 0 L 100 100"), ("dur", "1s")];
+        all.extend_from_slice(attrs);
+        with_attributes(&all).unwrap()
+    }
+
+    fn position_at(motion: &AnimateMotion, millis: u64) -> (f64, f64) {
+        let path = motion.path.clone().unwrap();
+        let t = motion.transform_at(&path, Duration::from_millis(millis));
+        t.transform_point(0.0, 0.0)
+    }
+
+    fn assert_point_eq(a: (f64, f64), b: (f64, f64)) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn parses_motion_rotate() {
+        assert_eq!(MotionRotate::parse_str("auto"), Ok(MotionRotate::Auto));
+        assert_eq!(
+            MotionRotate::parse_str("auto-reverse"),
+            Ok(MotionRotate::AutoReverse)
+        );
+        assert_eq!(
+            MotionRotate::parse_str("90"),
+            Ok(MotionRotate::Angle(Angle::from_degrees(90.0)))
+        );
+        assert!(MotionRotate::parse_str("foo").is_err());
+    }
+
+    #[test]
+    fn invalid_key_times_are_in_error() {
+        assert!(with_attributes::<AnimateMotion>(&[("keyTimes", "0; 0.5; 1")]).is_ok());
+        assert!(with_attributes::<AnimateMotion>(&[("keyTimes", "0.5; 1")]).is_err());
+        assert!(with_attributes::<AnimateMotion>(&[("keyTimes", "0; 1; 0.5")]).is_err());
+        assert!(with_attributes::<AnimateMotion>(&[("keyPoints", "0; 2")]).is_err());
+    }
+
+    #[test]
+    fn moves_along_the_path() {
+        let motion = motion_with_attributes(&[]);
+
+        assert_point_eq(position_at(&motion, 0), (0.0, 0.0));
+        assert_point_eq(position_at(&motion, 250), (50.0, 0.0));
+        assert_point_eq(position_at(&motion, 500), (100.0, 0.0));
+        assert_point_eq(position_at(&motion, 1000), (100.0, 100.0));
+        assert_point_eq(position_at(&motion, 2000), (100.0, 100.0));
+    }
+
+    #[test]
+    fn rotates_along_the_path() {
+        let motion = motion_with_attributes(&[("rotate", "auto")]);
+        assert_point_eq(
+            motion
+                .transform_at(&motion.path.clone().unwrap(), Duration::from_millis(750))
+                .transform_distance(1.0, 0.0),
+            (0.0, 1.0),
+        );
+
+        let motion = motion_with_attributes(&[("rotate", "auto-reverse")]);
+        assert_point_eq(
+            motion
+                .transform_at(&motion.path.clone().unwrap(), Duration::from_millis(250))
+                .transform_distance(1.0, 0.0),
+            (-1.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn maps_key_times_to_key_points() {
+        let motion =
+            motion_with_attributes(&[("keyTimes", "0; 0.5; 1"), ("keyPoints", "0; 0.25; 1")]);
+
+        assert_point_eq(position_at(&motion, 500), (50.0, 0.0));
+        assert_point_eq(position_at(&motion, 750), (100.0, 25.0));
+    }
+}
@@ -0,0 +1,83 @@
+//! ARIA attributes for accessibility.
+//!
+//! SVG 2 allows the WAI-ARIA `role` and `aria-*` attributes on all elements.
+//! Librsvg does not use them for rendering; it just keeps a copy of some of them
+//! so that callers can present the document to assistive technologies.
+//!
+//! https://www.w3.org/TR/SVG2/struct.html#WAIARIAAttributes
+
+use markup5ever::{namespace_url, ns, QualName};
+
+/// The ARIA attributes of an element.
+///
+/// Each field is `None` if the corresponding attribute is not present.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AriaAttributes {
+    /// The `role` attribute.
+    pub role: Option<String>,
+
+    /// The `aria-label` attribute.
+    pub label: Option<String>,
+
+    /// The `aria-describedby` attribute, a list of element ids.
+    pub describedby: Option<String>,
+
+    /// The `aria-hidden` attribute.
+    ///
+    /// This is `None` for `aria-hidden="undefined"` or an invalid value.
+    pub hidden: Option<bool>,
+}
+
+impl AriaAttributes {
+    /// Collects the ARIA attributes from an element's attributes.
+    pub fn from_attributes(attributes: &[(QualName, String)]) -> AriaAttributes {
+        let mut aria = AriaAttributes::default();
+
+        for (attr, value) in attributes.iter().filter(|(attr, _)| attr.ns == ns!()) {
+            match &*attr.local {
+                "role" => aria.role = Some(value.to_string()),
+                "aria-label" => aria.label = Some(value.to_string()),
+                "aria-describedby" => aria.describedby = Some(value.to_string()),
+                "aria-hidden" => aria.hidden = parse_true_false(value),
+                _ => (),
+            }
+        }
+
+        aria
+    }
+}
+
+/// Whether `attr` is one of the ARIA attributes in `AriaAttributes`.
+pub fn is_aria_attribute(attr: &QualName) -> bool {
+    attr.ns == ns!()
+        && match &*attr.local {
+            "role" | "aria-label" | "aria-describedby" | "aria-hidden" => true,
+            _ => false,
+        }
+}
+
+/// Parses an ARIA true/false value; values are compared ASCII case-insensitively.
+fn parse_true_false(value: &str) -> Option<bool> {
+    let value = value.trim();
+
+    if value.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_true_false() {
+        assert_eq!(parse_true_false("true"), Some(true));
+        assert_eq!(parse_true_false(" FALSE "), Some(false));
+        assert_eq!(parse_true_false("undefined"), None);
+        assert_eq!(parse_true_false("yes"), None);
+    }
+}
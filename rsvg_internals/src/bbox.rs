@@ -77,24 +77,24 @@ impl BoundingBox {
     /// `CoordUnits::ObjectBoundingBox`, the bounding box must not be
     /// empty, since the calling code would then not have a usable
     /// size to work with.  In that case, if the bbox is empty, this
-    /// function returns `Err(())`.
+    /// function returns `None`.
     ///
-    /// Usually calling code can simply ignore the action it was about
-    /// to take if this function returns an error.
-    pub fn rect_to_transform(&self, units: CoordUnits) -> Result<Transform, ()> {
+    /// Usually calling code can simply skip the action it was about
+    /// to take if this function returns `None`; this is not an error.
+    pub fn rect_to_transform(&self, units: CoordUnits) -> Option<Transform> {
         match units {
-            CoordUnits::UserSpaceOnUse => Ok(Transform::identity()),
+            CoordUnits::UserSpaceOnUse => Some(Transform::identity()),
             CoordUnits::ObjectBoundingBox => {
                 if self.rect_is_empty() {
-                    Err(())
+                    None
                 } else {
                     let r = self.rect.as_ref().unwrap();
                     let t = Transform::new_unchecked(r.width(), 0.0, 0.0, r.height(), r.x0, r.y0);
 
                     if t.is_invertible() {
-                        Ok(t)
+                        Some(t)
                     } else {
-                        Err(())
+                        None
                     }
                 }
             }
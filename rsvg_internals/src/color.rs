@@ -1,23 +1,46 @@
 //! CSS color values.
 
-use cssparser::Parser;
+use cssparser::{Parser, Token, RGBA};
+use nalgebra::{Matrix3, Vector3};
 
 use crate::error::*;
-use crate::parsers::Parse;
+use crate::parsers::{finite_f32, Parse};
+use crate::surface_utils::srgb;
 
 pub use cssparser::Color;
 
 impl Parse for cssparser::Color {
     fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<cssparser::Color, ParseError<'i>> {
+        // cssparser knows about the CSS Color 3 syntax; the functions from
+        // newer specs are handled here.
+        let functions: [(&str, ColorFunction); 3] = [
+            ("color-mix", parse_color_mix_arguments),
+            ("hwb", parse_hwb_arguments),
+            ("oklch", parse_oklch_arguments),
+        ];
+
+        for &(name, parse_arguments) in functions.iter() {
+            if parser
+                .try_parse(|p| p.expect_function_matching(name))
+                .is_ok()
+            {
+                return Ok(cssparser::Color::RGBA(
+                    parser.parse_nested_block(parse_arguments)?,
+                ));
+            }
+        }
+
         Ok(cssparser::Color::parse(parser)?)
     }
 }
 
+type ColorFunction = for<'i, 't> fn(&mut Parser<'i, 't>) -> Result<RGBA, ParseError<'i>>;
+
 impl Parse for cssparser::RGBA {
     fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<cssparser::RGBA, ParseError<'i>> {
         let loc = parser.current_source_location();
 
-        match cssparser::Color::parse(parser)? {
+        match <cssparser::Color as Parse>::parse(parser)? {
             cssparser::Color::RGBA(rgba) => Ok(rgba),
             cssparser::Color::CurrentColor => Err(loc.new_custom_error(ValueErrorKind::Value(
                 "currentColor is not allowed here".to_string(),
@@ -25,3 +48,738 @@ impl Parse for cssparser::RGBA {
         }
     }
 }
+
+/// Color spaces in which `color-mix()` can interpolate.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ColorSpace {
+    Srgb,
+    Hsl,
+    Hwb,
+    Oklch,
+}
+
+/// How to interpolate hues in polar color spaces.
+///
+/// https://www.w3.org/TR/css-color-4/#hue-interpolation
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum HueInterpolation {
+    Shorter,
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
+/// Components of a color in one of the `ColorSpace`s.
+///
+/// The hue of polar color spaces is `None` when it is powerless, i.e. for
+/// achromatic colors.  Lightness, saturation, etc. are in the range [0.0, 1.0],
+/// and hues are in degrees.
+type Components = [Option<f64>; 3];
+
+impl Parse for ColorSpace {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<ColorSpace, ParseError<'i>> {
+        Ok(parse_identifiers!(
+            parser,
+            "srgb" => ColorSpace::Srgb,
+            "hsl" => ColorSpace::Hsl,
+            "hwb" => ColorSpace::Hwb,
+            "oklch" => ColorSpace::Oklch,
+        )?)
+    }
+}
+
+impl Parse for HueInterpolation {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<HueInterpolation, ParseError<'i>> {
+        let method = parse_identifiers!(
+            parser,
+            "shorter" => HueInterpolation::Shorter,
+            "longer" => HueInterpolation::Longer,
+            "increasing" => HueInterpolation::Increasing,
+            "decreasing" => HueInterpolation::Decreasing,
+        )?;
+
+        parser.expect_ident_matching("hue")?;
+
+        Ok(method)
+    }
+}
+
+impl ColorSpace {
+    /// Index of the hue in the `Components`, for polar color spaces.
+    fn hue_index(self) -> Option<usize> {
+        match self {
+            ColorSpace::Srgb => None,
+            ColorSpace::Hsl | ColorSpace::Hwb => Some(0),
+            ColorSpace::Oklch => Some(2),
+        }
+    }
+
+    fn components_of(self, rgba: RGBA) -> Components {
+        let r = f64::from(rgba.red_f32());
+        let g = f64::from(rgba.green_f32());
+        let b = f64::from(rgba.blue_f32());
+
+        match self {
+            ColorSpace::Srgb => [Some(r), Some(g), Some(b)],
+
+            ColorSpace::Hsl => {
+                let max = r.max(g).max(b);
+                let min = r.min(g).min(b);
+                let l = (max + min) / 2.0;
+
+                if max == min {
+                    [None, Some(0.0), Some(l)]
+                } else {
+                    let s = (max - min) / (1.0 - (2.0 * l - 1.0).abs());
+                    [Some(rgb_to_hue(r, g, b)), Some(s), Some(l)]
+                }
+            }
+
+            ColorSpace::Hwb => {
+                let max = r.max(g).max(b);
+                let min = r.min(g).min(b);
+
+                let hue = if max == min {
+                    None
+                } else {
+                    Some(rgb_to_hue(r, g, b))
+                };
+
+                [hue, Some(min), Some(1.0 - max)]
+            }
+
+            ColorSpace::Oklch => {
+                let (l, a, b) = linear_srgb_to_oklab(
+                    srgb::linearize_f64(r),
+                    srgb::linearize_f64(g),
+                    srgb::linearize_f64(b),
+                );
+
+                let c = (a * a + b * b).sqrt();
+
+                // The hue of grays is not well defined due to rounding errors.
+                let h = if c < 1e-5 {
+                    None
+                } else {
+                    Some(b.atan2(a).to_degrees().rem_euclid(360.0))
+                };
+
+                [Some(l), Some(c), h]
+            }
+        }
+    }
+
+    fn to_rgba(self, components: [f64; 3], alpha: f64) -> RGBA {
+        let (c0, c1, c2) = (components[0], components[1], components[2]);
+
+        let (r, g, b) = match self {
+            ColorSpace::Srgb => (c0, c1, c2),
+
+            ColorSpace::Hsl => hsl_to_rgb(c0, c1, c2),
+
+            ColorSpace::Hwb => {
+                if c1 + c2 >= 1.0 {
+                    let gray = c1 / (c1 + c2);
+                    (gray, gray, gray)
+                } else {
+                    let (r, g, b) = hsl_to_rgb(c0, 1.0, 0.5);
+                    let f = |v| v * (1.0 - c1 - c2) + c1;
+                    (f(r), f(g), f(b))
+                }
+            }
+
+            ColorSpace::Oklch => {
+                let (a, b) = (c1 * c2.to_radians().cos(), c1 * c2.to_radians().sin());
+                let (r, g, b) = oklab_to_linear_srgb(c0, a, b);
+
+                // Clamp to the sRGB gamut before applying the gamma; it is not defined
+                // for negative values.
+                let f = |v: f64| srgb::unlinearize_f64(v.max(0.0).min(1.0));
+                (f(r), f(g), f(b))
+            }
+        };
+
+        // from_floats() clamps the values to the sRGB gamut
+        RGBA::from_floats(r as f32, g as f32, b as f32, alpha as f32)
+    }
+}
+
+/// Parses the arguments of a `hwb()` function.
+///
+/// https://www.w3.org/TR/css-color-4/#the-hwb-notation
+fn parse_hwb_arguments<'i>(parser: &mut Parser<'i, '_>) -> Result<RGBA, ParseError<'i>> {
+    let hue = parse_hue(parser)?;
+    let whiteness = parse_number_or_percentage(parser, 100.0)?.max(0.0).min(1.0);
+    let blackness = parse_number_or_percentage(parser, 100.0)?.max(0.0).min(1.0);
+    let alpha = parse_alpha(parser)?;
+
+    Ok(ColorSpace::Hwb.to_rgba([hue, whiteness, blackness], alpha))
+}
+
+/// Parses the arguments of an `oklch()` function.
+///
+/// https://www.w3.org/TR/css-color-4/#specifying-oklab-oklch
+fn parse_oklch_arguments<'i>(parser: &mut Parser<'i, '_>) -> Result<RGBA, ParseError<'i>> {
+    let lightness = parse_number_or_percentage(parser, 1.0)?.max(0.0).min(1.0);
+    // A number is taken as-is; a percentage is relative to a chroma of 0.4.
+    let chroma = (parse_number_or_percentage(parser, 0.4)? * 0.4).max(0.0);
+    let hue = parse_hue(parser)?;
+    let alpha = parse_alpha(parser)?;
+
+    Ok(ColorSpace::Oklch.to_rgba([lightness, chroma, hue], alpha))
+}
+
+/// Parses a `<hue>` in degrees.  The `none` keyword is taken as zero.
+fn parse_hue<'i>(parser: &mut Parser<'i, '_>) -> Result<f64, ParseError<'i>> {
+    if parser
+        .try_parse(|p| p.expect_ident_matching("none"))
+        .is_ok()
+    {
+        return Ok(0.0);
+    }
+
+    let loc = parser.current_source_location();
+    let token = parser.next()?;
+
+    let degrees = match *token {
+        Token::Number { value, .. } => {
+            f64::from(finite_f32(value).map_err(|e| loc.new_custom_error(e))?)
+        }
+
+        Token::Dimension {
+            value, ref unit, ..
+        } => {
+            let value = f64::from(finite_f32(value).map_err(|e| loc.new_custom_error(e))?);
+
+            match unit.as_ref() {
+                "deg" => value,
+                "grad" => value * 360.0 / 400.0,
+                "rad" => value.to_degrees(),
+                "turn" => value * 360.0,
+                _ => return Err(loc.new_unexpected_token_error(token.clone())),
+            }
+        }
+
+        _ => return Err(loc.new_unexpected_token_error(token.clone())),
+    };
+
+    Ok(degrees.rem_euclid(360.0))
+}
+
+/// Parses a `<number> | <percentage>`, where 100% corresponds to `reference`.
+///
+/// The result is scaled so that 100% becomes 1.0.  The `none` keyword is taken as zero.
+fn parse_number_or_percentage<'i>(
+    parser: &mut Parser<'i, '_>,
+    reference: f64,
+) -> Result<f64, ParseError<'i>> {
+    if parser
+        .try_parse(|p| p.expect_ident_matching("none"))
+        .is_ok()
+    {
+        return Ok(0.0);
+    }
+
+    let loc = parser.current_source_location();
+    let token = parser.next()?;
+
+    match *token {
+        Token::Number { value, .. } => finite_f32(value)
+            .map(|v| f64::from(v) / reference)
+            .map_err(|e| loc.new_custom_error(e)),
+
+        Token::Percentage { unit_value, .. } => finite_f32(unit_value)
+            .map(f64::from)
+            .map_err(|e| loc.new_custom_error(e)),
+
+        _ => Err(loc.new_unexpected_token_error(token.clone())),
+    }
+}
+
+/// Parses the optional `/ <alpha-value>` at the end of a color function.
+fn parse_alpha<'i>(parser: &mut Parser<'i, '_>) -> Result<f64, ParseError<'i>> {
+    if parser.try_parse(|p| p.expect_delim('/')).is_err() {
+        return Ok(1.0);
+    }
+
+    Ok(parse_number_or_percentage(parser, 1.0)?.max(0.0).min(1.0))
+}
+
+/// Parses the arguments of a `color-mix()` function.
+///
+/// https://www.w3.org/TR/css-color-5/#color-mix
+fn parse_color_mix_arguments<'i>(parser: &mut Parser<'i, '_>) -> Result<RGBA, ParseError<'i>> {
+    parser.expect_ident_matching("in")?;
+    let space = ColorSpace::parse(parser)?;
+
+    let hue_interpolation = if space.hue_index().is_some() {
+        parser
+            .try_parse(HueInterpolation::parse)
+            .unwrap_or(HueInterpolation::Shorter)
+    } else {
+        HueInterpolation::Shorter
+    };
+
+    parser.expect_comma()?;
+    let (color1, p1) = parse_mix_component(parser)?;
+    parser.expect_comma()?;
+    let loc = parser.current_source_location();
+    let (color2, p2) = parse_mix_component(parser)?;
+
+    let (p1, p2) = match (p1, p2) {
+        (None, None) => (0.5, 0.5),
+        (Some(p1), None) => (p1, 1.0 - p1),
+        (None, Some(p2)) => (1.0 - p2, p2),
+        (Some(p1), Some(p2)) => (p1, p2),
+    };
+
+    let sum = p1 + p2;
+
+    if sum == 0.0 {
+        return Err(loc.new_custom_error(ValueErrorKind::value_error(
+            "the percentages in color-mix() must not add up to zero",
+        )));
+    }
+
+    // If the percentages add up to less than 100%, the result is more transparent
+    let alpha_multiplier = sum.min(1.0);
+
+    Ok(mix(
+        space,
+        hue_interpolation,
+        color1,
+        p1 / sum,
+        color2,
+        p2 / sum,
+        alpha_multiplier,
+    ))
+}
+
+/// Parses `<color> && <percentage>?`
+fn parse_mix_component<'i>(
+    parser: &mut Parser<'i, '_>,
+) -> Result<(RGBA, Option<f64>), ParseError<'i>> {
+    let mut percentage = parser.try_parse(parse_mix_percentage).ok();
+    let color = <RGBA as Parse>::parse(parser)?;
+
+    if percentage.is_none() {
+        percentage = parser.try_parse(parse_mix_percentage).ok();
+    }
+
+    Ok((color, percentage))
+}
+
+fn parse_mix_percentage<'i>(parser: &mut Parser<'i, '_>) -> Result<f64, ParseError<'i>> {
+    let loc = parser.current_source_location();
+    let p = f64::from(parser.expect_percentage()?);
+
+    if p >= 0.0 && p <= 1.0 {
+        Ok(p)
+    } else {
+        Err(loc.new_custom_error(ValueErrorKind::value_error(
+            "percentages in color-mix() must be between 0% and 100%",
+        )))
+    }
+}
+
+/// Interpolates between two colors in the given color space.
+///
+/// The weights `p1` and `p2` must add up to 1.0.  Interpolation is done with
+/// premultiplied alpha, except for hues.
+fn mix(
+    space: ColorSpace,
+    hue_interpolation: HueInterpolation,
+    color1: RGBA,
+    p1: f64,
+    color2: RGBA,
+    p2: f64,
+    alpha_multiplier: f64,
+) -> RGBA {
+    let a1 = f64::from(color1.alpha_f32());
+    let a2 = f64::from(color2.alpha_f32());
+
+    let c1 = space.components_of(color1);
+    let c2 = space.components_of(color2);
+
+    let alpha = a1 * p1 + a2 * p2;
+
+    let mut result = [0.0; 3];
+
+    for i in 0..3 {
+        // A missing component takes its value from the other color
+        let (x1, x2) = match (c1[i], c2[i]) {
+            (Some(x1), Some(x2)) => (x1, x2),
+            (Some(x), None) | (None, Some(x)) => (x, x),
+            (None, None) => (0.0, 0.0),
+        };
+
+        result[i] = if Some(i) == space.hue_index() {
+            interpolate_hue(hue_interpolation, x1, p1, x2, p2)
+        } else if alpha > 0.0 {
+            (x1 * a1 * p1 + x2 * a2 * p2) / alpha
+        } else {
+            0.0
+        };
+    }
+
+    space.to_rgba(result, alpha * alpha_multiplier)
+}
+
+fn interpolate_hue(method: HueInterpolation, h1: f64, p1: f64, h2: f64, p2: f64) -> f64 {
+    let (mut h1, mut h2) = (h1, h2);
+    let diff = h2 - h1;
+
+    match method {
+        HueInterpolation::Shorter => {
+            if diff > 180.0 {
+                h1 += 360.0;
+            } else if diff < -180.0 {
+                h2 += 360.0;
+            }
+        }
+
+        HueInterpolation::Longer => {
+            if diff > 0.0 && diff < 180.0 {
+                h1 += 360.0;
+            } else if diff > -180.0 && diff <= 0.0 {
+                h2 += 360.0;
+            }
+        }
+
+        HueInterpolation::Increasing => {
+            if h2 < h1 {
+                h2 += 360.0;
+            }
+        }
+
+        HueInterpolation::Decreasing => {
+            if h1 < h2 {
+                h1 += 360.0;
+            }
+        }
+    }
+
+    (h1 * p1 + h2 * p2).rem_euclid(360.0)
+}
+
+/// Computes the hue in degrees of a non-gray sRGB color.
+fn rgb_to_hue(r: f64, g: f64, b: f64) -> f64 {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let d = max - min;
+
+    let h = if max == r {
+        (g - b) / d
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+
+    (h * 60.0).rem_euclid(360.0)
+}
+
+// https://www.w3.org/TR/css-color-4/#hsl-to-rgb
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (f64, f64, f64) {
+    let f = |n: f64| {
+        let k = (n + h / 30.0) % 12.0;
+        let a = s * l.min(1.0 - l);
+        l - a * (k - 3.0).min(9.0 - k).min(1.0).max(-1.0)
+    };
+
+    (f(0.0), f(8.0), f(4.0))
+}
+
+// The conversions between sRGB and OKLab go through CIE XYZ with a D65 white point,
+// with the matrices from https://www.w3.org/TR/css-color-4/#color-conversion-code
+
+#[allow(clippy::excessive_precision)]
+#[rustfmt::skip]
+fn linear_srgb_to_oklab(r: f64, g: f64, b: f64) -> (f64, f64, f64) {
+    let linear_srgb_to_xyz = Matrix3::new(
+        0.412_390_799_265_959_34, 0.357_584_339_383_878, 0.180_480_788_401_834_3,
+        0.212_639_005_871_510_27, 0.715_168_678_767_756, 0.072_192_315_360_733_71,
+        0.019_330_818_715_591_82, 0.119_194_779_794_625_98, 0.950_532_152_249_660_7,
+    );
+
+    let xyz_to_lms = Matrix3::new(
+        0.819_022_437_996_703, 0.361_906_260_052_890_4, -0.128_873_781_520_987_9,
+        0.032_983_653_932_388_5, 0.929_286_861_586_343_4, 0.036_144_666_350_642_4,
+        0.048_177_189_359_624_2, 0.264_239_531_752_730_8, 0.633_547_828_469_430_9,
+    );
+
+    let lms_to_oklab = Matrix3::new(
+        0.210_454_268_309_314, 0.793_617_774_702_305_4, -0.004_072_043_011_619_3,
+        1.977_998_532_431_168_4, -2.428_592_242_048_579_9, 0.450_593_709_617_411,
+        0.025_904_042_465_547_8, 0.782_771_712_457_529_6, -0.808_675_754_923_077_4,
+    );
+
+    let lms = xyz_to_lms * linear_srgb_to_xyz * Vector3::new(r, g, b);
+    let lab = lms_to_oklab * lms.map(f64::cbrt);
+
+    (lab[0], lab[1], lab[2])
+}
+
+#[allow(clippy::excessive_precision)]
+#[rustfmt::skip]
+fn oklab_to_linear_srgb(l: f64, a: f64, b: f64) -> (f64, f64, f64) {
+    let oklab_to_lms = Matrix3::new(
+        1.0, 0.396_337_777_376_174_9, 0.215_803_757_309_913_6,
+        1.0, -0.105_561_345_815_658_6, -0.063_854_172_825_813_3,
+        1.0, -0.089_484_177_529_811_9, -1.291_485_548_019_409_2,
+    );
+
+    let lms_to_xyz = Matrix3::new(
+        1.226_879_875_845_924_3, -0.557_814_994_460_217_1, 0.281_391_045_665_964_7,
+        -0.040_575_745_214_800_8, 1.112_286_803_280_317, -0.071_711_058_065_516_4,
+        -0.076_372_936_674_660_1, -0.421_493_332_402_243_2, 1.586_924_019_836_781_6,
+    );
+
+    let xyz_to_linear_srgb = Matrix3::new(
+        3.240_969_941_904_522_6, -1.537_383_177_570_094, -0.498_610_760_293_003_4,
+        -0.969_243_636_280_879_6, 1.875_967_501_507_720_2, 0.041_555_057_407_175_59,
+        0.055_630_079_696_993_66, -0.203_976_958_888_976_52, 1.056_971_514_242_878_6,
+    );
+
+    let lms = (oklab_to_lms * Vector3::new(l, a, b)).map(|v| v * v * v);
+    let rgb = xyz_to_linear_srgb * lms_to_xyz * lms;
+
+    (rgb[0], rgb[1], rgb[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_rgba(s: &str) -> RGBA {
+        match cssparser::Color::parse_str(s).unwrap() {
+            cssparser::Color::RGBA(rgba) => rgba,
+            cssparser::Color::CurrentColor => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn parses_plain_colors() {
+        assert_eq!(
+            cssparser::Color::parse_str("#ff0000").unwrap(),
+            cssparser::Color::RGBA(RGBA::new(255, 0, 0, 255))
+        );
+        assert_eq!(
+            cssparser::Color::parse_str("currentColor").unwrap(),
+            cssparser::Color::CurrentColor
+        );
+    }
+
+    #[test]
+    fn mixes_in_srgb() {
+        assert_eq!(
+            parse_rgba("color-mix(in srgb, #ff0000 50%, #0000ff)"),
+            RGBA::new(0x80, 0x00, 0x80, 0xff)
+        );
+        assert_eq!(
+            parse_rgba("color-mix(in srgb, red, blue)"),
+            RGBA::new(0x80, 0x00, 0x80, 0xff)
+        );
+        assert_eq!(
+            parse_rgba("color-mix(in srgb, 25% white, black)"),
+            RGBA::new(0x40, 0x40, 0x40, 0xff)
+        );
+        assert_eq!(
+            parse_rgba("color-mix(in srgb, white, black 75%)"),
+            RGBA::new(0x40, 0x40, 0x40, 0xff)
+        );
+    }
+
+    #[test]
+    fn mixes_with_premultiplied_alpha() {
+        assert_eq!(
+            parse_rgba("color-mix(in srgb, rgba(255, 0, 0, 1), rgba(0, 0, 255, 0))"),
+            RGBA::new(0xff, 0x00, 0x00, 0x80)
+        );
+    }
+
+    #[test]
+    fn percentages_less_than_100_reduce_alpha() {
+        assert_eq!(
+            parse_rgba("color-mix(in srgb, red 25%, blue 25%)"),
+            RGBA::new(0x80, 0x00, 0x80, 0x80)
+        );
+    }
+
+    #[test]
+    fn percentages_more_than_100_are_normalized() {
+        assert_eq!(
+            parse_rgba("color-mix(in srgb, red 100%, blue 100%)"),
+            RGBA::new(0x80, 0x00, 0x80, 0xff)
+        );
+    }
+
+    #[test]
+    fn mixes_in_hsl() {
+        // red is hue 0, lime is hue 120
+        assert_eq!(
+            parse_rgba("color-mix(in hsl, red, lime)"),
+            RGBA::new(0xff, 0xff, 0x00, 0xff)
+        );
+
+        // white has no hue, so it takes the hue from red
+        assert_eq!(
+            parse_rgba("color-mix(in hsl, red, white)"),
+            RGBA::new(0xdf, 0x9f, 0x9f, 0xff)
+        );
+    }
+
+    #[test]
+    fn mixes_hues_with_interpolation_methods() {
+        // red is hue 0, blue is hue 240
+        assert_eq!(
+            parse_rgba("color-mix(in hsl, red, blue)"),
+            RGBA::new(0xff, 0x00, 0xff, 0xff)
+        );
+        assert_eq!(
+            parse_rgba("color-mix(in hsl shorter hue, red, blue)"),
+            RGBA::new(0xff, 0x00, 0xff, 0xff)
+        );
+        assert_eq!(
+            parse_rgba("color-mix(in hsl longer hue, red, blue)"),
+            RGBA::new(0x00, 0xff, 0x00, 0xff)
+        );
+        assert_eq!(
+            parse_rgba("color-mix(in hsl increasing hue, red, blue)"),
+            RGBA::new(0x00, 0xff, 0x00, 0xff)
+        );
+        assert_eq!(
+            parse_rgba("color-mix(in hsl decreasing hue, red, blue)"),
+            RGBA::new(0xff, 0x00, 0xff, 0xff)
+        );
+    }
+
+    #[test]
+    fn mixes_in_hwb() {
+        assert_eq!(
+            parse_rgba("color-mix(in hwb, red, lime)"),
+            RGBA::new(0xff, 0xff, 0x00, 0xff)
+        );
+        assert_eq!(
+            parse_rgba("color-mix(in hwb, white, black)"),
+            RGBA::new(0x80, 0x80, 0x80, 0xff)
+        );
+    }
+
+    #[test]
+    fn mixes_in_oklch() {
+        assert_eq!(
+            parse_rgba("color-mix(in oklch, red 100%, blue)"),
+            RGBA::new(0xff, 0x00, 0x00, 0xff)
+        );
+        assert_eq!(
+            parse_rgba("color-mix(in oklch, white, white)"),
+            RGBA::new(0xff, 0xff, 0xff, 0xff)
+        );
+
+        let gray = parse_rgba("color-mix(in oklch, white, black)");
+        assert_eq!(gray.red, gray.green);
+        assert_eq!(gray.green, gray.blue);
+    }
+
+    #[test]
+    fn mixes_nested_color_mix() {
+        assert_eq!(
+            parse_rgba("color-mix(in srgb, color-mix(in srgb, red, blue), #800080)"),
+            RGBA::new(0x80, 0x00, 0x80, 0xff)
+        );
+    }
+
+    #[test]
+    fn parses_hwb() {
+        assert_eq!(parse_rgba("hwb(0 0% 0%)"), RGBA::new(255, 0, 0, 255));
+        assert_eq!(parse_rgba("hwb(120 0% 50%)"), RGBA::new(0, 128, 0, 255));
+        assert_eq!(parse_rgba("hwb(240 20% 20%)"), RGBA::new(51, 51, 204, 255));
+        assert_eq!(parse_rgba("hwb(240deg 20 20)"), RGBA::new(51, 51, 204, 255));
+        assert_eq!(
+            parse_rgba("hwb(0.5turn 0% 0%)"),
+            RGBA::new(0, 255, 255, 255)
+        );
+        assert_eq!(parse_rgba("hwb(-120 0% 0%)"), RGBA::new(0, 0, 255, 255));
+
+        // whiteness + blackness >= 100% is a gray
+        assert_eq!(
+            parse_rgba("hwb(90 100% 100%)"),
+            RGBA::new(128, 128, 128, 255)
+        );
+        assert_eq!(parse_rgba("hwb(90 none 100%)"), RGBA::new(0, 0, 0, 255));
+    }
+
+    #[test]
+    fn parses_hwb_with_alpha() {
+        assert_eq!(parse_rgba("hwb(0 0% 0% / 0.5)"), RGBA::new(255, 0, 0, 128));
+        assert_eq!(parse_rgba("hwb(0 0% 0% / 25%)"), RGBA::new(255, 0, 0, 64));
+        assert_eq!(parse_rgba("hwb(0 0% 0% / 2)"), RGBA::new(255, 0, 0, 255));
+    }
+
+    #[test]
+    fn parses_oklch() {
+        assert_eq!(parse_rgba("oklch(0 0 0)"), RGBA::new(0, 0, 0, 255));
+        assert_eq!(parse_rgba("oklch(1 0 0)"), RGBA::new(255, 255, 255, 255));
+        assert_eq!(
+            parse_rgba("oklch(100% 0 none)"),
+            RGBA::new(255, 255, 255, 255)
+        );
+        assert_eq!(parse_rgba("oklch(0.5 0 0)"), RGBA::new(99, 99, 99, 255));
+        assert_eq!(
+            parse_rgba("oklch(0.628 0.2577 29.23)"),
+            RGBA::new(255, 0, 0, 255)
+        );
+        assert_eq!(
+            parse_rgba("oklch(0.7 0.1 200)"),
+            RGBA::new(64, 177, 183, 255)
+        );
+        assert_eq!(
+            parse_rgba("oklch(60% 37.5% 150deg)"),
+            RGBA::new(37, 152, 77, 255)
+        );
+        assert_eq!(
+            parse_rgba("oklch(0.7 25% 200)"),
+            parse_rgba("oklch(0.7 0.1 200)")
+        );
+        assert_eq!(
+            parse_rgba("oklch(0.7 0.1 200 / 50%)"),
+            RGBA::new(64, 177, 183, 128)
+        );
+    }
+
+    #[test]
+    fn oklch_round_trips_primaries() {
+        for &(r, g, b) in &[(1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0, 1.0)] {
+            let (l, a, b_) = linear_srgb_to_oklab(r, g, b);
+            let (r2, g2, b2) = oklab_to_linear_srgb(l, a, b_);
+
+            assert!((r - r2).abs() < 1e-6);
+            assert!((g - g2).abs() < 1e-6);
+            assert!((b - b2).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn invalid_hwb_and_oklch_yield_error() {
+        assert!(cssparser::Color::parse_str("hwb(0 0%)").is_err());
+        assert!(cssparser::Color::parse_str("hwb(0px 0% 0%)").is_err());
+        assert!(cssparser::Color::parse_str("hwb(0 0% 0% 0%)").is_err());
+        assert!(cssparser::Color::parse_str("oklch(0.5 0.1)").is_err());
+        assert!(cssparser::Color::parse_str("oklch(0.5 0.1 red)").is_err());
+        assert!(cssparser::Color::parse_str("oklch(0.5 0.1 20 / )").is_err());
+    }
+
+    #[test]
+    fn invalid_color_mix_yields_error() {
+        assert!(cssparser::Color::parse_str("color-mix(srgb, red, blue)").is_err());
+        assert!(cssparser::Color::parse_str("color-mix(in lab, red, blue)").is_err());
+        assert!(cssparser::Color::parse_str("color-mix(in srgb, red)").is_err());
+        assert!(cssparser::Color::parse_str("color-mix(in srgb red, blue)").is_err());
+        assert!(cssparser::Color::parse_str("color-mix(in srgb, red 0%, blue 0%)").is_err());
+        assert!(cssparser::Color::parse_str("color-mix(in srgb, red 150%, blue)").is_err());
+        assert!(cssparser::Color::parse_str("color-mix(in srgb, red -10%, blue)").is_err());
+        assert!(cssparser::Color::parse_str("color-mix(in srgb, currentColor, blue)").is_err());
+        assert!(cssparser::Color::parse_str("color-mix(in srgb longer hue, red, blue)").is_err());
+    }
+}
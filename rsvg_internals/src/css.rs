@@ -24,8 +24,9 @@
 //! Let's look at each rule:
 //!
 //! `@import` is an **at-rule**.  This rule has a prelude, but no block.
-//! There are other at-rules like `@media` and some of them may have a block,
-//! but librsvg doesn't support those yet.
+//! There are other at-rules like `@media` and some of them may have a block;
+//! of those, librsvg only supports `@keyframes`, whose block is a list of
+//! keyframes like `from { opacity: 0; } 50% { opacity: 1; }`.
 //!
 //! The prelude of the following rule is `foo, .bar`.
 //! It is a **selector list** with two **selectors**, one for
@@ -72,26 +73,36 @@
 //! element, plus a selector, and returns a bool.  We iterate through
 //! the rules in the stylesheets and gather the matches; then sort the
 //! matches by specificity and apply the result to each element.
+//!
+//! Selectors with combinators, like `g.foo rect` (descendant), `g > rect`
+//! (child), `rect + circle` (next sibling), or `rect ~ circle`
+//! (subsequent sibling), are matched by the `selectors` crate itself.  It
+//! walks the tree through the `parent_element()`,
+//! `prev_sibling_element()` and friends in our `RsvgElement`
+//! implementation, so those methods must only ever yield element nodes.
 
 use cssparser::{
     self, match_ignore_ascii_case, parse_important, AtRuleParser, AtRuleType, BasicParseErrorKind,
     CowRcStr, DeclarationListParser, DeclarationParser, Parser, ParserInput, QualifiedRuleParser,
-    RuleListParser, SourceLocation, ToCss, _cssparser_internal_to_lowercase,
+    RuleListParser, SourceLocation, ToCss, Token, _cssparser_internal_to_lowercase,
 };
 use markup5ever::{namespace_url, ns, LocalName, Namespace, Prefix, QualName};
 use selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
 use selectors::matching::{ElementSelectorFlags, MatchingContext, MatchingMode, QuirksMode};
 use selectors::{OpaqueElement, SelectorImpl, SelectorList};
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt;
 use std::str;
 use url::Url;
 
 use crate::allowed_url::AllowedUrl;
+use crate::diagnostics::Diagnostics;
 use crate::error::*;
 use crate::io::{self, BinaryData};
 use crate::node::{Node, NodeBorrow, NodeCascade};
 use crate::properties::{parse_property, ComputedValues, ParsedProperty};
+use crate::unit_interval::UnitInterval;
 
 /// A parsed CSS declaration
 ///
@@ -105,6 +116,25 @@ pub struct Declaration {
     pub important: bool,
 }
 
+/// Declarations that override the styles of particular elements, by element id.
+///
+/// These are applied during the cascade after an element's `style` attribute, with a
+/// "User" origin.
+pub type StyleOverrides = HashMap<String, Vec<Declaration>>;
+
+/// Parses a list of declarations like `fill: red; stroke: blue !important`.
+///
+/// Unlike the declarations in a `style` attribute, which are ignored one by one if
+/// they are invalid, this returns an error if any declaration is invalid.
+pub fn parse_declarations(declarations: &str) -> Result<Vec<Declaration>, LoadingError> {
+    let mut input = ParserInput::new(declarations);
+    let mut parser = Parser::new(&mut input);
+
+    DeclarationListParser::new(&mut parser, DeclParser)
+        .map(|r| r.map_err(|_| LoadingError::BadCss))
+        .collect()
+}
+
 /// Dummy struct required to use `cssparser::DeclarationListParser`
 ///
 /// It implements `cssparser::DeclarationParser`, which knows how to parse
@@ -170,11 +200,13 @@ pub struct QualifiedRule {
 /// Prelude of at-rule used in the AtRuleParser.
 pub enum AtRulePrelude {
     Import(String),
+    Keyframes(String),
 }
 
 /// A CSS at-rule (or ruleset)
 pub enum AtRule {
     Import(String),
+    Keyframes(String, Vec<Keyframe>),
 }
 
 /// A CSS rule (or ruleset)
@@ -245,7 +277,7 @@ impl<'i> QualifiedRuleParser<'i> for RuleParser {
             .filter_map(|r| match r {
                 Ok(decl) => Some(decl),
                 Err(e) => {
-                    rsvg_log!("Invalid declaration; ignoring: {:?}", e);
+                    rsvg_log!(Warn, "Invalid declaration; ignoring: {:?}", e);
                     None
                 }
             })
@@ -260,9 +292,9 @@ impl<'i> QualifiedRuleParser<'i> for RuleParser {
 
 // Required by `cssparser::RuleListParser`.
 //
-// This only handles the `@import` at-rule.
+// This handles the `@import` and `@keyframes` at-rules.
 impl<'i> AtRuleParser<'i> for RuleParser {
-    type PreludeBlock = ();
+    type PreludeBlock = AtRulePrelude;
     type PreludeNoBlock = AtRulePrelude;
     type AtRule = Rule;
     type Error = ParseErrorKind<'i>;
@@ -282,6 +314,11 @@ impl<'i> AtRuleParser<'i> for RuleParser {
                 Ok(AtRuleType::WithoutBlock(AtRulePrelude::Import(url)))
             },
 
+            "keyframes" => {
+                let name = input.expect_ident_or_string()?.as_ref().to_owned();
+                Ok(AtRuleType::WithBlock(AtRulePrelude::Keyframes(name)))
+            },
+
             _ => Err(input.new_error(BasicParseErrorKind::AtRuleInvalid(name))),
         }
     }
@@ -291,8 +328,143 @@ impl<'i> AtRuleParser<'i> for RuleParser {
         prelude: Self::PreludeNoBlock,
         _location: SourceLocation,
     ) -> Self::AtRule {
-        let AtRulePrelude::Import(url) = prelude;
-        Rule::AtRule(AtRule::Import(url))
+        match prelude {
+            AtRulePrelude::Import(url) => Rule::AtRule(AtRule::Import(url)),
+            AtRulePrelude::Keyframes(_) => unreachable!("@keyframes always has a block"),
+        }
+    }
+
+    fn parse_block<'t>(
+        &mut self,
+        prelude: Self::PreludeBlock,
+        _location: SourceLocation,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self::AtRule, cssparser::ParseError<'i, Self::Error>> {
+        match prelude {
+            AtRulePrelude::Keyframes(name) => {
+                let mut keyframes = Vec::new();
+
+                for r in RuleListParser::new_for_nested_rule(input, KeyframeListParser) {
+                    match r {
+                        Ok(mut k) => keyframes.append(&mut k),
+                        Err(e) => rsvg_log!(Warn, "Invalid keyframe; ignoring: {:?}", e),
+                    }
+                }
+
+                Ok(Rule::AtRule(AtRule::Keyframes(name, keyframes)))
+            }
+
+            AtRulePrelude::Import(_) => unreachable!("@import never has a block"),
+        }
+    }
+}
+
+/// One keyframe from a `@keyframes` rule.
+#[derive(Clone)]
+pub struct Keyframe {
+    /// Position of the keyframe in the animation; `from` is 0 and `to` is 1.
+    pub offset: UnitInterval,
+
+    /// The properties that the keyframe declares.
+    pub properties: Vec<ParsedProperty>,
+}
+
+/// Dummy struct to parse the list of keyframes inside a `@keyframes` rule.
+///
+/// Each keyframe is a qualified rule whose prelude is a list of percentages
+/// or the `from` and `to` keywords, as in `0%, 50% { opacity: 0; }`.
+struct KeyframeListParser;
+
+impl<'i> QualifiedRuleParser<'i> for KeyframeListParser {
+    type Prelude = Vec<UnitInterval>;
+    type QualifiedRule = Vec<Keyframe>;
+    type Error = ValueErrorKind;
+
+    fn parse_prelude<'t>(
+        &mut self,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self::Prelude, ParseError<'i>> {
+        input.parse_comma_separated(|p| {
+            let loc = p.current_source_location();
+            let token = p.next()?;
+
+            match *token {
+                Token::Ident(ref s) if s.eq_ignore_ascii_case("from") => Ok(UnitInterval(0.0)),
+                Token::Ident(ref s) if s.eq_ignore_ascii_case("to") => Ok(UnitInterval(1.0)),
+                Token::Percentage { unit_value, .. } if (0.0..=1.0).contains(&unit_value) => {
+                    Ok(UnitInterval(f64::from(unit_value)))
+                }
+                _ => Err(loc.new_unexpected_token_error(token.clone())),
+            }
+        })
+    }
+
+    fn parse_block<'t>(
+        &mut self,
+        prelude: Self::Prelude,
+        _location: SourceLocation,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self::QualifiedRule, ParseError<'i>> {
+        let properties: Vec<ParsedProperty> = DeclarationListParser::new(input, DeclParser)
+            .filter_map(|r| match r {
+                // Declarations with !important are ignored in keyframes
+                Ok(decl) if !decl.important => Some(decl.property),
+                Ok(_) => None,
+                Err(e) => {
+                    rsvg_log!(Warn, "Invalid declaration; ignoring: {:?}", e);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(prelude
+            .into_iter()
+            .map(|offset| Keyframe {
+                offset,
+                properties: properties.clone(),
+            })
+            .collect())
+    }
+}
+
+// Required by `cssparser::RuleListParser`; keyframes don't have at-rules.
+impl<'i> AtRuleParser<'i> for KeyframeListParser {
+    type PreludeBlock = ();
+    type PreludeNoBlock = ();
+    type AtRule = Vec<Keyframe>;
+    type Error = ValueErrorKind;
+}
+
+/// The keyframes from `@keyframes` rules, by animation name.
+#[derive(Clone, Default)]
+pub struct KeyframesStore {
+    keyframes: HashMap<String, Vec<Keyframe>>,
+}
+
+impl KeyframesStore {
+    pub fn new() -> KeyframesStore {
+        Default::default()
+    }
+
+    /// Stores the keyframes for an animation, sorted by offset.
+    ///
+    /// As with other CSS rules, a later `@keyframes` rule with the same name
+    /// replaces an earlier one.
+    pub fn insert(&mut self, name: &str, mut keyframes: Vec<Keyframe>) {
+        keyframes.sort_by(|a, b| a.offset.0.partial_cmp(&b.offset.0).unwrap());
+        self.keyframes.insert(name.to_string(), keyframes);
+    }
+
+    /// Adds all the animations from another store, replacing existing ones with the same name.
+    pub fn extend(&mut self, other: &KeyframesStore) {
+        for (name, keyframes) in &other.keyframes {
+            self.keyframes.insert(name.clone(), keyframes.clone());
+        }
+    }
+
+    /// Returns the keyframes for an animation, ordered by offset.
+    pub fn get(&self, name: &str) -> Option<&[Keyframe]> {
+        self.keyframes.get(name).map(Vec::as_slice)
     }
 }
 
@@ -391,8 +563,10 @@ impl selectors::Element for RsvgElement {
         OpaqueElement::new(&self.0.borrow())
     }
 
+    /// Used by the matching engine to walk up the tree for the descendant
+    /// and child combinators.
     fn parent_element(&self) -> Option<Self> {
-        self.0.parent().map(|n| n.into())
+        self.0.parent().filter(|n| n.is_element()).map(|n| n.into())
     }
 
     /// Whether the parent node of this element is a shadow root.
@@ -574,6 +748,7 @@ pub enum Origin {
 pub struct Stylesheet {
     origin: Origin,
     qualified_rules: Vec<QualifiedRule>,
+    keyframes: KeyframesStore,
 }
 
 /// A match during the selector matching process
@@ -619,6 +794,7 @@ impl Stylesheet {
         Stylesheet {
             origin,
             qualified_rules: Vec::new(),
+            keyframes: KeyframesStore::new(),
         }
     }
 
@@ -654,7 +830,7 @@ impl Stylesheet {
             .filter_map(|r| match r {
                 Ok(rule) => Some(rule),
                 Err(e) => {
-                    rsvg_log!("Invalid rule; ignoring: {:?}", e);
+                    rsvg_log!(Warn, "Invalid rule; ignoring: {:?}", e);
                     None
                 }
             })
@@ -663,12 +839,20 @@ impl Stylesheet {
                     // ignore invalid imports
                     let _ = self.load(&url, base_url);
                 }
+                Rule::AtRule(AtRule::Keyframes(name, keyframes)) => {
+                    self.keyframes.insert(&name, keyframes)
+                }
                 Rule::QualifiedRule(qr) => self.qualified_rules.push(qr),
             });
 
         Ok(())
     }
 
+    /// Returns the keyframes from the `@keyframes` rules in the stylesheet.
+    pub fn keyframes(&self) -> &KeyframesStore {
+        &self.keyframes
+    }
+
     /// Parses a stylesheet referenced by an URL
     fn load(&mut self, href: &str, base_url: Option<&Url>) -> Result<(), LoadingError> {
         let aurl = AllowedUrl::from_href(href, base_url).map_err(|_| LoadingError::BadUrl)?;
@@ -683,13 +867,14 @@ impl Stylesheet {
                 if content_type.as_ref().map(String::as_ref) == Some("text/css") {
                     Ok(bytes)
                 } else {
-                    rsvg_log!("\"{}\" is not of type text/css; ignoring", aurl);
+                    rsvg_log!(Warn, "\"{}\" is not of type text/css; ignoring", aurl);
                     Err(LoadingError::BadCss)
                 }
             })
             .and_then(|bytes| {
                 String::from_utf8(bytes).map_err(|_| {
                     rsvg_log!(
+                        Warn,
                         "\"{}\" does not contain valid UTF-8 CSS data; ignoring",
                         aurl
                     );
@@ -731,13 +916,20 @@ impl Stylesheet {
 }
 
 /// Runs the CSS cascade on the specified tree from all the stylesheets
+///
+/// The cascade can be run more than once on the same tree; each run discards the
+/// results of the previous one.
 pub fn cascade(
     root: &mut Node,
     ua_stylesheets: &[Stylesheet],
     author_stylesheets: &[Stylesheet],
     user_stylesheets: &[Stylesheet],
+    overrides: &StyleOverrides,
+    diagnostics: &mut Diagnostics,
 ) {
     for mut node in root.descendants().filter(|n| n.is_element()) {
+        node.borrow_element_mut().reset_specified_values(diagnostics);
+
         let mut matches = Vec::new();
 
         let mut match_ctx = MatchingContext::new(
@@ -764,7 +956,17 @@ pub fn cascade(
                 .apply_style_declaration(m.declaration, m.origin);
         }
 
-        node.borrow_element_mut().set_style_attribute();
+        node.borrow_element_mut().set_style_attribute(diagnostics);
+
+        let element_overrides = node
+            .borrow_element()
+            .get_id()
+            .and_then(|id| overrides.get(id));
+
+        for declaration in element_overrides.into_iter().flatten() {
+            node.borrow_element_mut()
+                .apply_style_declaration(declaration, Origin::User);
+        }
     }
 
     let values = ComputedValues::default();
@@ -781,6 +983,10 @@ mod tests {
     use crate::allowed_url::Fragment;
     use crate::document::Document;
     use crate::handle::LoadOptions;
+    use crate::paint_server::PaintServer;
+    use crate::parsers::Parse;
+    use crate::properties::SpecifiedValue;
+    use crate::property_defs::Opacity;
 
     fn load_document(input: &'static [u8]) -> Document {
         let bytes = glib::Bytes::from_static(input);
@@ -877,4 +1083,153 @@ mod tests {
         assert!(d.is_empty());
         assert!(!a.is_empty());
     }
+
+    fn lookup_fill(document: &Document, id: &str) -> PaintServer {
+        let node = document
+            .lookup(&Fragment::new(None, id.to_string()))
+            .unwrap();
+        let fill = node.borrow_element().get_computed_values().fill();
+        fill.0
+    }
+
+    #[test]
+    fn matches_combinators() {
+        let document = load_document(
+            br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg">
+  <style type="text/css">
+    g.foo rect { fill: #00ff00; }
+    g.bar > rect { fill: #0000ff; }
+    circle + rect { fill: #ff0000; }
+    ellipse ~ path { fill: #ffff00; }
+  </style>
+  <g class="foo">
+    <g>
+      <rect id="descendant"/>
+    </g>
+  </g>
+  <g class="bar">
+    <rect id="child"/>
+    <g>
+      <rect id="grandchild"/>
+    </g>
+  </g>
+  <g>
+    <circle/>
+    <rect id="adjacent"/>
+    <rect id="not-adjacent"/>
+  </g>
+  <g>
+    <path id="before-ellipse"/>
+    <ellipse/>
+    <rect/>
+    <path id="after-ellipse"/>
+  </g>
+</svg>
+"#,
+        );
+
+        let black = PaintServer::parse_str("#000000").unwrap();
+
+        // descendant combinator
+        assert_eq!(
+            lookup_fill(&document, "descendant"),
+            PaintServer::parse_str("#00ff00").unwrap()
+        );
+
+        // child combinator
+        assert_eq!(
+            lookup_fill(&document, "child"),
+            PaintServer::parse_str("#0000ff").unwrap()
+        );
+        assert_eq!(lookup_fill(&document, "grandchild"), black);
+
+        // next-sibling combinator
+        assert_eq!(
+            lookup_fill(&document, "adjacent"),
+            PaintServer::parse_str("#ff0000").unwrap()
+        );
+        assert_eq!(lookup_fill(&document, "not-adjacent"), black);
+
+        // subsequent-sibling combinator
+        assert_eq!(lookup_fill(&document, "before-ellipse"), black);
+        assert_eq!(
+            lookup_fill(&document, "after-ellipse"),
+            PaintServer::parse_str("#ffff00").unwrap()
+        );
+    }
+
+    fn keyframe_opacities(keyframe: &Keyframe) -> Vec<f64> {
+        keyframe
+            .properties
+            .iter()
+            .filter_map(|p| match *p {
+                ParsedProperty::Opacity(SpecifiedValue::Specified(Opacity(UnitInterval(o)))) => {
+                    Some(o)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_keyframes() {
+        let stylesheet = Stylesheet::from_data(
+            "@keyframes fade {
+               to { opacity: 1; }
+               from { opacity: 0; fill: red; }
+               50% { opacity: 0.25 !important; opacity: 0.5; }
+             }
+             @keyframes \"other\" { 25%, 75% { opacity: 0.5; } 200% { opacity: 1; } }
+             rect { fill: blue; }",
+            None,
+            Origin::Author,
+        )
+        .unwrap();
+
+        let store = stylesheet.keyframes();
+
+        let fade = store.get("fade").unwrap();
+        assert_eq!(fade.len(), 3);
+
+        assert_eq!(fade[0].offset, UnitInterval(0.0));
+        assert_eq!(fade[0].properties.len(), 2);
+        assert_eq!(keyframe_opacities(&fade[0]), vec![0.0]);
+
+        assert_eq!(fade[1].offset, UnitInterval(0.5));
+        assert_eq!(keyframe_opacities(&fade[1]), vec![0.5]);
+
+        assert_eq!(fade[2].offset, UnitInterval(1.0));
+        assert_eq!(keyframe_opacities(&fade[2]), vec![1.0]);
+
+        let other = store.get("other").unwrap();
+        assert_eq!(other.len(), 2);
+        assert_eq!(other[0].offset, UnitInterval(0.25));
+        assert_eq!(other[1].offset, UnitInterval(0.75));
+
+        assert!(store.get("nonexistent").is_none());
+
+        // The qualified rule after the @keyframes rules is still parsed
+        assert_eq!(stylesheet.qualified_rules.len(), 1);
+    }
+
+    #[test]
+    fn document_collects_keyframes_from_style_elements() {
+        let document = load_document(
+            br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg">
+  <style>
+    @keyframes fade { from { opacity: 0; } to { opacity: 1; } }
+  </style>
+  <style>
+    @keyframes fade { from { opacity: 0.5; } to { opacity: 1; } }
+  </style>
+</svg>
+"#,
+        );
+
+        let fade = document.keyframes().get("fade").unwrap();
+        assert_eq!(fade.len(), 2);
+        assert_eq!(keyframe_opacities(&fade[0]), vec![0.5]);
+    }
 }
@@ -1,6 +1,7 @@
 //! Parser for the `stroke-dasharray` property.
 
-use cssparser::Parser;
+use cssparser::{Parser, ToCss};
+use std::fmt;
 
 use crate::error::*;
 use crate::length::*;
@@ -48,6 +49,29 @@ impl Parse for Dasharray {
     }
 }
 
+impl ToCss for Dasharray {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        match *self {
+            Dasharray::None => dest.write_str("none"),
+
+            Dasharray::Array(ref lengths) => {
+                for (i, length) in lengths.iter().enumerate() {
+                    if i > 0 {
+                        dest.write_str(", ")?;
+                    }
+
+                    length.to_css(dest)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,6 +123,12 @@ mod tests {
         assert_eq!(Dasharray::parse_str("5, 3.14").unwrap(), sample_6);
         assert_eq!(Dasharray::parse_str("2").unwrap(), sample_7);
 
+        // Zero-length dashes are a list, not "none"
+        assert_eq!(
+            Dasharray::parse_str("0").unwrap(),
+            Dasharray::Array(vec![length_parse("0")])
+        );
+
         // Negative numbers
         assert!(Dasharray::parse_str("20,40,-20").is_err());
 
@@ -0,0 +1,148 @@
+//! Collection of non-fatal problems found while loading or rendering.
+//!
+//! Many problems in SVG documents are not errors as far as the spec is concerned:
+//! an invalid property value is simply ignored, a filter primitive that fails to
+//! render is skipped, and so on.  We used to only note these with `rsvg_log!`.
+//! A [`Diagnostics`] collects them so that callers can inspect them afterwards.
+//!
+//! [`Diagnostics`]: struct.Diagnostics.html
+
+use std::fmt;
+
+/// How bad a [`Diagnostic`] is.
+///
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity {
+    /// Something was ignored, but the document can still be processed as intended.
+    Warning,
+
+    /// Something could not be processed, and the result may look different than intended.
+    Error,
+}
+
+/// Position of a problem within the text that was being parsed.
+///
+/// Both fields are zero-based and relative to the start of the text being
+/// parsed, for example, the value of a `style` attribute.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl From<cssparser::SourceLocation> for SourceLocation {
+    fn from(loc: cssparser::SourceLocation) -> SourceLocation {
+        SourceLocation {
+            line: loc.line,
+            column: loc.column,
+        }
+    }
+}
+
+/// A non-fatal problem found while loading or rendering a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub location: Option<SourceLocation>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        match self.location {
+            Some(loc) => write!(
+                f,
+                "{} at {}:{}: {}",
+                severity, loc.line, loc.column, self.message
+            ),
+            None => write!(f, "{}: {}", severity, self.message),
+        }
+    }
+}
+
+/// Accumulates [`Diagnostic`] values in the order in which they are found.
+///
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Diagnostics {
+        Default::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        match diagnostic.severity {
+            Severity::Warning => rsvg_log!(Warn, "({})", diagnostic),
+            Severity::Error => rsvg_log!(Error, "({})", diagnostic),
+        }
+
+        self.diagnostics.push(diagnostic);
+    }
+
+    pub fn warning(&mut self, message: String, location: Option<SourceLocation>) {
+        self.push(Diagnostic {
+            severity: Severity::Warning,
+            message,
+            location,
+        });
+    }
+
+    pub fn error(&mut self, message: String, location: Option<SourceLocation>) {
+        self.push(Diagnostic {
+            severity: Severity::Error,
+            message,
+            location,
+        });
+    }
+
+    /// Moves all the diagnostics from `other` to the end of this one.
+    pub fn append(&mut self, other: &mut Diagnostics) {
+        self.diagnostics.append(&mut other.diagnostics);
+    }
+
+    /// Returns the collected diagnostics and leaves this one empty.
+    pub fn take(&mut self) -> Vec<Diagnostic> {
+        std::mem::replace(&mut self.diagnostics, Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_empties_the_collection() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.warning("foo".to_string(), None);
+        diagnostics.error(
+            "bar".to_string(),
+            Some(SourceLocation { line: 0, column: 4 }),
+        );
+
+        let taken = diagnostics.take();
+        assert_eq!(taken.len(), 2);
+        assert_eq!(taken[0].severity, Severity::Warning);
+        assert_eq!(taken[1].severity, Severity::Error);
+        assert!(diagnostics.take().is_empty());
+    }
+
+    #[test]
+    fn displays_location() {
+        let diagnostic = Diagnostic {
+            severity: Severity::Warning,
+            message: "invalid value".to_string(),
+            location: Some(SourceLocation { line: 1, column: 2 }),
+        };
+
+        assert_eq!(diagnostic.to_string(), "warning at 1:2: invalid value");
+    }
+}
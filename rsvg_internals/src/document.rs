@@ -8,11 +8,14 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::include_str;
 use std::rc::Rc;
+use url::Url;
 
 use crate::allowed_url::{AllowedUrl, AllowedUrlError, Fragment};
-use crate::css::{self, Origin, Stylesheet};
+use crate::css::{self, KeyframesStore, Origin, StyleOverrides, Stylesheet};
+use crate::diagnostics::{Diagnostic, Diagnostics};
 use crate::error::{AcquireError, LoadingError};
 use crate::handle::LoadOptions;
+use crate::image::CrossOrigin;
 use crate::io::{self, BinaryData};
 use crate::limits;
 use crate::node::{Node, NodeBorrow, NodeData};
@@ -46,6 +49,12 @@ pub struct Document {
 
     /// Stylesheets defined in the document
     stylesheets: Vec<Stylesheet>,
+
+    /// Keyframes from the `@keyframes` rules in the document's stylesheets.
+    keyframes: KeyframesStore,
+
+    /// Non-fatal problems found while loading the document.
+    diagnostics: Diagnostics,
 }
 
 impl Document {
@@ -63,6 +72,12 @@ impl Document {
         )
     }
 
+    /// Whether the elements kept all their attributes from the XML, so that the
+    /// document can be serialized.
+    pub fn keeps_attributes(&self) -> bool {
+        self.load_options.keep_attributes
+    }
+
     /// Gets the root node.  This is guaranteed to be an `<svg>` element.
     pub fn root(&self) -> Node {
         self.tree.clone()
@@ -89,7 +104,38 @@ impl Document {
     }
 
     /// Loads an image by URL, or returns a pre-loaded one.
-    pub fn lookup_image(&self, href: &str) -> Result<SharedImageSurface, LoadingError> {
+    ///
+    /// `xml_base` is the base URI given by `xml:base` attributes on the referencing
+    /// element or its ancestors; relative `href`s are resolved against it.  The
+    /// resulting URL must still be allowed relative to the document's base URL.
+    ///
+    /// `cross_origin` is the CORS setting from the image's `crossorigin` attribute.
+    /// Since we don't fetch resources over the network, it doesn't change how images
+    /// are loaded yet; in particular, credentials are never sent.
+    pub fn lookup_image(
+        &self,
+        href: &str,
+        xml_base: Option<&Url>,
+        cross_origin: Option<CrossOrigin>,
+    ) -> Result<SharedImageSurface, LoadingError> {
+        if cross_origin == Some(CrossOrigin::UseCredentials) {
+            rsvg_log!(
+                Warn,
+                "crossorigin=\"use-credentials\" is not supported; loading \"{}\" without credentials",
+                href
+            );
+        }
+
+        let resolved;
+        let href = match xml_base {
+            Some(base) => {
+                resolved = base.join(href).map_err(|_| LoadingError::BadUrl)?;
+                resolved.as_str()
+            }
+
+            None => href,
+        };
+
         let aurl = AllowedUrl::from_href(href, self.load_options.base_url.as_ref())
             .map_err(|_| LoadingError::BadUrl)?;
 
@@ -99,9 +145,43 @@ impl Document {
     /// Runs the CSS cascade on the document tree
     ///
     /// This uses the deafault UserAgent stylesheet, the document's internal stylesheets,
-    /// plus an extra set of stylesheets supplied by the caller.
-    pub fn cascade(&mut self, extra: &[Stylesheet]) {
-        css::cascade(&mut self.tree, &UA_STYLESHEETS, &self.stylesheets, extra);
+    /// plus an extra set of stylesheets and per-element overrides supplied by the caller.
+    /// The result of any previous cascade is discarded.
+    pub fn cascade(&mut self, extra: &[Stylesheet], overrides: &StyleOverrides) {
+        // Problems in the elements' style attributes were already reported by the
+        // cascade done at loading time; don't report them again.
+        let mut diagnostics = Diagnostics::new();
+
+        css::cascade(
+            &mut self.tree,
+            &UA_STYLESHEETS,
+            &self.stylesheets,
+            extra,
+            overrides,
+            &mut diagnostics,
+        );
+    }
+
+    /// Runs the CSS cascade for the first time, while loading the document.
+    fn initial_cascade(&mut self) {
+        css::cascade(
+            &mut self.tree,
+            &UA_STYLESHEETS,
+            &self.stylesheets,
+            &[],
+            &StyleOverrides::new(),
+            &mut self.diagnostics,
+        );
+    }
+
+    /// Returns the keyframes from the `@keyframes` rules in the document's stylesheets.
+    pub fn keyframes(&self) -> &KeyframesStore {
+        &self.keyframes
+    }
+
+    /// Returns the non-fatal problems found while loading the document, and clears them.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        self.diagnostics.take()
     }
 }
 
@@ -278,8 +358,13 @@ impl<'i> AcquiredNodes<'i> {
         }
     }
 
-    pub fn lookup_image(&self, href: &str) -> Result<SharedImageSurface, LoadingError> {
-        self.document.lookup_image(href)
+    pub fn lookup_image(
+        &self,
+        href: &str,
+        xml_base: Option<&Url>,
+        cross_origin: Option<CrossOrigin>,
+    ) -> Result<SharedImageSurface, LoadingError> {
+        self.document.lookup_image(href, xml_base, cross_origin)
     }
 
     /// Acquires a node.
@@ -322,6 +407,9 @@ impl<'i> AcquiredNodes<'i> {
     pub fn acquire_ref(&self, node: &Node) -> Result<AcquiredNode, AcquireError> {
         if self.node_stack.borrow().contains(&node) {
             Err(AcquireError::CircularReference(node.clone()))
+        } else if self.node_stack.borrow().len() >= limits::MAX_REFERENCE_DEPTH {
+            // See limits.rs for details.
+            Err(AcquireError::MaxDepthExceeded)
         } else {
             self.node_stack.borrow_mut().push(&node);
             Ok(AcquiredNode {
@@ -352,6 +440,14 @@ impl NodeStack {
         self.0.pop()
     }
 
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn contains(&self, node: &Node) -> bool {
         self.0.iter().find(|n| **n == *node).is_some()
     }
@@ -362,6 +458,7 @@ pub struct DocumentBuilder {
     tree: Option<Node>,
     ids: HashMap<String, Node>,
     stylesheets: Vec<Stylesheet>,
+    diagnostics: Diagnostics,
 }
 
 impl DocumentBuilder {
@@ -371,6 +468,7 @@ impl DocumentBuilder {
             tree: None,
             ids: HashMap::new(),
             stylesheets: Vec::new(),
+            diagnostics: Diagnostics::new(),
         }
     }
 
@@ -402,7 +500,11 @@ impl DocumentBuilder {
         pbag: &PropertyBag,
         parent: Option<Node>,
     ) -> Node {
-        let node = Node::new(NodeData::new_element(name, pbag));
+        let mut node = Node::new(NodeData::new_element(name, pbag, &mut self.diagnostics));
+
+        if self.load_options.keep_attributes {
+            node.borrow_element_mut().keep_attributes(pbag);
+        }
 
         if let Some(id) = node.borrow_element().get_id() {
             // This is so we don't overwrite an existing id
@@ -443,7 +545,7 @@ impl DocumentBuilder {
         let chars_node = if let Some(child) = parent.last_child().filter(|c| c.is_chars()) {
             child
         } else {
-            let child = Node::new(NodeData::new_chars());
+            let child = Node::new(NodeData::new_chars(self.load_options.normalize_unicode));
             parent.append(child.clone());
             child
         };
@@ -451,6 +553,11 @@ impl DocumentBuilder {
         chars_node.borrow_chars().append(text);
     }
 
+    /// The base URL of the document being built.
+    pub fn base_url(&self) -> Option<&Url> {
+        self.load_options.base_url.as_ref()
+    }
+
     pub fn resolve_href(&self, href: &str) -> Result<AllowedUrl, AllowedUrlError> {
         AllowedUrl::from_href(href, self.load_options.base_url.as_ref())
     }
@@ -461,12 +568,18 @@ impl DocumentBuilder {
             tree,
             ids,
             stylesheets,
+            diagnostics,
             ..
         } = self;
 
         match tree {
             Some(root) if root.is_element() => {
                 if is_element_of_type!(root, Svg) {
+                    let mut keyframes = KeyframesStore::new();
+                    for stylesheet in &stylesheets {
+                        keyframes.extend(stylesheet.keyframes());
+                    }
+
                     let mut document = Document {
                         tree: root,
                         ids,
@@ -474,9 +587,11 @@ impl DocumentBuilder {
                         images: RefCell::new(Images::new()),
                         load_options,
                         stylesheets,
+                        keyframes,
+                        diagnostics,
                     };
 
-                    document.cascade(&[]);
+                    document.initial_cascade();
 
                     Ok(document)
                 } else {
@@ -4,6 +4,7 @@ use once_cell::sync::Lazy;
 use regex::{Captures, Regex};
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::rc::{Rc, Weak};
 
@@ -12,23 +13,26 @@ use crate::aspect_ratio::AspectRatio;
 use crate::bbox::BoundingBox;
 use crate::coord_units::CoordUnits;
 use crate::dasharray::Dasharray;
+use crate::diagnostics::Diagnostics;
 use crate::document::AcquiredNodes;
 use crate::dpi::Dpi;
 use crate::element::Element;
 use crate::error::{AcquireError, RenderingError};
+use crate::filter::{FilterValue, FilterValueList};
 use crate::filters;
+use crate::gradient::Gradient;
 use crate::marker;
-use crate::node::{CascadedValues, Node, NodeBorrow, NodeDraw};
+use crate::node::{CascadedValues, Node, NodeBorrow, NodeData, NodeDraw};
 use crate::paint_server::{PaintServer, PaintSource};
 use crate::path_builder::*;
 use crate::properties::ComputedValues;
 use crate::property_defs::{
-    ClipRule, FillRule, Opacity, Overflow, ShapeRendering, StrokeDasharray, StrokeLinecap,
-    StrokeLinejoin,
+    ClipRule, EnableBackground, FillRule, Opacity, Overflow, ShapeRendering, StrokeDasharray,
+    StrokeLinecap, StrokeLinejoin,
 };
 use crate::rect::Rect;
 use crate::shapes::Markers;
-use crate::structure::Mask;
+use crate::structure::{Mask, Symbol};
 use crate::surface_utils::{
     shared_surface::ExclusiveImageSurface, shared_surface::SharedImageSurface,
     shared_surface::SurfaceType,
@@ -54,6 +58,11 @@ pub struct ViewParams {
 }
 
 impl ViewParams {
+    /// Creates a `ViewParams` that is not tied to a `DrawingCtx`.
+    ///
+    /// This is for normalizing lengths where no rendering is taking place, like when
+    /// computing the intrinsic size of the toplevel `<svg>`, or in unit tests.
+    /// Dropping the result does not affect any viewport stack.
     pub fn new(dpi: Dpi, view_box_width: f64, view_box_height: f64) -> ViewParams {
         ViewParams {
             dpi,
@@ -81,6 +90,25 @@ pub enum ClipMode {
     ClipToVbox,
 }
 
+/// What a `DrawingCtx` does with the elements it draws.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RenderMode {
+    /// Paint the elements onto the Cairo surface.
+    Rasterize,
+
+    /// Only accumulate the elements' bounding boxes; nothing is painted, and
+    /// filters, masks, and paint servers are not evaluated.  This is useful
+    /// to compute geometries quickly, e.g. for hit testing.
+    BoundingBoxOnly,
+}
+
+/// State for a single rendering pass over the tree of nodes.
+///
+/// Rendering is single-threaded.  A `DrawingCtx` is not `Send`: it holds
+/// `cairo::Context` values, and the nodes it walks are reference-counted with
+/// `Rc` and `RefCell`.  So independent subtrees cannot be rendered in parallel
+/// into separate surfaces; parallelism happens only inside filter primitives,
+/// which operate on pixel data in `SharedImageSurface`.
 pub struct DrawingCtx {
     initial_transform: Transform,
 
@@ -90,12 +118,24 @@ pub struct DrawingCtx {
     cr_stack: Vec<cairo::Context>,
     cr: cairo::Context,
 
+    /// Index in `cr_stack` of the layer for the nearest ancestor with
+    /// `enable-background: new`; the background image starts there.
+    background_start: usize,
+
     view_box_stack: Rc<RefCell<Vec<ViewBox>>>,
 
     drawsub_stack: Vec<Node>,
 
     measuring: bool,
     testing: bool,
+    render_mode: RenderMode,
+
+    diagnostics: Diagnostics,
+
+    /// Bounding boxes of the `<a>` elements drawn while measuring, relative to
+    /// the initial transform.  They are keyed by the address of each element's
+    /// node data, which is only used to identify the node.
+    link_bboxes: HashMap<*const NodeData, BoundingBox>,
 }
 
 impl DrawingCtx {
@@ -106,6 +146,7 @@ impl DrawingCtx {
         dpi: Dpi,
         measuring: bool,
         testing: bool,
+        render_mode: RenderMode,
     ) -> DrawingCtx {
         let initial_transform = Transform::from(cr.get_matrix());
 
@@ -142,10 +183,14 @@ impl DrawingCtx {
             dpi,
             cr_stack: Vec::new(),
             cr: cr.clone(),
+            background_start: 0,
             view_box_stack: Rc::new(RefCell::new(view_box_stack)),
             drawsub_stack: Vec::new(),
             measuring,
             testing,
+            render_mode,
+            diagnostics: Diagnostics::new(),
+            link_bboxes: HashMap::new(),
         };
 
         if let Some(node) = node {
@@ -169,6 +214,33 @@ impl DrawingCtx {
         self.testing
     }
 
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    /// Non-fatal problems found while rendering.
+    pub fn diagnostics(&mut self) -> &mut Diagnostics {
+        &mut self.diagnostics
+    }
+
+    /// Records the bounding box of an `<a>` element that was just drawn, so that
+    /// all the links in a document can be measured in a single pass.
+    ///
+    /// An element that is drawn more than once accumulates all its bounding boxes.
+    pub fn record_link_bbox(&mut self, node: &Node, bbox: &BoundingBox) {
+        let initial_transform = self.initial_transform;
+
+        self.link_bboxes
+            .entry(link_key(node))
+            .or_insert_with(|| BoundingBox::new().with_transform(initial_transform))
+            .insert(bbox);
+    }
+
+    /// Bounding box of an `<a>` element that was drawn while measuring.
+    pub fn link_bbox(&self, node: &Node) -> Option<&BoundingBox> {
+        self.link_bboxes.get(&link_key(node))
+    }
+
     pub fn get_cairo_context(&self) -> cairo::Context {
         self.cr.clone()
     }
@@ -303,6 +375,7 @@ impl DrawingCtx {
                     ),
                     Some(v) => {
                         rsvg_log!(
+                            Warn,
                             "ignoring viewBox ({}, {}, {}, {}) since it is not usable",
                             v.0.x0,
                             v.0.y0,
@@ -342,13 +415,18 @@ impl DrawingCtx {
         let node = clip_node.as_ref().unwrap();
         let units = borrow_element_as!(node, ClipPath).get_units();
 
-        if let Ok(transform) = bbox.rect_to_transform(units) {
+        if let Some(transform) = bbox.rect_to_transform(units) {
             let cascaded = CascadedValues::new_from_node(node);
 
             self.with_saved_transform(Some(transform), &mut |dc| {
                 let cr = dc.get_cairo_context();
 
                 // here we don't push a layer because we are clipping
+                //
+                // Each child appends its path to the cr and sets the fill rule from its
+                // clip-rule property (not fill-rule); see draw_path().  Since Cairo only
+                // has a single fill rule per path, the clip region is only correct
+                // when the children do not overlap or all have the same clip-rule.
                 let res = node.draw_children(acquired_nodes, &cascaded, dc, true);
 
                 cr.clip();
@@ -478,174 +556,280 @@ impl DrawingCtx {
             &mut DrawingCtx,
         ) -> Result<BoundingBox, RenderingError>,
     ) -> Result<BoundingBox, RenderingError> {
-        if clipping {
+        if clipping || self.render_mode == RenderMode::BoundingBoxOnly {
             draw_fn(acquired_nodes, self)
         } else {
-            self.with_saved_cr(&mut |dc| {
-                let clip_path_value = values.clip_path();
-                let mask_value = values.mask();
-
-                let clip_uri = clip_path_value.0.get();
-                let mask = mask_value.0.get();
-
-                // The `filter` property does not apply to masks.
-                let filter = if node.is_element() {
-                    match *node.borrow_element() {
-                        Element::Mask(_) => None,
-                        _ => values.filter().0.get().cloned(),
-                    }
-                } else {
-                    values.filter().0.get().cloned()
-                };
+            let backdrop_filter = values.backdrop_filter();
+
+            if backdrop_filter.0.is_empty() {
+                self.with_compositing_layer(node, acquired_nodes, values, draw_fn)
+            } else {
+                self.with_backdrop_filter(
+                    node,
+                    acquired_nodes,
+                    values,
+                    &backdrop_filter.0,
+                    &mut |an, dc| dc.with_compositing_layer(node, an, values, draw_fn),
+                )
+            }
+        }
+    }
+
+    /// Draws an element into a temporary surface if it needs one for opacity,
+    /// clipping in object space, masking or filtering, and composites the
+    /// result onto the current surface.
+    fn with_compositing_layer(
+        &mut self,
+        node: &Node,
+        acquired_nodes: &mut AcquiredNodes,
+        values: &ComputedValues,
+        draw_fn: &mut dyn FnMut(
+            &mut AcquiredNodes,
+            &mut DrawingCtx,
+        ) -> Result<BoundingBox, RenderingError>,
+    ) -> Result<BoundingBox, RenderingError> {
+        self.with_saved_cr(&mut |dc| {
+            let clip_path_value = values.clip_path();
+            let mask_value = values.mask();
 
-                let UnitInterval(opacity) = values.opacity().0;
+            let clip_uri = clip_path_value.0.get();
+            let mask = mask_value.0.get();
 
-                let affine_at_start = dc.get_transform();
+            let filter_value = values.filter();
 
-                let (clip_in_user_space, clip_in_object_space) =
-                    get_clip_in_user_and_object_space(acquired_nodes, clip_uri);
+            // The `filter` property does not apply to masks.
+            let filter = if node.is_element() {
+                match *node.borrow_element() {
+                    Element::Mask(_) => None,
+                    _ => Some(&filter_value.0),
+                }
+            } else {
+                Some(&filter_value.0)
+            };
 
-                // Here we are clipping in user space, so the bbox doesn't matter
-                dc.clip_to_node(&clip_in_user_space, acquired_nodes, &dc.empty_bbox())?;
+            let filter = filter.filter(|f| !f.is_empty());
 
-                let needs_temporary_surface = !(opacity == 1.0
-                    && filter.is_none()
-                    && mask.is_none()
-                    && clip_in_object_space.is_none());
+            let UnitInterval(opacity) = values.opacity().0;
 
-                if needs_temporary_surface {
-                    // Compute our assortment of affines
+            let affine_at_start = dc.get_transform();
 
-                    let affines = CompositingAffines::new(
-                        affine_at_start,
-                        dc.initial_transform_with_offset(),
-                        dc.cr_stack.len(),
-                    );
+            let (clip_in_user_space, clip_in_object_space) =
+                get_clip_in_user_and_object_space(acquired_nodes, clip_uri);
 
-                    // Create temporary surface and its cr
+            // Here we are clipping in user space, so the bbox doesn't matter
+            dc.clip_to_node(&clip_in_user_space, acquired_nodes, &dc.empty_bbox())?;
 
-                    let cr = if filter.is_some() {
-                        cairo::Context::new(&*dc.create_surface_for_toplevel_viewport()?)
-                    } else {
-                        cairo::Context::new(
-                            &dc.create_similar_surface_for_toplevel_viewport(&dc.cr.get_target())?,
-                        )
-                    };
+            // An element with enable-background:new starts a new background
+            // image for its descendants, so it needs a layer of its own.
+            let new_background = values.enable_background() == EnableBackground::New;
 
-                    cr.set_matrix(affines.for_temporary_surface.into());
+            let needs_temporary_surface = !(opacity == 1.0
+                && filter.is_none()
+                && mask.is_none()
+                && clip_in_object_space.is_none()
+                && !new_background);
 
-                    dc.push_cairo_context(cr);
+            if needs_temporary_surface {
+                // Compute our assortment of affines
 
-                    // Draw!
+                let affines = CompositingAffines::new(
+                    affine_at_start,
+                    dc.initial_transform_with_offset(),
+                    dc.cr_stack.len(),
+                );
 
-                    let mut res = draw_fn(acquired_nodes, dc);
+                // Create temporary surface and its cr
 
-                    let bbox = if let Ok(ref bbox) = res {
-                        *bbox
-                    } else {
-                        BoundingBox::new().with_transform(affines.for_temporary_surface)
-                    };
+                let cr = if filter.is_some() {
+                    cairo::Context::new(&*dc.create_surface_for_toplevel_viewport()?)
+                } else {
+                    cairo::Context::new(
+                        &dc.create_similar_surface_for_toplevel_viewport(&dc.cr.get_target())?,
+                    )
+                };
 
-                    // Filter
+                cr.set_matrix(affines.for_temporary_surface.into());
 
-                    let source_surface = if let Some(filter_uri) = filter {
-                        // The target surface has multiple references.
-                        // We need to copy it to a new surface to have a unique
-                        // reference to be able to safely access the pixel data.
-                        let child_surface = SharedImageSurface::copy_from_surface(
-                            &cairo::ImageSurface::try_from(dc.cr.get_target()).unwrap(),
-                        )?;
+                dc.push_cairo_context(cr);
 
-                        let img_surface = dc
-                            .run_filter(
-                                acquired_nodes,
-                                &filter_uri,
-                                node,
-                                values,
-                                child_surface,
-                                bbox,
-                            )?
-                            .into_image_surface()?;
+                let saved_background_start = dc.background_start;
 
-                        // turn ImageSurface into a Surface
-                        (*img_surface).clone()
-                    } else {
-                        dc.cr.get_target()
-                    };
+                if new_background {
+                    dc.background_start = dc.cr_stack.len();
+                }
 
-                    dc.pop_cairo_context();
+                // Draw!
 
-                    // Set temporary surface as source
+                let mut res = draw_fn(acquired_nodes, dc);
 
-                    dc.cr.set_matrix(affines.compositing.into());
-                    dc.cr.set_source_surface(&source_surface, 0.0, 0.0);
-
-                    // Clip
-
-                    dc.cr.set_matrix(affines.outside_temporary_surface.into());
-                    dc.clip_to_node(&clip_in_object_space, acquired_nodes, &bbox)?;
-
-                    // Mask
-
-                    if let Some(fragment) = mask {
-                        if let Ok(acquired) = acquired_nodes.acquire(fragment) {
-                            let mask_node = acquired.get();
-
-                            match *mask_node.borrow_element() {
-                                Element::Mask(ref m) => {
-                                    res = res.and_then(|bbox| {
-                                        dc.generate_cairo_mask(
-                                            &m,
-                                            &mask_node,
-                                            affines.for_temporary_surface,
-                                            &bbox,
-                                            acquired_nodes,
-                                        )
-                                        .and_then(|mask_surf| {
-                                            if let Some(surf) = mask_surf {
-                                                dc.cr.set_matrix(affines.compositing.into());
-                                                dc.cr.mask_surface(&surf, 0.0, 0.0);
-                                            }
-                                            Ok(())
-                                        })
-                                        .map(|_: ()| bbox)
-                                    });
-                                }
-                                _ => {
-                                    rsvg_log!(
-                                        "element {} references \"{}\" which is not a mask",
-                                        node,
-                                        fragment
-                                    );
-                                }
+                dc.background_start = saved_background_start;
+
+                let bbox = if let Ok(ref bbox) = res {
+                    *bbox
+                } else {
+                    BoundingBox::new().with_transform(affines.for_temporary_surface)
+                };
+
+                // Filter
+
+                let source_surface = if let Some(filter_list) = filter {
+                    // The target surface has multiple references.
+                    // We need to copy it to a new surface to have a unique
+                    // reference to be able to safely access the pixel data.
+                    let target = cairo::ImageSurface::try_from(dc.cr.get_target())
+                        .map_err(|_| RenderingError::Cairo(cairo::Status::SurfaceTypeMismatch))?;
+                    let child_surface = SharedImageSurface::copy_from_surface(&target)?;
+
+                    let img_surface = dc
+                        .run_filters(
+                            acquired_nodes,
+                            filter_list,
+                            node,
+                            values,
+                            child_surface,
+                            bbox,
+                        )?
+                        .into_image_surface()?;
+
+                    // turn ImageSurface into a Surface
+                    (*img_surface).clone()
+                } else {
+                    dc.cr.get_target()
+                };
+
+                dc.pop_cairo_context();
+
+                // Set temporary surface as source
+
+                dc.cr.set_matrix(affines.compositing.into());
+                dc.cr.set_source_surface(&source_surface, 0.0, 0.0);
+
+                // Clip
+
+                dc.cr.set_matrix(affines.outside_temporary_surface.into());
+                dc.clip_to_node(&clip_in_object_space, acquired_nodes, &bbox)?;
+
+                // Mask
+
+                if let Some(fragment) = mask {
+                    if let Ok(acquired) = acquired_nodes.acquire(fragment) {
+                        let mask_node = acquired.get();
+
+                        match *mask_node.borrow_element() {
+                            Element::Mask(ref m) => {
+                                res = res.and_then(|bbox| {
+                                    dc.generate_cairo_mask(
+                                        &m,
+                                        &mask_node,
+                                        affines.for_temporary_surface,
+                                        &bbox,
+                                        acquired_nodes,
+                                    )
+                                    .and_then(|mask_surf| {
+                                        if let Some(surf) = mask_surf {
+                                            dc.cr.set_matrix(affines.compositing.into());
+                                            dc.cr.mask_surface(&surf, 0.0, 0.0);
+                                        }
+                                        Ok(())
+                                    })
+                                    .map(|_: ()| bbox)
+                                });
+                            }
+                            _ => {
+                                rsvg_log!(
+                                    Warn,
+                                    "element {} references \"{}\" which is not a mask",
+                                    node,
+                                    fragment
+                                );
                             }
-                        } else {
-                            rsvg_log!(
-                                "element {} references nonexistent mask \"{}\"",
-                                node,
-                                fragment
-                            );
                         }
                     } else {
-                        // No mask, so composite the temporary surface
+                        rsvg_log!(
+                            Warn,
+                            "element {} references nonexistent mask \"{}\"",
+                            node,
+                            fragment
+                        );
+                    }
+                } else {
+                    // No mask, so composite the temporary surface
 
-                        dc.cr.set_matrix(affines.compositing.into());
+                    dc.cr.set_matrix(affines.compositing.into());
 
-                        if opacity < 1.0 {
-                            dc.cr.paint_with_alpha(opacity);
-                        } else {
-                            dc.cr.paint();
-                        }
+                    if opacity < 1.0 {
+                        dc.cr.paint_with_alpha(opacity);
+                    } else {
+                        dc.cr.paint();
                     }
+                }
 
-                    dc.cr.set_matrix(affine_at_start.into());
+                dc.cr.set_matrix(affine_at_start.into());
 
-                    res
-                } else {
-                    draw_fn(acquired_nodes, dc)
-                }
-            })
+                res
+            } else {
+                draw_fn(acquired_nodes, dc)
+            }
+        })
+    }
+
+    /// Implements `backdrop-filter`.
+    ///
+    /// The element is drawn into a group so that its bounding box is known.
+    /// Then a snapshot of what was painted before the element is filtered,
+    /// painted back clipped to the element's bounding box, and finally the
+    /// element is painted on top.
+    fn with_backdrop_filter(
+        &mut self,
+        node: &Node,
+        acquired_nodes: &mut AcquiredNodes,
+        values: &ComputedValues,
+        filter_list: &FilterValueList,
+        draw_fn: &mut dyn FnMut(
+            &mut AcquiredNodes,
+            &mut DrawingCtx,
+        ) -> Result<BoundingBox, RenderingError>,
+    ) -> Result<BoundingBox, RenderingError> {
+        let target = match cairo::ImageSurface::try_from(self.cr.get_target()) {
+            Ok(surface) => surface,
+            Err(_) => {
+                rsvg_log!(
+                    Warn,
+                    "(ignoring backdrop-filter on {} since the target is not an image surface)",
+                    node
+                );
+                return draw_fn(acquired_nodes, self);
+            }
+        };
+
+        let backdrop = SharedImageSurface::copy_from_surface(&target)?;
+
+        self.cr.push_group();
+        let res = draw_fn(acquired_nodes, self);
+        let element = self.cr.pop_group();
+
+        let bbox = res?;
+
+        if let Some(rect) = bbox.rect {
+            let filtered = self
+                .run_filters(acquired_nodes, filter_list, node, values, backdrop, bbox)?
+                .into_image_surface()?;
+
+            self.cr.save();
+            self.cr.set_matrix(bbox.transform.into());
+            self.cr
+                .rectangle(rect.x0, rect.y0, rect.width(), rect.height());
+            self.cr.clip();
+            self.cr.identity_matrix();
+            self.cr.set_source_surface(&filtered, 0.0, 0.0);
+            self.cr.paint();
+            self.cr.restore();
         }
+
+        self.cr.set_source(&element);
+        self.cr.paint();
+
+        Ok(bbox)
     }
 
     fn initial_transform_with_offset(&self) -> Transform {
@@ -757,6 +941,39 @@ impl DrawingCtx {
         res
     }
 
+    /// Applies each item of the `filter` property in order, feeding the output
+    /// of one filter into the next.
+    fn run_filters(
+        &mut self,
+        acquired_nodes: &mut AcquiredNodes,
+        filter_list: &FilterValueList,
+        node: &Node,
+        values: &ComputedValues,
+        child_surface: SharedImageSurface,
+        node_bbox: BoundingBox,
+    ) -> Result<SharedImageSurface, RenderingError> {
+        let mut surface = child_surface;
+
+        for filter in filter_list.iter() {
+            surface = match *filter {
+                FilterValue::Url(ref filter_uri) => self.run_filter(
+                    acquired_nodes,
+                    filter_uri,
+                    node,
+                    values,
+                    surface,
+                    node_bbox,
+                )?,
+
+                FilterValue::Function(ref func) => {
+                    filters::render_function(func, values, surface, self)?
+                }
+            };
+        }
+
+        Ok(surface)
+    }
+
     fn run_filter(
         &mut self,
         acquired_nodes: &mut AcquiredNodes,
@@ -783,6 +1000,7 @@ impl DrawingCtx {
                     }
                     _ => {
                         rsvg_log!(
+                            Warn,
                             "element {} will not be rendered since \"{}\" is not a filter",
                             node,
                             filter_uri,
@@ -790,13 +1008,27 @@ impl DrawingCtx {
                     }
                 }
             }
-            _ => {
+            Err(AcquireError::CircularReference(filter_node)) => {
+                rsvg_log!(Warn, "circular reference in filter {}", filter_node);
+                return Err(RenderingError::CircularReference);
+            }
+            Err(AcquireError::LinkNotFound(_)) => {
                 rsvg_log!(
+                    Warn,
                     "element {} will not be rendered since its filter \"{}\" was not found",
                     node,
                     filter_uri,
                 );
             }
+            Err(e) => {
+                rsvg_log!(
+                    Warn,
+                    "element {} will not be rendered since its filter \"{}\" could not be used: {}",
+                    node,
+                    filter_uri,
+                    e,
+                );
+            }
         }
 
         // Non-existing filters must act as null filters (an empty surface is returned).
@@ -807,6 +1039,35 @@ impl DrawingCtx {
         )?)
     }
 
+    /// Sets a gradient as the source, like `PaintSource::resolve_fallbacks_and_set_pattern`.
+    ///
+    /// If the gradient cannot be used because the element has zero width or height, and
+    /// `use_average_color` is true, the average color of its stops is used instead.  The
+    /// caller passes false when the paint has a fallback color, which takes precedence.
+    fn set_gradient_source<T: PaintSource<Resolved = Gradient>>(
+        &mut self,
+        gradient: &T,
+        node: &Node,
+        acquired_nodes: &mut AcquiredNodes,
+        opacity: UnitInterval,
+        bbox: &BoundingBox,
+        use_average_color: bool,
+    ) -> Result<bool, RenderingError> {
+        if gradient.resolve_fallbacks_and_set_pattern(node, acquired_nodes, self, opacity, bbox)? {
+            return Ok(true);
+        }
+
+        if !use_average_color {
+            return Ok(false);
+        }
+
+        // The gradient is cached once it is resolved, so this does not resolve it again.
+        match gradient.resolve(node, acquired_nodes) {
+            Ok(resolved) => Ok(resolved.set_average_stop_color_as_source(self, opacity)),
+            Err(_) => Ok(false),
+        }
+    }
+
     fn set_color(
         &self,
         color: cssparser::Color,
@@ -849,21 +1110,37 @@ impl DrawingCtx {
                         assert!(node.is_element());
 
                         had_paint_server = match *node.borrow_element() {
-                            Element::LinearGradient(ref g) => g.resolve_fallbacks_and_set_pattern(
+                            Element::LinearGradient(ref g) => self.set_gradient_source(
+                                g,
+                                &node,
+                                acquired_nodes,
+                                opacity,
+                                bbox,
+                                alternate.is_none(),
+                            )?,
+                            Element::RadialGradient(ref g) => self.set_gradient_source(
+                                g,
+                                &node,
+                                acquired_nodes,
+                                opacity,
+                                bbox,
+                                alternate.is_none(),
+                            )?,
+                            Element::Pattern(ref p) => p.resolve_fallbacks_and_set_pattern(
                                 &node,
                                 acquired_nodes,
                                 self,
                                 opacity,
                                 bbox,
                             )?,
-                            Element::RadialGradient(ref g) => g.resolve_fallbacks_and_set_pattern(
+                            Element::Hatch(ref h) => h.resolve_fallbacks_and_set_pattern(
                                 &node,
                                 acquired_nodes,
                                 self,
                                 opacity,
                                 bbox,
                             )?,
-                            Element::Pattern(ref p) => p.resolve_fallbacks_and_set_pattern(
+                            Element::MeshGradient(ref m) => m.resolve_fallbacks_and_set_pattern(
                                 &node,
                                 acquired_nodes,
                                 self,
@@ -878,6 +1155,10 @@ impl DrawingCtx {
                         return Err(RenderingError::InstancingLimit);
                     }
 
+                    Err(AcquireError::MaxDepthExceeded) => {
+                        return Err(RenderingError::InstancingLimit);
+                    }
+
                     Err(_) => (),
                 }
 
@@ -886,6 +1167,7 @@ impl DrawingCtx {
                     had_paint_server = true;
                 } else {
                     rsvg_log!(
+                        Warn,
                         "pattern \"{}\" was not found and there was no fallback alternate",
                         iri
                     );
@@ -903,7 +1185,16 @@ impl DrawingCtx {
         }
     }
 
-    pub fn setup_cr_for_stroke(&self, cr: &cairo::Context, values: &ComputedValues) {
+    /// Sets the stroke parameters of `cr` from the `values`.
+    ///
+    /// The dash lengths and offset are multiplied by `dash_scale`; this is for the
+    /// `pathLength` attribute of shapes, and should be 1.0 otherwise.
+    pub fn setup_cr_for_stroke(
+        &self,
+        cr: &cairo::Context,
+        values: &ComputedValues,
+        dash_scale: f64,
+    ) {
         let params = self.get_view_params();
 
         cr.set_line_width(values.stroke_width().0.normalize(values, &params));
@@ -911,19 +1202,28 @@ impl DrawingCtx {
         cr.set_line_cap(cairo::LineCap::from(values.stroke_line_cap()));
         cr.set_line_join(cairo::LineJoin::from(values.stroke_line_join()));
 
-        if let StrokeDasharray(Dasharray::Array(ref dashes)) = values.stroke_dasharray() {
-            let normalized_dashes: Vec<f64> = dashes
-                .iter()
-                .map(|l| l.normalize(values, &params))
-                .collect();
-
-            let total_length = normalized_dashes.iter().fold(0.0, |acc, &len| acc + len);
-
-            if total_length > 0.0 {
-                let offset = values.stroke_dashoffset().0.normalize(values, &params);
-                cr.set_dash(&normalized_dashes, offset);
-            } else {
-                cr.set_dash(&[], 0.0);
+        match values.stroke_dasharray() {
+            // Don't rely on the Cairo context's default; it may come with dashes already
+            // set up by the caller.
+            StrokeDasharray(Dasharray::None) => cr.set_dash(&[], 0.0),
+
+            StrokeDasharray(Dasharray::Array(ref dashes)) => {
+                let normalized_dashes: Vec<f64> = dashes
+                    .iter()
+                    .map(|l| l.normalize(values, &params) * dash_scale)
+                    .collect();
+
+                let total_length = normalized_dashes.iter().fold(0.0, |acc, &len| acc + len);
+
+                // A dash array that adds up to zero is rendered as a solid line, per
+                // https://www.w3.org/TR/SVG2/painting.html#StrokeDashing
+                if total_length > 0.0 {
+                    let offset =
+                        values.stroke_dashoffset().0.normalize(values, &params) * dash_scale;
+                    cr.set_dash(&normalized_dashes, offset);
+                } else {
+                    cr.set_dash(&[], 0.0);
+                }
             }
         }
     }
@@ -933,10 +1233,20 @@ impl DrawingCtx {
         cr: &cairo::Context,
         acquired_nodes: &mut AcquiredNodes,
         values: &ComputedValues,
+        dash_scale: f64,
     ) -> Result<BoundingBox, RenderingError> {
-        cr.set_antialias(cairo::Antialias::from(values.shape_rendering()));
+        let shape_rendering = values.shape_rendering();
 
-        self.setup_cr_for_stroke(cr, values);
+        let backup_tolerance = cr.get_tolerance();
+
+        cr.set_antialias(cairo::Antialias::from(shape_rendering));
+        cr.set_tolerance(shape_rendering.tolerance());
+
+        if shape_rendering == ShapeRendering::CrispEdges {
+            snap_path_to_pixel_centers(cr);
+        }
+
+        self.setup_cr_for_stroke(cr, values, dash_scale);
 
         // Update the bbox in the rendering context.  Below, we actually set the
         // fill/stroke patterns on the cairo_t.  That process requires the
@@ -944,6 +1254,12 @@ impl DrawingCtx {
         // coordinate system in patterns.
         let bbox = compute_stroke_and_fill_box(cr, values);
 
+        if self.render_mode == RenderMode::BoundingBoxOnly {
+            cr.new_path();
+            cr.set_tolerance(backup_tolerance);
+            return Ok(bbox);
+        }
+
         let current_color = values.color().0;
 
         let res = self
@@ -985,6 +1301,8 @@ impl DrawingCtx {
         // we leave it around from computing the bounding box
         cr.new_path();
 
+        cr.set_tolerance(backup_tolerance);
+
         res.and_then(|_: ()| Ok(bbox))
     }
 
@@ -995,9 +1313,18 @@ impl DrawingCtx {
         acquired_nodes: &mut AcquiredNodes,
         values: &ComputedValues,
         markers: Markers,
+        path_length: Option<f64>,
         clipping: bool,
     ) -> Result<BoundingBox, RenderingError> {
         if !path.is_empty() {
+            // Dashes are specified in terms of the author's pathLength, if any;
+            // scale them to the actual length of the path.  A pathLength of zero
+            // is ignored.
+            let dash_scale = match path_length {
+                Some(l) if l > 0.0 => path.path_length() / l,
+                _ => 1.0,
+            };
+
             let bbox =
                 self.with_discrete_layer(node, acquired_nodes, values, clipping, &mut |an, dc| {
                     let cr = dc.get_cairo_context();
@@ -1009,7 +1336,7 @@ impl DrawingCtx {
                         Ok(dc.empty_bbox())
                     } else {
                         cr.set_fill_rule(cairo::FillRule::from(values.fill_rule()));
-                        dc.stroke_and_fill(&cr, an, values)
+                        dc.stroke_and_fill(&cr, an, values, dash_scale)
                     }
                 })?;
 
@@ -1028,11 +1355,10 @@ impl DrawingCtx {
         width: i32,
         height: i32,
     ) -> Result<SharedImageSurface, cairo::Status> {
-        // TODO: as far as I can tell this should not render elements past the last (topmost) one
-        // with enable-background: new (because technically we shouldn't have been caching them).
-        // Right now there are no enable-background checks whatsoever.
+        // Only the layers starting at the nearest ancestor with enable-background: new
+        // are part of the background image.  Layers below that one are skipped.
         //
-        // Addendum: SVG 2 has deprecated the enable-background property, and replaced it with an
+        // Note that SVG 2 has deprecated the enable-background property, and replaced it with an
         // "isolation" property from the CSS Compositing and Blending spec.
         //
         // Deprecation:
@@ -1046,7 +1372,7 @@ impl DrawingCtx {
         let mut surface = ExclusiveImageSurface::new(width, height, SurfaceType::SRgb)?;
 
         surface.draw(&mut |cr| {
-            for (depth, draw) in self.cr_stack.iter().enumerate() {
+            for (depth, draw) in self.cr_stack.iter().enumerate().skip(self.background_start) {
                 let affines = CompositingAffines::new(
                     Transform::from(draw.get_matrix()),
                     self.initial_transform_with_offset(),
@@ -1139,13 +1465,18 @@ impl DrawingCtx {
         // another <use> which references the first one, etc.).  So,
         // we acquire the <use> element itself so that circular
         // references can be caught.
-        let _self_acquired = acquired_nodes.acquire_ref(node).map_err(|e| {
-            if let AcquireError::CircularReference(_) = e {
-                rsvg_log!("circular reference in element {}", node);
+        let _self_acquired = acquired_nodes.acquire_ref(node).map_err(|e| match e {
+            AcquireError::CircularReference(_) => {
+                rsvg_log!(Warn, "circular reference in element {}", node);
                 RenderingError::CircularReference
-            } else {
-                unreachable!();
             }
+
+            AcquireError::MaxDepthExceeded => {
+                rsvg_log!(Warn, "element {} is nested too deeply", node);
+                RenderingError::InstancingLimit
+            }
+
+            _ => unreachable!(),
         })?;
 
         if link.is_none() {
@@ -1156,7 +1487,7 @@ impl DrawingCtx {
             Ok(acquired) => acquired,
 
             Err(AcquireError::CircularReference(node)) => {
-                rsvg_log!("circular reference in element {}", node);
+                rsvg_log!(Warn, "circular reference in element {}", node);
                 return Err(RenderingError::CircularReference);
             }
 
@@ -1164,23 +1495,27 @@ impl DrawingCtx {
                 return Err(RenderingError::InstancingLimit);
             }
 
+            Err(AcquireError::MaxDepthExceeded) => {
+                rsvg_log!(Warn, "element {} is nested too deeply", node);
+                return Err(RenderingError::InstancingLimit);
+            }
+
             Err(AcquireError::InvalidLinkType(_)) => unreachable!(),
 
             Err(AcquireError::LinkNotFound(fragment)) => {
-                rsvg_log!("element {} references nonexistent \"{}\"", node, fragment);
+                rsvg_log!(
+                    Warn,
+                    "element {} references nonexistent \"{}\"",
+                    node,
+                    fragment
+                );
                 return Ok(self.empty_bbox());
             }
         };
 
         let values = cascaded.get();
         let params = self.get_view_params();
-        let use_rect = borrow_element_as!(node, Use).get_rect(values, &params);
-
-        // width or height set to 0 disables rendering of the element
-        // https://www.w3.org/TR/SVG/struct.html#UseElementWidthAttribute
-        if use_rect.is_empty() {
-            return Ok(self.empty_bbox());
-        }
+        let use_element = borrow_element_as!(node, Use);
 
         let child = acquired.get();
 
@@ -1189,6 +1524,14 @@ impl DrawingCtx {
             let elt = child.borrow_element();
 
             if let Element::Symbol(ref symbol) = *elt {
+                let use_rect = use_element.get_rect(Some(symbol), values, &params);
+
+                // width or height set to 0 disables rendering of the element
+                // https://www.w3.org/TR/SVG/struct.html#UseElementWidthAttribute
+                if use_rect.is_empty() {
+                    return Ok(self.empty_bbox());
+                }
+
                 let clip_mode = if !values.is_overflow()
                     || (values.overflow() == Overflow::Visible
                         && elt.get_specified_values().is_overflow())
@@ -1198,31 +1541,26 @@ impl DrawingCtx {
                     None
                 };
 
-                return self.with_discrete_layer(
+                return self.draw_symbol(
                     node,
+                    &child,
+                    symbol,
                     acquired_nodes,
                     values,
+                    use_rect,
+                    clip_mode,
                     clipping,
-                    &mut |an, dc| {
-                        let _params = dc.push_new_viewport(
-                            symbol.get_viewbox(),
-                            use_rect,
-                            symbol.get_preserve_aspect_ratio(),
-                            clip_mode,
-                        );
-
-                        child.draw_children(
-                            an,
-                            &CascadedValues::new_from_values(&child, values),
-                            dc,
-                            clipping,
-                        )
-                    },
                 );
             }
         };
 
         // all other nodes
+        let use_rect = use_element.get_rect(None, values, &params);
+
+        if use_rect.is_empty() {
+            return Ok(self.empty_bbox());
+        }
+
         let cr = self.get_cairo_context();
         cr.translate(use_rect.x0, use_rect.y0);
 
@@ -1235,6 +1573,44 @@ impl DrawingCtx {
             )
         })
     }
+
+    /// Draws the children of a `<symbol>` so that the symbol's viewBox fills `viewport`.
+    ///
+    /// The group opacity, filter, etc. are taken from `layer_node`, whose computed
+    /// values are `values`; for a `<use>` this is the `<use>` element itself.
+    pub fn draw_symbol(
+        &mut self,
+        layer_node: &Node,
+        symbol_node: &Node,
+        symbol: &Symbol,
+        acquired_nodes: &mut AcquiredNodes,
+        values: &ComputedValues,
+        viewport: Rect,
+        clip_mode: Option<ClipMode>,
+        clipping: bool,
+    ) -> Result<BoundingBox, RenderingError> {
+        self.with_discrete_layer(
+            layer_node,
+            acquired_nodes,
+            values,
+            clipping,
+            &mut |an, dc| {
+                let _params = dc.push_new_viewport(
+                    symbol.get_viewbox(),
+                    viewport,
+                    symbol.get_preserve_aspect_ratio(),
+                    clip_mode,
+                );
+
+                symbol_node.draw_children(
+                    an,
+                    &CascadedValues::new_from_values(symbol_node, values),
+                    dc,
+                    clipping,
+                )
+            },
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -1340,9 +1716,15 @@ fn compute_stroke_and_fill_box(cr: &cairo::Context, values: &ComputedValues) ->
 
     if values.stroke().0 != PaintServer::None {
         let (x0, y0, x1, y1) = cr.stroke_extents();
+        let mut stroke_rect = Rect::new(x0, y0, x1, y1);
+
+        if let Some(r) = round_stroke_extents(cr) {
+            stroke_rect = stroke_rect.union(&r);
+        }
+
         let sb = BoundingBox::new()
             .with_transform(affine)
-            .with_ink_rect(Rect::new(x0, y0, x1, y1));
+            .with_ink_rect(stroke_rect);
         bbox.insert(&sb);
     }
 
@@ -1361,7 +1743,127 @@ fn compute_stroke_and_fill_box(cr: &cairo::Context, values: &ComputedValues) ->
     bbox
 }
 
+/// Computes the extents of the round caps and round joins of the current path, in user space.
+///
+/// Cairo strokes round caps and joins as polygons that approximate the circles
+/// within the current tolerance, so `stroke_extents()` can fall short of the
+/// rendered pixels.  A round cap or join covers a whole circle of diameter equal to
+/// the line width around an endpoint or vertex, so the extents of those circles are
+/// exact.  Square caps and miter or bevel joins are polygons already, so Cairo's
+/// extents for them need no correction.
+///
+/// Returns `None` if the stroke has neither round caps nor round joins.
+fn round_stroke_extents(cr: &cairo::Context) -> Option<Rect> {
+    let round_caps = cr.get_line_cap() == cairo::LineCap::Round;
+    let round_joins = cr.get_line_join() == cairo::LineJoin::Round;
+
+    if !round_caps && !round_joins {
+        return None;
+    }
+
+    let radius = cr.get_line_width() / 2.0;
+
+    let mut extents: Option<Rect> = None;
+
+    let mut add_circle = |(x, y): (f64, f64)| {
+        let r = Rect::new(x - radius, y - radius, x + radius, y + radius);
+        extents = Some(extents.map_or(r, |e| e.union(&r)));
+    };
+
+    // Vertices of the current subpath, starting with the point of its moveto
+    let mut vertices: Vec<(f64, f64)> = Vec::new();
+
+    let mut finish_subpath = |vertices: &mut Vec<(f64, f64)>, closed: bool| {
+        // A lone moveto gets no stroke
+        if vertices.len() > 1 {
+            let last = vertices.len() - 1;
+
+            for (i, &v) in vertices.iter().enumerate() {
+                let is_endpoint = !closed && (i == 0 || i == last);
+
+                if (is_endpoint && round_caps) || (!is_endpoint && round_joins) {
+                    add_circle(v);
+                }
+            }
+        }
+
+        vertices.clear();
+    };
+
+    for segment in cr.copy_path().iter() {
+        match segment {
+            cairo::PathSegment::MoveTo(p) => {
+                finish_subpath(&mut vertices, false);
+                vertices.push(p);
+            }
+
+            cairo::PathSegment::LineTo(p) => vertices.push(p),
+
+            cairo::PathSegment::CurveTo(_, _, p) => vertices.push(p),
+
+            cairo::PathSegment::ClosePath => {
+                // A closed subpath of zero length gets drawn as a dot with round caps
+                let is_degenerate = vertices.iter().all(|&v| v == vertices[0]);
+
+                if is_degenerate {
+                    if let Some(&v) = vertices.first() {
+                        vertices.push(v);
+                    }
+                }
+
+                finish_subpath(&mut vertices, !is_degenerate);
+            }
+        }
+    }
+
+    finish_subpath(&mut vertices, false);
+
+    extents
+}
+
+/// Replaces the current path with one whose points are snapped to the centers of device pixels.
+///
+/// This is for `shape-rendering: crispEdges`, so that axis-aligned edges don't get smeared
+/// across two pixels.
+fn snap_path_to_pixel_centers(cr: &cairo::Context) {
+    let path = cr.copy_path();
+
+    let snap = |(x, y): (f64, f64)| {
+        let (dx, dy) = cr.user_to_device(x, y);
+        cr.device_to_user(dx.floor() + 0.5, dy.floor() + 0.5)
+    };
+
+    cr.new_path();
+
+    for segment in path.iter() {
+        match segment {
+            cairo::PathSegment::MoveTo(p) => {
+                let (x, y) = snap(p);
+                cr.move_to(x, y);
+            }
+
+            cairo::PathSegment::LineTo(p) => {
+                let (x, y) = snap(p);
+                cr.line_to(x, y);
+            }
+
+            cairo::PathSegment::CurveTo(p1, p2, p3) => {
+                let (x1, y1) = snap(p1);
+                let (x2, y2) = snap(p2);
+                let (x3, y3) = snap(p3);
+                cr.curve_to(x1, y1, x2, y2, x3, y3);
+            }
+
+            cairo::PathSegment::ClosePath => cr.close_path(),
+        }
+    }
+}
+
 /// escape quotes and backslashes with backslash
+fn link_key(node: &Node) -> *const NodeData {
+    &*node.borrow() as *const NodeData
+}
+
 fn escape_link_target(value: &str) -> Cow<'_, str> {
     static REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"['\\]").unwrap());
 
@@ -1412,6 +1914,21 @@ impl From<FillRule> for cairo::FillRule {
     }
 }
 
+impl ShapeRendering {
+    /// Returns the tolerance to use for `cairo::Context::set_tolerance()`.
+    ///
+    /// Cairo's default is 0.1 device pixels; `geometricPrecision` asks for the
+    /// highest-quality flattening of curves, so we use a smaller tolerance there.
+    fn tolerance(self) -> f64 {
+        match self {
+            ShapeRendering::GeometricPrecision => 0.01,
+            ShapeRendering::Auto | ShapeRendering::OptimizeSpeed | ShapeRendering::CrispEdges => {
+                0.1
+            }
+        }
+    }
+}
+
 impl From<ShapeRendering> for cairo::Antialias {
     fn from(sr: ShapeRendering) -> cairo::Antialias {
         match sr {
@@ -8,9 +8,12 @@ use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::ops::Deref;
 
+use crate::animation::{AnimateMotion, Mpath, Set};
+use crate::aria::{self, AriaAttributes};
 use crate::bbox::BoundingBox;
 use crate::cond::{RequiredExtensions, RequiredFeatures, SystemLanguage};
 use crate::css::{Declaration, Origin};
+use crate::diagnostics::Diagnostics;
 use crate::document::AcquiredNodes;
 use crate::drawing_ctx::DrawingCtx;
 use crate::error::*;
@@ -34,12 +37,14 @@ use crate::filters::{
     FilterEffect,
 };
 use crate::gradient::{LinearGradient, RadialGradient, Stop};
+use crate::hatch::{Hatch, HatchPath};
 use crate::image::Image;
 use crate::marker::Marker;
+use crate::mesh_gradient::{MeshGradient, MeshPatch, MeshRow};
 use crate::node::*;
 use crate::parsers::Parse;
 use crate::pattern::Pattern;
-use crate::properties::{ComputedValues, SpecifiedValues};
+use crate::properties::{self, ComputedValues, SpecifiedValues};
 use crate::property_bag::PropertyBag;
 use crate::shapes::{Circle, Ellipse, Line, Path, Polygon, Polyline, Rect};
 use crate::structure::{ClipPath, Group, Link, Mask, NonRendering, Svg, Switch, Symbol, Use};
@@ -105,7 +110,11 @@ pub struct ElementInner<T: SetAttributes + Draw> {
     transform: Transform,
     values: ComputedValues,
     cond: bool,
-    style_attr: String,
+    tab_index: Option<i32>,
+    /// Copy of the attributes that are needed after loading, or of all of them if
+    /// the document keeps its attributes.  Each run of the cascade starts from the
+    /// presentation attributes and the style attribute in here.
+    attributes: Vec<(QualName, String)>,
     pub element_impl: T,
 }
 
@@ -142,13 +151,36 @@ impl<T: SetAttributes + Draw> ElementInner<T> {
         self.transform
     }
 
-    fn save_style_attribute(&mut self, pbag: &PropertyBag<'_>) {
-        for (attr, value) in pbag.iter() {
-            match attr.expanded() {
-                expanded_name!("", "style") => self.style_attr.push_str(value),
-                _ => (),
-            }
-        }
+    fn get_tab_index(&self) -> Option<i32> {
+        self.tab_index
+    }
+
+    fn get_aria_attributes(&self) -> AriaAttributes {
+        AriaAttributes::from_attributes(&self.attributes)
+    }
+
+    fn attributes(&self) -> &[(QualName, String)] {
+        &self.attributes
+    }
+
+    /// Saves the attributes that are used after loading.
+    fn save_attributes(&mut self, pbag: &PropertyBag<'_>) {
+        self.attributes = pbag
+            .iter()
+            .filter(|(attr, _)| {
+                properties::is_presentation_attribute(attr)
+                    || attr.expanded() == expanded_name!("", "style")
+                    || aria::is_aria_attribute(attr)
+            })
+            .map(|(attr, value)| (attr, value.to_string()))
+            .collect();
+    }
+
+    fn keep_attributes(&mut self, pbag: &PropertyBag<'_>) {
+        self.attributes = pbag
+            .iter()
+            .map(|(attr, value)| (attr, value.to_string()))
+            .collect();
     }
 
     fn set_transform_attribute(&mut self, pbag: &PropertyBag<'_>) -> Result<(), ElementError> {
@@ -169,6 +201,20 @@ impl<T: SetAttributes + Draw> ElementInner<T> {
         Ok(())
     }
 
+    /// Parses the `tabindex` attribute.
+    ///
+    /// As in HTML, an invalid value is ignored instead of putting the element in error.
+    fn set_tab_index_attribute(&mut self, pbag: &PropertyBag<'_>) {
+        for (attr, value) in pbag.iter() {
+            if attr.expanded() == expanded_name!("", "tabindex") {
+                match i32::parse_str(value) {
+                    Ok(index) => self.tab_index = Some(index),
+                    Err(_) => rsvg_log!(Warn, "ignoring invalid tabindex \"{}\"", value),
+                }
+            }
+        }
+    }
+
     fn set_conditional_processing_attributes(
         &mut self,
         pbag: &PropertyBag<'_>,
@@ -205,28 +251,37 @@ impl<T: SetAttributes + Draw> ElementInner<T> {
         Ok(())
     }
 
-    /// Hands the pbag to the node's state, to apply the presentation attributes
-    fn set_presentation_attributes(&mut self, pbag: &PropertyBag<'_>) -> Result<(), ElementError> {
-        match self.specified_values.parse_presentation_attributes(pbag) {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                // FIXME: we'll ignore errors here for now.
-                //
-                // If we set the node to be in error, we expose buggy handling of the
-                // enable-background property; we are not parsing it correctly. This
-                // causes tests/fixtures/reftests/bugs/587721-text-transform.svg to fail
-                // because it has enable-background="new 0 0 1179.75118 687.74173" in the
-                // toplevel svg element.
-                //
-                //   self.set_error(e);
-                //   return;
-
-                rsvg_log!("(attribute error: {})", e);
-                Ok(())
-            }
+    /// Applies the presentation attributes from the saved attributes
+    fn set_presentation_attributes(&mut self, diagnostics: &mut Diagnostics) {
+        let pbag = PropertyBag::new_from_attributes(&self.attributes);
+
+        if let Err(e) = self
+            .specified_values
+            .parse_presentation_attributes(&pbag, diagnostics)
+        {
+            // FIXME: we'll ignore errors here for now.
+            //
+            // If we set the node to be in error, we expose buggy handling of the
+            // enable-background property; we are not parsing it correctly. This
+            // causes tests/fixtures/reftests/bugs/587721-text-transform.svg to fail
+            // because it has enable-background="new 0 0 1179.75118 687.74173" in the
+            // toplevel svg element.
+            //
+            //   self.set_error(e);
+            //   return;
+
+            rsvg_log!(Warn, "(attribute error: {})", e);
         }
     }
 
+    /// Discards the results of a previous cascade, so that a new one can start
+    /// from the presentation attributes.
+    fn reset_specified_values(&mut self, diagnostics: &mut Diagnostics) {
+        self.specified_values = Default::default();
+        self.important_styles.clear();
+        self.set_presentation_attributes(diagnostics);
+    }
+
     // Applies a style declaration to the node's specified_values
     fn apply_style_declaration(&mut self, declaration: &Declaration, origin: Origin) {
         self.specified_values.set_property_from_declaration(
@@ -237,23 +292,26 @@ impl<T: SetAttributes + Draw> ElementInner<T> {
     }
 
     /// Applies CSS styles from the saved value of the "style" attribute
-    fn set_style_attribute(&mut self) {
-        if !self.style_attr.is_empty() {
+    fn set_style_attribute(&mut self, diagnostics: &mut Diagnostics) {
+        let style_attr = self
+            .attributes
+            .iter()
+            .find(|(attr, _)| attr.expanded() == expanded_name!("", "style"));
+
+        if let Some((_, style)) = style_attr {
             if let Err(e) = self.specified_values.parse_style_declarations(
-                self.style_attr.as_str(),
+                style.as_str(),
                 Origin::Author,
                 &mut self.important_styles,
+                diagnostics,
             ) {
                 self.set_error(e);
             }
-
-            self.style_attr.clear();
-            self.style_attr.shrink_to_fit();
         }
     }
 
     fn set_error(&mut self, error: ElementError) {
-        rsvg_log!("setting node {} in error: {}", self, error);
+        rsvg_log!(Warn, "setting node {} in error: {}", self, error);
         self.result = Err(error);
     }
 
@@ -264,12 +322,12 @@ impl<T: SetAttributes + Draw> ElementInner<T> {
 
 impl<T: SetAttributes + Draw> SetAttributes for ElementInner<T> {
     fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
-        self.save_style_attribute(pbag);
+        self.save_attributes(pbag);
+        self.set_tab_index_attribute(pbag);
 
         self.set_transform_attribute(pbag)
             .and_then(|_| self.set_conditional_processing_attributes(pbag))
             .and_then(|_| self.element_impl.set_attributes(pbag))
-            .and_then(|_| self.set_presentation_attributes(pbag))
     }
 }
 
@@ -288,7 +346,11 @@ impl<T: SetAttributes + Draw> Draw for ElementInner<T> {
                     .draw(node, acquired_nodes, cascaded, dc, clipping)
             })
         } else {
-            rsvg_log!("(not rendering element {} because it is in error)", self);
+            rsvg_log!(
+                Debug,
+                "(not rendering element {} because it is in error)",
+                self
+            );
 
             // maybe we should actually return a RenderingError::ElementIsInError here?
             Ok(draw_ctx.empty_bbox())
@@ -313,22 +375,216 @@ impl<T: SetAttributes + Draw> Deref for ElementInner<T> {
     }
 }
 
+/// The type of an SVG element, for introspection from the public API.
+///
+/// Elements that librsvg does not support, or which are not in the SVG namespace,
+/// are reported as `Unknown`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SvgElementType {
+    /// `<a>`
+    A,
+
+    /// `<animateMotion>`
+    AnimateMotion,
+
+    /// `<circle>`
+    Circle,
+
+    /// `<clipPath>`
+    ClipPath,
+
+    /// `<defs>`
+    Defs,
+
+    /// `<ellipse>`
+    Ellipse,
+
+    /// `<feBlend>`
+    FeBlend,
+
+    /// `<feColorMatrix>`
+    FeColorMatrix,
+
+    /// `<feComponentTransfer>`
+    FeComponentTransfer,
+
+    /// `<feComposite>`
+    FeComposite,
+
+    /// `<feConvolveMatrix>`
+    FeConvolveMatrix,
+
+    /// `<feDiffuseLighting>`
+    FeDiffuseLighting,
+
+    /// `<feDisplacementMap>`
+    FeDisplacementMap,
+
+    /// `<feDistantLight>`
+    FeDistantLight,
+
+    /// `<feFlood>`
+    FeFlood,
+
+    /// `<feFuncA>`
+    FeFuncA,
+
+    /// `<feFuncB>`
+    FeFuncB,
+
+    /// `<feFuncG>`
+    FeFuncG,
+
+    /// `<feFuncR>`
+    FeFuncR,
+
+    /// `<feGaussianBlur>`
+    FeGaussianBlur,
+
+    /// `<feImage>`
+    FeImage,
+
+    /// `<feMerge>`
+    FeMerge,
+
+    /// `<feMergeNode>`
+    FeMergeNode,
+
+    /// `<feMorphology>`
+    FeMorphology,
+
+    /// `<feOffset>`
+    FeOffset,
+
+    /// `<fePointLight>`
+    FePointLight,
+
+    /// `<feSpecularLighting>`
+    FeSpecularLighting,
+
+    /// `<feSpotLight>`
+    FeSpotLight,
+
+    /// `<feTile>`
+    FeTile,
+
+    /// `<feTurbulence>`
+    FeTurbulence,
+
+    /// `<filter>`
+    Filter,
+
+    /// `<g>`
+    G,
+
+    /// `<hatch>`
+    Hatch,
+
+    /// `<hatchpath>`
+    HatchPath,
+
+    /// `<image>`
+    Image,
+
+    /// `<line>`
+    Line,
+
+    /// `<linearGradient>`
+    LinearGradient,
+
+    /// `<marker>`
+    Marker,
+
+    /// `<mask>`
+    Mask,
+
+    /// `<meshgradient>`
+    MeshGradient,
+
+    /// `<meshpatch>`
+    MeshPatch,
+
+    /// `<meshrow>`
+    MeshRow,
+
+    /// `<mpath>`
+    Mpath,
+
+    /// `<path>`
+    Path,
+
+    /// `<pattern>`
+    Pattern,
+
+    /// `<polygon>`
+    Polygon,
+
+    /// `<polyline>`
+    Polyline,
+
+    /// `<radialGradient>`
+    RadialGradient,
+
+    /// `<rect>`
+    Rect,
+
+    /// `<set>`
+    Set,
+
+    /// `<stop>`
+    Stop,
+
+    /// `<style>`
+    Style,
+
+    /// `<svg>`
+    Svg,
+
+    /// `<switch>`
+    Switch,
+
+    /// `<symbol>`
+    Symbol,
+
+    /// `<text>`
+    Text,
+
+    /// `<tref>`
+    TRef,
+
+    /// `<tspan>`
+    TSpan,
+
+    /// `<use>`
+    Use,
+
+    /// An element that librsvg does not support.
+    Unknown,
+}
+
 /// Contents of an element node in the DOM
 /// This enum uses `Box<ElementInner>` in order to make each `Element`
 /// the size of a pointer.
 
 pub enum Element {
+    AnimateMotion(Box<ElementInner<AnimateMotion>>),
     Circle(Box<ElementInner<Circle>>),
     ClipPath(Box<ElementInner<ClipPath>>),
     Ellipse(Box<ElementInner<Ellipse>>),
     Filter(Box<ElementInner<Filter>>),
     Group(Box<ElementInner<Group>>),
+    Hatch(Box<ElementInner<Hatch>>),
+    HatchPath(Box<ElementInner<HatchPath>>),
     Image(Box<ElementInner<Image>>),
     Line(Box<ElementInner<Line>>),
     LinearGradient(Box<ElementInner<LinearGradient>>),
     Link(Box<ElementInner<Link>>),
     Marker(Box<ElementInner<Marker>>),
     Mask(Box<ElementInner<Mask>>),
+    MeshGradient(Box<ElementInner<MeshGradient>>),
+    MeshPatch(Box<ElementInner<MeshPatch>>),
+    MeshRow(Box<ElementInner<MeshRow>>),
+    Mpath(Box<ElementInner<Mpath>>),
     NonRendering(Box<ElementInner<NonRendering>>),
     Path(Box<ElementInner<Path>>),
     Pattern(Box<ElementInner<Pattern>>),
@@ -336,6 +592,7 @@ pub enum Element {
     Polyline(Box<ElementInner<Polyline>>),
     RadialGradient(Box<ElementInner<RadialGradient>>),
     Rect(Box<ElementInner<Rect>>),
+    Set(Box<ElementInner<Set>>),
     Stop(Box<ElementInner<Stop>>),
     Style(Box<ElementInner<Style>>),
     Svg(Box<ElementInner<Svg>>),
@@ -377,17 +634,24 @@ macro_rules! call_inner {
     // end recursion, call the method
     ($element:ident, $method:ident [$($args:expr),*]) => {
         match $element {
+            Element::AnimateMotion(i) => i.$method($($args),*),
             Element::Circle(i) => i.$method($($args),*),
             Element::ClipPath(i) => i.$method($($args),*),
             Element::Ellipse(i) => i.$method($($args),*),
             Element::Filter(i) => i.$method($($args),*),
             Element::Group(i) => i.$method($($args),*),
+            Element::Hatch(i) => i.$method($($args),*),
+            Element::HatchPath(i) => i.$method($($args),*),
             Element::Image(i) => i.$method($($args),*),
             Element::Line(i) => i.$method($($args),*),
             Element::LinearGradient(i) => i.$method($($args),*),
             Element::Link(i) => i.$method($($args),*),
             Element::Marker(i) => i.$method($($args),*),
             Element::Mask(i) => i.$method($($args),*),
+            Element::MeshGradient(i) => i.$method($($args),*),
+            Element::MeshPatch(i) => i.$method($($args),*),
+            Element::MeshRow(i) => i.$method($($args),*),
+            Element::Mpath(i) => i.$method($($args),*),
             Element::NonRendering(i) => i.$method($($args),*),
             Element::Path(i) => i.$method($($args),*),
             Element::Pattern(i) => i.$method($($args),*),
@@ -395,6 +659,7 @@ macro_rules! call_inner {
             Element::Polyline(i) => i.$method($($args),*),
             Element::RadialGradient(i) => i.$method($($args),*),
             Element::Rect(i) => i.$method($($args),*),
+            Element::Set(i) => i.$method($($args),*),
             Element::Stop(i) => i.$method($($args),*),
             Element::Style(i) => i.$method($($args),*),
             Element::Svg(i) => i.$method($($args),*),
@@ -456,11 +721,11 @@ impl Element {
     /// Takes an XML element name and a list of attribute/value pairs and creates an [`Element`].
     ///
     /// This operation does not fail.  Unknown element names simply produce a [`NonRendering`]
-    /// element.
+    /// element.  Presentation attributes are applied later, during the CSS cascade.
     ///
     /// [`Element`]: type.Element.html
     /// [`NonRendering`]: ../structure/struct.NonRendering.html
-    pub fn new(name: &QualName, pbag: &PropertyBag) -> Element {
+    pub fn new(name: &QualName, pbag: &PropertyBag, diagnostics: &mut Diagnostics) -> Element {
         let mut id = None;
         let mut class = None;
 
@@ -475,7 +740,7 @@ impl Element {
         let (create_fn, flags) = if name.ns == ns!(svg) {
             match ELEMENT_CREATORS.get(name.local.as_ref()) {
                 // hack in the SVG namespace for supported element names
-                Some(&(create_fn, flags)) => (create_fn, flags),
+                Some(&(create_fn, flags, _)) => (create_fn, flags),
 
                 // Whenever we encounter a element name we don't understand, represent it as a
                 // non-rendering element.  This is like a group, but it doesn't do any rendering
@@ -501,6 +766,7 @@ impl Element {
         let mut element = create_fn(name, id, class);
 
         if let Err(e) = element.set_attributes(pbag) {
+            diagnostics.error(format!("element {} is in error: {}", element, e), None);
             element.set_error(e);
         }
 
@@ -511,6 +777,20 @@ impl Element {
         call_inner!(self, element_name)
     }
 
+    pub fn element_type(&self) -> SvgElementType {
+        let name = self.element_name();
+
+        if name.ns == ns!(svg) {
+            ELEMENT_CREATORS
+                .get(name.local.as_ref())
+                .map_or(SvgElementType::Unknown, |&(_, _, element_type)| {
+                    element_type
+                })
+        } else {
+            SvgElementType::Unknown
+        }
+    }
+
     pub fn get_id(&self) -> Option<&str> {
         call_inner!(self, get_id)
     }
@@ -539,12 +819,39 @@ impl Element {
         call_inner!(self, get_transform)
     }
 
+    pub fn get_tab_index(&self) -> Option<i32> {
+        call_inner!(self, get_tab_index)
+    }
+
+    pub fn get_aria_attributes(&self) -> AriaAttributes {
+        call_inner!(self, get_aria_attributes)
+    }
+
+    /// Returns the element's attributes as they were in the XML.
+    ///
+    /// Unless the document was loaded with [`LoadOptions::keep_attributes`], this
+    /// only has the attributes that librsvg uses after loading, like the ARIA ones.
+    ///
+    /// [`LoadOptions::keep_attributes`]: ../handle/struct.LoadOptions.html#method.keep_attributes
+    pub fn attributes(&self) -> &[(QualName, String)] {
+        call_inner!(self, attributes)
+    }
+
+    /// Keeps a copy of all the attributes, for serialization.
+    pub fn keep_attributes(&mut self, pbag: &PropertyBag<'_>) {
+        call_inner!(self, keep_attributes, pbag);
+    }
+
+    pub fn reset_specified_values(&mut self, diagnostics: &mut Diagnostics) {
+        call_inner!(self, reset_specified_values, diagnostics);
+    }
+
     pub fn apply_style_declaration(&mut self, declaration: &Declaration, origin: Origin) {
         call_inner!(self, apply_style_declaration, declaration, origin)
     }
 
-    pub fn set_style_attribute(&mut self) {
-        call_inner!(self, set_style_attribute);
+    pub fn set_style_attribute(&mut self, diagnostics: &mut Diagnostics) {
+        call_inner!(self, set_style_attribute, diagnostics);
     }
 
     fn set_error(&mut self, error: ElementError) {
@@ -585,9 +892,11 @@ impl Element {
             self,
             Element::ClipPath(_) |
             Element::Filter(_) |
+            Element::Hatch(_) |
             Element::LinearGradient(_) |
             Element::Marker(_) |
             Element::Mask(_) |
+            Element::MeshGradient(_) |
             Element::Pattern(_) |
             Element::RadialGradient(_)
         )
@@ -640,7 +949,8 @@ macro_rules! e {
                 result: Ok(()),
                 values: ComputedValues::default(),
                 cond: true,
-                style_attr: String::new(),
+                tab_index: None,
+                attributes: Vec::new(),
                 element_impl: <$element_type>::default(),
             }))
         }
@@ -651,6 +961,7 @@ macro_rules! e {
 mod creators {
     use super::*;
 
+    e!(create_animate_motion,           AnimateMotion);
     e!(create_circle,                   Circle);
     e!(create_clip_path,                ClipPath);
     e!(create_defs,                     NonRendering);
@@ -681,12 +992,18 @@ mod creators {
     e!(create_fe_turbulence,            FeTurbulence);
     e!(create_filter,                   Filter);
     e!(create_group,                    Group);
+    e!(create_hatch,                    Hatch);
+    e!(create_hatch_path,               HatchPath);
     e!(create_image,                    Image);
     e!(create_line,                     Line);
     e!(create_linear_gradient,          LinearGradient);
     e!(create_link,                     Link);
     e!(create_marker,                   Marker);
     e!(create_mask,                     Mask);
+    e!(create_mesh_gradient,            MeshGradient);
+    e!(create_mesh_patch,               MeshPatch);
+    e!(create_mesh_row,                 MeshRow);
+    e!(create_mpath,                    Mpath);
     e!(create_non_rendering,            NonRendering);
     e!(create_path,                     Path);
     e!(create_pattern,                  Pattern);
@@ -694,6 +1011,7 @@ mod creators {
     e!(create_polyline,                 Polyline);
     e!(create_radial_gradient,          RadialGradient);
     e!(create_rect,                     Rect);
+    e!(create_set,                      Set);
     e!(create_stop,                     Stop);
     e!(create_style,                    Style);
     e!(create_svg,                      Svg);
@@ -731,51 +1049,52 @@ enum ElementCreateFlags {
 
 // Lines in comments are elements that we don't support.
 #[rustfmt::skip]
-static ELEMENT_CREATORS: Lazy<HashMap<&'static str, (ElementCreateFn, ElementCreateFlags)>> = Lazy::new(|| {
+static ELEMENT_CREATORS: Lazy<HashMap<&'static str, (ElementCreateFn, ElementCreateFlags, SvgElementType)>> = Lazy::new(|| {
     use ElementCreateFlags::*;
+    use SvgElementType::*;
 
-    let creators_table: Vec<(&str, ElementCreateFn, ElementCreateFlags)> = vec![
-        // name, supports_class, create_fn
-        ("a",                   create_link,                  Default),
+    let creators_table: Vec<(&str, ElementCreateFn, ElementCreateFlags, SvgElementType)> = vec![
+        // name, create_fn, flags, element_type
+        ("a",                   create_link,                  Default,     A),
         /* ("altGlyph",         ), */
         /* ("altGlyphDef",      ), */
         /* ("altGlyphItem",     ), */
         /* ("animate",          ), */
         /* ("animateColor",     ), */
-        /* ("animateMotion",    ), */
+        ("animateMotion",       create_animate_motion,        Default,     AnimateMotion),
         /* ("animateTransform", ), */
-        ("circle",              create_circle,                Default),
-        ("clipPath",            create_clip_path,             Default),
+        ("circle",              create_circle,                Default,     Circle),
+        ("clipPath",            create_clip_path,             Default,     ClipPath),
         /* ("color-profile",    ), */
         /* ("cursor",           ), */
-        ("defs",                create_defs,                  Default),
+        ("defs",                create_defs,                  Default,     Defs),
         /* ("desc",             ), */
-        ("ellipse",             create_ellipse,               Default),
-        ("feBlend",             create_fe_blend,              Default),
-        ("feColorMatrix",       create_fe_color_matrix,       Default),
-        ("feComponentTransfer", create_fe_component_transfer, Default),
-        ("feComposite",         create_fe_composite,          Default),
-        ("feConvolveMatrix",    create_fe_convolve_matrix,    Default),
-        ("feDiffuseLighting",   create_fe_diffuse_lighting,   Default),
-        ("feDisplacementMap",   create_fe_displacement_map,   Default),
-        ("feDistantLight",      create_fe_distant_light,      IgnoreClass),
-        ("feFuncA",             create_fe_func_a,             IgnoreClass),
-        ("feFuncB",             create_fe_func_b,             IgnoreClass),
-        ("feFuncG",             create_fe_func_g,             IgnoreClass),
-        ("feFuncR",             create_fe_func_r,             IgnoreClass),
-        ("feFlood",             create_fe_flood,              Default),
-        ("feGaussianBlur",      create_fe_gaussian_blur,      Default),
-        ("feImage",             create_fe_image,              Default),
-        ("feMerge",             create_fe_merge,              Default),
-        ("feMergeNode",         create_fe_merge_node,         IgnoreClass),
-        ("feMorphology",        create_fe_morphology,         Default),
-        ("feOffset",            create_fe_offset,             Default),
-        ("fePointLight",        create_fe_point_light,        IgnoreClass),
-        ("feSpecularLighting",  create_fe_specular_lighting,  Default),
-        ("feSpotLight",         create_fe_spot_light,         IgnoreClass),
-        ("feTile",              create_fe_tile,               Default),
-        ("feTurbulence",        create_fe_turbulence,         Default),
-        ("filter",              create_filter,                Default),
+        ("ellipse",             create_ellipse,               Default,     Ellipse),
+        ("feBlend",             create_fe_blend,              Default,     FeBlend),
+        ("feColorMatrix",       create_fe_color_matrix,       Default,     FeColorMatrix),
+        ("feComponentTransfer", create_fe_component_transfer, Default,     FeComponentTransfer),
+        ("feComposite",         create_fe_composite,          Default,     FeComposite),
+        ("feConvolveMatrix",    create_fe_convolve_matrix,    Default,     FeConvolveMatrix),
+        ("feDiffuseLighting",   create_fe_diffuse_lighting,   Default,     FeDiffuseLighting),
+        ("feDisplacementMap",   create_fe_displacement_map,   Default,     FeDisplacementMap),
+        ("feDistantLight",      create_fe_distant_light,      IgnoreClass, FeDistantLight),
+        ("feFuncA",             create_fe_func_a,             IgnoreClass, FeFuncA),
+        ("feFuncB",             create_fe_func_b,             IgnoreClass, FeFuncB),
+        ("feFuncG",             create_fe_func_g,             IgnoreClass, FeFuncG),
+        ("feFuncR",             create_fe_func_r,             IgnoreClass, FeFuncR),
+        ("feFlood",             create_fe_flood,              Default,     FeFlood),
+        ("feGaussianBlur",      create_fe_gaussian_blur,      Default,     FeGaussianBlur),
+        ("feImage",             create_fe_image,              Default,     FeImage),
+        ("feMerge",             create_fe_merge,              Default,     FeMerge),
+        ("feMergeNode",         create_fe_merge_node,         IgnoreClass, FeMergeNode),
+        ("feMorphology",        create_fe_morphology,         Default,     FeMorphology),
+        ("feOffset",            create_fe_offset,             Default,     FeOffset),
+        ("fePointLight",        create_fe_point_light,        IgnoreClass, FePointLight),
+        ("feSpecularLighting",  create_fe_specular_lighting,  Default,     FeSpecularLighting),
+        ("feSpotLight",         create_fe_spot_light,         IgnoreClass, FeSpotLight),
+        ("feTile",              create_fe_tile,               Default,     FeTile),
+        ("feTurbulence",        create_fe_turbulence,         Default,     FeTurbulence),
+        ("filter",              create_filter,                Default,     Filter),
         /* ("font",             ), */
         /* ("font-face",        ), */
         /* ("font-face-format", ), */
@@ -783,45 +1102,51 @@ static ELEMENT_CREATORS: Lazy<HashMap<&'static str, (ElementCreateFn, ElementCre
         /* ("font-face-src",    ), */
         /* ("font-face-uri",    ), */
         /* ("foreignObject",    ), */
-        ("g",                   create_group,                 Default),
+        ("g",                   create_group,                 Default,     G),
         /* ("glyph",            ), */
         /* ("glyphRef",         ), */
+        ("hatch",               create_hatch,                 Default,     Hatch),
+        ("hatchpath",           create_hatch_path,            Default,     HatchPath),
         /* ("hkern",            ), */
-        ("image",               create_image,                 Default),
-        ("line",                create_line,                  Default),
-        ("linearGradient",      create_linear_gradient,       Default),
-        ("marker",              create_marker,                Default),
-        ("mask",                create_mask,                  Default),
+        ("image",               create_image,                 Default,     Image),
+        ("line",                create_line,                  Default,     Line),
+        ("linearGradient",      create_linear_gradient,       Default,     LinearGradient),
+        ("marker",              create_marker,                Default,     Marker),
+        ("mask",                create_mask,                  Default,     Mask),
+        ("meshgradient",        create_mesh_gradient,         Default,     MeshGradient),
+        ("meshGradient",        create_mesh_gradient,         Default,     MeshGradient),
+        ("meshpatch",           create_mesh_patch,            Default,     MeshPatch),
+        ("meshrow",             create_mesh_row,              Default,     MeshRow),
         /* ("metadata",         ), */
         /* ("missing-glyph",    ), */
-        /* ("mpath",            ), */
+        ("mpath",               create_mpath,                 Default,     Mpath),
         /* ("multiImage",       ), */
-        ("path",                create_path,                  Default),
-        ("pattern",             create_pattern,               Default),
-        ("polygon",             create_polygon,               Default),
-        ("polyline",            create_polyline,              Default),
-        ("radialGradient",      create_radial_gradient,       Default),
-        ("rect",                create_rect,                  Default),
+        ("path",                create_path,                  Default,     Path),
+        ("pattern",             create_pattern,               Default,     Pattern),
+        ("polygon",             create_polygon,               Default,     Polygon),
+        ("polyline",            create_polyline,              Default,     Polyline),
+        ("radialGradient",      create_radial_gradient,       Default,     RadialGradient),
+        ("rect",                create_rect,                  Default,     Rect),
         /* ("script",           ), */
-        /* ("set",              ), */
-        ("stop",                create_stop,                  Default),
-        ("style",               create_style,                 IgnoreClass),
+        ("set",                 create_set,                   Default,     Set),
+        ("stop",                create_stop,                  Default,     Stop),
+        ("style",               create_style,                 IgnoreClass, Style),
         /* ("subImage",         ), */
         /* ("subImageRef",      ), */
-        ("svg",                 create_svg,                   Default),
-        ("switch",              create_switch,                Default),
-        ("symbol",              create_symbol,                Default),
-        ("text",                create_text,                  Default),
+        ("svg",                 create_svg,                   Default,     Svg),
+        ("switch",              create_switch,                Default,     Switch),
+        ("symbol",              create_symbol,                Default,     Symbol),
+        ("text",                create_text,                  Default,     Text),
         /* ("textPath",         ), */
         /* ("title",            ), */
-        ("tref",                create_tref,                  Default),
-        ("tspan",               create_tspan,                 Default),
-        ("use",                 create_use,                   Default),
+        ("tref",                create_tref,                  Default,     TRef),
+        ("tspan",               create_tspan,                 Default,     TSpan),
+        ("use",                 create_use,                   Default,     Use),
         /* ("view",             ), */
         /* ("vkern",            ), */
     ];
 
-    creators_table.into_iter().map(|(n, c, f)| (n, (c, f))).collect()
+    creators_table.into_iter().map(|(n, c, f, t)| (n, (c, f, t))).collect()
 });
 
 /// Gets the user's preferred locale from the environment and
@@ -869,6 +1194,7 @@ mod sizes {
         print_size!(ComputedValues);
         print_size!(ParsedProperty);
 
+        print_size!(AnimateMotion);
         print_size!(Circle);
         print_size!(ClipPath);
         print_size!(NonRendering);
@@ -899,12 +1225,18 @@ mod sizes {
         print_size!(FeTurbulence);
         print_size!(Filter);
         print_size!(Group);
+        print_size!(Hatch);
+        print_size!(HatchPath);
         print_size!(Image);
         print_size!(Line);
         print_size!(LinearGradient);
         print_size!(Link);
         print_size!(Marker);
         print_size!(Mask);
+        print_size!(MeshGradient);
+        print_size!(MeshPatch);
+        print_size!(MeshRow);
+        print_size!(Mpath);
         print_size!(NonRendering);
         print_size!(Path);
         print_size!(Pattern);
@@ -912,6 +1244,7 @@ mod sizes {
         print_size!(Polyline);
         print_size!(RadialGradient);
         print_size!(Rect);
+        print_size!(Set);
         print_size!(Stop);
         print_size!(Style);
         print_size!(Svg);
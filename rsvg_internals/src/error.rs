@@ -129,7 +129,9 @@ pub enum RenderingError {
     /// The maximum number of rendered objects was reached.
     ///
     /// Librsvg has a limit on the number of rendered objects, so that malicious
-    /// files cannot consume CPU time arbitrarily.
+    /// files cannot consume CPU time arbitrarily.  This is also returned when
+    /// references between elements are nested too deeply, so that those files
+    /// cannot exhaust the stack.
     InstancingLimit,
 
     /// Tried to reference an SVG element from a fragment identifier that is incorrect.
@@ -170,11 +172,31 @@ impl From<cairo::Status> for RenderingError {
     }
 }
 
+/// Errors that can happen while serializing a document back to SVG.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SerializeError {
+    /// The document was not loaded with the option to keep the attributes of elements.
+    AttributesNotKept,
+}
+
+impl error::Error for SerializeError {}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SerializeError::AttributesNotKept => {
+                write!(f, "the document was loaded without keeping its attributes")
+            }
+        }
+    }
+}
+
 pub enum AcquireError {
     LinkNotFound(Fragment),
     InvalidLinkType(Fragment),
     CircularReference(Node),
     MaxReferencesExceeded,
+    MaxDepthExceeded,
 }
 
 impl fmt::Display for AcquireError {
@@ -193,6 +215,8 @@ impl fmt::Display for AcquireError {
             AcquireError::MaxReferencesExceeded => {
                 write!(f, "maximum number of references exceeded")
             }
+
+            AcquireError::MaxDepthExceeded => write!(f, "maximum depth of references exceeded"),
         }
     }
 }
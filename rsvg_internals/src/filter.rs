@@ -1,18 +1,21 @@
-//! The `filter` element.
+//! The `filter` element and the values of the `filter` property.
 
+use cssparser::{Parser, ToCss};
 use markup5ever::{expanded_name, local_name, namespace_url, ns};
+use std::fmt;
 
-use crate::bbox::BoundingBox;
+use crate::allowed_url::{Fragment, Href};
 use crate::coord_units::CoordUnits;
 use crate::drawing_ctx::DrawingCtx;
 use crate::element::{Draw, ElementResult, SetAttributes};
-use crate::error::ValueErrorKind;
+use crate::error::{ParseError, ValueErrorKind};
+use crate::filter_func::FilterFunction;
+use crate::iri::serialize_funciri;
 use crate::length::*;
 use crate::parsers::{Parse, ParseValue};
 use crate::properties::ComputedValues;
 use crate::property_bag::PropertyBag;
 use crate::rect::Rect;
-use crate::transform::Transform;
 
 /// The <filter> node.
 pub struct Filter {
@@ -47,66 +50,52 @@ impl Filter {
         self.primitiveunits
     }
 
-    /// Computes and returns the filter effects region.
-    pub fn compute_effects_region(
+    /// Returns whether the width or the height of the filter effects region is zero.
+    ///
+    /// Per the spec, this disables the effect of the filter, so the element that
+    /// references it is not rendered.
+    pub fn is_region_empty(&self) -> bool {
+        self.width.length == 0.0 || self.height.length == 0.0
+    }
+
+    /// Computes the filter region in the user space of the element being filtered.
+    ///
+    /// The `bbox_rect` is the bounding box of the element being filtered, for
+    /// `filterUnits="objectBoundingBox"`.
+    pub fn compute_region(
         &self,
         computed_from_target_node: &ComputedValues,
-        draw_ctx: &mut DrawingCtx,
-        transform: Transform,
-        width: f64,
-        height: f64,
-    ) -> BoundingBox {
+        draw_ctx: &DrawingCtx,
+        bbox_rect: &Rect,
+    ) -> Rect {
         // Filters use the properties of the target node.
         let values = computed_from_target_node;
 
-        let mut bbox = BoundingBox::new();
-
-        // transform is set up in FilterContext::new() in such a way that for
-        // filterunits == ObjectBoundingBox it includes scaling to correct width, height and
-        // this is why width and height are set to 1, 1 (and for filterunits ==
-        // UserSpaceOnUse, transform doesn't include scaling because in this case the correct
-        // width, height already happens to be the viewbox width, height).
-        //
-        // It's done this way because with ObjectBoundingBox, non-percentage values are supposed to
-        // represent the fractions of the referenced node, and with width and height = 1, 1 this
-        // works out exactly like that.
-        let params = if self.filterunits == CoordUnits::ObjectBoundingBox {
-            draw_ctx.push_view_box(1.0, 1.0)
-        } else {
-            draw_ctx.get_view_params()
-        };
-
-        // With filterunits == ObjectBoundingBox, lengths represent fractions or percentages of the
-        // referencing node. No units are allowed (it's checked during attribute parsing).
-        let (x, y, w, h) = if self.filterunits == CoordUnits::ObjectBoundingBox {
-            (
+        if self.filterunits == CoordUnits::ObjectBoundingBox {
+            // With ObjectBoundingBox, lengths represent fractions or percentages of the
+            // referencing node. No units are allowed (it's checked during attribute parsing).
+            let (x, y, w, h) = (
                 self.x.length,
                 self.y.length,
                 self.width.length,
                 self.height.length,
-            )
-        } else {
-            (
-                self.x.normalize(values, &params),
-                self.y.normalize(values, &params),
-                self.width.normalize(values, &params),
-                self.height.normalize(values, &params),
-            )
-        };
+            );
 
-        let rect = Rect::new(x, y, x + w, y + h);
-        let other_bbox = BoundingBox::new().with_transform(transform).with_rect(rect);
+            let (bw, bh) = bbox_rect.size();
+            let x0 = bbox_rect.x0 + x * bw;
+            let y0 = bbox_rect.y0 + y * bh;
 
-        // At this point all of the previous viewbox and matrix business gets converted to pixel
-        // coordinates in the final surface, because bbox is created with an identity transform.
-        bbox.insert(&other_bbox);
+            Rect::new(x0, y0, x0 + w * bw, y0 + h * bh)
+        } else {
+            let params = draw_ctx.get_view_params();
 
-        // Finally, clip to the width and height of our surface.
-        let rect = Rect::from_size(width, height);
-        let other_bbox = BoundingBox::new().with_rect(rect);
-        bbox.clip(&other_bbox);
+            let x = self.x.normalize(values, &params);
+            let y = self.y.normalize(values, &params);
+            let w = self.width.normalize(values, &params);
+            let h = self.height.normalize(values, &params);
 
-        bbox
+            Rect::new(x, y, x + w, y + h)
+        }
     }
 }
 
@@ -184,3 +173,145 @@ impl SetAttributes for Filter {
 }
 
 impl Draw for Filter {}
+
+/// A single item in the value of the `filter` property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    /// Reference to a `<filter>` element.
+    Url(Fragment),
+
+    /// A filter function like `blur(5px)`.
+    Function(FilterFunction),
+}
+
+/// The value of the `filter` property: a list of filters to apply in order.
+///
+/// An empty list means `none`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FilterValueList(Vec<FilterValue>);
+
+impl FilterValueList {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &FilterValue> {
+        self.0.iter()
+    }
+}
+
+impl Parse for FilterValue {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<FilterValue, ParseError<'i>> {
+        if let Ok(func) = parser.try_parse(FilterFunction::parse) {
+            return Ok(FilterValue::Function(func));
+        }
+
+        let loc = parser.current_source_location();
+
+        let url = parser.expect_url()?;
+        let href = Href::parse(&url).map_err(|e| loc.new_custom_error(ValueErrorKind::from(e)))?;
+
+        match href {
+            Href::PlainUrl(_) => Err(loc.new_custom_error(ValueErrorKind::parse_error(
+                "href requires a fragment identifier",
+            ))),
+            Href::WithFragment(f) => Ok(FilterValue::Url(f)),
+        }
+    }
+}
+
+impl Parse for FilterValueList {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<FilterValueList, ParseError<'i>> {
+        if parser
+            .try_parse(|i| i.expect_ident_matching("none"))
+            .is_ok()
+        {
+            return Ok(FilterValueList::default());
+        }
+
+        let mut list = vec![FilterValue::parse(parser)?];
+
+        while !parser.is_exhausted() {
+            list.push(FilterValue::parse(parser)?);
+        }
+
+        Ok(FilterValueList(list))
+    }
+}
+
+impl ToCss for FilterValue {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        match *self {
+            FilterValue::Url(ref f) => serialize_funciri(f, dest),
+            FilterValue::Function(ref func) => func.to_css(dest),
+        }
+    }
+}
+
+impl ToCss for FilterValueList {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        if self.0.is_empty() {
+            return dest.write_str("none");
+        }
+
+        for (i, value) in self.0.iter().enumerate() {
+            if i > 0 {
+                dest.write_str(" ")?;
+            }
+
+            value.to_css(dest)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_none() {
+        assert_eq!(
+            FilterValueList::parse_str("none"),
+            Ok(FilterValueList::default())
+        );
+    }
+
+    #[test]
+    fn parses_list_of_urls_and_functions() {
+        assert_eq!(
+            FilterValueList::parse_str("url(#foo) blur(5px) brightness(0.5)"),
+            Ok(FilterValueList(vec![
+                FilterValue::Url(Fragment::new(None, "foo".to_string())),
+                FilterValue::Function(FilterFunction::Blur(Length::new(5.0, LengthUnit::Px))),
+                FilterValue::Function(FilterFunction::Brightness(0.5)),
+            ]))
+        );
+    }
+
+    #[test]
+    fn invalid_list_yields_error() {
+        assert!(FilterValueList::parse_str("").is_err());
+        assert!(FilterValueList::parse_str("url(foo)").is_err());
+        assert!(FilterValueList::parse_str("blur(5px) 42").is_err());
+    }
+
+    #[test]
+    fn serializes_list() {
+        assert_eq!(FilterValueList::default().to_css_string(), "none");
+
+        assert_eq!(
+            FilterValueList::parse_str("url(#foo) blur(5px) brightness(50%)")
+                .unwrap()
+                .to_css_string(),
+            "url(\"#foo\") blur(5) brightness(0.5)"
+        );
+    }
+}
@@ -0,0 +1,421 @@
+//! Filter functions for the `filter` property, like `blur()` or `drop-shadow()`.
+//!
+//! Each function is a shorthand for a small chain of filter primitives.  Instead of
+//! building an actual `<filter>` element, the equivalent operations are applied
+//! directly to the surface being filtered.
+//!
+//! https://www.w3.org/TR/filter-effects/#filter-functions
+
+use cssparser::{Parser, ToCss, Token};
+use nalgebra::{Matrix3, Matrix5};
+use std::fmt;
+
+use crate::angle::Angle;
+use crate::drawing_ctx::DrawingCtx;
+use crate::error::*;
+use crate::filters::color_matrix::{apply_color_matrix, hue_rotate_matrix, saturate_matrix};
+use crate::filters::gaussian_blur;
+use crate::filters::FilterError;
+use crate::length::*;
+use crate::parsers::{finite_f32, serialize_number, Parse};
+use crate::properties::ComputedValues;
+use crate::rect::IRect;
+use crate::surface_utils::shared_surface::SharedImageSurface;
+use crate::unit_interval::UnitInterval;
+
+/// A filter function from the `filter` property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterFunction {
+    Blur(Length<Both>),
+    Brightness(f64),
+    Contrast(f64),
+    DropShadow(DropShadow),
+    Grayscale(f64),
+    HueRotate(Angle),
+    Invert(f64),
+    Opacity(f64),
+    Saturate(f64),
+    Sepia(f64),
+}
+
+/// Parameters for the `drop-shadow()` filter function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropShadow {
+    color: Option<cssparser::Color>,
+    dx: Length<Horizontal>,
+    dy: Length<Vertical>,
+    std_deviation: Length<Both>,
+}
+
+/// Parses the `<number-percentage>` argument of the color filter functions.
+fn parse_amount<'i>(parser: &mut Parser<'i, '_>) -> Result<f64, ParseError<'i>> {
+    let loc = parser.current_source_location();
+
+    let value = match *parser.next()? {
+        Token::Number { value, .. } => value,
+        Token::Percentage { unit_value, .. } => unit_value,
+        ref t => return Err(loc.new_unexpected_token_error(t.clone())),
+    };
+
+    let value = finite_f32(value).map_err(|e| loc.new_custom_error(e))?;
+
+    if value < 0.0 {
+        Err(loc.new_custom_error(ValueErrorKind::value_error("value can't be negative")))
+    } else {
+        Ok(f64::from(value))
+    }
+}
+
+/// Parses an optional amount, which defaults to 1.
+fn parse_optional_amount(parser: &mut Parser<'_, '_>) -> f64 {
+    parser.try_parse(parse_amount).unwrap_or(1.0)
+}
+
+/// Parses a standard deviation, which must be a non-negative length without percentages.
+fn parse_std_deviation<'i>(parser: &mut Parser<'i, '_>) -> Result<Length<Both>, ParseError<'i>> {
+    let loc = parser.current_source_location();
+
+    let length = Length::<Both>::parse(parser)?;
+
+    if length.length < 0.0 {
+        Err(loc.new_custom_error(ValueErrorKind::value_error("value can't be negative")))
+    } else if length.unit == LengthUnit::Percent {
+        Err(loc.new_custom_error(ValueErrorKind::value_error(
+            "percentages are not allowed here",
+        )))
+    } else {
+        Ok(length)
+    }
+}
+
+fn parse_drop_shadow<'i>(parser: &mut Parser<'i, '_>) -> Result<DropShadow, ParseError<'i>> {
+    let mut color = parser
+        .try_parse(|p| <cssparser::Color as Parse>::parse(p))
+        .ok();
+
+    let dx = Length::<Horizontal>::parse(parser)?;
+    let dy = Length::<Vertical>::parse(parser)?;
+    let std_deviation = parser
+        .try_parse(parse_std_deviation)
+        .unwrap_or_else(|_| Length::new(0.0, LengthUnit::Px));
+
+    if color.is_none() {
+        color = parser
+            .try_parse(|p| <cssparser::Color as Parse>::parse(p))
+            .ok();
+    }
+
+    Ok(DropShadow {
+        color,
+        dx,
+        dy,
+        std_deviation,
+    })
+}
+
+impl Parse for FilterFunction {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<FilterFunction, ParseError<'i>> {
+        let loc = parser.current_source_location();
+        let name = parser.expect_function()?.clone();
+
+        parser.parse_nested_block(|p| match name.as_ref() {
+            "blur" => Ok(FilterFunction::Blur(
+                p.try_parse(parse_std_deviation)
+                    .unwrap_or_else(|_| Length::new(0.0, LengthUnit::Px)),
+            )),
+            "brightness" => Ok(FilterFunction::Brightness(parse_optional_amount(p))),
+            "contrast" => Ok(FilterFunction::Contrast(parse_optional_amount(p))),
+            "drop-shadow" => Ok(FilterFunction::DropShadow(parse_drop_shadow(p)?)),
+            "grayscale" => Ok(FilterFunction::Grayscale(parse_optional_amount(p))),
+            "hue-rotate" => Ok(FilterFunction::HueRotate(
+                p.try_parse(Angle::parse)
+                    .unwrap_or_else(|_| Angle::new(0.0)),
+            )),
+            "invert" => Ok(FilterFunction::Invert(parse_optional_amount(p))),
+            "opacity" => Ok(FilterFunction::Opacity(parse_optional_amount(p))),
+            "saturate" => Ok(FilterFunction::Saturate(parse_optional_amount(p))),
+            "sepia" => Ok(FilterFunction::Sepia(parse_optional_amount(p))),
+            _ => Err(loc.new_unexpected_token_error(Token::Function(name.clone()))),
+        })
+    }
+}
+
+impl ToCss for DropShadow {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        if let Some(ref color) = self.color {
+            color.to_css(dest)?;
+            dest.write_str(" ")?;
+        }
+
+        self.dx.to_css(dest)?;
+        dest.write_str(" ")?;
+        self.dy.to_css(dest)?;
+        dest.write_str(" ")?;
+        self.std_deviation.to_css(dest)
+    }
+}
+
+impl ToCss for FilterFunction {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        let (name, amount) = match *self {
+            FilterFunction::Blur(ref l) => {
+                dest.write_str("blur(")?;
+                l.to_css(dest)?;
+                return dest.write_str(")");
+            }
+
+            FilterFunction::DropShadow(ref d) => {
+                dest.write_str("drop-shadow(")?;
+                d.to_css(dest)?;
+                return dest.write_str(")");
+            }
+
+            FilterFunction::HueRotate(ref a) => {
+                dest.write_str("hue-rotate(")?;
+                a.to_css(dest)?;
+                return dest.write_str(")");
+            }
+
+            FilterFunction::Brightness(v) => ("brightness", v),
+            FilterFunction::Contrast(v) => ("contrast", v),
+            FilterFunction::Grayscale(v) => ("grayscale", v),
+            FilterFunction::Invert(v) => ("invert", v),
+            FilterFunction::Opacity(v) => ("opacity", v),
+            FilterFunction::Saturate(v) => ("saturate", v),
+            FilterFunction::Sepia(v) => ("sepia", v),
+        };
+
+        dest.write_str(name)?;
+        dest.write_str("(")?;
+        serialize_number(amount, dest)?;
+        dest.write_str(")")
+    }
+}
+
+/// Returns a color matrix that applies `slope * c + intercept` to each color channel.
+#[rustfmt::skip]
+fn linear_transfer_matrix(slope: f64, intercept: f64) -> Matrix5<f64> {
+    Matrix5::new(
+        slope, 0.0,   0.0,   0.0, intercept,
+        0.0,   slope, 0.0,   0.0, intercept,
+        0.0,   0.0,   slope, 0.0, intercept,
+        0.0,   0.0,   0.0,   1.0, 0.0,
+        0.0,   0.0,   0.0,   0.0, 1.0,
+    )
+}
+
+/// Extends a 3×3 matrix for the color channels into a full color matrix.
+fn rgb_matrix(m: Matrix3<f64>) -> Matrix5<f64> {
+    let mut matrix = m.fixed_resize(0.0);
+    matrix[(3, 3)] = 1.0;
+    matrix[(4, 4)] = 1.0;
+    matrix
+}
+
+#[rustfmt::skip]
+fn grayscale_matrix(amount: f64) -> Matrix5<f64> {
+    let s = 1.0 - amount.min(1.0);
+
+    rgb_matrix(Matrix3::new(
+        0.2126 + 0.7874 * s, 0.7152 - 0.7152 * s, 0.0722 - 0.0722 * s,
+        0.2126 - 0.2126 * s, 0.7152 + 0.2848 * s, 0.0722 - 0.0722 * s,
+        0.2126 - 0.2126 * s, 0.7152 - 0.7152 * s, 0.0722 + 0.9278 * s,
+    ))
+}
+
+#[rustfmt::skip]
+fn sepia_matrix(amount: f64) -> Matrix5<f64> {
+    let s = 1.0 - amount.min(1.0);
+
+    rgb_matrix(Matrix3::new(
+        0.393 + 0.607 * s, 0.769 - 0.769 * s, 0.189 - 0.189 * s,
+        0.349 - 0.349 * s, 0.686 + 0.314 * s, 0.168 - 0.168 * s,
+        0.272 - 0.272 * s, 0.534 - 0.534 * s, 0.131 + 0.869 * s,
+    ))
+}
+
+fn opacity_matrix(amount: f64) -> Matrix5<f64> {
+    let mut matrix = Matrix5::identity();
+    matrix[(3, 3)] = amount.min(1.0);
+    matrix
+}
+
+impl FilterFunction {
+    /// Whether the function's primitive chain honors `color-interpolation-filters`.
+    pub fn is_affected_by_color_interpolation_filters(&self) -> bool {
+        match *self {
+            FilterFunction::Opacity(_) | FilterFunction::DropShadow(_) => false,
+            _ => true,
+        }
+    }
+
+    /// Applies the filter function to `input` within `bounds`.
+    ///
+    /// `values` are the computed values of the element being filtered; they
+    /// are used to resolve lengths and `currentColor`.
+    pub fn render(
+        &self,
+        input: &SharedImageSurface,
+        bounds: IRect,
+        values: &ComputedValues,
+        draw_ctx: &DrawingCtx,
+    ) -> Result<SharedImageSurface, FilterError> {
+        let params = draw_ctx.get_view_params();
+        let transform = draw_ctx.get_transform();
+
+        let device_std_deviation = |length: &Length<Both>| {
+            let std = length.normalize(values, &params);
+            let (std_x, std_y) = transform.transform_distance(std, std);
+
+            // The deviation can become negative here due to the transform.
+            (std_x.abs(), std_y.abs())
+        };
+
+        let matrix = match *self {
+            FilterFunction::Blur(ref std_deviation) => {
+                let (std_x, std_y) = device_std_deviation(std_deviation);
+                return gaussian_blur::blur(input, bounds, std_x, std_y);
+            }
+
+            FilterFunction::DropShadow(ref shadow) => {
+                let (std_x, std_y) = device_std_deviation(&shadow.std_deviation);
+                let (dx, dy) = transform.transform_distance(
+                    shadow.dx.normalize(values, &params),
+                    shadow.dy.normalize(values, &params),
+                );
+
+                let color = match shadow.color {
+                    Some(cssparser::Color::RGBA(rgba)) => rgba,
+                    Some(cssparser::Color::CurrentColor) | None => values.color().0,
+                };
+
+                let alpha = input.extract_alpha(bounds)?;
+                let blurred = gaussian_blur::blur(&alpha, bounds, std_x, std_y)?;
+                let offset = blurred.offset(bounds, dx, dy)?;
+
+                let shadow = input
+                    .flood(
+                        bounds,
+                        bounds,
+                        color,
+                        UnitInterval::clamp(f64::from(color.alpha_f32())),
+                    )?
+                    .compose(&offset, bounds, cairo::Operator::In)?;
+
+                return Ok(input.compose(&shadow, bounds, cairo::Operator::Over)?);
+            }
+
+            FilterFunction::Brightness(amount) => linear_transfer_matrix(amount, 0.0),
+            FilterFunction::Contrast(amount) => linear_transfer_matrix(amount, 0.5 - 0.5 * amount),
+            FilterFunction::Grayscale(amount) => grayscale_matrix(amount),
+            FilterFunction::HueRotate(angle) => hue_rotate_matrix(angle.radians().to_degrees()),
+            FilterFunction::Invert(amount) => {
+                let amount = amount.min(1.0);
+                linear_transfer_matrix(1.0 - 2.0 * amount, amount)
+            }
+            FilterFunction::Opacity(amount) => opacity_matrix(amount),
+            FilterFunction::Saturate(amount) => saturate_matrix(amount),
+            FilterFunction::Sepia(amount) => sepia_matrix(amount),
+        };
+
+        Ok(apply_color_matrix(
+            input,
+            bounds,
+            &matrix,
+            input.surface_type(),
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_color_functions() {
+        assert_eq!(
+            FilterFunction::parse_str("brightness(0.5)"),
+            Ok(FilterFunction::Brightness(0.5))
+        );
+        assert_eq!(
+            FilterFunction::parse_str("contrast(50%)"),
+            Ok(FilterFunction::Contrast(0.5))
+        );
+        assert_eq!(
+            FilterFunction::parse_str("grayscale()"),
+            Ok(FilterFunction::Grayscale(1.0))
+        );
+        assert_eq!(
+            FilterFunction::parse_str("hue-rotate(90deg)"),
+            Ok(FilterFunction::HueRotate(Angle::from_degrees(90.0)))
+        );
+    }
+
+    #[test]
+    fn parses_blur() {
+        assert_eq!(
+            FilterFunction::parse_str("blur(5px)"),
+            Ok(FilterFunction::Blur(Length::new(5.0, LengthUnit::Px)))
+        );
+        assert_eq!(
+            FilterFunction::parse_str("blur()"),
+            Ok(FilterFunction::Blur(Length::new(0.0, LengthUnit::Px)))
+        );
+    }
+
+    #[test]
+    fn parses_drop_shadow() {
+        let expected = FilterFunction::DropShadow(DropShadow {
+            color: Some(cssparser::Color::RGBA(cssparser::RGBA::new(0, 0, 255, 255))),
+            dx: Length::new(2.0, LengthUnit::Px),
+            dy: Length::new(3.0, LengthUnit::Px),
+            std_deviation: Length::new(4.0, LengthUnit::Px),
+        });
+
+        assert_eq!(
+            FilterFunction::parse_str("drop-shadow(blue 2px 3px 4px)"),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            FilterFunction::parse_str("drop-shadow(2px 3px 4px blue)"),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn invalid_functions_yield_error() {
+        assert!(FilterFunction::parse_str("blur(-1px)").is_err());
+        assert!(FilterFunction::parse_str("blur(10%)").is_err());
+        assert!(FilterFunction::parse_str("brightness(-1)").is_err());
+        assert!(FilterFunction::parse_str("brightness(1 2)").is_err());
+        assert!(FilterFunction::parse_str("drop-shadow(2px)").is_err());
+        assert!(FilterFunction::parse_str("frobnicate(1)").is_err());
+    }
+
+    #[test]
+    fn serializes_functions() {
+        for (input, expected) in &[
+            ("blur()", "blur(0)"),
+            ("blur(2mm)", "blur(2mm)"),
+            ("grayscale(50%)", "grayscale(0.5)"),
+            ("sepia()", "sepia(1)"),
+            ("hue-rotate(0)", "hue-rotate(0rad)"),
+            ("drop-shadow(1px 2px)", "drop-shadow(1 2 0)"),
+            (
+                "drop-shadow(1px 2px 3px red)",
+                "drop-shadow(rgb(255, 0, 0) 1 2 3)",
+            ),
+        ] {
+            assert_eq!(
+                FilterFunction::parse_str(input).unwrap().to_css_string(),
+                *expected
+            );
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! Filter primitive subregion computation.
 use crate::bbox::BoundingBox;
+use crate::coord_units::CoordUnits;
 use crate::drawing_ctx::DrawingCtx;
 use crate::length::*;
 use crate::rect::{IRect, Rect};
@@ -110,25 +111,54 @@ impl<'a> BoundsBuilder<'a> {
             let params = self.ctx.get_view_params(draw_ctx);
             let values = self.ctx.get_computed_values_from_node_being_filtered();
 
+            // With primitiveUnits="userSpaceOnUse", percentages refer to the filter
+            // region.  With objectBoundingBox, the paffine transform already makes them
+            // fractions of the bounding box of the node being filtered.
+            let region = if self.ctx.primitive_units() == CoordUnits::UserSpaceOnUse {
+                Some(self.ctx.region())
+            } else {
+                None
+            };
+
+            // Returns the fraction of the filter region for a percentage, if it applies.
+            let fraction = |length: f64, unit: LengthUnit| match region {
+                Some(r) if unit == LengthUnit::Percent => Some((r, length)),
+                _ => None,
+            };
+
             // These replacements are correct only because self.bbox is used with the
             // paffine transform.
             let rect = self.bbox.rect.as_mut().unwrap();
 
             if let Some(x) = self.x {
                 let w = rect.width();
-                rect.x0 = x.normalize(values, &params);
+                rect.x0 = match fraction(x.length, x.unit) {
+                    Some((r, f)) => r.x0 + f * r.width(),
+                    None => x.normalize(values, &params),
+                };
                 rect.x1 = rect.x0 + w;
             }
             if let Some(y) = self.y {
                 let h = rect.height();
-                rect.y0 = y.normalize(values, &params);
+                rect.y0 = match fraction(y.length, y.unit) {
+                    Some((r, f)) => r.y0 + f * r.height(),
+                    None => y.normalize(values, &params),
+                };
                 rect.y1 = rect.y0 + h;
             }
             if let Some(width) = self.width {
-                rect.x1 = rect.x0 + width.normalize(values, &params);
+                rect.x1 = rect.x0
+                    + match fraction(width.length, width.unit) {
+                        Some((r, f)) => f * r.width(),
+                        None => width.normalize(values, &params),
+                    };
             }
             if let Some(height) = self.height {
-                rect.y1 = rect.y0 + height.normalize(values, &params);
+                rect.y1 = rect.y0
+                    + match fraction(height.length, height.unit) {
+                        Some((r, f)) => f * r.height(),
+                        None => height.normalize(values, &params),
+                    };
             }
         }
 
@@ -10,8 +10,11 @@ use crate::node::Node;
 use crate::number_list::{NumberList, NumberListLength};
 use crate::parsers::{Parse, ParseValue};
 use crate::property_bag::PropertyBag;
+use crate::rect::IRect;
 use crate::surface_utils::{
-    iterators::Pixels, shared_surface::ExclusiveImageSurface, ImageSurfaceDataExt, Pixel,
+    iterators::Pixels,
+    shared_surface::{ExclusiveImageSurface, SharedImageSurface, SurfaceType},
+    ImageSurfaceDataExt, Pixel,
 };
 use crate::util::clamp;
 
@@ -101,42 +104,11 @@ impl SetAttributes for FeColorMatrix {
                             }
                         })?;
 
-                        Matrix5::new(
-                            0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s, 0.0, 0.0,
-                            0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s, 0.0, 0.0,
-                            0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s, 0.0, 0.0,
-                            0.0,               0.0,               0.0,               1.0, 0.0,
-                            0.0,               0.0,               0.0,               0.0, 1.0,
-                        )
+                        saturate_matrix(s)
                     }
                     OperationType::HueRotate => {
                         let degrees: f64 = attr.parse(value)?;
-                        let (sin, cos) = degrees.to_radians().sin_cos();
-
-                        let a = Matrix3::new(
-                            0.213, 0.715, 0.072,
-                            0.213, 0.715, 0.072,
-                            0.213, 0.715, 0.072,
-                        );
-
-                        let b = Matrix3::new(
-                             0.787, -0.715, -0.072,
-                            -0.213,  0.285, -0.072,
-                            -0.213, -0.715,  0.928,
-                        );
-
-                        let c = Matrix3::new(
-                            -0.213, -0.715,  0.928,
-                             0.143,  0.140, -0.283,
-                            -0.787,  0.715,  0.072,
-                        );
-
-                        let top_left = a + b * cos + c * sin;
-
-                        let mut matrix = top_left.fixed_resize(0.0);
-                        matrix[(3, 3)] = 1.0;
-                        matrix[(4, 4)] = 1.0;
-                        matrix
+                        hue_rotate_matrix(degrees)
                     }
                 };
 
@@ -148,6 +120,97 @@ impl SetAttributes for FeColorMatrix {
     }
 }
 
+/// Returns the color matrix for a `saturate` operation.
+#[rustfmt::skip]
+pub fn saturate_matrix(s: f64) -> Matrix5<f64> {
+    Matrix5::new(
+        0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s, 0.0, 0.0,
+        0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s, 0.0, 0.0,
+        0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s, 0.0, 0.0,
+        0.0,               0.0,               0.0,               1.0, 0.0,
+        0.0,               0.0,               0.0,               0.0, 1.0,
+    )
+}
+
+/// Returns the color matrix for a `hueRotate` operation.
+#[rustfmt::skip]
+pub fn hue_rotate_matrix(degrees: f64) -> Matrix5<f64> {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+
+    let a = Matrix3::new(
+        0.213, 0.715, 0.072,
+        0.213, 0.715, 0.072,
+        0.213, 0.715, 0.072,
+    );
+
+    let b = Matrix3::new(
+         0.787, -0.715, -0.072,
+        -0.213,  0.285, -0.072,
+        -0.213, -0.715,  0.928,
+    );
+
+    let c = Matrix3::new(
+        -0.213, -0.715,  0.928,
+         0.143,  0.140, -0.283,
+        -0.787,  0.715,  0.072,
+    );
+
+    let top_left = a + b * cos + c * sin;
+
+    let mut matrix = top_left.fixed_resize(0.0);
+    matrix[(3, 3)] = 1.0;
+    matrix[(4, 4)] = 1.0;
+    matrix
+}
+
+/// Applies a color matrix to the unpremultiplied pixels of `input` within `bounds`.
+///
+/// The matrix can produce colors even if `input` is alpha-only, so the output is
+/// of the given `surface_type`.
+pub fn apply_color_matrix(
+    input: &SharedImageSurface,
+    bounds: IRect,
+    matrix: &Matrix5<f64>,
+    surface_type: SurfaceType,
+) -> Result<SharedImageSurface, cairo::Status> {
+    let mut surface = ExclusiveImageSurface::new(input.width(), input.height(), surface_type)?;
+
+    surface.modify(&mut |data, stride| {
+        for (x, y, pixel) in Pixels::within(input, bounds) {
+            let alpha = f64::from(pixel.a) / 255f64;
+
+            let pixel_vec = if alpha == 0.0 {
+                Vector5::new(0.0, 0.0, 0.0, 0.0, 1.0)
+            } else {
+                Vector5::new(
+                    f64::from(pixel.r) / 255f64 / alpha,
+                    f64::from(pixel.g) / 255f64 / alpha,
+                    f64::from(pixel.b) / 255f64 / alpha,
+                    alpha,
+                    1.0,
+                )
+            };
+            let mut new_pixel_vec = Vector5::zeros();
+            matrix.mul_to(&pixel_vec, &mut new_pixel_vec);
+
+            let new_alpha = clamp(new_pixel_vec[3], 0.0, 1.0);
+
+            let premultiply = |x: f64| ((clamp(x, 0.0, 1.0) * new_alpha * 255f64) + 0.5) as u8;
+
+            let output_pixel = Pixel {
+                r: premultiply(new_pixel_vec[0]),
+                g: premultiply(new_pixel_vec[1]),
+                b: premultiply(new_pixel_vec[2]),
+                a: ((new_alpha * 255f64) + 0.5) as u8,
+            };
+
+            data.set_pixel(stride, output_pixel, x, y);
+        }
+    });
+
+    surface.share()
+}
+
 impl FilterEffect for FeColorMatrix {
     fn render(
         &self,
@@ -163,49 +226,17 @@ impl FilterEffect for FeColorMatrix {
             .add_input(&input)
             .into_irect(draw_ctx);
 
-        let mut surface = ExclusiveImageSurface::new(
-            ctx.source_graphic().width(),
-            ctx.source_graphic().height(),
-            input.surface().surface_type(),
+        let surface = apply_color_matrix(
+            input.surface(),
+            bounds,
+            &self.matrix,
+            ctx.processing_surface_type(),
         )?;
 
-        surface.modify(&mut |data, stride| {
-            for (x, y, pixel) in Pixels::within(input.surface(), bounds) {
-                let alpha = f64::from(pixel.a) / 255f64;
-
-                let pixel_vec = if alpha == 0.0 {
-                    Vector5::new(0.0, 0.0, 0.0, 0.0, 1.0)
-                } else {
-                    Vector5::new(
-                        f64::from(pixel.r) / 255f64 / alpha,
-                        f64::from(pixel.g) / 255f64 / alpha,
-                        f64::from(pixel.b) / 255f64 / alpha,
-                        alpha,
-                        1.0,
-                    )
-                };
-                let mut new_pixel_vec = Vector5::zeros();
-                self.matrix.mul_to(&pixel_vec, &mut new_pixel_vec);
-
-                let new_alpha = clamp(new_pixel_vec[3], 0.0, 1.0);
-
-                let premultiply = |x: f64| ((clamp(x, 0.0, 1.0) * new_alpha * 255f64) + 0.5) as u8;
-
-                let output_pixel = Pixel {
-                    r: premultiply(new_pixel_vec[0]),
-                    g: premultiply(new_pixel_vec[1]),
-                    b: premultiply(new_pixel_vec[2]),
-                    a: ((new_alpha * 255f64) + 0.5) as u8,
-                };
-
-                data.set_pixel(stride, output_pixel, x, y);
-            }
-        });
-
         Ok(FilterResult {
             name: self.base.result.clone(),
             output: FilterOutput {
-                surface: surface.share()?,
+                surface,
                 bounds,
             },
         })
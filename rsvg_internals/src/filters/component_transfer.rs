@@ -292,11 +292,12 @@ impl FilterEffect for FeComponentTransfer {
             .add_input(&input)
             .into_irect(draw_ctx);
 
-        // Create the output surface.
+        // Create the output surface.  The transfer functions can produce colors
+        // even if the input is alpha-only.
         let mut surface = ExclusiveImageSurface::new(
             ctx.source_graphic().width(),
             ctx.source_graphic().height(),
-            input.surface().surface_type(),
+            ctx.processing_surface_type(),
         )?;
 
         let func_r_node = get_func_x_node!(node, FeFuncR, Channel::R);
@@ -95,6 +95,7 @@ impl FilterEffect for FeComposite {
                 self.k2,
                 self.k3,
                 self.k4,
+                ctx.processing_surface_type(),
             )?
         } else {
             input.surface().compose(
@@ -10,7 +10,7 @@ use crate::node::{Node, NodeBorrow};
 use crate::paint_server::PaintServer;
 use crate::parsers::CustomIdent;
 use crate::properties::ComputedValues;
-use crate::rect::IRect;
+use crate::rect::{IRect, Rect};
 use crate::surface_utils::shared_surface::{
     ExclusiveImageSurface, SharedImageSurface, SurfaceType,
 };
@@ -65,6 +65,8 @@ pub struct FilterContext {
     previous_results: HashMap<CustomIdent, FilterOutput>,
     /// The background surface. Computed lazily.
     background_surface: RefCell<Option<Result<SharedImageSurface, FilterError>>>,
+    /// The filter region in the user space of the node being filtered.
+    region: Rect,
     /// The filter effects region.
     effects_region: BoundingBox,
     /// Whether the currently rendered filter primitive uses linear RGB for color operations.
@@ -139,8 +141,22 @@ impl FilterContext {
             .post_transform(&draw_transform),
         };
 
+        let region = filter.compute_region(computed_from_node_being_filtered, draw_ctx, &bbox_rect);
+
+        // The effects region is the filter region in pixel coordinates of the surface,
+        // clipped to the surface.
         let (width, height) = (source_surface.width(), source_surface.height());
 
+        let mut effects_region = BoundingBox::new();
+        effects_region.insert(
+            &BoundingBox::new()
+                .with_transform(draw_transform)
+                .with_rect(region),
+        );
+        effects_region.clip(
+            &BoundingBox::new().with_rect(Rect::from_size(f64::from(width), f64::from(height))),
+        );
+
         Self {
             node: filter_node.clone(),
             node_bbox,
@@ -149,13 +165,8 @@ impl FilterContext {
             last_result: None,
             previous_results: HashMap::new(),
             background_surface: RefCell::new(None),
-            effects_region: filter.compute_effects_region(
-                computed_from_node_being_filtered,
-                draw_ctx,
-                affine,
-                f64::from(width),
-                f64::from(height),
-            ),
+            region,
+            effects_region,
             processing_linear_rgb: false,
             _affine: affine,
             paffine,
@@ -217,7 +228,11 @@ impl FilterContext {
     #[inline]
     pub fn into_output(self) -> Result<SharedImageSurface, cairo::Status> {
         match self.last_result {
-            Some(FilterOutput { surface, bounds }) => surface.to_srgb(bounds),
+            // Some results, like the one from feFlood, only cover the filter effects
+            // region; the filters that come after this one need the full size.
+            Some(FilterOutput { surface, bounds }) => surface
+                .to_srgb(bounds)?
+                .with_size(self.source_surface.width(), self.source_surface.height()),
             None => SharedImageSurface::empty(
                 self.source_surface.width(),
                 self.source_surface.height(),
@@ -243,6 +258,20 @@ impl FilterContext {
         self.paffine
     }
 
+    /// Returns the filter region in the user space of the node being filtered.
+    ///
+    /// Unlike `effects_region()`, this is not clipped to the surface.
+    #[inline]
+    pub fn region(&self) -> Rect {
+        self.region
+    }
+
+    /// Returns the `primitiveUnits` of the filter.
+    #[inline]
+    pub fn primitive_units(&self) -> CoordUnits {
+        borrow_element_as!(self.node, Filter).get_primitive_units()
+    }
+
     /// Returns the filter effects region.
     #[inline]
     pub fn effects_region(&self) -> BoundingBox {
@@ -255,9 +284,9 @@ impl FilterContext {
 
     /// Pushes the viewport size based on the value of `primitiveUnits`.
     pub fn get_view_params(&self, draw_ctx: &mut DrawingCtx) -> ViewParams {
-        // See comments in compute_effects_region() for how this works.
-        let units = borrow_element_as!(self.node, Filter).get_primitive_units();
-        if units == CoordUnits::ObjectBoundingBox {
+        // With objectBoundingBox, the paffine transform includes the scaling to the
+        // bounding box of the node being filtered, so lengths are fractions of a 1x1 box.
+        if self.primitive_units() == CoordUnits::ObjectBoundingBox {
             draw_ctx.push_view_box(1.0, 1.0)
         } else {
             draw_ctx.get_view_params()
@@ -370,12 +399,21 @@ impl FilterContext {
                 .map_err(FilterError::CairoError)
                 .map(FilterInput::StandardInput),
 
-            Input::FilterOutput(ref name) => self
-                .previous_results
-                .get(name)
-                .cloned()
-                .map(FilterInput::PrimitiveOutput)
-                .ok_or(FilterError::InvalidInput),
+            // "References to non-existent results will be treated as if no result was
+            // specified."
+            // https://www.w3.org/TR/filter-effects/#attr-valuedef-in-filter-primitive-reference
+            Input::FilterOutput(ref name) => match self.previous_results.get(name) {
+                Some(output) => Ok(FilterInput::PrimitiveOutput(output.clone())),
+
+                None => {
+                    rsvg_log!(
+                        Warn,
+                        "filter result \"{}\" does not exist; using the default input",
+                        name
+                    );
+                    self.get_input_raw(acquired_nodes, draw_ctx, None)
+                }
+            },
         }
     }
 
@@ -414,6 +452,20 @@ impl FilterContext {
             })
     }
 
+    /// Returns the surface type for colors computed in the current color space.
+    ///
+    /// `get_input()` converts the inputs to this color space, except for alpha-only
+    /// inputs, which keep their type since they have no colors.  Primitives that can
+    /// produce colors from such an input must use this type for their output.
+    #[inline]
+    pub fn processing_surface_type(&self) -> SurfaceType {
+        if self.processing_linear_rgb {
+            SurfaceType::LinearRgb
+        } else {
+            SurfaceType::SRgb
+        }
+    }
+
     /// Calls the given closure with linear RGB processing enabled.
     #[inline]
     pub fn with_linear_rgb<T, F: FnOnce(&mut FilterContext) -> T>(&mut self, f: F) -> T {
@@ -230,10 +230,11 @@ impl FilterEffect for FeConvolveMatrix {
 
         let matrix = self.kernel_matrix.as_ref().unwrap();
 
+        // The bias can produce colors even if the input is alpha-only.
         let mut surface = ExclusiveImageSurface::new(
             input_surface.width(),
             input_surface.height(),
-            input.surface().surface_type(),
+            ctx.processing_surface_type(),
         )?;
 
         surface.modify(&mut |data, stride| {
@@ -23,6 +23,9 @@ pub enum FilterError {
     LightingInputTooSmall,
     /// Child node was in error.
     ChildNodeInError,
+    /// A filter primitive references an element that is already being rendered,
+    /// for example, the element that the filter is applied to.
+    CircularReference,
 }
 
 impl Error for FilterError {}
@@ -45,6 +48,7 @@ impl fmt::Display for FilterError {
                 "lighting filter input surface is too small (less than 2×2 pixels)"
             ),
             FilterError::ChildNodeInError => write!(f, "child node was in error"),
+            FilterError::CircularReference => write!(f, "circular reference"),
         }
     }
 }
@@ -59,12 +63,30 @@ impl From<cairo::Status> for FilterError {
 impl From<RenderingError> for FilterError {
     #[inline]
     fn from(e: RenderingError) -> Self {
-        if let RenderingError::Cairo(status) = e {
-            FilterError::CairoError(status)
-        } else {
+        match e {
+            RenderingError::Cairo(status) => FilterError::CairoError(status),
+            RenderingError::CircularReference => FilterError::CircularReference,
+
             // FIXME: this is just a dummy value; we should probably have a way to indicate
             // an error in the underlying drawing process.
-            FilterError::CairoError(cairo::Status::InvalidStatus)
+            _ => FilterError::CairoError(cairo::Status::InvalidStatus),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendering_errors_keep_their_kind_in_filters() {
+        assert_eq!(
+            FilterError::from(RenderingError::Cairo(cairo::Status::NoMemory)),
+            FilterError::CairoError(cairo::Status::NoMemory)
+        );
+        assert_eq!(
+            FilterError::from(RenderingError::CircularReference),
+            FilterError::CircularReference
+        );
+    }
+}
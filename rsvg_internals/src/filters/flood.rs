@@ -44,13 +44,17 @@ impl FilterEffect for FeFlood {
         let cascaded = CascadedValues::new_from_node(node);
         let values = cascaded.get();
 
+        // currentColor refers to the element that references the filter
         let color = match values.flood_color().0 {
-            cssparser::Color::CurrentColor => values.color().0,
+            cssparser::Color::CurrentColor => {
+                ctx.get_computed_values_from_node_being_filtered().color().0
+            }
             cssparser::Color::RGBA(rgba) => rgba,
         };
         let opacity = values.flood_opacity().0;
 
-        let surface = ctx.source_graphic().flood(bounds, color, opacity)?;
+        let region = ctx.effects_region().rect.unwrap().into();
+        let surface = ctx.source_graphic().flood(bounds, region, color, opacity)?;
 
         Ok(FilterResult {
             name: self.base.result.clone(),
@@ -197,6 +197,40 @@ fn gaussian_blur(
     )?)
 }
 
+/// Blurs `input` within `bounds` with the given standard deviations, in device pixels.
+pub fn blur(
+    input: &SharedImageSurface,
+    bounds: IRect,
+    std_x: f64,
+    std_y: f64,
+) -> Result<SharedImageSurface, FilterError> {
+    // Performance TODO: gaussian blur is frequently used for shadows, operating on SourceAlpha
+    // (so the image is alpha-only). We can use this to not waste time processing the other
+    // channels.
+
+    // Horizontal convolution.
+    let horiz_result_surface = if std_x >= 2.0 {
+        // The spec says for deviation >= 2.0 three box blurs can be used as an optimization.
+        three_box_blurs::<Horizontal>(input, bounds, std_x)?
+    } else if std_x != 0.0 {
+        gaussian_blur(input, bounds, std_x, false)?
+    } else {
+        input.clone()
+    };
+
+    // Vertical convolution.
+    let output_surface = if std_y >= 2.0 {
+        // The spec says for deviation >= 2.0 three box blurs can be used as an optimization.
+        three_box_blurs::<Vertical>(&horiz_result_surface, bounds, std_y)?
+    } else if std_y != 0.0 {
+        gaussian_blur(&horiz_result_surface, bounds, std_y, true)?
+    } else {
+        horiz_result_surface
+    };
+
+    Ok(output_surface)
+}
+
 impl FilterEffect for FeGaussianBlur {
     fn render(
         &self,
@@ -219,29 +253,7 @@ impl FilterEffect for FeGaussianBlur {
         let std_x = std_x.abs();
         let std_y = std_y.abs();
 
-        // Performance TODO: gaussian blur is frequently used for shadows, operating on SourceAlpha
-        // (so the image is alpha-only). We can use this to not waste time processing the other
-        // channels.
-
-        // Horizontal convolution.
-        let horiz_result_surface = if std_x >= 2.0 {
-            // The spec says for deviation >= 2.0 three box blurs can be used as an optimization.
-            three_box_blurs::<Horizontal>(input.surface(), bounds, std_x)?
-        } else if std_x != 0.0 {
-            gaussian_blur(input.surface(), bounds, std_x, false)?
-        } else {
-            input.surface().clone()
-        };
-
-        // Vertical convolution.
-        let output_surface = if std_y >= 2.0 {
-            // The spec says for deviation >= 2.0 three box blurs can be used as an optimization.
-            three_box_blurs::<Vertical>(&horiz_result_surface, bounds, std_y)?
-        } else if std_y != 0.0 {
-            gaussian_blur(&horiz_result_surface, bounds, std_y, true)?
-        } else {
-            horiz_result_surface
-        };
+        let output_surface = blur(input.surface(), bounds, std_x, std_y)?;
 
         Ok(FilterResult {
             name: self.base.result.clone(),
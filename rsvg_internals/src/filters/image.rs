@@ -1,4 +1,5 @@
 use markup5ever::{expanded_name, local_name, namespace_url, ns};
+use url::Url;
 
 use crate::allowed_url::{Fragment, Href};
 use crate::aspect_ratio::AspectRatio;
@@ -20,6 +21,7 @@ pub struct FeImage {
     base: Primitive,
     aspect: AspectRatio,
     href: Option<Href>,
+    xml_base: Option<Url>,
 }
 
 impl Default for FeImage {
@@ -30,11 +32,17 @@ impl Default for FeImage {
             base: Primitive::new::<Self>(),
             aspect: AspectRatio::default(),
             href: None,
+            xml_base: None,
         }
     }
 }
 
 impl FeImage {
+    /// Sets the base URI from the `xml:base` attributes in effect for this element.
+    pub fn set_xml_base(&mut self, xml_base: Url) {
+        self.xml_base = Some(xml_base);
+    }
+
     /// Renders the filter if the source is an existing node.
     fn render_node(
         &self,
@@ -44,9 +52,10 @@ impl FeImage {
         bounds: Rect,
         fragment: &Fragment,
     ) -> Result<FilterResult, FilterError> {
-        let acquired_drawable = acquired_nodes
-            .acquire(fragment)
-            .map_err(|_| FilterError::InvalidInput)?;
+        let acquired_drawable = acquired_nodes.acquire(fragment).map_err(|e| match e {
+            AcquireError::CircularReference(_) => FilterError::CircularReference,
+            _ => FilterError::InvalidInput,
+        })?;
         let drawable = acquired_drawable.get();
 
         let node_being_filtered_values = ctx.get_computed_values_from_node_being_filtered();
@@ -84,7 +93,7 @@ impl FeImage {
     ) -> Result<FilterResult, FilterError> {
         // FIXME: translate the error better here
         let image = acquired_nodes
-            .lookup_image(url)
+            .lookup_image(url, self.xml_base.as_ref(), None)
             .map_err(|_| FilterError::InvalidInput)?;
 
         let rect = self.aspect.compute(
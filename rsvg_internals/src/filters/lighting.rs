@@ -476,8 +476,11 @@ macro_rules! impl_lighting_filter {
 
                 let cascaded = CascadedValues::new_from_node(node);
                 let values = cascaded.get();
+                // currentColor refers to the element that references the filter
                 let lighting_color = match values.lighting_color().0 {
-                    cssparser::Color::CurrentColor => values.color().0,
+                    cssparser::Color::CurrentColor => {
+                        ctx.get_computed_values_from_node_being_filtered().color().0
+                    }
                     cssparser::Color::RGBA(rgba) => rgba,
                 };
 
@@ -960,3 +963,66 @@ impl Normal {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Returns the x and y components of the normal before normalization.
+    fn components(normal: Normal) -> (f64, f64) {
+        (
+            normal.factor.x * f64::from(normal.normal.x),
+            normal.factor.y * f64::from(normal.normal.y),
+        )
+    }
+
+    #[test]
+    fn edge_normals_match_interior_on_a_ramp() {
+        const SIDE: i32 = 5;
+
+        let mut surface = ExclusiveImageSurface::new(SIDE, SIDE, SurfaceType::SRgb).unwrap();
+
+        // Alpha grows by 10 for every pixel to the right, and by 20 for every pixel down.
+        {
+            let stride = surface.stride() as usize;
+            let mut data = surface.get_data();
+
+            for y in 0..SIDE as u32 {
+                for x in 0..SIDE as u32 {
+                    let pixel = Pixel {
+                        r: 0,
+                        g: 0,
+                        b: 0,
+                        a: (10 * x + 20 * y) as u8,
+                    };
+                    data.set_pixel(stride, pixel, x, y);
+                }
+            }
+        }
+
+        let surface = surface.share().unwrap();
+        let bounds = IRect::from_size(SIDE, SIDE);
+
+        // The spec's kernels use one-sided differences at the edges, so a constant
+        // slope gives the same normal everywhere; padding with zeros would not.
+        let expected = components(Normal::interior(&surface, bounds, 2, 2));
+        assert_eq!(expected, (-20.0, -40.0));
+
+        let edge_normals = [
+            Normal::top_left(&surface, bounds),
+            Normal::top_row(&surface, bounds, 2),
+            Normal::top_right(&surface, bounds),
+            Normal::left_column(&surface, bounds, 2),
+            Normal::right_column(&surface, bounds, 2),
+            Normal::bottom_left(&surface, bounds),
+            Normal::bottom_row(&surface, bounds, 2),
+            Normal::bottom_right(&surface, bounds),
+        ];
+
+        for normal in edge_normals.iter() {
+            let (nx, ny) = components(*normal);
+            assert!((nx - expected.0).abs() < 1e-10, "{:?}", normal);
+            assert!((ny - expected.1).abs() < 1e-10, "{:?}", normal);
+        }
+    }
+}
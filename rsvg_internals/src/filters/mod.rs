@@ -11,12 +11,14 @@ use crate::document::AcquiredNodes;
 use crate::drawing_ctx::DrawingCtx;
 use crate::element::{Draw, Element, ElementResult, SetAttributes};
 use crate::error::{ParseError, RenderingError};
+use crate::filter_func::FilterFunction;
 use crate::length::*;
 use crate::node::{CascadedValues, Node, NodeBorrow};
 use crate::parsers::{CustomIdent, Parse, ParseValue};
 use crate::properties::ComputedValues;
 use crate::property_bag::PropertyBag;
 use crate::property_defs::ColorInterpolationFilters;
+use crate::rect::IRect;
 use crate::surface_utils::shared_surface::{SharedImageSurface, SurfaceType};
 
 mod bounds;
@@ -26,7 +28,7 @@ pub mod context;
 use self::context::{FilterContext, FilterInput, FilterResult};
 
 mod error;
-use self::error::FilterError;
+pub use self::error::FilterError;
 
 /// A filter primitive interface.
 pub trait FilterEffect: SetAttributes + Draw {
@@ -284,6 +286,22 @@ pub fn render(
         return Ok(source_surface);
     }
 
+    if borrow_element_as!(filter_node, Filter).is_region_empty() {
+        draw_ctx.diagnostics().warning(
+            format!(
+                "filter {} has an empty filter effects region; the element will not be rendered",
+                filter_node
+            ),
+            None,
+        );
+
+        return Ok(SharedImageSurface::empty(
+            source_surface.width(),
+            source_surface.height(),
+            SurfaceType::AlphaOnly,
+        )?);
+    }
+
     let mut filter_ctx = FilterContext::new(
         filter_node,
         computed_from_node_being_filtered,
@@ -306,7 +324,11 @@ pub fn render(
             let in_error = c.borrow_element().is_in_error();
 
             if in_error {
-                rsvg_log!("(ignoring filter primitive {} because it is in error)", c);
+                rsvg_log!(
+                    Debug,
+                    "(ignoring filter primitive {} because it is in error)",
+                    c
+                );
             }
 
             !in_error
@@ -334,11 +356,19 @@ pub fn render(
                 .render(&c, filter_ctx, acquired_nodes, draw_ctx)
                 .and_then(|result| filter_ctx.store_result(result))
             {
-                rsvg_log!("(filter primitive {} returned an error: {})", c, err);
-
-                // Exit early on Cairo errors. Continue rendering otherwise.
-                if let FilterError::CairoError(status) = err {
-                    return Err(status);
+                draw_ctx.diagnostics().error(
+                    format!("filter primitive {} returned an error: {}", c, err),
+                    None,
+                );
+
+                // Exit early on Cairo errors and circular references. Continue rendering
+                // otherwise.
+                match err {
+                    FilterError::CairoError(status) => return Err(RenderingError::Cairo(status)),
+                    FilterError::CircularReference => {
+                        return Err(RenderingError::CircularReference)
+                    }
+                    _ => (),
                 }
             }
 
@@ -355,6 +385,7 @@ pub fn render(
 
         let elapsed = start.elapsed();
         rsvg_log!(
+            Debug,
             "(rendered filter primitive {} in\n    {} seconds)",
             c,
             elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9
@@ -364,6 +395,46 @@ pub fn render(
     Ok(filter_ctx.into_output()?)
 }
 
+/// Applies a filter function from the `filter` property and returns the resulting surface.
+pub fn render_function(
+    func: &FilterFunction,
+    computed_from_node_being_filtered: &ComputedValues,
+    source_surface: SharedImageSurface,
+    draw_ctx: &mut DrawingCtx,
+) -> Result<SharedImageSurface, RenderingError> {
+    let values = computed_from_node_being_filtered;
+    let bounds = IRect::from_size(source_surface.width(), source_surface.height());
+
+    let linear_rgb = func.is_affected_by_color_interpolation_filters()
+        && values.color_interpolation_filters() == ColorInterpolationFilters::LinearRgb;
+
+    let result = if linear_rgb {
+        source_surface
+            .to_linear_rgb(bounds)
+            .map_err(FilterError::from)
+            .and_then(|surface| func.render(&surface, bounds, values, draw_ctx))
+            .and_then(|surface| Ok(surface.to_srgb(bounds)?))
+    } else {
+        func.render(&source_surface, bounds, values, draw_ctx)
+    };
+
+    match result {
+        Ok(surface) => Ok(surface),
+
+        // Exit early on Cairo errors, just like for filter primitives.
+        Err(FilterError::CairoError(status)) => Err(RenderingError::Cairo(status)),
+
+        // Otherwise draw the element unfiltered, like when a url(#filter) fails.
+        Err(err) => {
+            draw_ctx.diagnostics().error(
+                format!("filter function {:?} returned an error: {}", func, err),
+                None,
+            );
+            Ok(source_surface)
+        }
+    }
+}
+
 impl From<ColorInterpolationFilters> for SurfaceType {
     fn from(c: ColorInterpolationFilters) -> Self {
         match c {
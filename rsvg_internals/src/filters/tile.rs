@@ -3,6 +3,7 @@ use crate::drawing_ctx::DrawingCtx;
 use crate::element::{ElementResult, SetAttributes};
 use crate::node::Node;
 use crate::property_bag::PropertyBag;
+use crate::surface_utils::shared_surface::SharedImageSurface;
 
 use super::context::{FilterContext, FilterInput, FilterOutput, FilterResult};
 use super::{FilterEffect, FilterError, PrimitiveWithInput};
@@ -44,21 +45,33 @@ impl FilterEffect for FeTile {
             .get_bounds(ctx, node.parent().as_ref())?
             .into_irect(draw_ctx);
 
-        let surface = match input {
-            FilterInput::StandardInput(input_surface) => input_surface,
+        // The tile is the subregion of the input: the filter effects region for
+        // standard inputs, or the subregion of the primitive that produced it.
+        let (input_surface, input_bounds) = match input {
+            FilterInput::StandardInput(input_surface) => {
+                (input_surface, ctx.effects_region().rect.unwrap().into())
+            }
             FilterInput::PrimitiveOutput(FilterOutput {
                 surface: input_surface,
                 bounds: input_bounds,
-            }) => {
-                let tile_surface = input_surface.tile(input_bounds)?;
+            }) => (input_surface, input_bounds),
+        };
 
-                ctx.source_graphic().paint_image_tiled(
-                    bounds,
-                    &tile_surface,
-                    input_bounds.x0,
-                    input_bounds.y0,
-                )?
-            }
+        let surface = if input_bounds.is_empty() {
+            SharedImageSurface::empty(
+                input_surface.width(),
+                input_surface.height(),
+                input_surface.surface_type(),
+            )?
+        } else {
+            let tile_surface = input_surface.tile(input_bounds)?;
+
+            ctx.source_graphic().paint_image_tiled(
+                bounds,
+                &tile_surface,
+                input_bounds.x0,
+                input_bounds.y0,
+            )?
         };
 
         Ok(FilterResult {
@@ -8,9 +8,11 @@ use crate::error::*;
 use crate::node::{CascadedValues, Node};
 use crate::parsers::{NumberOptionalNumber, Parse, ParseValue};
 use crate::property_bag::PropertyBag;
+use crate::rect::Rect;
 use crate::surface_utils::{
+    iterators::PixelsMut,
     shared_surface::{ExclusiveImageSurface, SurfaceType},
-    ImageSurfaceDataExt, Pixel,
+    Pixel,
 };
 use crate::util::clamp;
 
@@ -135,8 +137,8 @@ struct NoiseGenerator {
     stitch_tiles: StitchTiles,
     type_: NoiseType,
 
-    tile_width: f64,
-    tile_height: f64,
+    /// The tile to stitch, in the same coordinate system as the sampled points.
+    tile: Rect,
 
     lattice_selector: [usize; B_SIZE + B_SIZE + 2],
     gradient: [[[f64; 2]; B_SIZE + B_SIZE + 2]; 4],
@@ -157,8 +159,7 @@ impl NoiseGenerator {
         num_octaves: i32,
         type_: NoiseType,
         stitch_tiles: StitchTiles,
-        tile_width: f64,
-        tile_height: f64,
+        tile: Rect,
     ) -> Self {
         let mut rv = Self {
             base_frequency,
@@ -166,8 +167,7 @@ impl NoiseGenerator {
             type_,
             stitch_tiles,
 
-            tile_width,
-            tile_height,
+            tile,
 
             lattice_selector: [0; B_SIZE + B_SIZE + 2],
             gradient: [[[0.0; 2]; B_SIZE + B_SIZE + 2]; 4],
@@ -266,17 +266,20 @@ impl NoiseGenerator {
         lerp(sy, a, b)
     }
 
-    fn turbulence(&self, color_channel: usize, point: [f64; 2], tile_x: f64, tile_y: f64) -> f64 {
+    fn turbulence(&self, color_channel: usize, point: [f64; 2]) -> f64 {
         let mut stitch_info = None;
         let mut base_frequency = self.base_frequency;
 
         // Adjust the base frequencies if necessary for stitching.
         if self.stitch_tiles == StitchTiles::Stitch {
+            let tile_width = self.tile.width();
+            let tile_height = self.tile.height();
+
             // When stitching tiled turbulence, the frequencies must be adjusted
             // so that the tile borders will be continuous.
             if base_frequency.0 != 0.0 {
-                let freq_lo = (self.tile_width * base_frequency.0).floor() / self.tile_width;
-                let freq_hi = (self.tile_width * base_frequency.0).ceil() / self.tile_width;
+                let freq_lo = (tile_width * base_frequency.0).floor() / tile_width;
+                let freq_hi = (tile_width * base_frequency.0).ceil() / tile_width;
                 if base_frequency.0 / freq_lo < freq_hi / base_frequency.0 {
                     base_frequency.0 = freq_lo;
                 } else {
@@ -284,8 +287,8 @@ impl NoiseGenerator {
                 }
             }
             if base_frequency.1 != 0.0 {
-                let freq_lo = (self.tile_height * base_frequency.1).floor() / self.tile_height;
-                let freq_hi = (self.tile_height * base_frequency.1).ceil() / self.tile_height;
+                let freq_lo = (tile_height * base_frequency.1).floor() / tile_height;
+                let freq_hi = (tile_height * base_frequency.1).ceil() / tile_height;
                 if base_frequency.1 / freq_lo < freq_hi / base_frequency.1 {
                     base_frequency.1 = freq_lo;
                 } else {
@@ -294,13 +297,13 @@ impl NoiseGenerator {
             }
 
             // Set up initial stitch values.
-            let width = (self.tile_width * base_frequency.0 + 0.5) as usize;
-            let height = (self.tile_height * base_frequency.1 + 0.5) as usize;
+            let width = (tile_width * base_frequency.0 + 0.5) as usize;
+            let height = (tile_height * base_frequency.1 + 0.5) as usize;
             stitch_info = Some(StitchInfo {
                 width,
-                wrap_x: (tile_x * base_frequency.0) as usize + PERLIN_N as usize + width,
+                wrap_x: (self.tile.x0 * base_frequency.0 + f64::from(PERLIN_N)) as usize + width,
                 height,
-                wrap_y: (tile_y * base_frequency.1) as usize + PERLIN_N as usize + height,
+                wrap_y: (self.tile.y0 * base_frequency.1 + f64::from(PERLIN_N)) as usize + height,
             });
         }
 
@@ -344,14 +347,17 @@ impl FilterEffect for FeTurbulence {
 
         let affine = ctx.paffine().invert().unwrap();
 
+        // The noise is sampled in the primitive's user space, so the tile to
+        // stitch must be expressed in that same space.
+        let tile = affine.transform_rect(&Rect::from(bounds));
+
         let noise_generator = NoiseGenerator::new(
             self.seed,
             self.base_frequency,
             self.num_octaves,
             self.type_,
             self.stitch_tiles,
-            f64::from(bounds.width()),
-            f64::from(bounds.height()),
+            tile,
         );
 
         let cascaded = CascadedValues::new_from_node(node);
@@ -367,37 +373,30 @@ impl FilterEffect for FeTurbulence {
         )?;
 
         surface.modify(&mut |data, stride| {
-            for y in bounds.y_range() {
-                for x in bounds.x_range() {
-                    let point = affine.transform_point(f64::from(x), f64::from(y));
-                    let point = [point.0, point.1];
-
-                    let generate = |color_channel| {
-                        let v = noise_generator.turbulence(
-                            color_channel,
-                            point,
-                            f64::from(x - bounds.x0),
-                            f64::from(y - bounds.y0),
-                        );
-
-                        let v = match self.type_ {
-                            NoiseType::FractalNoise => (v * 255.0 + 255.0) / 2.0,
-                            NoiseType::Turbulence => v * 255.0,
-                        };
-
-                        (clamp(v, 0.0, 255.0) + 0.5) as u8
+            for (x, y, p) in PixelsMut::within(data, stride, bounds) {
+                let point = affine.transform_point(f64::from(x), f64::from(y));
+                let point = [point.0, point.1];
+
+                let generate = |color_channel| {
+                    let v = noise_generator.turbulence(color_channel, point);
+
+                    let v = match self.type_ {
+                        NoiseType::FractalNoise => (v * 255.0 + 255.0) / 2.0,
+                        NoiseType::Turbulence => v * 255.0,
                     };
 
-                    let pixel = Pixel {
-                        r: generate(0),
-                        g: generate(1),
-                        b: generate(2),
-                        a: generate(3),
-                    }
-                    .premultiply();
+                    (clamp(v, 0.0, 255.0) + 0.5) as u8
+                };
 
-                    data.set_pixel(stride, pixel, x as u32, y as u32);
+                let pixel = Pixel {
+                    r: generate(0),
+                    g: generate(1),
+                    b: generate(2),
+                    a: generate(3),
                 }
+                .premultiply();
+
+                *p = pixel.to_u32().to_ne_bytes();
             }
         });
 
@@ -1,6 +1,7 @@
 //! CSS font properties.
 
-use cssparser::Parser;
+use cssparser::{Parser, ToCss};
+use std::fmt;
 
 use crate::drawing_ctx::ViewParams;
 use crate::error::*;
@@ -104,6 +105,30 @@ impl Parse for FontSizeSpec {
     }
 }
 
+impl ToCss for FontSizeSpec {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        use FontSizeSpec::*;
+
+        let s = match *self {
+            Smaller => "smaller",
+            Larger => "larger",
+            XXSmall => "xx-small",
+            XSmall => "x-small",
+            Small => "small",
+            Medium => "medium",
+            Large => "large",
+            XLarge => "x-large",
+            XXLarge => "xx-large",
+            Value(ref l) => return l.to_css(dest),
+        };
+
+        dest.write_str(s)
+    }
+}
+
 // https://www.w3.org/TR/2008/REC-CSS2-20080411/fonts.html#propdef-font-weight
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum FontWeightSpec {
@@ -153,6 +178,31 @@ impl Parse for FontWeightSpec {
     }
 }
 
+impl ToCss for FontWeightSpec {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        use FontWeightSpec::*;
+
+        dest.write_str(match *self {
+            Normal => "normal",
+            Bold => "bold",
+            Bolder => "bolder",
+            Lighter => "lighter",
+            W100 => "100",
+            W200 => "200",
+            W300 => "300",
+            W400 => "400",
+            W500 => "500",
+            W600 => "600",
+            W700 => "700",
+            W800 => "800",
+            W900 => "900",
+        })
+    }
+}
+
 // https://www.w3.org/TR/css-text-3/#letter-spacing-property
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum LetterSpacingSpec {
@@ -161,20 +211,18 @@ pub enum LetterSpacingSpec {
 }
 
 impl LetterSpacingSpec {
+    /// Returns the extra spacing between glyphs; this is zero for `normal`.
     pub fn value(&self) -> Length<Horizontal> {
         match self {
+            LetterSpacingSpec::Normal => Length::<Horizontal>::new(0.0, LengthUnit::Px),
             LetterSpacingSpec::Value(s) => *s,
-            _ => unreachable!(),
         }
     }
 
+    /// `normal` is kept as a distinct computed value, so that text layout can leave
+    /// the font's own spacing alone instead of forcing a spacing of zero.
     pub fn compute(&self) -> Self {
-        let spacing = match self {
-            LetterSpacingSpec::Normal => Length::<Horizontal>::new(0.0, LengthUnit::Px),
-            LetterSpacingSpec::Value(s) => *s,
-        };
-
-        LetterSpacingSpec::Value(spacing)
+        *self
     }
 
     pub fn normalize(&self, values: &ComputedValues, params: &ViewParams) -> f64 {
@@ -196,6 +244,18 @@ impl Parse for LetterSpacingSpec {
     }
 }
 
+impl ToCss for LetterSpacingSpec {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        match *self {
+            LetterSpacingSpec::Normal => dest.write_str("normal"),
+            LetterSpacingSpec::Value(ref l) => l.to_css(dest),
+        }
+    }
+}
+
 /// https://www.w3.org/TR/2008/REC-CSS2-20080411/fonts.html#propdef-font-family
 #[derive(Debug, Clone, PartialEq)]
 pub struct SingleFontFamily(pub String);
@@ -227,6 +287,16 @@ impl Parse for SingleFontFamily {
     }
 }
 
+impl ToCss for SingleFontFamily {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        // Always quote the name; a list of identifiers gets parsed into the same value.
+        cssparser::serialize_string(&self.0, dest)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -325,10 +395,11 @@ mod tests {
     fn computes_letter_spacing() {
         assert_eq!(
             <LetterSpacingSpec as Parse>::parse_str("normal").map(|s| s.compute()),
-            Ok(LetterSpacingSpec::Value(Length::<Horizontal>::new(
-                0.0,
-                LengthUnit::Px,
-            )))
+            Ok(LetterSpacingSpec::Normal)
+        );
+        assert_eq!(
+            LetterSpacingSpec::Normal.value(),
+            Length::<Horizontal>::new(0.0, LengthUnit::Px)
         );
         assert_eq!(
             <LetterSpacingSpec as Parse>::parse_str("10em").map(|s| s.compute()),
@@ -15,12 +15,14 @@ use crate::drawing_ctx::{DrawingCtx, ViewParams};
 use crate::element::{Draw, Element, ElementResult, SetAttributes};
 use crate::error::*;
 use crate::length::*;
+use crate::limits;
 use crate::node::{CascadedValues, Node, NodeBorrow};
 use crate::paint_server::{AsPaintSource, PaintSource};
 use crate::parsers::{Parse, ParseValue};
 use crate::properties::ComputedValues;
 use crate::property_bag::PropertyBag;
-use crate::property_defs::StopColor;
+use crate::property_defs::{ColorInterpolation, StopColor};
+use crate::surface_utils::srgb;
 use crate::transform::Transform;
 use crate::unit_interval::UnitInterval;
 
@@ -37,6 +39,13 @@ struct ColorStop {
     opacity: UnitInterval,
 }
 
+/// Number of intermediate color stops to insert between each pair of stops
+/// when interpolating in linearRGB.
+///
+/// Cairo always interpolates gradient colors in sRGB, so we approximate
+/// linear-light interpolation by subdividing each segment of the gradient.
+const LINEAR_RGB_SUBDIVISIONS: usize = 16;
+
 // gradientUnits attibute; its default is objectBoundingBox
 coord_units!(GradientUnits, CoordUnits::ObjectBoundingBox);
 
@@ -80,6 +89,9 @@ impl From<SpreadMethod> for cairo::Extend {
 pub struct Stop {
     /// <stop offset="..."/>
     offset: UnitInterval,
+
+    /// <stop path="..."/>, only used for stops in mesh gradients
+    path: Option<String>,
     /* stop-color and stop-opacity are not attributes; they are properties, so
      * they go into property_defs.rs */
 }
@@ -102,6 +114,7 @@ impl SetAttributes for Stop {
                         .parse_and_validate(value, validate_offset)
                         .map(|l| UnitInterval::clamp(l.length))?
                 }
+                expanded_name!("", "path") => self.path = Some(value.to_string()),
                 _ => (),
             }
         }
@@ -112,6 +125,13 @@ impl SetAttributes for Stop {
 
 impl Draw for Stop {}
 
+impl Stop {
+    /// Returns the `path` attribute, which describes an edge of a mesh patch.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_ref().map(String::as_str)
+    }
+}
+
 /// Parameters specific to each gradient type, before being resolved.
 /// These will be composed together with UnreseolvedVariant from fallback
 /// nodes (referenced with e.g. <linearGradient xlink:href="#fallback">) to form
@@ -297,6 +317,26 @@ impl UnresolvedVariant {
     }
 }
 
+/// Moves the focus point of a radial gradient onto its circle if it lies outside.
+///
+/// The SVG spec says that if the point defined by `fx` and `fy` lies outside the
+/// circle defined by `cx`, `cy` and `r`, the focal point must be set to the
+/// intersection of the line from (`cx`, `cy`) to (`fx`, `fy`) with that circle.
+/// Otherwise cairo would render an extended cone instead of a radial gradient.
+fn fix_focus_point(fx: f64, fy: f64, cx: f64, cy: f64, radius: f64) -> (f64, f64) {
+    let dx = fx - cx;
+    let dy = fy - cy;
+    let distance = dx.hypot(dy);
+
+    if distance <= radius {
+        return (fx, fy);
+    }
+
+    let scale = radius / distance;
+
+    (cx + dx * scale, cy + dy * scale)
+}
+
 impl Variant {
     /// Creates a cairo::Gradient corresponding to the gradient type of the
     /// &self Variant.  This does not have color stops set on it yet;
@@ -327,6 +367,8 @@ impl Variant {
                 let n_fy = fy.normalize(values, params);
                 let n_fr = fr.normalize(values, params);
 
+                let (n_fx, n_fy) = fix_focus_point(n_fx, n_fy, n_cx, n_cy, n_r);
+
                 cairo::Gradient::clone(&cairo::RadialGradient::new(
                     n_fx, n_fy, n_fr, n_cx, n_cy, n_r,
                 ))
@@ -382,6 +424,10 @@ struct UnresolvedGradient {
     stops: Option<Vec<ColorStop>>,
 
     variant: UnresolvedVariant,
+
+    /// The `color-interpolation` property of the gradient element itself; this
+    /// does not get resolved from fallbacks.
+    color_interpolation: ColorInterpolation,
 }
 
 /// Resolved gradient; this is memoizable after the initial resolution.
@@ -393,6 +439,8 @@ pub struct Gradient {
     stops: Vec<ColorStop>,
 
     variant: Variant,
+
+    color_interpolation: ColorInterpolation,
 }
 
 impl UnresolvedGradient {
@@ -405,6 +453,7 @@ impl UnresolvedGradient {
             spread,
             stops,
             variant,
+            color_interpolation,
         } = self;
 
         match variant {
@@ -415,6 +464,7 @@ impl UnresolvedGradient {
                 stops: stops.unwrap(),
 
                 variant: variant.to_resolved(),
+                color_interpolation,
             },
 
             UnresolvedVariant::Radial { .. } => Gradient {
@@ -424,6 +474,7 @@ impl UnresolvedGradient {
                 stops: stops.unwrap(),
 
                 variant: variant.to_resolved(),
+                color_interpolation,
             },
         }
     }
@@ -474,7 +525,11 @@ impl UnresolvedGradient {
 
             if let Element::Stop(ref stop) = *elt {
                 if elt.is_in_error() {
-                    rsvg_log!("(not using gradient stop {} because it is in error)", child);
+                    rsvg_log!(
+                        Debug,
+                        "(not using gradient stop {} because it is in error)",
+                        child
+                    );
                 } else {
                     let cascaded = CascadedValues::new_from_node(&child);
                     let values = cascaded.get();
@@ -510,6 +565,7 @@ impl UnresolvedGradient {
             spread,
             stops,
             variant,
+            color_interpolation: self.color_interpolation,
         }
     }
 
@@ -526,6 +582,7 @@ impl UnresolvedGradient {
             spread,
             stops,
             variant,
+            color_interpolation: self.color_interpolation,
         }
     }
 }
@@ -574,6 +631,10 @@ macro_rules! impl_get_unresolved {
                     spread: self.common.spread,
                     stops: None,
                     variant: self.get_unresolved_variant(),
+                    color_interpolation: node
+                        .borrow_element()
+                        .get_computed_values()
+                        .color_interpolation(),
                 };
 
                 gradient.add_color_stops_from_node(node);
@@ -694,6 +755,10 @@ macro_rules! impl_paint_source {
                             return Err(AcquireError::CircularReference(acquired_node.clone()));
                         }
 
+                        if stack.len() >= limits::MAX_REFERENCE_DEPTH {
+                            return Err(AcquireError::MaxDepthExceeded);
+                        }
+
                         let unresolved = match *acquired_node.borrow_element() {
                             Element::$gradient_type(ref g) => g.get_unresolved(&acquired_node),
                             Element::$other_type(ref g) => g.get_unresolved(&acquired_node),
@@ -733,7 +798,7 @@ impl AsPaintSource for Gradient {
         opacity: UnitInterval,
         bbox: &BoundingBox,
     ) -> Result<bool, RenderingError> {
-        if let Ok(transform) = bbox.rect_to_transform(self.units.0) {
+        if let Some(transform) = bbox.rect_to_transform(self.units.0) {
             let params = if self.units == GradientUnits(CoordUnits::ObjectBoundingBox) {
                 draw_ctx.push_view_box(1.0, 1.0)
             } else {
@@ -765,6 +830,25 @@ impl AsPaintSource for Gradient {
 }
 
 impl Gradient {
+    /// Sets the average color of the stops as the source for `draw_ctx`.
+    ///
+    /// This is for elements with zero width or height, where objectBoundingBox
+    /// units would give us a degenerate coordinate system.  Returns `false` if
+    /// the gradient has no stops.
+    pub fn set_average_stop_color_as_source(
+        &self,
+        draw_ctx: &mut DrawingCtx,
+        opacity: UnitInterval,
+    ) -> bool {
+        if let Some((r, g, b, a)) = self.average_stop_color() {
+            let cr = draw_ctx.get_cairo_context();
+            cr.set_source_rgba(r, g, b, a * opacity.0);
+            true
+        } else {
+            false
+        }
+    }
+
     fn set_on_cairo_pattern(
         &self,
         pattern: &cairo::Gradient,
@@ -778,10 +862,49 @@ impl Gradient {
         self.add_color_stops_to_pattern(pattern, opacity);
     }
 
+    /// Returns the mean of the colors of the stops as (r, g, b, a), or `None` if
+    /// the gradient has no stops.
+    ///
+    /// The colors are averaged with premultiplied alpha, so that transparent
+    /// stops do not contribute their color.
+    fn average_stop_color(&self) -> Option<(f64, f64, f64, f64)> {
+        if self.stops.is_empty() {
+            return None;
+        }
+
+        let (r, g, b, a) = self.stops.iter().fold((0.0, 0.0, 0.0, 0.0), |acc, stop| {
+            let alpha = f64::from(stop.rgba.alpha_f32()) * stop.opacity.0;
+
+            (
+                acc.0 + f64::from(stop.rgba.red_f32()) * alpha,
+                acc.1 + f64::from(stop.rgba.green_f32()) * alpha,
+                acc.2 + f64::from(stop.rgba.blue_f32()) * alpha,
+                acc.3 + alpha,
+            )
+        });
+
+        if a == 0.0 {
+            return Some((0.0, 0.0, 0.0, 0.0));
+        }
+
+        let n = self.stops.len() as f64;
+
+        Some((r / a, g / a, b / a, a / n))
+    }
+
     fn add_color_stops_to_pattern(&self, pattern: &cairo::Gradient, opacity: UnitInterval) {
+        let UnitInterval(o) = opacity;
+
+        let mut prev: Option<&ColorStop> = None;
+
         for stop in &self.stops {
+            if self.color_interpolation == ColorInterpolation::LinearRgb {
+                if let Some(prev) = prev {
+                    add_linear_rgb_stops_between(pattern, prev, stop, o);
+                }
+            }
+
             let UnitInterval(stop_offset) = stop.offset;
-            let UnitInterval(o) = opacity;
             let UnitInterval(stop_opacity) = stop.opacity;
 
             pattern.add_color_stop_rgba(
@@ -791,15 +914,61 @@ impl Gradient {
                 f64::from(stop.rgba.blue_f32()),
                 f64::from(stop.rgba.alpha_f32()) * stop_opacity * o,
             );
+
+            prev = Some(stop);
         }
     }
 }
 
+/// Adds intermediate color stops between `start` and `end`, whose colors are
+/// interpolated in linear light and then converted back to sRGB for Cairo.
+fn add_linear_rgb_stops_between(
+    pattern: &cairo::Gradient,
+    start: &ColorStop,
+    end: &ColorStop,
+    opacity: f64,
+) {
+    let UnitInterval(start_offset) = start.offset;
+    let UnitInterval(end_offset) = end.offset;
+
+    if end_offset <= start_offset {
+        return;
+    }
+
+    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+
+    let linear_components = |stop: &ColorStop| {
+        (
+            srgb::linearize_f64(f64::from(stop.rgba.red_f32())),
+            srgb::linearize_f64(f64::from(stop.rgba.green_f32())),
+            srgb::linearize_f64(f64::from(stop.rgba.blue_f32())),
+            f64::from(stop.rgba.alpha_f32()) * stop.opacity.0,
+        )
+    };
+
+    let (r0, g0, b0, a0) = linear_components(start);
+    let (r1, g1, b1, a1) = linear_components(end);
+
+    for i in 1..LINEAR_RGB_SUBDIVISIONS {
+        let t = i as f64 / LINEAR_RGB_SUBDIVISIONS as f64;
+
+        pattern.add_color_stop_rgba(
+            lerp(start_offset, end_offset, t),
+            srgb::unlinearize_f64(lerp(r0, r1, t)),
+            srgb::unlinearize_f64(lerp(g0, g1, t)),
+            srgb::unlinearize_f64(lerp(b0, b1, t)),
+            lerp(a0, a1, t) * opacity,
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diagnostics::Diagnostics;
     use crate::node::{Node, NodeData};
     use markup5ever::{namespace_url, ns, QualName};
+    use std::ffi::CString;
     use std::ptr;
 
     #[test]
@@ -813,6 +982,19 @@ mod tests {
         assert!(SpreadMethod::parse_str("foobar").is_err());
     }
 
+    #[test]
+    fn keeps_focus_point_inside_circle() {
+        assert_eq!(fix_focus_point(1.0, 2.0, 0.0, 0.0, 5.0), (1.0, 2.0));
+        assert_eq!(fix_focus_point(3.0, 4.0, 0.0, 0.0, 5.0), (3.0, 4.0));
+    }
+
+    #[test]
+    fn clamps_focus_point_to_circumference() {
+        assert_eq!(fix_focus_point(6.0, 8.0, 0.0, 0.0, 5.0), (3.0, 4.0));
+        assert_eq!(fix_focus_point(50.0, 10.0, 10.0, 10.0, 5.0), (15.0, 10.0));
+        assert_eq!(fix_focus_point(10.0, -30.0, 10.0, 10.0, 5.0), (10.0, 5.0));
+    }
+
     #[test]
     fn gradient_resolved_from_defaults_is_really_resolved() {
         let bag = unsafe { PropertyBag::new_from_xml2_attributes(0, ptr::null()) };
@@ -820,6 +1002,7 @@ mod tests {
         let node = Node::new(NodeData::new_element(
             &QualName::new(None, ns!(svg), local_name!("linearGradient")),
             &bag,
+            &mut Diagnostics::new(),
         ));
 
         let unresolved = borrow_element_as!(node, LinearGradient).get_unresolved(&node);
@@ -829,10 +1012,79 @@ mod tests {
         let node = Node::new(NodeData::new_element(
             &QualName::new(None, ns!(svg), local_name!("radialGradient")),
             &bag,
+            &mut Diagnostics::new(),
         ));
 
         let unresolved = borrow_element_as!(node, RadialGradient).get_unresolved(&node);
         let gradient = unresolved.gradient.resolve_from_defaults();
         assert!(gradient.is_resolved());
     }
+
+    fn stop_offset(offset: &str) -> Result<UnitInterval, ElementError> {
+        let name = CString::new("offset").unwrap();
+        let value = CString::new(offset).unwrap();
+
+        let value_start = value.as_ptr();
+        let value_end = unsafe { value_start.add(value.as_bytes().len()) };
+
+        let v = vec![
+            name.as_ptr(),
+            ptr::null(), // prefix
+            ptr::null(), // uri
+            value_start,
+            value_end,
+        ];
+
+        let pbag = unsafe { PropertyBag::new_from_xml2_attributes(1, v.as_ptr()) };
+
+        let mut stop = Stop::default();
+        stop.set_attributes(&pbag).map(|_| stop.offset)
+    }
+
+    #[test]
+    fn parses_stop_offset_numbers_and_percentages() {
+        assert_eq!(stop_offset("0.25"), Ok(UnitInterval(0.25)));
+        assert_eq!(stop_offset("50%"), Ok(UnitInterval(0.5)));
+        assert!(stop_offset("1em").is_err());
+    }
+
+    #[test]
+    fn clamps_stop_offset_to_unit_interval() {
+        assert_eq!(stop_offset("1.5"), Ok(UnitInterval(1.0)));
+        assert_eq!(stop_offset("150%"), Ok(UnitInterval(1.0)));
+        assert_eq!(stop_offset("-0.5"), Ok(UnitInterval(0.0)));
+        assert_eq!(stop_offset("-20%"), Ok(UnitInterval(0.0)));
+    }
+
+    #[test]
+    fn color_stop_offsets_never_decrease() {
+        let bag = unsafe { PropertyBag::new_from_xml2_attributes(0, ptr::null()) };
+
+        let node = Node::new(NodeData::new_element(
+            &QualName::new(None, ns!(svg), local_name!("linearGradient")),
+            &bag,
+            &mut Diagnostics::new(),
+        ));
+
+        let mut unresolved = borrow_element_as!(node, LinearGradient).get_unresolved(&node);
+
+        let black = cssparser::RGBA::new(0, 0, 0, 255);
+        let opacity = UnitInterval(1.0);
+
+        for &offset in &[0.5, 0.25, 0.75, 0.0] {
+            unresolved
+                .gradient
+                .add_color_stop(UnitInterval(offset), black, opacity);
+        }
+
+        let offsets: Vec<f64> = unresolved
+            .gradient
+            .stops
+            .unwrap()
+            .iter()
+            .map(|stop| stop.offset.0)
+            .collect();
+
+        assert_eq!(offsets, vec![0.5, 0.5, 0.75, 0.75]);
+    }
 }
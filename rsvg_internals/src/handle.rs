@@ -2,16 +2,22 @@
 //!
 //! This module provides the primitives on which the public APIs are implemented.
 
+use markup5ever::{namespace_url, ns};
+use std::cell::RefCell;
+
 use crate::allowed_url::{AllowedUrl, Href};
+use crate::aria::AriaAttributes;
 use crate::bbox::BoundingBox;
-use crate::css::{Origin, Stylesheet};
+use crate::css::{self, KeyframesStore, Origin, StyleOverrides, Stylesheet};
+use crate::diagnostics::{Diagnostic, Diagnostics};
 use crate::document::{AcquiredNodes, Document};
 use crate::dpi::Dpi;
-use crate::drawing_ctx::DrawingCtx;
-use crate::element::Element;
-use crate::error::{DefsLookupErrorKind, LoadingError, RenderingError};
+use crate::drawing_ctx::{ClipMode, DrawingCtx, RenderMode};
+use crate::element::{Element, SvgElementType};
+use crate::error::{DefsLookupErrorKind, LoadingError, RenderingError, SerializeError};
 use crate::node::{CascadedValues, Node, NodeBorrow};
 use crate::rect::Rect;
+use crate::serialize;
 use crate::structure::IntrinsicDimensions;
 use url::Url;
 
@@ -26,6 +32,12 @@ pub struct LoadOptions {
 
     /// Whether to keep original (undecoded) image data to embed in Cairo PDF surfaces.
     pub keep_image_data: bool,
+
+    /// Whether to normalize text content to Unicode NFC.
+    pub normalize_unicode: bool,
+
+    /// Whether to keep all the attributes of elements, so that the document can be serialized.
+    pub keep_attributes: bool,
 }
 
 impl LoadOptions {
@@ -35,6 +47,8 @@ impl LoadOptions {
             base_url,
             unlimited_size: false,
             keep_image_data: false,
+            normalize_unicode: true,
+            keep_attributes: false,
         }
     }
 
@@ -56,6 +70,26 @@ impl LoadOptions {
         self
     }
 
+    /// Sets whether to normalize the character data in text elements to Unicode NFC.
+    ///
+    /// This is on by default, so that Pango picks the same glyphs for text
+    /// that may arrive in different normalization forms.
+    pub fn normalize_unicode(mut self, normalize: bool) -> Self {
+        self.normalize_unicode = normalize;
+        self
+    }
+
+    /// Sets whether elements keep a copy of all their attributes as they were in the XML.
+    ///
+    /// This is needed for [`Handle::to_svg_string`]; otherwise elements only keep
+    /// the parsed values they use for rendering.
+    ///
+    /// [`Handle::to_svg_string`]: struct.Handle.html#method.to_svg_string
+    pub fn keep_attributes(mut self, keep: bool) -> Self {
+        self.keep_attributes = keep;
+        self
+    }
+
     /// Creates a new `LoadOptions` with a different `base_url`.
     ///
     /// This is used when loading a referenced file that may in turn cause other files
@@ -65,10 +99,32 @@ impl LoadOptions {
             base_url: Some((**base_url).clone()),
             unlimited_size: self.unlimited_size,
             keep_image_data: self.keep_image_data,
+            normalize_unicode: self.normalize_unicode,
+            keep_attributes: self.keep_attributes,
         }
     }
 }
 
+/// A hyperlink from an `<a>` element, for callers that want to handle clicks on links.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkTarget {
+    /// For links to a fragment like `#foo` in the same document, the id of the
+    /// element being linked to; `None` for links to other resources.
+    pub element_id: Option<String>,
+
+    /// The link's URI, as it appears in the `href` or `xlink:href` attribute.
+    pub href: String,
+
+    /// The `target` attribute of the link, if any.
+    pub target: Option<String>,
+
+    /// The `xlink:title` attribute of the link, if any.
+    pub title: Option<String>,
+
+    /// The ink rectangle of the link's contents, in viewport coordinates.
+    pub bounding_box: cairo::Rectangle,
+}
+
 /// Main handle to an SVG document.
 ///
 /// This is the main object in librsvg.  It gets created with the [`from_stream`] method
@@ -77,6 +133,15 @@ impl LoadOptions {
 /// [`from_stream`]: #method.from_stream
 pub struct Handle {
     document: Document,
+
+    /// Stylesheets from `set_stylesheet()`, with a "User" origin.
+    user_stylesheets: Vec<Stylesheet>,
+
+    /// Declarations from `set_element_style_override()`.
+    style_overrides: StyleOverrides,
+
+    /// Non-fatal problems found while rendering; the rendering methods take `&self`.
+    render_diagnostics: RefCell<Diagnostics>,
 }
 
 impl Handle {
@@ -88,6 +153,9 @@ impl Handle {
     ) -> Result<Handle, LoadingError> {
         Ok(Handle {
             document: Document::load_from_stream(load_options, stream, cancellable)?,
+            user_stylesheets: Vec::new(),
+            style_overrides: StyleOverrides::new(),
+            render_diagnostics: RefCell::new(Diagnostics::new()),
         })
     }
 
@@ -105,19 +173,105 @@ impl Handle {
         }
     }
 
-    /// Returns (ink_rect, logical_rect)
-    fn get_node_geometry_with_viewport(
+    /// Returns the type of the element `#foo`, or `None` if there is no such element.
+    ///
+    /// The `id` must be an URL fragment identifier, as in `has_sub()`.
+    pub fn get_element_type(&self, id: &str) -> Result<Option<SvgElementType>, RenderingError> {
+        match self.lookup_node(id) {
+            Ok(node) => Ok(Some(node.borrow_element().element_type())),
+
+            Err(DefsLookupErrorKind::NotFound) => Ok(None),
+
+            Err(e) => Err(RenderingError::InvalidId(e)),
+        }
+    }
+
+    /// Returns the ARIA attributes of the element `#foo`, or `None` if there is no
+    /// such element.
+    ///
+    /// The `id` must be an URL fragment identifier, as in `has_sub()`.
+    pub fn get_aria_attributes(&self, id: &str) -> Result<Option<AriaAttributes>, RenderingError> {
+        match self.lookup_node(id) {
+            Ok(node) => Ok(Some(node.borrow_element().get_aria_attributes())),
+
+            Err(DefsLookupErrorKind::NotFound) => Ok(None),
+
+            Err(e) => Err(RenderingError::InvalidId(e)),
+        }
+    }
+
+    /// Returns the ids of the elements in keyboard navigation order.
+    ///
+    /// Elements with a `tabindex` come first, sorted by its value, followed by the
+    /// links without a `tabindex`; ties keep document order.  Elements without an
+    /// `id` are skipped, since they could not be referred to.
+    pub fn get_tab_order(&self) -> Vec<String> {
+        let mut elements: Vec<(Option<i32>, String)> = self
+            .document
+            .root()
+            .descendants()
+            .filter(|n| n.is_element())
+            .filter_map(|n| {
+                let elt = n.borrow_element();
+                let tab_index = elt.get_tab_index();
+
+                // Without a tabindex, only links can get the keyboard focus.
+                if tab_index.is_none() && elt.element_type() != SvgElementType::A {
+                    None
+                } else {
+                    elt.get_id().map(|id| (tab_index, id.to_string()))
+                }
+            })
+            .collect();
+
+        // The sort is stable, so ties keep document order.
+        elements.sort_by_key(|&(tab_index, _)| (tab_index.is_none(), tab_index));
+
+        elements.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Returns the keyframes from the `@keyframes` rules in the document's stylesheets.
+    pub fn get_keyframes(&self) -> &KeyframesStore {
+        self.document.keyframes()
+    }
+
+    /// Serializes the document back to SVG XML.
+    ///
+    /// The document must have been loaded with [`LoadOptions::keep_attributes`].
+    ///
+    /// [`LoadOptions::keep_attributes`]: struct.LoadOptions.html#method.keep_attributes
+    pub fn to_svg_string(&self) -> Result<String, SerializeError> {
+        if !self.document.keeps_attributes() {
+            return Err(SerializeError::AttributesNotKept);
+        }
+
+        Ok(serialize::to_svg_string(&self.document.root()))
+    }
+
+    /// Draws the document for measuring, as if it were rendered to `viewport`.
+    ///
+    /// If `node` is given, only that element is drawn.  Returns the bounding box of
+    /// what was drawn, and the drawing context so that callers can query it.
+    fn measure_with_viewport(
         &self,
-        node: &Node,
+        node: Option<&Node>,
         viewport: Rect,
         dpi: Dpi,
         is_testing: bool,
-    ) -> Result<(Rect, Rect), RenderingError> {
+    ) -> Result<(BoundingBox, DrawingCtx), RenderingError> {
         let root = self.document.root();
 
         let target = cairo::ImageSurface::create(cairo::Format::Rgb24, 1, 1)?;
         let cr = cairo::Context::new(&target);
-        let mut draw_ctx = DrawingCtx::new(Some(node), &cr, viewport, dpi, true, is_testing);
+        let mut draw_ctx = DrawingCtx::new(
+            node,
+            &cr,
+            viewport,
+            dpi,
+            true,
+            is_testing,
+            RenderMode::BoundingBoxOnly,
+        );
 
         let bbox = draw_ctx.draw_node_from_stack(
             &root,
@@ -126,6 +280,19 @@ impl Handle {
             false,
         )?;
 
+        Ok((bbox, draw_ctx))
+    }
+
+    /// Returns (ink_rect, logical_rect)
+    fn get_node_geometry_with_viewport(
+        &self,
+        node: &Node,
+        viewport: Rect,
+        dpi: Dpi,
+        is_testing: bool,
+    ) -> Result<(Rect, Rect), RenderingError> {
+        let (bbox, _) = self.measure_with_viewport(Some(node), viewport, dpi, is_testing)?;
+
         let ink_rect = bbox.ink_rect.unwrap_or_default();
         let logical_rect = bbox.rect.unwrap_or_default();
 
@@ -170,6 +337,47 @@ impl Handle {
         }
     }
 
+    /// Returns the text of the `<title>` child of the element `id`, or of the
+    /// toplevel element if `id` is `None`.
+    ///
+    /// Whitespace in the text is collapsed, since it is meant to be presented to the user.
+    pub fn get_title(&self, id: Option<&str>) -> Result<Option<String>, RenderingError> {
+        self.get_child_element_text(id, "title")
+    }
+
+    /// Returns the text of the `<desc>` child of the element `id`, or of the
+    /// toplevel element if `id` is `None`.
+    ///
+    /// Whitespace in the text is collapsed, as in `get_title()`.
+    pub fn get_desc(&self, id: Option<&str>) -> Result<Option<String>, RenderingError> {
+        self.get_child_element_text(id, "desc")
+    }
+
+    fn get_child_element_text(
+        &self,
+        id: Option<&str>,
+        local_name: &str,
+    ) -> Result<Option<String>, RenderingError> {
+        let node = self.get_node_or_root(id)?;
+
+        let child = node.children().filter(|c| c.is_element()).find(|c| {
+            let elt = c.borrow_element();
+            let name = elt.element_name();
+
+            name.ns == ns!(svg) && &*name.local == local_name
+        });
+
+        Ok(child.map(|c| {
+            let text: String = c
+                .children()
+                .filter(|c| c.is_chars())
+                .map(|c| c.borrow_chars().get_string())
+                .collect();
+
+            text.split_whitespace().collect::<Vec<_>>().join(" ")
+        }))
+    }
+
     pub fn get_geometry_for_layer(
         &self,
         id: Option<&str>,
@@ -189,6 +397,62 @@ impl Handle {
         ))
     }
 
+    /// Returns the hyperlinks in the document, in document order.
+    ///
+    /// Bounding boxes are computed as if the SVG were rendered to `viewport`; the
+    /// document is measured once for all the links.  `<a>` elements without a link
+    /// are skipped, and links that are not drawn get an empty bounding box.
+    pub fn get_links(
+        &self,
+        viewport: &cairo::Rectangle,
+        dpi: Dpi,
+        is_testing: bool,
+    ) -> Result<Vec<LinkTarget>, RenderingError> {
+        let viewport = Rect::from(*viewport);
+
+        let (_, draw_ctx) = self.measure_with_viewport(None, viewport, dpi, is_testing)?;
+
+        let root = self.document.root();
+
+        let mut links = Vec::new();
+
+        for node in root.descendants().filter(|n| n.is_element()) {
+            let (href, target, title) = match *node.borrow_element() {
+                Element::Link(ref link) => match link.link() {
+                    Some(href) => (
+                        href.to_string(),
+                        link.target().map(str::to_string),
+                        link.title().map(str::to_string),
+                    ),
+                    None => continue,
+                },
+                _ => continue,
+            };
+
+            let element_id = match Href::parse(&href) {
+                Ok(Href::WithFragment(ref fragment)) if fragment.uri().is_none() => {
+                    Some(fragment.fragment().to_string())
+                }
+                _ => None,
+            };
+
+            let ink_rect = draw_ctx
+                .link_bbox(&node)
+                .and_then(|bbox| bbox.ink_rect)
+                .unwrap_or_default();
+
+            links.push(LinkTarget {
+                element_id,
+                href,
+                target,
+                title,
+                bounding_box: cairo::Rectangle::from(ink_rect),
+            });
+        }
+
+        Ok(links)
+    }
+
     fn lookup_node(&self, id: &str) -> Result<Node, DefsLookupErrorKind> {
         match Href::parse(&id).map_err(DefsLookupErrorKind::HrefError)? {
             Href::PlainUrl(_) => Err(DefsLookupErrorKind::CannotLookupExternalReferences),
@@ -209,7 +473,7 @@ impl Handle {
                         fragment.fragment()
                     );
 
-                    rsvg_log!("{}", msg);
+                    rsvg_log!(Warn, "{}", msg);
 
                     return Err(DefsLookupErrorKind::CannotLookupExternalReferences);
                 }
@@ -258,6 +522,7 @@ impl Handle {
             dpi,
             false,
             is_testing,
+            RenderMode::Rasterize,
         );
 
         let res = draw_ctx
@@ -271,6 +536,10 @@ impl Handle {
 
         cr.restore();
 
+        self.render_diagnostics
+            .borrow_mut()
+            .append(draw_ctx.diagnostics());
+
         res
     }
 
@@ -282,7 +551,15 @@ impl Handle {
     ) -> Result<BoundingBox, RenderingError> {
         let target = cairo::ImageSurface::create(cairo::Format::Rgb24, 1, 1)?;
         let cr = cairo::Context::new(&target);
-        let mut draw_ctx = DrawingCtx::new(None, &cr, unit_rectangle(), dpi, true, is_testing);
+        let mut draw_ctx = DrawingCtx::new(
+            None,
+            &cr,
+            unit_rectangle(),
+            dpi,
+            true,
+            is_testing,
+            RenderMode::BoundingBoxOnly,
+        );
 
         draw_ctx.draw_node_from_stack(
             node,
@@ -327,6 +604,10 @@ impl Handle {
 
         let node = self.get_node_or_root(id)?;
 
+        if let Element::Symbol(_) = *node.borrow_element() {
+            return self.render_symbol(cr, &node, element_viewport, dpi, is_testing);
+        }
+
         let bbox = self.get_bbox_for_element(&node, dpi, is_testing)?;
 
         if bbox.ink_rect.is_none() || bbox.rect.is_none() {
@@ -351,7 +632,15 @@ impl Handle {
         cr.scale(factor, factor);
         cr.translate(-ink_r.x0, -ink_r.y0);
 
-        let mut draw_ctx = DrawingCtx::new(None, &cr, unit_rectangle(), dpi, false, is_testing);
+        let mut draw_ctx = DrawingCtx::new(
+            None,
+            &cr,
+            unit_rectangle(),
+            dpi,
+            false,
+            is_testing,
+            RenderMode::Rasterize,
+        );
 
         let res = draw_ctx
             .draw_node_from_stack(
@@ -364,6 +653,73 @@ impl Handle {
 
         cr.restore();
 
+        self.render_diagnostics
+            .borrow_mut()
+            .append(draw_ctx.diagnostics());
+
+        res
+    }
+
+    /// Renders a `<symbol>` as if it were referenced by a `<use>` whose
+    /// viewport is `element_viewport`.
+    ///
+    /// A symbol is never rendered by itself, so it has no geometry of its own
+    /// that `render_element` could use; instead, the symbol's viewBox gets
+    /// fitted into the viewport.
+    fn render_symbol(
+        &self,
+        cr: &cairo::Context,
+        node: &Node,
+        element_viewport: &cairo::Rectangle,
+        dpi: Dpi,
+        is_testing: bool,
+    ) -> Result<(), RenderingError> {
+        let viewport = Rect::from(*element_viewport);
+
+        cr.save();
+
+        let mut draw_ctx = DrawingCtx::new(
+            None,
+            &cr,
+            viewport,
+            dpi,
+            false,
+            is_testing,
+            RenderMode::Rasterize,
+        );
+
+        let cascaded = CascadedValues::new_from_node(node);
+        let values = cascaded.get();
+
+        let clip_mode = if values.is_overflow() {
+            None
+        } else {
+            Some(ClipMode::ClipToVbox)
+        };
+
+        let res = match *node.borrow_element() {
+            Element::Symbol(ref symbol) => draw_ctx
+                .draw_symbol(
+                    node,
+                    node,
+                    symbol,
+                    &mut AcquiredNodes::new(&self.document),
+                    values,
+                    viewport,
+                    clip_mode,
+                    false,
+                )
+                .map(|_bbox| ()),
+
+            _ => unreachable!(),
+        };
+
+        cr.restore();
+
+        self.render_diagnostics
+            .borrow_mut()
+            .append(draw_ctx.diagnostics());
+
         res
     }
 
@@ -374,9 +730,49 @@ impl Handle {
     pub fn set_stylesheet(&mut self, css: &str) -> Result<(), LoadingError> {
         let mut stylesheet = Stylesheet::new(Origin::User);
         stylesheet.parse(css, None)?;
-        self.document.cascade(&[stylesheet]);
+        self.user_stylesheets.push(stylesheet);
+        self.cascade();
         Ok(())
     }
+
+    /// Overrides the style of the element with the specified `id` with a list of CSS
+    /// declarations, like `fill: red; stroke: blue`.
+    ///
+    /// The declarations replace any previous override for the same element.  They are
+    /// applied with a "User" origin after the element's `style` attribute, so they win
+    /// over everything in the document except for `!important` declarations.
+    pub fn set_element_style_override(
+        &mut self,
+        id: &str,
+        declarations: &str,
+    ) -> Result<(), LoadingError> {
+        let declarations = css::parse_declarations(declarations)?;
+        self.style_overrides.insert(id.to_string(), declarations);
+        self.cascade();
+        Ok(())
+    }
+
+    /// Removes all the overrides from `set_element_style_override()`.
+    pub fn clear_style_overrides(&mut self) {
+        self.style_overrides.clear();
+        self.cascade();
+    }
+
+    fn cascade(&mut self) {
+        self.document
+            .cascade(&self.user_stylesheets, &self.style_overrides);
+    }
+
+    /// Returns the non-fatal problems found so far while loading and rendering
+    /// the document, and clears them.
+    ///
+    /// Problems found while loading come first, followed by those from rendering
+    /// in the order in which the rendering calls were made.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.document.take_diagnostics();
+        diagnostics.extend(self.render_diagnostics.get_mut().take());
+        diagnostics
+    }
 }
 
 fn check_cairo_context(cr: &cairo::Context) -> Result<(), RenderingError> {
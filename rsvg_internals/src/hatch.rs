@@ -0,0 +1,365 @@
+//! The `hatch` and `hatchpath` elements from SVG 2.
+//!
+//! A hatch is a paint server made of parallel lines.  The lines are the
+//! `hatchpath` children of the hatch, which get repeated every `pitch` units
+//! along the x axis of the hatch's coordinate system; the `rotate` attribute
+//! rotates that coordinate system.
+
+use markup5ever::{namespace_url, ns};
+use std::f64::consts::SQRT_2;
+use std::rc::Rc;
+
+use crate::angle::Angle;
+use crate::bbox::BoundingBox;
+use crate::coord_units::CoordUnits;
+use crate::document::AcquiredNodes;
+use crate::drawing_ctx::DrawingCtx;
+use crate::element::{Draw, Element, ElementResult, SetAttributes};
+use crate::error::*;
+use crate::length::*;
+use crate::node::{CascadedValues, Node, NodeBorrow};
+use crate::paint_server::{AsPaintSource, PaintSource};
+use crate::parsers::ParseValue;
+use crate::path_builder::{Path, PathBuilder};
+use crate::path_parser;
+use crate::properties::ComputedValues;
+use crate::property_bag::PropertyBag;
+use crate::rect::Rect;
+use crate::transform::Transform;
+use crate::unit_interval::UnitInterval;
+
+coord_units!(HatchUnits, CoordUnits::ObjectBoundingBox);
+coord_units!(HatchContentUnits, CoordUnits::UserSpaceOnUse);
+
+#[derive(Default)]
+pub struct Hatch {
+    units: HatchUnits,
+    content_units: HatchContentUnits,
+    transform: Transform,
+    x: Length<Horizontal>,
+    y: Length<Vertical>,
+    pitch: Length<Both>,
+    rotate: f64,
+}
+
+impl SetAttributes for Hatch {
+    fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
+        // markup5ever doesn't have built-in local names for most of these
+        // attributes, so match them by string.
+        for (attr, value) in pbag.iter().filter(|(attr, _)| attr.ns == ns!()) {
+            match &*attr.local {
+                "hatchUnits" => self.units = attr.parse(value)?,
+                "hatchContentUnits" => self.content_units = attr.parse(value)?,
+                "hatchTransform" => self.transform = attr.parse(value)?,
+                "x" => self.x = attr.parse(value)?,
+                "y" => self.y = attr.parse(value)?,
+                "pitch" => {
+                    self.pitch =
+                        attr.parse_and_validate(value, Length::<Both>::check_nonnegative)?
+                }
+                "rotate" => self.rotate = attr.parse(value)?,
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Draw for Hatch {}
+
+pub struct ResolvedHatch {
+    units: HatchUnits,
+    content_units: HatchContentUnits,
+    transform: Transform,
+    x: Length<Horizontal>,
+    y: Length<Vertical>,
+    pitch: Length<Both>,
+    rotate: f64,
+    node: Node,
+}
+
+impl PaintSource for Hatch {
+    type Resolved = ResolvedHatch;
+
+    fn resolve(
+        &self,
+        node: &Node,
+        _acquired_nodes: &mut AcquiredNodes,
+    ) -> Result<Self::Resolved, AcquireError> {
+        Ok(ResolvedHatch {
+            units: self.units,
+            content_units: self.content_units,
+            transform: self.transform,
+            x: self.x,
+            y: self.y,
+            pitch: self.pitch,
+            rotate: self.rotate,
+            node: node.clone(),
+        })
+    }
+}
+
+impl AsPaintSource for ResolvedHatch {
+    fn set_as_paint_source(
+        self,
+        acquired_nodes: &mut AcquiredNodes,
+        values: &ComputedValues,
+        draw_ctx: &mut DrawingCtx,
+        opacity: UnitInterval,
+        bbox: &BoundingBox,
+    ) -> Result<bool, RenderingError> {
+        let bbox_rect = bbox.rect.filter(|r| !r.is_empty());
+
+        let needs_bbox = self.units == HatchUnits(CoordUnits::ObjectBoundingBox)
+            || self.content_units == HatchContentUnits(CoordUnits::ObjectBoundingBox);
+
+        if needs_bbox && bbox_rect.is_none() {
+            return Ok(false);
+        }
+
+        let (x, y, pitch) = {
+            let params = if self.units == HatchUnits(CoordUnits::ObjectBoundingBox) {
+                draw_ctx.push_view_box(1.0, 1.0)
+            } else {
+                draw_ctx.get_view_params()
+            };
+
+            let x = self.x.normalize(values, &params);
+            let y = self.y.normalize(values, &params);
+            let pitch = self.pitch.normalize(values, &params);
+
+            if self.units == HatchUnits(CoordUnits::ObjectBoundingBox) {
+                // Like percentages for lengths in both directions, a pitch in
+                // objectBoundingBox units is relative to the normalized diagonal.
+                let r = bbox_rect.unwrap();
+                let (w, h) = r.size();
+
+                (
+                    r.x0 + x * w,
+                    r.y0 + y * h,
+                    pitch * (w * w + h * h).sqrt() / SQRT_2,
+                )
+            } else {
+                (x, y, pitch)
+            }
+        };
+
+        // A zero pitch disables rendering of the hatch.
+        if pitch <= 0.0 {
+            return Ok(false);
+        }
+
+        // From the hatch's rotated coordinate system to user space
+        let affine = Transform::new_rotate(Angle::from_degrees(self.rotate))
+            .post_translate(x, y)
+            .post_transform(&self.transform);
+
+        // From the coordinate system of the hatchpaths to the hatch's
+        let content_affine = match self.content_units {
+            HatchContentUnits(CoordUnits::ObjectBoundingBox) => {
+                let (w, h) = bbox_rect.unwrap().size();
+                Transform::new_scale(w, h)
+            }
+
+            HatchContentUnits(CoordUnits::UserSpaceOnUse) => Transform::identity(),
+        };
+
+        // Render one pitch x pitch tile at the device resolution
+
+        let taffine = draw_ctx.get_transform().pre_transform(&affine);
+
+        let scale_x = (taffine.xx.powi(2) + taffine.xy.powi(2)).sqrt();
+        let scale_y = (taffine.yx.powi(2) + taffine.yy.powi(2)).sqrt();
+
+        let pw = (pitch * scale_x).ceil() as i32;
+        let ph = (pitch * scale_y).ceil() as i32;
+
+        if pw < 1 || ph < 1 {
+            return Ok(false);
+        }
+
+        let to_tile_pixels = Transform::new_scale(f64::from(pw) / pitch, f64::from(ph) / pitch);
+
+        let cr_save = draw_ctx.get_cairo_context();
+
+        let surface = cr_save
+            .get_target()
+            .create_similar(cairo::Content::ColorAlpha, pw, ph)?;
+
+        let cr_hatch = cairo::Context::new(&surface);
+
+        draw_ctx.set_cairo_context(&cr_hatch);
+
+        let node = &self.node;
+
+        let res = draw_ctx.with_alpha(opacity, &mut |dc| {
+            for child in node.children().filter(|c| c.is_element()) {
+                if let Element::HatchPath(ref hatch_path) = *child.borrow_element() {
+                    hatch_path.draw_in_tile(
+                        &child,
+                        acquired_nodes,
+                        dc,
+                        pitch,
+                        &content_affine,
+                        &to_tile_pixels,
+                    )?;
+                }
+            }
+
+            Ok(dc.empty_bbox())
+        });
+
+        draw_ctx.set_cairo_context(&cr_save);
+
+        let pattern = cairo::SurfacePattern::create(&surface);
+
+        let tile_to_user = to_tile_pixels.invert().unwrap().post_transform(&affine);
+
+        tile_to_user.invert().map(|m| pattern.set_matrix(m.into()));
+        pattern.set_extend(cairo::Extend::Repeat);
+        pattern.set_filter(cairo::Filter::Best);
+        cr_save.set_source(&pattern);
+
+        res.and_then(|_| Ok(true))
+    }
+}
+
+#[derive(Default)]
+pub struct HatchPath {
+    offset: Length<Horizontal>,
+    path: Option<Rc<Path>>,
+}
+
+impl SetAttributes for HatchPath {
+    fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
+        for (attr, value) in pbag.iter().filter(|(attr, _)| attr.ns == ns!()) {
+            match &*attr.local {
+                "offset" => self.offset = attr.parse(value)?,
+                "d" => {
+                    let mut builder = PathBuilder::new();
+                    if let Err(e) = path_parser::parse_path_into_builder(value, &mut builder) {
+                        // Creating a partial path is OK per the spec, as for <path>
+                        rsvg_log!(Warn, "could not parse path: {}", e);
+                    }
+                    self.path = Some(Rc::new(builder.into_path()));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Draw for HatchPath {}
+
+impl HatchPath {
+    /// Strokes the hatch path into a hatch tile of size `pitch`.
+    ///
+    /// The path is drawn once for each of its repetitions that overlap the
+    /// tile, so that strokes which cross the edges of the tile wrap around
+    /// when the tile gets repeated.
+    fn draw_in_tile(
+        &self,
+        node: &Node,
+        acquired_nodes: &mut AcquiredNodes,
+        draw_ctx: &mut DrawingCtx,
+        pitch: f64,
+        content_affine: &Transform,
+        to_tile_pixels: &Transform,
+    ) -> Result<(), RenderingError> {
+        let cascaded = CascadedValues::new_from_node(node);
+        let values = cascaded.get();
+
+        let params = draw_ctx.get_view_params();
+        let offset = self.offset.normalize(values, &params);
+
+        let path_to_tile = Transform::new_translate(offset, 0.0).post_transform(content_affine);
+
+        let (path, is_default) = if let Some(ref path) = self.path {
+            (path.clone(), false)
+        } else {
+            // Without a "d" attribute, the hatch path is a line along the y
+            // axis; make it longer than the tile so that it has no gaps.
+            let inverse = content_affine.invert().unwrap();
+            let (x0, y0) = inverse.transform_point(0.0, -pitch);
+            let (x1, y1) = inverse.transform_point(0.0, 2.0 * pitch);
+
+            let mut builder = PathBuilder::new();
+            builder.move_to(x0, y0);
+            builder.line_to(x1, y1);
+            (Rc::new(builder.into_path()), true)
+        };
+
+        let path_bbox = match path.bounding_box() {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        // Hatch lines are repeated every `pitch` horizontally, and a hatch path
+        // is repeated vertically by its own extent in y, so that it makes up an
+        // infinite line.  Draw as many copies as overlap this tile, counting the
+        // width of the stroke, so that they wrap around its edges when the tile
+        // gets repeated.
+        let half_width = values.stroke_width().0.normalize(values, &params) / 2.0;
+        let extents = path_to_tile.transform_rect(&Rect::new(
+            path_bbox.x0 - half_width,
+            path_bbox.y0 - half_width,
+            path_bbox.x1 + half_width,
+            path_bbox.y1 + half_width,
+        ));
+
+        let shifts_x = tile_shifts(extents.x0, extents.x1, pitch, pitch);
+        let shifts_y = if is_default {
+            vec![0.0]
+        } else {
+            let period = path_to_tile.transform_rect(&path_bbox).height();
+            let period = if period > 0.0 { period } else { pitch };
+
+            tile_shifts(extents.y0, extents.y1, period, pitch)
+        };
+
+        let cr = draw_ctx.get_cairo_context();
+
+        for &dx in &shifts_x {
+            for &dy in &shifts_y {
+                let affine = path_to_tile
+                    .post_translate(dx, dy)
+                    .post_transform(to_tile_pixels);
+
+                cr.set_matrix(affine.into());
+                path.to_cairo(&cr)?;
+
+                draw_ctx.setup_cr_for_stroke(&cr, values, 1.0);
+
+                let bbox = BoundingBox::new()
+                    .with_transform(affine)
+                    .with_rect(path_bbox);
+
+                if draw_ctx.set_source_paint_server(
+                    acquired_nodes,
+                    &values.stroke().0,
+                    values.stroke_opacity().0,
+                    &bbox,
+                    values.color().0,
+                )? {
+                    cr.stroke();
+                } else {
+                    cr.new_path();
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the multiples of `period` by which something that goes from `min` to
+/// `max` needs to be shifted to cover all of a tile that goes from 0 to `size`.
+fn tile_shifts(min: f64, max: f64, period: f64, size: f64) -> Vec<f64> {
+    let first = (-max / period).floor() as i32;
+    let last = ((size - min) / period).ceil() as i32;
+
+    (first..=last).map(|n| f64::from(n) * period).collect()
+}
@@ -1,22 +1,44 @@
 //! The `image` element.
 
+use cssparser::Parser;
 use markup5ever::{expanded_name, local_name, namespace_url, ns};
+use url::Url;
 
 use crate::allowed_url::Href;
 use crate::aspect_ratio::AspectRatio;
 use crate::bbox::BoundingBox;
 use crate::document::AcquiredNodes;
-use crate::drawing_ctx::{ClipMode, DrawingCtx, ViewParams};
+use crate::drawing_ctx::{ClipMode, DrawingCtx, RenderMode, ViewParams};
 use crate::element::{Draw, ElementResult, SetAttributes};
 use crate::error::*;
 use crate::length::*;
 use crate::node::{CascadedValues, Node};
-use crate::parsers::ParseValue;
+use crate::parsers::{Parse, ParseValue};
 use crate::properties::ComputedValues;
 use crate::property_bag::PropertyBag;
 use crate::rect::Rect;
 use crate::viewbox::ViewBox;
 
+/// The value of the `crossorigin` attribute of images.
+///
+/// This is the image's CORS setting, as in HTML; see
+/// https://html.spec.whatwg.org/multipage/urls-and-fetching.html#cors-settings-attributes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CrossOrigin {
+    Anonymous,
+    UseCredentials,
+}
+
+impl Parse for CrossOrigin {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<CrossOrigin, ParseError<'i>> {
+        Ok(parse_identifiers!(
+            parser,
+            "anonymous" => CrossOrigin::Anonymous,
+            "use-credentials" => CrossOrigin::UseCredentials,
+        )?)
+    }
+}
+
 #[derive(Default)]
 pub struct Image {
     x: Length<Horizontal>,
@@ -25,6 +47,15 @@ pub struct Image {
     height: Length<Vertical>,
     aspect: AspectRatio,
     href: Option<Href>,
+    cross_origin: Option<CrossOrigin>,
+    xml_base: Option<Url>,
+}
+
+impl Image {
+    /// Sets the base URI from the `xml:base` attributes in effect for this element.
+    pub fn set_xml_base(&mut self, xml_base: Url) {
+        self.xml_base = Some(xml_base);
+    }
 }
 
 impl SetAttributes for Image {
@@ -50,7 +81,15 @@ impl SetAttributes for Image {
                     self.href = Some(href);
                 }
 
-                _ => (),
+                _ => match &*attr.local {
+                    // As in HTML, invalid values, including the empty string, mean
+                    // "anonymous".
+                    "crossorigin" => {
+                        self.cross_origin =
+                            Some(CrossOrigin::parse_str(value).unwrap_or(CrossOrigin::Anonymous))
+                    }
+                    _ => (),
+                },
             }
         }
 
@@ -81,6 +120,7 @@ impl Draw for Image {
             Href::PlainUrl(ref url) => url,
             Href::WithFragment(_) => {
                 rsvg_log!(
+                    Warn,
                     "not rendering {} because its xlink:href cannot contain a fragment identifier",
                     node
                 );
@@ -90,10 +130,10 @@ impl Draw for Image {
         };
 
         draw_ctx.with_discrete_layer(node, acquired_nodes, values, clipping, &mut |an, dc| {
-            let surface = match an.lookup_image(url) {
+            let surface = match an.lookup_image(url, self.xml_base.as_ref(), self.cross_origin) {
                 Ok(surf) => surf,
                 Err(e) => {
-                    rsvg_log!("could not load image \"{}\": {}", url, e);
+                    rsvg_log!(Warn, "could not load image \"{}\": {}", url, e);
                     return Ok(dc.empty_bbox());
                 }
             };
@@ -135,7 +175,9 @@ impl Draw for Image {
                     cr.rectangle(0.0, 0.0, image_width, image_height);
                     cr.clip();
 
-                    cr.paint();
+                    if dc.render_mode() == RenderMode::Rasterize {
+                        cr.paint();
+                    }
                 }
 
                 // The bounding box for <image> is decided by the values of x, y, w, h
@@ -156,3 +198,27 @@ impl Image {
         Rect::new(x, y, x + w, y + h)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cross_origin() {
+        assert_eq!(
+            CrossOrigin::parse_str("anonymous"),
+            Ok(CrossOrigin::Anonymous)
+        );
+        assert_eq!(
+            CrossOrigin::parse_str("use-credentials"),
+            Ok(CrossOrigin::UseCredentials)
+        );
+        assert_eq!(
+            CrossOrigin::parse_str("Use-Credentials"),
+            Ok(CrossOrigin::UseCredentials)
+        );
+
+        assert!(CrossOrigin::parse_str("").is_err());
+        assert!(CrossOrigin::parse_str("foo").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 //! CSS funciri values.
 
-use cssparser::Parser;
+use cssparser::{Parser, ToCss};
+use std::fmt;
 
 use crate::allowed_url::{Fragment, Href};
 use crate::error::*;
@@ -60,6 +61,25 @@ impl Parse for IRI {
     }
 }
 
+impl ToCss for IRI {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        match *self {
+            IRI::None => dest.write_str("none"),
+            IRI::Resource(ref f) => serialize_funciri(f, dest),
+        }
+    }
+}
+
+/// Writes a fragment as a funciri like `url("foo.svg#bar")`.
+pub fn serialize_funciri<W: fmt::Write>(fragment: &Fragment, dest: &mut W) -> fmt::Result {
+    dest.write_str("url(")?;
+    cssparser::serialize_string(&fragment.to_string(), dest)?;
+    dest.write_str(")")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,13 +41,14 @@
 //! [diag]: https://www.w3.org/TR/SVG/coords.html#Units
 //! [`normalize`]: struct.Length.html#method.normalize
 
-use cssparser::{Parser, Token};
+use cssparser::{Parser, ToCss, Token};
 use std::f64::consts::*;
+use std::fmt;
 use std::marker::PhantomData;
 
 use crate::drawing_ctx::ViewParams;
 use crate::error::*;
-use crate::parsers::{finite_f32, Parse};
+use crate::parsers::{finite_f32, serialize_number, serialize_percentage, Parse};
 use crate::properties::ComputedValues;
 
 /// Units for length values.
@@ -270,6 +271,28 @@ impl<N: Normalize> Parse for Length<N> {
     }
 }
 
+impl<N: Normalize> ToCss for Length<N> {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        let unit = match self.unit {
+            LengthUnit::Percent => return serialize_percentage(self.length, dest),
+            LengthUnit::Px => "",
+            LengthUnit::Em => "em",
+            LengthUnit::Ex => "ex",
+            LengthUnit::In => "in",
+            LengthUnit::Cm => "cm",
+            LengthUnit::Mm => "mm",
+            LengthUnit::Pt => "pt",
+            LengthUnit::Pc => "pc",
+        };
+
+        serialize_number(self.length, dest)?;
+        dest.write_str(unit)
+    }
+}
+
 impl<N: Normalize> Length<N> {
     /// Creates a Length.
     ///
@@ -410,6 +433,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serializes_lengths() {
+        for s in &[
+            "42", "-42", "50%", "22.5em", "1ex", "0.5in", "2cm", "10mm", "12pt", "1pc",
+        ] {
+            assert_eq!(Length::<Both>::parse_str(s).unwrap().to_css_string(), *s);
+        }
+    }
+
     #[test]
     fn parses_font_em() {
         assert_eq!(
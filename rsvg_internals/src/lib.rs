@@ -47,18 +47,32 @@
 #![allow(clippy::too_many_arguments)]
 #![warn(unused)]
 
+pub use crate::aria::AriaAttributes;
+
 pub use crate::color::Color;
 
+pub use crate::diagnostics::{Diagnostic, Severity, SourceLocation};
+
 pub use crate::dpi::Dpi;
 
-pub use crate::error::{DefsLookupErrorKind, HrefError, LoadingError, RenderingError};
+pub use crate::element::SvgElementType;
+
+pub use crate::error::{
+    DefsLookupErrorKind, HrefError, LoadingError, RenderingError, SerializeError,
+};
 
-pub use crate::handle::{Handle, LoadOptions};
+pub use crate::handle::{Handle, LinkTarget, LoadOptions};
 
 pub use crate::length::{Length, LengthUnit, RsvgLength};
 
 pub use crate::parsers::Parse;
 
+#[doc(hidden)]
+pub use crate::path_parser::parse_and_serialize_path;
+
+#[doc(hidden)]
+pub use crate::properties::{parse_and_serialize_property, PROPERTY_NAMES};
+
 pub use crate::rect::{IRect, Rect};
 
 pub use crate::structure::IntrinsicDimensions;
@@ -68,6 +82,8 @@ pub use crate::surface_utils::{
     shared_surface::{SharedImageSurface, SurfaceType},
 };
 
+pub use crate::text::{measure_text, TextMetrics};
+
 pub use crate::viewbox::ViewBox;
 
 #[macro_use]
@@ -90,28 +106,34 @@ mod property_macros;
 
 mod allowed_url;
 mod angle;
+mod animation;
+mod aria;
 mod aspect_ratio;
 mod bbox;
 mod color;
 mod cond;
 mod css;
 mod dasharray;
+mod diagnostics;
 mod document;
 mod dpi;
 mod drawing_ctx;
 mod element;
 mod error;
 mod filter;
+mod filter_func;
 pub mod filters;
 mod font_props;
 mod gradient;
 mod handle;
+mod hatch;
 mod image;
 mod io;
 mod iri;
 mod length;
-mod limits;
+pub mod limits;
 mod marker;
+mod mesh_gradient;
 mod number_list;
 mod paint_server;
 mod path_builder;
@@ -121,6 +143,7 @@ mod properties;
 mod property_bag;
 mod property_defs;
 pub mod rect;
+mod serialize;
 mod shapes;
 mod space;
 mod structure;
@@ -26,6 +26,17 @@
 /// [billion laughs attack]: https://bitbucket.org/tiran/defusedxml
 pub const MAX_REFERENCED_ELEMENTS: usize = 500_000;
 
+/// Maximum depth of nested references between elements.
+///
+/// A chain of `<use>` elements that reference each other, or of
+/// gradients and patterns that reference each other through `href`,
+/// is resolved recursively.  Even if the total number of references is
+/// below [`MAX_REFERENCED_ELEMENTS`], a long enough chain could exhaust
+/// the stack, so we also limit how deeply references can be nested.
+///
+/// [`MAX_REFERENCED_ELEMENTS`]: constant.MAX_REFERENCED_ELEMENTS.html
+pub const MAX_REFERENCE_DEPTH: usize = 30;
+
 /// Maximum number of elements loadable per document.
 ///
 /// This is a mitigation for SVG files which create millions of elements
@@ -1,15 +1,33 @@
 //! Utilities for logging messages from the library.
 
 use once_cell::sync::Lazy;
+use std::fmt;
 
+pub use ::log::Level as LogLevel;
+
+/// Target for the messages that librsvg sends to the `log` crate.
+///
+/// Embedding applications can use this to filter librsvg's messages.
+pub const LOG_TARGET: &str = "librsvg";
+
+/// Logs a message at the given level.
+///
+/// The first argument is the name of a [`LogLevel`] variant, like `Warn` or
+/// `Debug`; the rest are as for `format!`.  The message is sent to the `log`
+/// crate, and is also printed to stdout if the `RSVG_LOG` environment variable
+/// is set.
+///
+/// ```ignore
+/// rsvg_log!(Warn, "could not parse path: {}", e);
+/// ```
+///
+/// [`LogLevel`]: log/type.LogLevel.html
 #[macro_export]
 macro_rules! rsvg_log {
     (
-        $($arg:tt)+
+        $level:ident, $($arg:tt)+
     ) => {
-        if $crate::log::log_enabled() {
-            println!("{}", format_args!($($arg)+));
-        }
+        $crate::log::log($crate::log::LogLevel::$level, format_args!($($arg)+))
     };
 }
 
@@ -18,3 +36,12 @@ pub fn log_enabled() -> bool {
 
     *ENABLED
 }
+
+#[doc(hidden)]
+pub fn log(level: LogLevel, args: fmt::Arguments) {
+    if log_enabled() {
+        println!("{}", args);
+    }
+
+    ::log::log!(target: LOG_TARGET, level, "{}", args);
+}
@@ -579,12 +579,12 @@ fn emit_marker_by_name(
                 clipping,
             ),
             _ => {
-                rsvg_log!("\"{}\" is not marker", name);
+                rsvg_log!(Warn, "\"{}\" is not marker", name);
                 Ok(draw_ctx.empty_bbox())
             }
         }
     } else {
-        rsvg_log!("marker \"{}\" not found", name);
+        rsvg_log!(Warn, "marker \"{}\" not found", name);
         Ok(draw_ctx.empty_bbox())
     }
 }
@@ -0,0 +1,549 @@
+//! Mesh gradient paint server from SVG 2; the `meshgradient` element.
+//!
+//! A mesh gradient is a grid of Coons patches, given as `meshrow` elements
+//! with `meshpatch` children.  Each patch is bounded by four cubic Bézier
+//! edges, which are described by the `path` attribute of the `stop` elements
+//! inside the patch; the `stop-color` of each stop is the color of the corner
+//! where its edge starts.  Edges and corners that are shared with the patch
+//! above or to the left are not repeated, so only the first patch in the mesh
+//! has four stops.
+//!
+//! Only the default `type="bilinear"` interpolation of colors is supported;
+//! `type="bicubic"` meshes are rendered with bilinear interpolation too.
+
+use cssparser::Parser;
+use markup5ever::{expanded_name, local_name, namespace_url, ns};
+use matches::matches;
+
+use crate::bbox::BoundingBox;
+use crate::coord_units::CoordUnits;
+use crate::document::AcquiredNodes;
+use crate::drawing_ctx::DrawingCtx;
+use crate::element::{Draw, Element, ElementResult, SetAttributes};
+use crate::error::*;
+use crate::length::*;
+use crate::node::{CascadedValues, Node, NodeBorrow};
+use crate::paint_server::{AsPaintSource, PaintSource};
+use crate::parsers::{Parse, ParseValue};
+use crate::path_builder::{PathBuilder, PathCommand};
+use crate::path_parser;
+use crate::properties::ComputedValues;
+use crate::property_bag::PropertyBag;
+use crate::property_defs::StopColor;
+use crate::transform::Transform;
+use crate::unit_interval::UnitInterval;
+
+// gradientUnits attibute; its default is objectBoundingBox
+coord_units!(GradientUnits, CoordUnits::ObjectBoundingBox);
+
+/// type attribute for mesh gradients
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum MeshType {
+    Bilinear,
+    Bicubic,
+}
+
+impl Parse for MeshType {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<MeshType, ParseError<'i>> {
+        Ok(parse_identifiers!(
+            parser,
+            "bilinear" => MeshType::Bilinear,
+            "bicubic" => MeshType::Bicubic,
+        )?)
+    }
+}
+
+impl Default for MeshType {
+    fn default() -> MeshType {
+        MeshType::Bilinear
+    }
+}
+
+/// Node for the `<meshgradient>` element
+#[derive(Default)]
+pub struct MeshGradient {
+    type_: MeshType,
+    units: GradientUnits,
+    transform: Transform,
+    x: Length<Horizontal>,
+    y: Length<Vertical>,
+}
+
+impl SetAttributes for MeshGradient {
+    fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
+        for (attr, value) in pbag.iter() {
+            match attr.expanded() {
+                expanded_name!("", "type") => self.type_ = attr.parse(value)?,
+                expanded_name!("", "gradientUnits") => self.units = attr.parse(value)?,
+                expanded_name!("", "gradientTransform") => self.transform = attr.parse(value)?,
+                expanded_name!("", "x") => self.x = attr.parse(value)?,
+                expanded_name!("", "y") => self.y = attr.parse(value)?,
+                _ => (),
+            }
+        }
+
+        if self.type_ == MeshType::Bicubic {
+            rsvg_log!(
+                Warn,
+                "bicubic mesh gradients are not supported; using bilinear interpolation"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl Draw for MeshGradient {}
+
+/// Node for the `<meshrow>` element
+#[derive(Default)]
+pub struct MeshRow;
+
+impl SetAttributes for MeshRow {}
+
+impl Draw for MeshRow {}
+
+/// Node for the `<meshpatch>` element
+#[derive(Default)]
+pub struct MeshPatch;
+
+impl SetAttributes for MeshPatch {}
+
+impl Draw for MeshPatch {}
+
+/// A cubic Bézier curve for one edge of a patch.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Edge {
+    from: (f64, f64),
+    pt1: (f64, f64),
+    pt2: (f64, f64),
+    to: (f64, f64),
+}
+
+impl Edge {
+    fn reversed(&self) -> Edge {
+        Edge {
+            from: self.to,
+            pt1: self.pt2,
+            pt2: self.pt1,
+            to: self.from,
+        }
+    }
+}
+
+/// Parses the `path` attribute of a stop in a mesh patch into an edge that
+/// starts at `from`.
+///
+/// The path is a single `c`, `C`, `l` or `L` command.  If the end point of the
+/// edge is already known, it is passed in `to`; in that case the path may omit
+/// it, as is usual for the edge that closes a patch.
+fn parse_edge(path: &str, from: (f64, f64), to: Option<(f64, f64)>) -> Option<Edge> {
+    let parse = |s: &str| {
+        let mut builder = PathBuilder::new();
+        path_parser::parse_path_into_builder(s, &mut builder)
+            .ok()
+            .map(|_| builder.into_path())
+    };
+
+    let mut parsed = parse(&format!("M {} {} {}", from.0, from.1, path));
+
+    if parsed.is_none() {
+        if let Some((x, y)) = to {
+            let relative = path
+                .trim_start()
+                .starts_with(|c: char| c == 'c' || c == 'l');
+            let (x, y) = if relative {
+                (x - from.0, y - from.1)
+            } else {
+                (x, y)
+            };
+
+            parsed = parse(&format!("M {} {} {} {} {}", from.0, from.1, path, x, y));
+        }
+    }
+
+    let commands: Vec<PathCommand> = parsed?.iter().collect();
+
+    let (pt1, pt2, end) = match commands[..] {
+        [PathCommand::MoveTo(..), PathCommand::CurveTo(curve)] => (curve.pt1, curve.pt2, curve.to),
+        [PathCommand::MoveTo(..), PathCommand::LineTo(x, y)] => (
+            (from.0 + (x - from.0) / 3.0, from.1 + (y - from.1) / 3.0),
+            (
+                from.0 + (x - from.0) * 2.0 / 3.0,
+                from.1 + (y - from.1) * 2.0 / 3.0,
+            ),
+            (x, y),
+        ),
+        _ => return None,
+    };
+
+    Some(Edge {
+        from,
+        pt1,
+        pt2,
+        to: to.unwrap_or(end),
+    })
+}
+
+/// A color with its alpha already multiplied by the stop-opacity.
+type Color = (f64, f64, f64, f64);
+
+/// A fully specified patch.  Corner 0 is the top-left one, and the rest follow
+/// clockwise; edge `i` goes from corner `i` to the next one.
+#[derive(Debug, Clone, PartialEq)]
+struct Patch {
+    edges: [Edge; 4],
+    colors: [Color; 4],
+}
+
+/// The `path` attribute and color of a `<stop>` in a mesh patch.
+struct PatchStop {
+    path: Option<String>,
+    color: Color,
+}
+
+/// Builds a patch from its stops, given the patches above and to its left.
+///
+/// `start` is the starting point of the whole mesh, which is only used for the
+/// first patch.
+fn build_patch(
+    stops: &[PatchStop],
+    start: (f64, f64),
+    above: Option<&Patch>,
+    left: Option<&Patch>,
+) -> Option<Patch> {
+    let mut corners: [Option<(f64, f64)>; 4] = [None; 4];
+    let mut colors: [Option<Color>; 4] = [None; 4];
+    let mut edges: [Option<Edge>; 4] = [None; 4];
+
+    if let Some(left) = left {
+        edges[3] = Some(left.edges[1].reversed());
+        corners[0] = Some(left.edges[1].from);
+        corners[3] = Some(left.edges[1].to);
+        colors[0] = Some(left.colors[1]);
+        colors[3] = Some(left.colors[2]);
+    }
+
+    if let Some(above) = above {
+        edges[0] = Some(above.edges[2].reversed());
+        corners[0] = Some(above.edges[2].to);
+        corners[1] = Some(above.edges[2].from);
+        colors[0] = Some(above.colors[3]);
+        colors[1] = Some(above.colors[2]);
+    }
+
+    if above.is_none() && left.is_none() {
+        corners[0] = Some(start);
+    }
+
+    let mut stops = stops.iter();
+
+    for i in 0..4 {
+        if edges[i].is_some() {
+            continue;
+        }
+
+        let stop = stops.next()?;
+        let next = (i + 1) % 4;
+
+        let edge = parse_edge(stop.path.as_ref()?, corners[i]?, corners[next])?;
+
+        corners[next] = Some(edge.to);
+        edges[i] = Some(edge);
+
+        if colors[i].is_none() {
+            colors[i] = Some(stop.color);
+        }
+    }
+
+    Some(Patch {
+        edges: [edges[0]?, edges[1]?, edges[2]?, edges[3]?],
+        colors: [colors[0]?, colors[1]?, colors[2]?, colors[3]?],
+    })
+}
+
+/// Builds all the patches in a mesh, row by row.  Returns `None` if the mesh
+/// is in error, in which case it must not be rendered.
+fn build_mesh(rows: &[Vec<Vec<PatchStop>>], start: (f64, f64)) -> Option<Vec<Patch>> {
+    let mut prev_row: Vec<Patch> = Vec::new();
+    let mut patches = Vec::new();
+
+    for (r, row) in rows.iter().enumerate() {
+        let mut cur_row: Vec<Patch> = Vec::new();
+
+        for (c, stops) in row.iter().enumerate() {
+            let above = if r > 0 { Some(prev_row.get(c)?) } else { None };
+            let patch = build_patch(stops, start, above, cur_row.last())?;
+            cur_row.push(patch);
+        }
+
+        patches.extend(cur_row.iter().cloned());
+        prev_row = cur_row;
+    }
+
+    if patches.is_empty() {
+        None
+    } else {
+        Some(patches)
+    }
+}
+
+fn element_children<'a>(node: &'a Node) -> impl Iterator<Item = Node> + 'a {
+    node.children().filter(|c| c.is_element())
+}
+
+/// Collects the stops of each patch in each row of a `<meshgradient>`.
+fn collect_rows(node: &Node) -> Vec<Vec<Vec<PatchStop>>> {
+    element_children(node)
+        .filter(|row| matches!(*row.borrow_element(), Element::MeshRow(_)))
+        .map(|row| {
+            element_children(&row)
+                .filter(|patch| matches!(*patch.borrow_element(), Element::MeshPatch(_)))
+                .map(|patch| {
+                    element_children(&patch)
+                        .filter_map(|stop| {
+                            if let Element::Stop(ref s) = *stop.borrow_element() {
+                                let cascaded = CascadedValues::new_from_node(&stop);
+                                let values = cascaded.get();
+                                let rgba = match values.stop_color() {
+                                    StopColor(cssparser::Color::CurrentColor) => values.color().0,
+                                    StopColor(cssparser::Color::RGBA(ref rgba)) => *rgba,
+                                };
+                                let UnitInterval(stop_opacity) = values.stop_opacity().0;
+
+                                Some(PatchStop {
+                                    path: s.path().map(str::to_string),
+                                    color: (
+                                        f64::from(rgba.red_f32()),
+                                        f64::from(rgba.green_f32()),
+                                        f64::from(rgba.blue_f32()),
+                                        f64::from(rgba.alpha_f32()) * stop_opacity,
+                                    ),
+                                })
+                            } else {
+                                None
+                            }
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub struct ResolvedMeshGradient {
+    units: GradientUnits,
+    transform: Transform,
+    x: Length<Horizontal>,
+    y: Length<Vertical>,
+    rows: Vec<Vec<Vec<PatchStop>>>,
+}
+
+impl PaintSource for MeshGradient {
+    type Resolved = ResolvedMeshGradient;
+
+    fn resolve(
+        &self,
+        node: &Node,
+        _acquired_nodes: &mut AcquiredNodes,
+    ) -> Result<Self::Resolved, AcquireError> {
+        Ok(ResolvedMeshGradient {
+            units: self.units,
+            transform: self.transform,
+            x: self.x,
+            y: self.y,
+            rows: collect_rows(node),
+        })
+    }
+}
+
+impl AsPaintSource for ResolvedMeshGradient {
+    fn set_as_paint_source(
+        self,
+        _acquired_nodes: &mut AcquiredNodes,
+        values: &ComputedValues,
+        draw_ctx: &mut DrawingCtx,
+        opacity: UnitInterval,
+        bbox: &BoundingBox,
+    ) -> Result<bool, RenderingError> {
+        let transform = match bbox.rect_to_transform(self.units.0) {
+            Some(t) => t,
+            None => return Ok(false),
+        };
+
+        let params = if self.units == GradientUnits(CoordUnits::ObjectBoundingBox) {
+            draw_ctx.push_view_box(1.0, 1.0)
+        } else {
+            draw_ctx.get_view_params()
+        };
+
+        let start = (
+            self.x.normalize(values, &params),
+            self.y.normalize(values, &params),
+        );
+
+        let patches = match build_mesh(&self.rows, start) {
+            Some(p) => p,
+            None => {
+                rsvg_log!(Debug, "mesh gradient is in error; not rendering it");
+                return Ok(false);
+            }
+        };
+
+        let UnitInterval(o) = opacity;
+
+        let mesh = cairo::Mesh::new();
+
+        for patch in &patches {
+            mesh.begin_patch();
+
+            let (x, y) = patch.edges[0].from;
+            mesh.move_to(x, y);
+
+            for edge in &patch.edges {
+                mesh.curve_to(
+                    edge.pt1.0, edge.pt1.1, edge.pt2.0, edge.pt2.1, edge.to.0, edge.to.1,
+                );
+            }
+
+            let corners = [
+                cairo::MeshCorner::MeshCorner0,
+                cairo::MeshCorner::MeshCorner1,
+                cairo::MeshCorner::MeshCorner2,
+                cairo::MeshCorner::MeshCorner3,
+            ];
+
+            for (corner, &(r, g, b, a)) in corners.iter().zip(patch.colors.iter()) {
+                mesh.set_corner_color_rgba(*corner, r, g, b, a * o);
+            }
+
+            mesh.end_patch();
+        }
+
+        let transform = transform.pre_transform(&self.transform);
+        transform.invert().map(|m| mesh.set_matrix(m.into()));
+
+        let cr = draw_ctx.get_cairo_context();
+        cr.set_source(&mesh);
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stop(path: &str, color: Color) -> PatchStop {
+        PatchStop {
+            path: Some(path.to_string()),
+            color,
+        }
+    }
+
+    #[test]
+    fn parses_mesh_type() {
+        assert_eq!(MeshType::parse_str("bilinear"), Ok(MeshType::Bilinear));
+        assert_eq!(MeshType::parse_str("bicubic"), Ok(MeshType::Bicubic));
+        assert!(MeshType::parse_str("linear").is_err());
+    }
+
+    #[test]
+    fn parses_edges() {
+        assert_eq!(
+            parse_edge("c 1,2 3,4 5,6", (10.0, 10.0), None),
+            Some(Edge {
+                from: (10.0, 10.0),
+                pt1: (11.0, 12.0),
+                pt2: (13.0, 14.0),
+                to: (15.0, 16.0),
+            })
+        );
+
+        assert_eq!(
+            parse_edge("L 13 10", (10.0, 10.0), None),
+            Some(Edge {
+                from: (10.0, 10.0),
+                pt1: (11.0, 10.0),
+                pt2: (12.0, 10.0),
+                to: (13.0, 10.0),
+            })
+        );
+    }
+
+    #[test]
+    fn closing_edge_may_omit_end_point() {
+        assert_eq!(
+            parse_edge("c -1,-1 1,-2", (0.0, 3.0), Some((0.0, 0.0))),
+            Some(Edge {
+                from: (0.0, 3.0),
+                pt1: (-1.0, 2.0),
+                pt2: (1.0, 1.0),
+                to: (0.0, 0.0),
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_edges_yield_none() {
+        assert!(parse_edge("", (0.0, 0.0), None).is_none());
+        assert!(parse_edge("c 1,2 3,4", (0.0, 0.0), None).is_none());
+        assert!(parse_edge("l 1,2 3,4", (0.0, 0.0), None).is_none());
+        assert!(parse_edge("a 1 1 0 0 0 2 2", (0.0, 0.0), None).is_none());
+    }
+
+    #[test]
+    fn builds_mesh_with_shared_edges() {
+        let red = (1.0, 0.0, 0.0, 1.0);
+        let green = (0.0, 1.0, 0.0, 1.0);
+        let blue = (0.0, 0.0, 1.0, 1.0);
+        let white = (1.0, 1.0, 1.0, 1.0);
+        let black = (0.0, 0.0, 0.0, 1.0);
+
+        let rows = vec![
+            vec![
+                vec![
+                    stop("l 1,0", red),
+                    stop("l 0,1", green),
+                    stop("l -1,0", blue),
+                    stop("l 0,-1", white),
+                ],
+                vec![
+                    stop("l 1,0", white),
+                    stop("l 0,1", black),
+                    stop("l -1,0", red),
+                ],
+            ],
+            vec![vec![
+                stop("l 0,1", black),
+                stop("l -1,0", green),
+                stop("l 0,-1", blue),
+            ]],
+        ];
+
+        let patches = build_mesh(&rows, (0.0, 0.0)).unwrap();
+        assert_eq!(patches.len(), 3);
+
+        // Second patch in the first row shares its left edge with the first patch
+        assert_eq!(patches[1].edges[3], patches[0].edges[1].reversed());
+        assert_eq!(patches[1].edges[0].from, (1.0, 0.0));
+        assert_eq!(patches[1].colors, [green, black, red, blue]);
+
+        // First patch in the second row shares its top edge with the first patch
+        assert_eq!(patches[2].edges[0], patches[0].edges[2].reversed());
+        assert_eq!(patches[2].edges[2].to, (0.0, 2.0));
+        assert_eq!(patches[2].edges[3].to, (0.0, 1.0));
+        assert_eq!(patches[2].colors, [white, blue, green, blue]);
+    }
+
+    #[test]
+    fn mesh_with_missing_stops_is_in_error() {
+        let rows = vec![vec![vec![
+            stop("l 1,0", (0.0, 0.0, 0.0, 1.0)),
+            stop("l 0,1", (0.0, 0.0, 0.0, 1.0)),
+        ]]];
+
+        assert!(build_mesh(&rows, (0.0, 0.0)).is_none());
+        assert!(build_mesh(&[], (0.0, 0.0)).is_none());
+    }
+}
@@ -16,6 +16,7 @@ use std::cell::{Ref, RefMut};
 use std::fmt;
 
 use crate::bbox::BoundingBox;
+use crate::diagnostics::Diagnostics;
 use crate::document::AcquiredNodes;
 use crate::drawing_ctx::DrawingCtx;
 use crate::element::*;
@@ -68,12 +69,16 @@ pub enum NodeData {
 }
 
 impl NodeData {
-    pub fn new_element(name: &QualName, pbag: &PropertyBag) -> NodeData {
-        NodeData::Element(Element::new(name, pbag))
+    pub fn new_element(
+        name: &QualName,
+        pbag: &PropertyBag,
+        diagnostics: &mut Diagnostics,
+    ) -> NodeData {
+        NodeData::Element(Element::new(name, pbag, diagnostics))
     }
 
-    pub fn new_chars() -> NodeData {
-        NodeData::Text(Chars::new())
+    pub fn new_chars(normalize_unicode: bool) -> NodeData {
+        NodeData::Text(Chars::new(normalize_unicode))
     }
 }
 
@@ -1,12 +1,14 @@
 //! SVG paint servers.
 
-use cssparser::Parser;
+use cssparser::{Parser, ToCss};
+use std::fmt;
 
 use crate::allowed_url::Fragment;
 use crate::bbox::BoundingBox;
 use crate::document::AcquiredNodes;
 use crate::drawing_ctx::DrawingCtx;
 use crate::error::*;
+use crate::iri::serialize_funciri;
 use crate::node::{CascadedValues, Node};
 use crate::parsers::Parse;
 use crate::properties::ComputedValues;
@@ -39,7 +41,7 @@ impl Parse for PaintServer {
                 {
                     None
                 } else {
-                    Some(parser.try_parse(|i| cssparser::Color::parse(i))?)
+                    Some(parser.try_parse(|i| <cssparser::Color as Parse>::parse(i))?)
                 }
             } else {
                 None
@@ -52,7 +54,34 @@ impl Parse for PaintServer {
                 alternate,
             })
         } else {
-            Ok(cssparser::Color::parse(parser).map(PaintServer::SolidColor)?)
+            <cssparser::Color as Parse>::parse(parser).map(PaintServer::SolidColor)
+        }
+    }
+}
+
+impl ToCss for PaintServer {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        match *self {
+            PaintServer::None => dest.write_str("none"),
+
+            PaintServer::Iri {
+                ref iri,
+                ref alternate,
+            } => {
+                serialize_funciri(iri, dest)?;
+
+                if let Some(ref color) = *alternate {
+                    dest.write_str(" ")?;
+                    color.to_css(dest)?;
+                }
+
+                Ok(())
+            }
+
+            PaintServer::SolidColor(ref color) => color.to_css(dest),
         }
     }
 }
@@ -82,17 +111,22 @@ pub trait PaintSource {
             }
 
             Err(AcquireError::CircularReference(node)) => {
-                rsvg_log!("circular reference in paint server {}", node);
+                rsvg_log!(Warn, "circular reference in paint server {}", node);
                 Err(RenderingError::CircularReference)
             }
 
             Err(AcquireError::MaxReferencesExceeded) => {
-                rsvg_log!("maximum number of references exceeded");
+                rsvg_log!(Warn, "maximum number of references exceeded");
+                Err(RenderingError::InstancingLimit)
+            }
+
+            Err(AcquireError::MaxDepthExceeded) => {
+                rsvg_log!(Warn, "paint server {} is nested too deeply", node);
                 Err(RenderingError::InstancingLimit)
             }
 
             Err(e) => {
-                rsvg_log!("not using paint server {}: {}", node, e);
+                rsvg_log!(Warn, "not using paint server {}: {}", node, e);
 
                 // "could not resolve" means caller needs to fall back to color
                 Ok(false)
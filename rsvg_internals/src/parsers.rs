@@ -2,6 +2,7 @@
 
 use cssparser::{Parser, ParserInput, Token};
 use markup5ever::QualName;
+use std::fmt;
 use std::str;
 
 use crate::error::*;
@@ -41,6 +42,41 @@ pub fn finite_f32(n: f32) -> Result<f32, ValueErrorKind> {
     }
 }
 
+/// Writes a number so that parsing it back yields the same value.
+///
+/// Numbers that come from CSS were parsed as `f32`, so they get written with
+/// `f32` precision when that is exact; this avoids output like `0.30000001192092896`.
+pub fn serialize_number<W: fmt::Write>(value: f64, dest: &mut W) -> fmt::Result {
+    let single = value as f32;
+
+    if f64::from(single) == value {
+        write!(dest, "{}", single)
+    } else {
+        write!(dest, "{}", value)
+    }
+}
+
+/// Writes a value where `1.0` means 100% as a CSS percentage.
+///
+/// cssparser divides percentages by 100 and rounds the result to `f32`, so this
+/// looks for the shortest decimal that goes back to the same value; `0.5` gets
+/// written as `50%` instead of something with a long tail of digits.
+pub fn serialize_percentage<W: fmt::Write>(value: f64, dest: &mut W) -> fmt::Result {
+    let percent = value * 100.0;
+
+    for precision in 0..9 {
+        let s = format!("{:.*}", precision, percent);
+
+        if let Ok(p) = s.parse::<f64>() {
+            if (p / 100.0) as f32 == value as f32 {
+                return write!(dest, "{}%", s);
+            }
+        }
+    }
+
+    write!(dest, "{}%", percent)
+}
+
 pub trait ParseValue<T: Parse> {
     /// Parses a `value` string into a type `T`.
     fn parse(&self, value: &str) -> Result<T, ElementError>;
@@ -289,4 +325,23 @@ mod tests {
         assert!(CustomIdent::parse_str("default").is_err());
         assert!(CustomIdent::parse_str("").is_err());
     }
+
+    #[test]
+    fn serializes_numbers_without_noise() {
+        let mut s = String::new();
+        serialize_number(f64::from(0.3f32), &mut s).unwrap();
+        assert_eq!(s, "0.3");
+
+        let mut s = String::new();
+        serialize_number(0.3, &mut s).unwrap();
+        assert_eq!(s, "0.3");
+
+        let mut s = String::new();
+        serialize_percentage(f64::from(0.5f32), &mut s).unwrap();
+        assert_eq!(s, "50%");
+
+        let mut s = String::new();
+        serialize_percentage(f64::from(0.1234f32), &mut s).unwrap();
+        assert_eq!(s, "12.34%");
+    }
 }
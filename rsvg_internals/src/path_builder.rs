@@ -4,9 +4,12 @@ use tinyvec::TinyVec;
 
 use std::f64;
 use std::f64::consts::*;
+use std::fmt;
 use std::slice;
 
+use crate::angle::Angle;
 use crate::float_eq_cairo::ApproxEqCairo;
+use crate::rect::Rect;
 use crate::util::clamp;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -42,6 +45,142 @@ impl CubicBezierCurve {
         CubicBezierCurve { pt1, pt2, to }
     }
 
+    /// Approximates the length of the curve when it starts at `from`.
+    ///
+    /// The curve is subdivided until its control polygon is close enough
+    /// to its chord; then the length of each piece is estimated as the
+    /// average of both, which is Gravesen's method for cubics.
+    fn length(&self, from: (f64, f64)) -> f64 {
+        fn dist(a: (f64, f64), b: (f64, f64)) -> f64 {
+            (b.0 - a.0).hypot(b.1 - a.1)
+        }
+
+        fn lerp(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+            ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+        }
+
+        fn subdivide(p0: (f64, f64), c: &CubicBezierCurve, depth: u32) -> f64 {
+            let chord = dist(p0, c.to);
+            let polygon = dist(p0, c.pt1) + dist(c.pt1, c.pt2) + dist(c.pt2, c.to);
+
+            if depth == 0 || !(polygon - chord > 1e-3) {
+                return (chord + polygon) / 2.0;
+            }
+
+            // de Casteljau at t = 0.5
+            let p01 = lerp(p0, c.pt1);
+            let p12 = lerp(c.pt1, c.pt2);
+            let p23 = lerp(c.pt2, c.to);
+            let p012 = lerp(p01, p12);
+            let p123 = lerp(p12, p23);
+            let mid = lerp(p012, p123);
+
+            let first = CubicBezierCurve {
+                pt1: p01,
+                pt2: p012,
+                to: mid,
+            };
+            let second = CubicBezierCurve {
+                pt1: p123,
+                pt2: p23,
+                to: c.to,
+            };
+
+            subdivide(p0, &first, depth - 1) + subdivide(mid, &second, depth - 1)
+        }
+
+        subdivide(from, self, 10)
+    }
+
+    /// Splits the curve at parameter `t` with de Casteljau's algorithm, and
+    /// returns the part from 0 to `t`.
+    fn head(&self, from: (f64, f64), t: f64) -> CubicBezierCurve {
+        fn lerp(a: (f64, f64), b: (f64, f64), t: f64) -> (f64, f64) {
+            (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+        }
+
+        let p01 = lerp(from, self.pt1, t);
+        let p12 = lerp(self.pt1, self.pt2, t);
+        let p23 = lerp(self.pt2, self.to, t);
+        let p012 = lerp(p01, p12, t);
+        let p123 = lerp(p12, p23, t);
+
+        CubicBezierCurve {
+            pt1: p01,
+            pt2: p012,
+            to: lerp(p012, p123, t),
+        }
+    }
+
+    /// Computes the direction of the curve at parameter `t`, when it starts at `from`.
+    fn tangent(&self, from: (f64, f64), t: f64) -> (f64, f64) {
+        let mt = 1.0 - t;
+
+        let d = |p0: f64, p1: f64, p2: f64, p3: f64| {
+            3.0 * mt * mt * (p1 - p0) + 6.0 * mt * t * (p2 - p1) + 3.0 * t * t * (p3 - p2)
+        };
+
+        let dx = d(from.0, self.pt1.0, self.pt2.0, self.to.0);
+        let dy = d(from.1, self.pt1.1, self.pt2.1, self.to.1);
+
+        if dx.approx_eq_cairo(0.0) && dy.approx_eq_cairo(0.0) {
+            // Coincident control points; use the chord instead.
+            (self.to.0 - from.0, self.to.1 - from.1)
+        } else {
+            (dx, dy)
+        }
+    }
+
+    /// Computes the tight bounding box of the curve when it starts at `from`.
+    fn extents(&self, from: (f64, f64)) -> Rect {
+        // Coordinate of the curve at parameter t, for a single axis.
+        fn eval(p0: f64, p1: f64, p2: f64, p3: f64, t: f64) -> f64 {
+            let mt = 1.0 - t;
+            mt * mt * mt * p0 + 3.0 * mt * mt * t * p1 + 3.0 * mt * t * t * p2 + t * t * t * p3
+        }
+
+        // Range of the curve along a single axis: the end points plus
+        // the points where the derivative is zero.
+        fn range(p0: f64, p1: f64, p2: f64, p3: f64) -> (f64, f64) {
+            let mut min = p0.min(p3);
+            let mut max = p0.max(p3);
+
+            let a = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+            let b = 2.0 * (p0 - 2.0 * p1 + p2);
+            let c = p1 - p0;
+
+            let mut roots = [None, None];
+
+            if a.abs() < 1e-12 {
+                if b.abs() >= 1e-12 {
+                    roots[0] = Some(-c / b);
+                }
+            } else {
+                let disc = b * b - 4.0 * a * c;
+                if disc >= 0.0 {
+                    let sqrt_disc = disc.sqrt();
+                    roots[0] = Some((-b + sqrt_disc) / (2.0 * a));
+                    roots[1] = Some((-b - sqrt_disc) / (2.0 * a));
+                }
+            }
+
+            for t in roots.iter().filter_map(|t| *t) {
+                if t > 0.0 && t < 1.0 {
+                    let v = eval(p0, p1, p2, p3, t);
+                    min = min.min(v);
+                    max = max.max(v);
+                }
+            }
+
+            (min, max)
+        }
+
+        let (x0, x1) = range(from.0, self.pt1.0, self.pt2.0, self.to.0);
+        let (y0, y1) = range(from.1, self.pt1.1, self.pt2.1, self.to.1);
+
+        Rect::new(x0, y0, x1, y1)
+    }
+
     fn to_packed_and_coords(&self, coords: &mut Vec<f64>) -> PackedCommand {
         coords.push(self.pt1.0);
         coords.push(self.pt1.1);
@@ -327,6 +466,73 @@ pub(crate) fn arc_segment(
     }
 }
 
+/// A drawn piece of a path, with its start point made explicit.
+///
+/// This is what `Path::segments` produces for measuring a path; moves are not
+/// segments, and arcs get turned into lines or curves.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Segment {
+    Line((f64, f64), (f64, f64)),
+    Curve((f64, f64), CubicBezierCurve),
+}
+
+impl Segment {
+    fn length(&self) -> f64 {
+        match *self {
+            Segment::Line(from, to) => (to.0 - from.0).hypot(to.1 - from.1),
+            Segment::Curve(from, ref curve) => curve.length(from),
+        }
+    }
+
+    /// Returns the point at a distance `length` from the start of the segment,
+    /// and the direction of the segment there.
+    fn point_at_length(&self, length: f64) -> ((f64, f64), (f64, f64)) {
+        match *self {
+            Segment::Line(from, to) => {
+                let total = self.length();
+                let t = if total > 0.0 { length / total } else { 0.0 };
+
+                (
+                    (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t),
+                    (to.0 - from.0, to.1 - from.1),
+                )
+            }
+
+            Segment::Curve(from, ref curve) => {
+                // Find the curve parameter for the length by bisection.
+                let mut lo = 0.0;
+                let mut hi = 1.0;
+
+                for _ in 0..32 {
+                    let mid = (lo + hi) / 2.0;
+
+                    if curve.head(from, mid).length(from) < length {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+
+                let t = (lo + hi) / 2.0;
+
+                (curve.head(from, t).to, curve.tangent(from, t))
+            }
+        }
+    }
+
+    fn extents(&self) -> Rect {
+        match *self {
+            Segment::Line(from, to) => Rect::new(
+                from.0.min(to.0),
+                from.1.min(to.1),
+                from.0.max(to.0),
+                from.1.max(to.1),
+            ),
+            Segment::Curve(from, ref curve) => curve.extents(from),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum PathCommand {
     MoveTo(f64, f64),
@@ -556,6 +762,119 @@ impl Path {
         self.commands.is_empty()
     }
 
+    /// Returns the drawn segments of the path, in order.
+    fn segments(&self) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        let mut current = (0.0, 0.0);
+        let mut subpath_start = (0.0, 0.0);
+
+        for cmd in self.iter() {
+            match cmd {
+                PathCommand::MoveTo(x, y) => {
+                    current = (x, y);
+                    subpath_start = current;
+                }
+
+                PathCommand::LineTo(x, y) => {
+                    segments.push(Segment::Line(current, (x, y)));
+                    current = (x, y);
+                }
+
+                PathCommand::CurveTo(curve) => {
+                    segments.push(Segment::Curve(current, curve));
+                    current = curve.to;
+                }
+
+                PathCommand::Arc(arc) => {
+                    match arc.center_parameterization() {
+                        ArcParameterization::CenterParameters {
+                            center,
+                            radii,
+                            theta1,
+                            delta_theta,
+                        } => {
+                            let n_segs = (delta_theta / (PI * 0.5 + 0.001)).abs().ceil() as u32;
+                            let d_theta = delta_theta / f64::from(n_segs);
+
+                            let mut from = arc.from;
+                            let mut theta = theta1;
+                            for _ in 0..n_segs {
+                                let curve = arc_segment(
+                                    center,
+                                    radii,
+                                    arc.x_axis_rotation,
+                                    theta,
+                                    theta + d_theta,
+                                );
+                                segments.push(Segment::Curve(from, curve));
+                                from = curve.to;
+                                theta += d_theta;
+                            }
+                        }
+                        ArcParameterization::LineTo => {
+                            segments.push(Segment::Line(arc.from, arc.to));
+                        }
+                        ArcParameterization::Omit => {}
+                    }
+                    current = arc.to;
+                }
+
+                PathCommand::ClosePath => {
+                    segments.push(Segment::Line(current, subpath_start));
+                    current = subpath_start;
+                }
+            }
+        }
+
+        segments
+    }
+
+    /// Computes the total length of the path, including the implicit lines of closed subpaths.
+    ///
+    /// Curves and arcs are measured by approximation.
+    pub fn path_length(&self) -> f64 {
+        self.segments()
+            .iter()
+            .fold(0.0, |acc, segment| acc + segment.length())
+    }
+
+    /// Finds the point at a distance `length` along the path, and the angle of
+    /// the path's direction at that point.
+    ///
+    /// The length is clamped to the length of the path.  Returns `None` if the
+    /// path has no drawn segments.
+    pub fn point_at_length(&self, length: f64) -> Option<((f64, f64), Angle)> {
+        let segments = self.segments();
+        let mut remaining = length.max(0.0);
+
+        for (i, segment) in segments.iter().enumerate() {
+            let segment_length = segment.length();
+
+            if remaining <= segment_length || i == segments.len() - 1 {
+                let (point, (dx, dy)) = segment.point_at_length(remaining.min(segment_length));
+                return Some((point, Angle::from_vector(dx, dy)));
+            }
+
+            remaining -= segment_length;
+        }
+
+        None
+    }
+
+    /// Computes the bounding box of the drawn segments of the path.
+    ///
+    /// Returns `None` if the path has no drawn segments, for example, if it
+    /// only has move-to commands.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        self.segments()
+            .iter()
+            .map(Segment::extents)
+            .fold(None, |acc, r| match acc {
+                None => Some(r),
+                Some(acc) => Some(acc.union(&r)),
+            })
+    }
+
     pub fn to_cairo(&self, cr: &cairo::Context) -> Result<(), cairo::Status> {
         assert!(!self.is_empty());
 
@@ -581,6 +900,51 @@ impl Path {
     }
 }
 
+/// Serializes the path as SVG path data with absolute commands.
+///
+/// Since an arc's start point is always the current point, parsing the result
+/// gives back the same commands.
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, cmd) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+
+            match cmd {
+                PathCommand::MoveTo(x, y) => write!(f, "M {} {}", x, y)?,
+                PathCommand::LineTo(x, y) => write!(f, "L {} {}", x, y)?,
+                PathCommand::CurveTo(CubicBezierCurve { pt1, pt2, to }) => write!(
+                    f,
+                    "C {} {} {} {} {} {}",
+                    pt1.0, pt1.1, pt2.0, pt2.1, to.0, to.1
+                )?,
+                PathCommand::Arc(EllipticalArc {
+                    r,
+                    x_axis_rotation,
+                    large_arc: LargeArc(large_arc),
+                    sweep,
+                    to,
+                    ..
+                }) => write!(
+                    f,
+                    "A {} {} {} {} {} {} {}",
+                    r.0,
+                    r.1,
+                    x_axis_rotation,
+                    large_arc as u8,
+                    (sweep == Sweep::Positive) as u8,
+                    to.0,
+                    to.1
+                )?,
+                PathCommand::ClosePath => f.write_str("Z")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
 fn take_one<'a>(iter: &mut slice::Iter<'a, f64>) -> f64 {
     *iter.next().unwrap()
 }
@@ -639,4 +1003,116 @@ mod tests {
             PathCommand::ClosePath,
         ]));
     }
+
+    #[test]
+    fn measures_closed_square() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(10.0, 10.0);
+        builder.line_to(20.0, 10.0);
+        builder.line_to(20.0, 20.0);
+        builder.line_to(10.0, 20.0);
+        builder.close_path();
+        let path = builder.into_path();
+
+        assert_eq!(path.path_length(), 40.0);
+        assert_eq!(path.bounding_box(), Some(Rect::new(10.0, 10.0, 20.0, 20.0)));
+    }
+
+    #[test]
+    fn curve_bounding_box_is_tight() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.curve_to(0.0, 100.0, 100.0, 100.0, 100.0, 0.0);
+        let path = builder.into_path();
+
+        assert_eq!(path.bounding_box(), Some(Rect::new(0.0, 0.0, 100.0, 75.0)));
+    }
+
+    #[test]
+    fn measures_arcs() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.arc(
+            0.0,
+            0.0,
+            50.0,
+            50.0,
+            0.0,
+            LargeArc(false),
+            Sweep::Positive,
+            100.0,
+            0.0,
+        );
+        builder.arc(
+            100.0,
+            0.0,
+            50.0,
+            50.0,
+            0.0,
+            LargeArc(false),
+            Sweep::Positive,
+            0.0,
+            0.0,
+        );
+        let path = builder.into_path();
+
+        assert!((path.path_length() - 100.0 * PI).abs() < 0.1);
+    }
+
+    #[test]
+    fn moves_only_have_no_extents() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(1.0, 2.0);
+        builder.move_to(3.0, 4.0);
+        let path = builder.into_path();
+
+        assert_eq!(path.path_length(), 0.0);
+        assert_eq!(path.bounding_box(), None);
+    }
+
+    #[test]
+    fn finds_points_along_lines() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(10.0, 10.0);
+        builder.line_to(20.0, 10.0);
+        builder.move_to(50.0, 50.0);
+        builder.line_to(50.0, 60.0);
+        let path = builder.into_path();
+
+        let at = |length| {
+            let ((x, y), angle) = path.point_at_length(length).unwrap();
+            (x, y, angle.radians())
+        };
+
+        assert_eq!(at(-5.0), (10.0, 10.0, 0.0));
+        assert_eq!(at(5.0), (15.0, 10.0, 0.0));
+        assert_eq!(at(15.0), (50.0, 55.0, FRAC_PI_2));
+        assert_eq!(at(100.0), (50.0, 60.0, FRAC_PI_2));
+
+        assert_eq!(PathBuilder::new().into_path().point_at_length(0.0), None);
+    }
+
+    #[test]
+    fn finds_points_along_curves() {
+        // A symmetric curve; its midpoint by length is at t = 0.5.
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.curve_to(0.0, 100.0, 100.0, 100.0, 100.0, 0.0);
+        let path = builder.into_path();
+
+        let length = path.path_length();
+
+        let ((x, y), angle) = path.point_at_length(length / 2.0).unwrap();
+        assert!((x - 50.0).abs() < 0.01);
+        assert!((y - 75.0).abs() < 0.01);
+        assert!(angle.radians().sin().abs() < 0.001);
+        assert!(angle.radians().cos() > 0.0);
+
+        let ((x, y), angle) = path.point_at_length(0.0).unwrap();
+        assert!(x.abs() < 0.01 && y.abs() < 0.01);
+        assert!((angle.radians() - FRAC_PI_2).abs() < 0.001);
+
+        let ((x, y), _) = path.point_at_length(length).unwrap();
+        assert!((x - 100.0).abs() < 0.01 && y.abs() < 0.01);
+    }
 }
@@ -933,10 +933,23 @@ pub fn parse_path_into_builder(
     parser.parse()
 }
 
+/// Parses path data and serializes the resulting path back to path data.
+///
+/// Returns `None` if the data has an error.  This is only for fuzzing the
+/// path parser.
+#[doc(hidden)]
+pub fn parse_and_serialize_path(path_str: &str) -> Option<String> {
+    let mut builder = PathBuilder::new();
+    parse_path_into_builder(path_str, &mut builder).ok()?;
+
+    Some(builder.into_path().to_string())
+}
+
 #[cfg(test)]
 #[rustfmt::skip]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     fn find_error_pos(s: &str) -> Option<usize> {
         s.find('^')
@@ -991,6 +1004,31 @@ mod tests {
         })
     }
 
+    fn arc(
+        x1: f64,
+        y1: f64,
+        rx: f64,
+        ry: f64,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        x2: f64,
+        y2: f64,
+    ) -> PathCommand {
+        PathCommand::Arc(EllipticalArc {
+            r: (rx, ry),
+            x_axis_rotation,
+            large_arc: LargeArc(large_arc),
+            sweep: if sweep {
+                Sweep::Positive
+            } else {
+                Sweep::Negative
+            },
+            from: (x1, y1),
+            to: (x2, y2),
+        })
+    }
+
     fn closepath() -> PathCommand {
         PathCommand::ClosePath
     }
@@ -1305,6 +1343,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn handles_relative_moveto_after_closepath_with_implicit_linetos() {
+        // After a closepath, the current point is the start of the closed subpath, so
+        // both the moveto and its implicit linetos are relative to it.
+        test_parser(
+            "M10 20 L30 40 z m5 5 10 10 10 0",
+            "",
+            &vec![
+                moveto(10.0, 20.0),
+                lineto(30.0, 40.0),
+                closepath(),
+                moveto(15.0, 25.0),
+                lineto(25.0, 35.0),
+                lineto(35.0, 35.0),
+            ],
+            None,
+        );
+    }
+
     #[test]
     fn handles_absolute_moveto_moveto() {
         test_parser(
@@ -2176,13 +2233,35 @@ mod tests {
             Some(ErrorKind::UnexpectedEof),
         );
 
-        // FIXME: we need tests for arcs
-        //
-        // test_parser("M10-20A1 2 3,1,1,6,7,",
-        //             "                     ^",
-        //             &vec![moveto(10.0, -20.0)
-        //                   arc(...)],
-        //             Some(ErrorKind::UnexpectedEof));
+        test_parser(
+            "M10-20A1 2 3,1,1,6,7,",
+            "                     ^",
+            &vec![
+                moveto(10.0, -20.0),
+                arc(10.0, -20.0, 1.0, 2.0, 3.0, true, true, 6.0, 7.0),
+            ],
+            Some(ErrorKind::UnexpectedEof),
+        );
+    }
+
+    #[test]
+    fn handles_arc_flags_without_separators() {
+        let expected = vec![
+            moveto(10.0, 20.0),
+            arc(10.0, 20.0, 20.0, 20.0, 0.0, true, false, 40.0, 20.0),
+        ];
+
+        test_parser("M10 20 a20 20 0 1 0 30 0", "", &expected, None);
+        test_parser("M10 20 a20,20,0,1,0,30,0", "", &expected, None);
+        test_parser("M10 20 a20,20,0,10,30,0", "", &expected, None);
+        test_parser("M10 20 a20 20 0 1030 0", "", &expected, None);
+
+        let expected = vec![
+            moveto(10.0, 20.0),
+            arc(10.0, 20.0, 20.0, 20.0, 0.0, true, false, -20.0, 20.0),
+        ];
+
+        test_parser("M10 20 a20 20 0 10-30-0", "", &expected, None);
     }
 
     #[test]
@@ -2195,4 +2274,71 @@ mod tests {
             Some(ErrorKind::UnexpectedToken),
         );
     }
+
+    #[test]
+    fn serializes_parsed_path() {
+        let path = "m10 20 h 5 v-5 c 1 2 3 4 5 6 a 10 20 30 1 0 40 50 z";
+
+        assert_eq!(
+            parse_and_serialize_path(path).as_ref().map(String::as_str),
+            Some("M 10 20 L 15 20 L 15 15 C 16 17 18 19 20 21 A 10 20 30 1 0 60 71 Z")
+        );
+        assert_eq!(
+            parse_and_serialize_path("").as_ref().map(String::as_str),
+            Some("")
+        );
+        assert_eq!(parse_and_serialize_path("M 10 20 L"), None);
+    }
+
+    fn coord() -> impl Strategy<Value = f64> {
+        -1000.0..1000.0f64
+    }
+
+    // Produces a single path command with absolute or relative coordinates.
+    fn command_strategy() -> impl Strategy<Value = String> {
+        prop_oneof![
+            (coord(), coord()).prop_map(|(x, y)| format!("M {} {}", x, y)),
+            (coord(), coord()).prop_map(|(x, y)| format!("l {} {}", x, y)),
+            coord().prop_map(|x| format!("H {}", x)),
+            coord().prop_map(|y| format!("v {}", y)),
+            (coord(), coord(), coord(), coord(), coord(), coord())
+                .prop_map(|(a, b, c, d, e, f)| format!("C {} {} {} {} {} {}", a, b, c, d, e, f)),
+            (coord(), coord(), coord(), coord())
+                .prop_map(|(a, b, c, d)| format!("s {} {} {} {}", a, b, c, d)),
+            (coord(), coord(), coord(), coord())
+                .prop_map(|(a, b, c, d)| format!("Q {} {} {} {}", a, b, c, d)),
+            (coord(), coord()).prop_map(|(x, y)| format!("t {} {}", x, y)),
+            (coord(), coord(), coord(), any::<bool>(), any::<bool>(), coord(), coord())
+                .prop_map(|(rx, ry, rot, large, sweep, x, y)| {
+                    format!("a {} {} {} {} {} {} {}", rx, ry, rot, large as u8, sweep as u8, x, y)
+                }),
+            Just("Z".to_string()),
+        ]
+    }
+
+    fn path_data_strategy() -> impl Strategy<Value = String> {
+        (coord(), coord(), prop::collection::vec(command_strategy(), 0..20))
+            .prop_map(|(x, y, commands)| format!("M {} {} {}", x, y, commands.join(" ")))
+    }
+
+    proptest! {
+        #[test]
+        fn valid_paths_have_finite_measurements(path_str in path_data_strategy()) {
+            let mut builder = PathBuilder::new();
+            prop_assert_eq!(parse_path_into_builder(&path_str, &mut builder), Ok(()));
+            let path = builder.into_path();
+
+            prop_assert!(path.path_length().is_finite());
+
+            if let Some(bbox) = path.bounding_box() {
+                prop_assert!(bbox.x0.is_finite() && bbox.y0.is_finite());
+                prop_assert!(bbox.x1.is_finite() && bbox.y1.is_finite());
+            }
+
+            let serialized = path.to_string();
+            let mut builder = PathBuilder::new();
+            prop_assert_eq!(parse_path_into_builder(&serialized, &mut builder), Ok(()));
+            prop_assert_eq!(builder.into_path().iter().count(), path.iter().count());
+        }
+    }
 }
@@ -14,11 +14,13 @@ use crate::element::{Draw, Element, ElementResult, SetAttributes};
 use crate::error::*;
 use crate::float_eq_cairo::ApproxEqCairo;
 use crate::length::*;
+use crate::limits;
 use crate::node::{CascadedValues, Node, NodeBorrow, NodeDraw, WeakNode};
 use crate::paint_server::{AsPaintSource, PaintSource};
 use crate::parsers::ParseValue;
 use crate::properties::ComputedValues;
 use crate::property_bag::PropertyBag;
+use crate::property_defs::Overflow;
 use crate::rect::Rect;
 use crate::transform::Transform;
 use crate::unit_interval::UnitInterval;
@@ -187,6 +189,10 @@ impl PaintSource for Pattern {
                             return Err(AcquireError::CircularReference(acquired_node.clone()));
                         }
 
+                        if stack.len() >= limits::MAX_REFERENCE_DEPTH {
+                            return Err(AcquireError::MaxDepthExceeded);
+                        }
+
                         match *acquired_node.borrow_element() {
                             Element::Pattern(ref p) => {
                                 let unresolved = p.get_unresolved(&acquired_node);
@@ -203,8 +209,12 @@ impl PaintSource for Pattern {
                         return Err(AcquireError::MaxReferencesExceeded)
                     }
 
+                    Err(AcquireError::MaxDepthExceeded) => {
+                        return Err(AcquireError::MaxDepthExceeded)
+                    }
+
                     Err(e) => {
-                        rsvg_log!("Stopping pattern resolution: {}", e);
+                        rsvg_log!(Warn, "Stopping pattern resolution: {}", e);
                         pattern = pattern.resolve_from_defaults();
                         break;
                     }
@@ -334,25 +344,42 @@ impl AsPaintSource for ResolvedPattern {
             affine = affine.pre_scale(1.0 / scwscale, 1.0 / schscale);
         }
 
+        let pattern_cascaded = CascadedValues::new_from_node(&node);
+        let pattern_values = pattern_cascaded.get();
+
+        // With overflow="visible", the contents of a tile may bleed into the neighboring
+        // tiles.  In that case we draw the contents with room for one extra tile on each
+        // side, and then wrap that around into a single tile.  Contents that extend
+        // farther than that get clipped.
+        let margin = if pattern_values.overflow() == Overflow::Visible {
+            1
+        } else {
+            0
+        };
+
         // Draw to another surface
 
         let cr_save = draw_ctx.get_cairo_context();
 
-        let surface = cr_save
-            .get_target()
-            .create_similar(cairo::Content::ColorAlpha, pw, ph)?;
+        let surface = cr_save.get_target().create_similar(
+            cairo::Content::ColorAlpha,
+            pw * (2 * margin + 1),
+            ph * (2 * margin + 1),
+        )?;
 
         let cr_pattern = cairo::Context::new(&surface);
 
         draw_ctx.set_cairo_context(&cr_pattern);
 
         // Set up transformations to be determined by the contents units
-        cr_pattern.set_matrix(caffine.into());
+        cr_pattern.set_matrix(
+            caffine
+                .post_translate(f64::from(margin * pw), f64::from(margin * ph))
+                .into(),
+        );
 
         // Draw everything
         let res = draw_ctx.with_alpha(opacity, &mut |dc| {
-            let pattern_cascaded = CascadedValues::new_from_node(&node);
-            let pattern_values = pattern_cascaded.get();
             dc.with_discrete_layer(
                 &node,
                 acquired_nodes,
@@ -365,6 +392,25 @@ impl AsPaintSource for ResolvedPattern {
         // Return to the original coordinate system and rendering context
         draw_ctx.set_cairo_context(&cr_save);
 
+        let surface = if margin == 0 {
+            surface
+        } else {
+            let tile = cr_save
+                .get_target()
+                .create_similar(cairo::Content::ColorAlpha, pw, ph)?;
+
+            let cr_tile = cairo::Context::new(&tile);
+
+            for i in 0..=2 * margin {
+                for j in 0..=2 * margin {
+                    cr_tile.set_source_surface(&surface, -f64::from(i * pw), -f64::from(j * ph));
+                    cr_tile.paint();
+                }
+            }
+
+            tile
+        };
+
         // Set the final surface as a Cairo pattern into the Cairo context
         let pattern = cairo::SurfacePattern::create(&surface);
 
@@ -563,6 +609,7 @@ impl Pattern {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diagnostics::Diagnostics;
     use crate::node::NodeData;
     use markup5ever::{namespace_url, ns, QualName};
     use std::ptr;
@@ -574,6 +621,7 @@ mod tests {
         let node = Node::new(NodeData::new_element(
             &QualName::new(None, ns!(svg), local_name!("pattern")),
             &bag,
+            &mut Diagnostics::new(),
         ));
 
         let unresolved = borrow_element_as!(node, Pattern).get_unresolved(&node);
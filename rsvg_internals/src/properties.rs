@@ -3,10 +3,12 @@
 use cssparser::{
     self, BasicParseErrorKind, DeclarationListParser, ParseErrorKind, Parser, ParserInput, ToCss,
 };
-use markup5ever::{expanded_name, local_name, namespace_url, ns, QualName};
+use markup5ever::{expanded_name, local_name, namespace_url, ns, LocalName, QualName};
 use std::collections::HashSet;
+use std::fmt;
 
 use crate::css::{DeclParser, Declaration, Origin};
+use crate::diagnostics::Diagnostics;
 use crate::error::*;
 use crate::parsers::{Parse, ParseValue};
 use crate::property_bag::PropertyBag;
@@ -56,6 +58,22 @@ where
     }
 }
 
+impl<T> ToCss for SpecifiedValue<T>
+where
+    T: Property<ComputedValues> + Clone + Default + ToCss,
+{
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        match *self {
+            SpecifiedValue::Unspecified => Ok(()),
+            SpecifiedValue::Inherit => dest.write_str("inherit"),
+            SpecifiedValue::Specified(ref v) => v.to_css(dest),
+        }
+    }
+}
+
 impl PropertyId {
     fn as_u8(&self) -> u8 {
         *self as u8
@@ -84,11 +102,12 @@ impl Default for SpecifiedValues {
 }
 
 impl ComputedValues {
+    /// Whether content is allowed to overflow the element's viewport.
+    ///
+    /// SVG elements have no scrolling mechanism, so `clip`, `auto` and `scroll`
+    /// clip just like `hidden`; only `visible` lets content overflow.
     pub fn is_overflow(&self) -> bool {
-        match self.overflow() {
-            Overflow::Auto | Overflow::Visible => true,
-            _ => false,
-        }
+        self.overflow() == Overflow::Visible
     }
 
     pub fn is_visible(&self) -> bool {
@@ -108,6 +127,7 @@ impl ComputedValues {
 /// * `ParsedProperty`, a variant enum for all the specified property values.
 /// * `ComputedValue`, a variant enum for all the computed values.
 /// * `parse_property`, the main function to parse a property declaration from user input.
+/// * `PROPERTY_NAMES`, the names of all the properties that `parse_property` knows about.
 ///
 /// There is a lot of repetitive code, for example, because sometimes
 /// we need to operate on `PropertyId::Foo`, `ParsedProperty::Foo` and
@@ -121,10 +141,18 @@ macro_rules! make_properties {
             $($short_str:tt => $short_field:ident: $short_name:ident,)*
         }
 
+        shorthands_not_supported_by_markup5ever: {
+            $($nssma_str:tt => $nssma_field:ident: $nssma_name:ident,)+
+        }
+
         longhands: {
             $($long_str:tt => $long_field:ident: $long_name:ident,)+
         }
 
+        longhands_not_supported_by_markup5ever: {
+            $($nsma_str:tt => $nsma_field:ident: $nsma_name:ident,)+
+        }
+
         non_properties: {
             $($nonprop_field:ident: $nonprop_name:ident,)+
         }
@@ -138,7 +166,9 @@ macro_rules! make_properties {
         #[derive(Copy, Clone, PartialEq)]
         enum PropertyId {
             $($short_name,)+
+            $($nssma_name,)+
             $($long_name,)+
+            $($nsma_name,)+
             $($nonprop_name,)+
 
             UnsetProperty,
@@ -148,6 +178,7 @@ macro_rules! make_properties {
             fn is_shorthand(self) -> bool {
                 match self {
                     $(PropertyId::$short_name => true,)+
+                    $(PropertyId::$nssma_name => true,)+
                     _ => false,
                 }
             }
@@ -158,15 +189,44 @@ macro_rules! make_properties {
         pub enum ParsedProperty {
             // we put all the properties here; these are for SpecifiedValues
             $($short_name(SpecifiedValue<$short_name>),)+
+            $($nssma_name(SpecifiedValue<$nssma_name>),)+
             $($long_name(SpecifiedValue<$long_name>),)+
+            $($nsma_name(SpecifiedValue<$nsma_name>),)+
             $($nonprop_name(SpecifiedValue<$nonprop_name>),)+
         }
 
+        impl ToCss for ParsedProperty {
+            fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+            where
+                W: fmt::Write,
+            {
+                match *self {
+                    $(ParsedProperty::$short_name(ref v) => v.to_css(dest),)+
+                    $(ParsedProperty::$nssma_name(ref v) => v.to_css(dest),)+
+                    $(ParsedProperty::$long_name(ref v) => v.to_css(dest),)+
+                    $(ParsedProperty::$nsma_name(ref v) => v.to_css(dest),)+
+                    $(ParsedProperty::$nonprop_name(ref v) => v.to_css(dest),)+
+                }
+            }
+        }
+
+        /// Names of all the properties, including shorthands, that `parse_property` accepts.
+        pub const PROPERTY_NAMES: &[&str] = &[
+            $($short_str,)+
+            $($nssma_str,)+
+            $($long_str,)+
+            $($nsma_str,)+
+        ];
+
         enum ComputedValue {
             $(
                 $long_name($long_name),
             )+
 
+            $(
+                $nsma_name($nsma_name),
+            )+
+
             $(
                 $nonprop_name($nonprop_name),
             )+
@@ -178,6 +238,10 @@ macro_rules! make_properties {
                 $long_field: $long_name,
             )+
 
+            $(
+                $nsma_field: $nsma_name,
+            )+
+
             $(
                 $nonprop_field: $nonprop_name,
             )+
@@ -187,7 +251,9 @@ macro_rules! make_properties {
             fn get_property_id(&self) -> PropertyId {
                 match *self {
                     $(ParsedProperty::$long_name(_) => PropertyId::$long_name,)+
+                    $(ParsedProperty::$nsma_name(_) => PropertyId::$nsma_name,)+
                     $(ParsedProperty::$short_name(_) => PropertyId::$short_name,)+
+                    $(ParsedProperty::$nssma_name(_) => PropertyId::$nssma_name,)+
                     $(ParsedProperty::$nonprop_name(_) => PropertyId::$nonprop_name,)+
                 }
             }
@@ -197,14 +263,47 @@ macro_rules! make_properties {
 
                 match id {
                     $(PropertyId::$long_name => ParsedProperty::$long_name(Unspecified),)+
+                    $(PropertyId::$nsma_name => ParsedProperty::$nsma_name(Unspecified),)+
                     $(PropertyId::$short_name => ParsedProperty::$short_name(Unspecified),)+
+                    $(PropertyId::$nssma_name => ParsedProperty::$nssma_name(Unspecified),)+
                     $(PropertyId::$nonprop_name => ParsedProperty::$nonprop_name(Unspecified),)+
 
                     PropertyId::UnsetProperty => unreachable!(),
                 }
             }
+
+            /// Creates an `inherit` value for the longhand property `id`.
+            fn inherit(id: PropertyId) -> Self {
+                use SpecifiedValue::Inherit;
+
+                match id {
+                    $(PropertyId::$long_name => ParsedProperty::$long_name(Inherit),)+
+                    $(PropertyId::$nsma_name => ParsedProperty::$nsma_name(Inherit),)+
+
+                    _ => unreachable!(),
+                }
+            }
+
+            /// Creates a value for the longhand property `id` with its initial value.
+            fn initial(id: PropertyId) -> Self {
+                use SpecifiedValue::Specified;
+
+                match id {
+                    $(PropertyId::$long_name => ParsedProperty::$long_name(Specified(Default::default())),)+
+                    $(PropertyId::$nsma_name => ParsedProperty::$nsma_name(Specified(Default::default())),)+
+
+                    _ => unreachable!(),
+                }
+            }
         }
 
+        /// All the longhand properties, i.e. those which are not shorthands
+        /// nor XML attributes.
+        const LONGHANDS: &[PropertyId] = &[
+            $(PropertyId::$long_name,)+
+            $(PropertyId::$nsma_name,)+
+        ];
+
         impl ComputedValues {
             $(
                 pub fn $long_field(&self) -> $long_name {
@@ -216,6 +315,16 @@ macro_rules! make_properties {
                 }
             )+
 
+            $(
+                pub fn $nsma_field(&self) -> $nsma_name {
+                    if let ComputedValue::$nsma_name(v) = self.get_value(PropertyId::$nsma_name) {
+                        v
+                    } else {
+                        unreachable!();
+                    }
+                }
+            )+
+
             $(
                 pub fn $nonprop_field(&self) -> $nonprop_name {
                     if let ComputedValue::$nonprop_name(v) = self.get_value(PropertyId::$nonprop_name) {
@@ -229,6 +338,7 @@ macro_rules! make_properties {
             fn set_value(&mut self, computed: ComputedValue) {
                 match computed {
                     $(ComputedValue::$long_name(v) => self.$long_field = v,)+
+                    $(ComputedValue::$nsma_name(v) => self.$nsma_field = v,)+
                     $(ComputedValue::$nonprop_name(v) => self.$nonprop_field = v,)+
                 }
             }
@@ -241,6 +351,10 @@ macro_rules! make_properties {
                         PropertyId::$long_name =>
                             ComputedValue::$long_name(self.$long_field.clone()),
                     )+
+                    $(
+                        PropertyId::$nsma_name =>
+                            ComputedValue::$nsma_name(self.$nsma_field.clone()),
+                    )+
                     $(
                         PropertyId::$nonprop_name =>
                             ComputedValue::$nonprop_name(self.$nonprop_field.clone()),
@@ -273,11 +387,51 @@ macro_rules! make_properties {
                 )+
 
                 _ => {
+                    // Property names that are not static atoms in markup5ever
+                    // can't be matched with expanded_name!(), so compare them
+                    // as strings.
+                    if prop_name.ns == ns!() {
+                        match prop_name.local.as_ref() {
+                            $(
+                                $nsma_str => {
+                                    return Ok(ParsedProperty::$nsma_name(parse_input(input)?));
+                                }
+                            )+
+
+                            $(
+                                $nssma_str if accept_shorthands => {
+                                    return Ok(ParsedProperty::$nssma_name(parse_input(input)?));
+                                }
+                            )+
+
+                            _ => (),
+                        }
+                    }
+
                     let loc = input.current_source_location();
                     Err(loc.new_custom_error(ValueErrorKind::UnknownProperty))
                 }
             }
         }
+
+        /// Whether an attribute is one that `parse_presentation_attributes()` uses.
+        pub fn is_presentation_attribute(attr: &QualName) -> bool {
+            match attr.expanded() {
+                expanded_name!(xml "lang") | expanded_name!(xml "space") => true,
+
+                $(
+                    expanded_name!("", $long_str) => true,
+                )+
+
+                _ => attr.ns == ns!() && match attr.local.as_ref() {
+                    $(
+                        $nsma_str => true,
+                    )+
+
+                    _ => false,
+                },
+            }
+        }
     };
 }
 
@@ -287,11 +441,17 @@ make_properties! {
         "marker" => marker: Marker,
     }
 
+    // Like longhands_not_supported_by_markup5ever below, but for shorthands.
+    shorthands_not_supported_by_markup5ever: {
+        "all" => all: All,
+    }
+
     longhands: {
         "baseline-shift"              => baseline_shift              : BaselineShift,
         "clip-path"                   => clip_path                   : ClipPath,
         "clip-rule"                   => clip_rule                   : ClipRule,
         "color"                       => color                       : Color,
+        "color-interpolation"         => color_interpolation         : ColorInterpolation,
         "color-interpolation-filters" => color_interpolation_filters : ColorInterpolationFilters,
         "direction"                   => direction                   : Direction,
         "display"                     => display                     : Display,
@@ -304,6 +464,7 @@ make_properties! {
         "flood-opacity"               => flood_opacity               : FloodOpacity,
         "font-family"                 => font_family                 : FontFamily,
         "font-size"                   => font_size                   : FontSize,
+        "font-size-adjust"            => font_size_adjust            : FontSizeAdjust,
         "font-stretch"                => font_stretch                : FontStretch,
         "font-style"                  => font_style                  : FontStyle,
         "font-variant"                => font_variant                : FontVariant,
@@ -335,6 +496,12 @@ make_properties! {
         "writing-mode"                => writing_mode                : WritingMode,
     }
 
+    // These are properties whose names are not known to markup5ever as
+    // local names, so they need to be parsed by string comparison.
+    longhands_not_supported_by_markup5ever: {
+        "backdrop-filter"             => backdrop_filter             : BackdropFilter,
+    }
+
     // These are not properties, but presentation attributes.  However,
     // both xml:lang and xml:space *do* inherit.  We are abusing the
     // property inheritance code for these XML-specific attributes.
@@ -384,20 +551,48 @@ impl SpecifiedValues {
         use crate::properties as p;
         use crate::properties::ParsedProperty::*;
 
-        if let Marker(SpecifiedValue::Specified(p::Marker(ref v))) = *prop {
+        if let All(ref v) = *prop {
+            // "all" expands to every property except for direction and unicode-bidi
+            // https://www.w3.org/TR/css-cascade-3/#all-shorthand
+            for &id in LONGHANDS
+                .iter()
+                .filter(|&&id| id != PropertyId::Direction && id != PropertyId::UnicodeBidi)
+            {
+                let longhand = match *v {
+                    SpecifiedValue::Unspecified | SpecifiedValue::Specified(p::All::Unset) => {
+                        ParsedProperty::unspecified(id)
+                    }
+                    SpecifiedValue::Inherit => ParsedProperty::inherit(id),
+                    SpecifiedValue::Specified(p::All::Initial) => ParsedProperty::initial(id),
+                };
+
+                self.set_property(&longhand, replace);
+            }
+        } else if let Marker(ref v) = *prop {
             // Since "marker" is a shorthand property, we'll just expand it here
-            self.set_property(
-                &MarkerStart(SpecifiedValue::Specified(p::MarkerStart(v.clone()))),
-                replace,
-            );
-            self.set_property(
-                &MarkerMid(SpecifiedValue::Specified(p::MarkerMid(v.clone()))),
-                replace,
-            );
-            self.set_property(
-                &MarkerEnd(SpecifiedValue::Specified(p::MarkerEnd(v.clone()))),
-                replace,
-            );
+            let (start, mid, end) = match *v {
+                SpecifiedValue::Unspecified => (
+                    SpecifiedValue::Unspecified,
+                    SpecifiedValue::Unspecified,
+                    SpecifiedValue::Unspecified,
+                ),
+
+                SpecifiedValue::Inherit => (
+                    SpecifiedValue::Inherit,
+                    SpecifiedValue::Inherit,
+                    SpecifiedValue::Inherit,
+                ),
+
+                SpecifiedValue::Specified(p::Marker(ref iri)) => (
+                    SpecifiedValue::Specified(p::MarkerStart(iri.clone())),
+                    SpecifiedValue::Specified(p::MarkerMid(iri.clone())),
+                    SpecifiedValue::Specified(p::MarkerEnd(iri.clone())),
+                ),
+            };
+
+            self.set_property(&MarkerStart(start), replace);
+            self.set_property(&MarkerMid(mid), replace);
+            self.set_property(&MarkerEnd(end), replace);
         } else {
             self.set_property(prop, replace);
         }
@@ -435,10 +630,12 @@ impl SpecifiedValues {
 
         // Then, do all the other properties.
 
+        compute!(BackdropFilter, backdrop_filter);
         compute!(BaselineShift, baseline_shift);
         compute!(ClipPath, clip_path);
         compute!(ClipRule, clip_rule);
         compute!(Color, color);
+        compute!(ColorInterpolation, color_interpolation);
         compute!(ColorInterpolationFilters, color_interpolation_filters);
         compute!(Direction, direction);
         compute!(Display, display);
@@ -450,6 +647,7 @@ impl SpecifiedValues {
         compute!(FloodColor, flood_color);
         compute!(FloodOpacity, flood_opacity);
         compute!(FontFamily, font_family);
+        compute!(FontSizeAdjust, font_size_adjust);
         compute!(FontStretch, font_stretch);
         compute!(FontStyle, font_style);
         compute!(FontVariant, font_variant);
@@ -486,7 +684,6 @@ impl SpecifiedValues {
     pub fn is_overflow(&self) -> bool {
         if let Some(overflow_index) = self.property_index(PropertyId::Overflow) {
             match self.props[overflow_index] {
-                ParsedProperty::Overflow(SpecifiedValue::Specified(Overflow::Auto)) => true,
                 ParsedProperty::Overflow(SpecifiedValue::Specified(Overflow::Visible)) => true,
                 ParsedProperty::Overflow(_) => false,
                 _ => unreachable!(),
@@ -500,6 +697,7 @@ impl SpecifiedValues {
         &mut self,
         attr: QualName,
         value: &str,
+        diagnostics: &mut Diagnostics,
     ) -> Result<(), ElementError> {
         let mut input = ParserInput::new(value);
         let mut parser = Parser::new(&mut input);
@@ -507,14 +705,17 @@ impl SpecifiedValues {
         // Presentation attributes don't accept shorthands, e.g. there is no
         // attribute like marker="#foo" and it needs to be set in the style attribute
         // like style="marker: #foo;".  So, pass false for accept_shorthands here.
-        match parse_property(&attr, &mut parser, false) {
-            Ok(prop) => self.set_parsed_property(&prop),
+        let reason = match parse_property(&attr, &mut parser, false) {
+            Ok(prop) => {
+                self.set_parsed_property(&prop);
+                return Ok(());
+            }
 
             // not a presentation attribute; just ignore it
             Err(ParseError {
                 kind: ParseErrorKind::Custom(ValueErrorKind::UnknownProperty),
                 ..
-            }) => (),
+            }) => return Ok(()),
 
             // https://www.w3.org/TR/CSS2/syndata.html#unsupported-values
             // For all the following cases, ignore illegal values; don't set the whole node to
@@ -526,51 +727,34 @@ impl SpecifiedValues {
                 let mut tok = String::new();
 
                 t.to_css(&mut tok).unwrap(); // FIXME: what do we do with a fmt::Error?
-                rsvg_log!(
-                    "(ignoring invalid presentation attribute {:?}\n    value=\"{}\"\n    \
-                     unexpected token '{}')",
-                    attr.expanded(),
-                    value,
-                    tok,
-                );
+                format!("unexpected token '{}'", tok)
             }
 
             Err(ParseError {
                 kind: ParseErrorKind::Basic(BasicParseErrorKind::EndOfInput),
                 ..
-            }) => {
-                rsvg_log!(
-                    "(ignoring invalid presentation attribute {:?}\n    value=\"{}\"\n    \
-                     unexpected end of input)",
-                    attr.expanded(),
-                    value,
-                );
-            }
+            }) => String::from("unexpected end of input"),
 
             Err(ParseError {
                 kind: ParseErrorKind::Basic(_),
                 ..
-            }) => {
-                rsvg_log!(
-                    "(ignoring invalid presentation attribute {:?}\n    value=\"{}\"\n    \
-                     unexpected error)",
-                    attr.expanded(),
-                    value,
-                );
-            }
+            }) => String::from("unexpected error"),
 
             Err(ParseError {
                 kind: ParseErrorKind::Custom(ref v),
                 ..
-            }) => {
-                rsvg_log!(
-                    "(ignoring invalid presentation attribute {:?}\n    value=\"{}\"\n    {})",
-                    attr.expanded(),
-                    value,
-                    v
-                );
-            }
-        }
+            }) => v.to_string(),
+        };
+
+        diagnostics.warning(
+            format!(
+                "ignoring invalid presentation attribute {:?} value=\"{}\": {}",
+                attr.expanded(),
+                value,
+                reason
+            ),
+            None,
+        );
 
         Ok(())
     }
@@ -578,6 +762,7 @@ impl SpecifiedValues {
     pub fn parse_presentation_attributes(
         &mut self,
         pbag: &PropertyBag<'_>,
+        diagnostics: &mut Diagnostics,
     ) -> Result<(), ElementError> {
         for (attr, value) in pbag.iter() {
             match attr.expanded() {
@@ -599,7 +784,7 @@ impl SpecifiedValues {
                     )));
                 }
 
-                _ => self.parse_one_presentation_attribute(attr, value)?,
+                _ => self.parse_one_presentation_attribute(attr, value, diagnostics)?,
             }
         }
 
@@ -632,6 +817,7 @@ impl SpecifiedValues {
         declarations: &str,
         origin: Origin,
         important_styles: &mut HashSet<QualName>,
+        diagnostics: &mut Diagnostics,
     ) -> Result<(), ElementError> {
         let mut input = ParserInput::new(declarations);
         let mut parser = Parser::new(&mut input);
@@ -639,8 +825,16 @@ impl SpecifiedValues {
         DeclarationListParser::new(&mut parser, DeclParser)
             .filter_map(|r| match r {
                 Ok(decl) => Some(decl),
-                Err(e) => {
-                    rsvg_log!("Invalid declaration; ignoring: {:?}", e);
+                Err((e, slice)) => {
+                    let reason = match e.kind {
+                        ParseErrorKind::Custom(ref v) => v.to_string(),
+                        ParseErrorKind::Basic(ref b) => format!("{:?}", b),
+                    };
+
+                    diagnostics.warning(
+                        format!("ignoring invalid declaration \"{}\": {}", slice, reason),
+                        Some(e.location.into()),
+                    );
                     None
                 }
             })
@@ -650,6 +844,26 @@ impl SpecifiedValues {
     }
 }
 
+/// Parses the value of a property given its name, and serializes it back to a string.
+///
+/// The value must be a complete declaration value, as in a `style` attribute;
+/// shorthands are accepted.  Returns `None` if the property name is unknown or if
+/// the value is invalid.
+///
+/// This is used by the fuzzer and the round-trip tests.
+#[doc(hidden)]
+pub fn parse_and_serialize_property(name: &str, value: &str) -> Option<String> {
+    let prop_name = QualName::new(None, ns!(), LocalName::from(name));
+
+    let mut input = ParserInput::new(value);
+    let mut parser = Parser::new(&mut input);
+
+    parser
+        .parse_entirely(|p| parse_property(&prop_name, p, true))
+        .ok()
+        .map(|prop| prop.to_css_string())
+}
+
 // Parses the value for the type `T` of the property out of the Parser, including `inherit` values.
 fn parse_input<'i, T>(input: &mut Parser<'i, '_>) -> Result<SpecifiedValue<T>, ParseError<'i>>
 where
@@ -670,6 +884,7 @@ mod tests {
     use super::*;
     use crate::iri::IRI;
     use crate::length::*;
+    use proptest::prelude::*;
 
     #[test]
     fn empty_values_computes_to_defaults() {
@@ -753,6 +968,126 @@ mod tests {
         assert_eq!(computed.marker_end(), MarkerEnd(iri2.clone()));
     }
 
+    #[test]
+    fn expands_marker_shorthand_with_inherit() {
+        let iri = IRI::parse_str("url(#foo)").unwrap();
+
+        let mut parent = SpecifiedValues::default();
+        parent.set_parsed_property(&ParsedProperty::Marker(SpecifiedValue::Specified(Marker(
+            iri.clone(),
+        ))));
+
+        let mut parent_computed = ComputedValues::default();
+        parent.to_computed_values(&mut parent_computed);
+
+        let mut child = SpecifiedValues::default();
+        let mut important_styles = HashSet::new();
+        let mut diagnostics = Diagnostics::new();
+
+        child
+            .parse_style_declarations(
+                "marker: inherit",
+                Origin::Author,
+                &mut important_styles,
+                &mut diagnostics,
+            )
+            .unwrap();
+
+        let mut child_computed = parent_computed.clone();
+        child.to_computed_values(&mut child_computed);
+
+        assert_eq!(child_computed.marker_start(), MarkerStart(iri.clone()));
+        assert_eq!(child_computed.marker_mid(), MarkerMid(iri.clone()));
+        assert_eq!(child_computed.marker_end(), MarkerEnd(iri.clone()));
+    }
+
+    #[test]
+    fn marker_inherit_overrides_previous_markers() {
+        let iri = IRI::parse_str("url(#foo)").unwrap();
+        let other = IRI::parse_str("url(#bar)").unwrap();
+
+        let mut parent = SpecifiedValues::default();
+        parent.set_parsed_property(&ParsedProperty::Marker(SpecifiedValue::Specified(Marker(
+            iri.clone(),
+        ))));
+
+        let mut parent_computed = ComputedValues::default();
+        parent.to_computed_values(&mut parent_computed);
+
+        let mut child = SpecifiedValues::default();
+        child.set_parsed_property(&ParsedProperty::MarkerMid(SpecifiedValue::Specified(
+            MarkerMid(other),
+        )));
+        child.set_parsed_property(&ParsedProperty::Marker(SpecifiedValue::Inherit));
+
+        let mut child_computed = parent_computed.clone();
+        child.to_computed_values(&mut child_computed);
+
+        assert_eq!(child_computed.marker_mid(), MarkerMid(iri.clone()));
+    }
+
+    fn compute_with_style(parent: &ComputedValues, style: &str) -> ComputedValues {
+        let mut specified = SpecifiedValues::default();
+        let mut important_styles = HashSet::new();
+        let mut diagnostics = Diagnostics::new();
+
+        specified
+            .parse_style_declarations(
+                style,
+                Origin::Author,
+                &mut important_styles,
+                &mut diagnostics,
+            )
+            .unwrap();
+
+        let mut computed = parent.clone();
+        specified.to_computed_values(&mut computed);
+        computed
+    }
+
+    #[test]
+    fn all_shorthand_expands_to_every_property() {
+        let parent = compute_with_style(
+            &ComputedValues::default(),
+            "color: red; opacity: 0.5; direction: rtl",
+        );
+
+        let red = Color(cssparser::RGBA::new(255, 0, 0, 255));
+        let half_opacity = Opacity::parse_str("0.5").unwrap();
+
+        let initial = compute_with_style(&parent, "all: initial");
+        assert_eq!(initial.color(), Color::default());
+        assert_eq!(initial.opacity(), Opacity::default());
+
+        let inherit = compute_with_style(&parent, "all: inherit");
+        assert_eq!(inherit.color(), red);
+        assert_eq!(inherit.opacity(), half_opacity);
+
+        let unset = compute_with_style(&parent, "all: unset");
+        assert_eq!(unset.color(), red);
+        assert_eq!(unset.opacity(), Opacity::default());
+
+        // direction is not affected by "all"
+        assert_eq!(initial.direction(), Direction::Rtl);
+    }
+
+    #[test]
+    fn all_shorthand_is_overridden_by_later_declarations() {
+        let parent = compute_with_style(&ComputedValues::default(), "color: red");
+
+        let computed = compute_with_style(&parent, "all: initial; color: blue");
+        assert_eq!(
+            computed.color(),
+            Color(cssparser::RGBA::new(0, 0, 255, 255))
+        );
+
+        let computed = compute_with_style(&parent, "color: blue; all: inherit");
+        assert_eq!(
+            computed.color(),
+            Color(cssparser::RGBA::new(255, 0, 0, 255))
+        );
+    }
+
     #[test]
     fn computes_property_that_does_not_inherit_automatically() {
         assert_eq!(
@@ -793,4 +1128,163 @@ mod tests {
 
         assert_eq!(computed.opacity(), half_opacity.clone());
     }
+
+    #[test]
+    fn invalid_stroke_miterlimit_computes_to_default() {
+        let mut specified = SpecifiedValues::default();
+        let mut important_styles = HashSet::new();
+        let mut diagnostics = Diagnostics::new();
+
+        specified
+            .parse_style_declarations(
+                "stroke-miterlimit: 0.5",
+                Origin::Author,
+                &mut important_styles,
+                &mut diagnostics,
+            )
+            .unwrap();
+
+        let mut computed = ComputedValues::default();
+        specified.to_computed_values(&mut computed);
+
+        assert_eq!(computed.stroke_miterlimit(), StrokeMiterlimit::default());
+        assert_eq!(computed.stroke_miterlimit(), StrokeMiterlimit(4.0));
+    }
+
+    #[test]
+    fn serializes_parsed_properties() {
+        for &(name, value, expected) in &[
+            ("stroke-width", "2px", "2"),
+            ("stroke-linejoin", "ROUND", "round"),
+            ("fill", "inherit", "inherit"),
+            ("fill", "url(#foo) none", "url(\"#foo\")"),
+            ("stroke-dasharray", "1 2,3%", "1, 2, 3%"),
+            ("font-family", "Hello world", "\"Hello world\""),
+            ("font-weight", "700", "700"),
+            ("font-size-adjust", "0.5", "0.5"),
+            ("font-size-adjust", "none", "none"),
+            ("baseline-shift", "sub", "-20%"),
+            (
+                "text-decoration",
+                "line-through underline",
+                "underline line-through",
+            ),
+            ("marker", "none", "none"),
+            ("backdrop-filter", "blur(2px)", "blur(2)"),
+            ("all", "INITIAL", "initial"),
+            ("all", "inherit", "inherit"),
+        ] {
+            assert_eq!(
+                parse_and_serialize_property(name, value)
+                    .as_ref()
+                    .map(String::as_str),
+                Some(expected)
+            );
+        }
+
+        assert_eq!(
+            parse_and_serialize_property("stroke-width", "2px 3px"),
+            None
+        );
+        assert_eq!(parse_and_serialize_property("not-a-property", "2px"), None);
+    }
+
+    fn value_strategy() -> impl Strategy<Value = String> {
+        let number = (-10000i32..10000).prop_map(|n| format!("{}", f64::from(n) / 100.0));
+        let unit = prop::sample::select(vec![
+            "", "px", "em", "ex", "in", "cm", "mm", "pt", "pc", "%", "deg", "rad", "grad",
+        ]);
+        let dimension = (number.clone(), unit).prop_map(|(n, u)| format!("{}{}", n, u));
+
+        let keyword = prop::sample::select(vec![
+            "none",
+            "inherit",
+            "normal",
+            "auto",
+            "bold",
+            "bolder",
+            "nonzero",
+            "evenodd",
+            "visible",
+            "hidden",
+            "round",
+            "bevel",
+            "miter",
+            "square",
+            "middle",
+            "end",
+            "small-caps",
+            "italic",
+            "x-large",
+            "smaller",
+            "sRGB",
+            "linearRGB",
+            "underline overline",
+            "line-through",
+            "currentColor",
+            "sub",
+            "super",
+            "new",
+            "tb-rl",
+            "bidi-override",
+        ])
+        .prop_map(String::from);
+
+        let color =
+            prop_oneof![
+                "#[0-9a-f]{3}",
+                "#[0-9a-f]{6}",
+                (any::<u8>(), any::<u8>(), any::<u8>(), any::<u8>()).prop_map(
+                    |(r, g, b, a)| format!("rgba({}, {}, {}, {})", r, g, b, f64::from(a) / 255.0)
+                ),
+            ];
+
+        let url = ("[a-z]{1,8}", prop::option::of(color.clone()))
+            .prop_map(|(id, c)| format!("url(#{}) {}", id, c.unwrap_or_default()));
+
+        let function = (
+            prop::sample::select(vec![
+                "blur",
+                "brightness",
+                "contrast",
+                "grayscale",
+                "hue-rotate",
+                "invert",
+                "opacity",
+                "saturate",
+                "sepia",
+            ]),
+            prop::option::of(dimension.clone()),
+        )
+            .prop_map(|(f, arg)| format!("{}({})", f, arg.unwrap_or_default()));
+
+        let list = prop::collection::vec(dimension.clone(), 1..5).prop_map(|v| v.join(", "));
+
+        prop_oneof![
+            dimension,
+            keyword,
+            color,
+            url,
+            function,
+            list,
+            "[a-z-]{1,12}"
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn parse_then_serialize_round_trips(value in value_strategy()) {
+            for name in PROPERTY_NAMES {
+                if let Some(serialized) = parse_and_serialize_property(name, &value) {
+                    prop_assert_eq!(
+                        parse_and_serialize_property(name, &serialized),
+                        Some(serialized.clone()),
+                        "property {} with value {:?}",
+                        name,
+                        value
+                    );
+                }
+            }
+        }
+    }
 }
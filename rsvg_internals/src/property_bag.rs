@@ -8,6 +8,8 @@ use std::str;
 
 use markup5ever::{namespace_url, LocalName, Namespace, Prefix, QualName};
 
+use crate::error::ElementError;
+use crate::parsers::{Parse, ParseValue};
 use crate::util::{opt_utf8_cstr, utf8_cstr};
 
 /// Iterable wrapper for libxml2's representation of attribute/value.
@@ -86,6 +88,16 @@ impl<'a> PropertyBag<'a> {
         PropertyBag(array)
     }
 
+    /// Creates a `PropertyBag` that borrows the values from a list of attributes.
+    pub fn new_from_attributes(attributes: &'a [(QualName, String)]) -> PropertyBag<'a> {
+        PropertyBag(
+            attributes
+                .iter()
+                .map(|(attr, value)| (attr.clone(), value.as_str()))
+                .collect(),
+        )
+    }
+
     /// Returns the number of attributes in the property bag.
     pub fn len(&self) -> usize {
         self.0.len()
@@ -95,6 +107,16 @@ impl<'a> PropertyBag<'a> {
     pub fn iter(&self) -> PropertyBagIter<'_> {
         PropertyBagIter(self.0.iter())
     }
+
+    /// Returns the value of the attribute `attr`, or `None` if it is not present.
+    pub fn get_str(&self, attr: QualName) -> Option<&'a str> {
+        self.0.iter().find(|(a, _)| *a == attr).map(|&(_, v)| v)
+    }
+
+    /// Parses the value of the attribute `attr`, or returns `None` if it is not present.
+    pub fn get<T: Parse>(&self, attr: QualName) -> Option<Result<T, ElementError>> {
+        self.get_str(attr.clone()).map(|v| attr.parse(v))
+    }
 }
 
 impl<'a> Iterator for PropertyBagIter<'a> {
@@ -194,4 +216,38 @@ mod tests {
         assert!(had_ry);
         assert!(had_d);
     }
+
+    #[test]
+    fn gets_parsed_attributes() {
+        let attrs = [
+            (CString::new("x").unwrap(), CString::new("1.5").unwrap()),
+            (CString::new("y").unwrap(), CString::new("foo").unwrap()),
+        ];
+
+        let mut v: Vec<*const libc::c_char> = Vec::new();
+
+        for (localname, val) in &attrs {
+            let val_start = val.as_ptr() as *const libc::c_char;
+            let val_end = unsafe { val_start.offset(val.as_bytes().len() as isize) };
+
+            v.push(localname.as_ptr() as *const libc::c_char);
+            v.push(ptr::null()); // prefix
+            v.push(ptr::null()); // uri
+            v.push(val_start); // value_start
+            v.push(val_end); // value_end
+        }
+
+        let pbag = unsafe { PropertyBag::new_from_xml2_attributes(2, v.as_ptr()) };
+
+        let x = QualName::new(None, ns!(), local_name!("x"));
+        let y = QualName::new(None, ns!(), local_name!("y"));
+        let width = QualName::new(None, ns!(), local_name!("width"));
+
+        assert_eq!(pbag.get_str(x.clone()), Some("1.5"));
+        assert_eq!(pbag.get_str(width.clone()), None);
+
+        assert_eq!(pbag.get::<f64>(x).unwrap().unwrap(), 1.5);
+        assert!(pbag.get::<f64>(y).unwrap().is_err());
+        assert!(pbag.get::<f64>(width).is_none());
+    }
 }
@@ -1,18 +1,44 @@
 //! Definitions for CSS property types.
 
-use cssparser::{Parser, Token};
+use cssparser::{Parser, ToCss, Token};
+use std::fmt;
 
 use crate::dasharray::Dasharray;
 use crate::error::*;
+use crate::filter::FilterValueList;
 use crate::font_props::{FontSizeSpec, FontWeightSpec, LetterSpacingSpec, SingleFontFamily};
 use crate::iri::IRI;
 use crate::length::*;
 use crate::paint_server::PaintServer;
-use crate::parsers::Parse;
+use crate::parsers::{serialize_number, Parse};
 use crate::properties::ComputedValues;
 use crate::property_macros::Property;
 use crate::unit_interval::UnitInterval;
 
+// https://www.w3.org/TR/css-cascade-3/#all-shorthand
+//
+// The "inherit" value is handled by the property machinery, like for all the
+// other properties.
+make_property!(
+    ComputedValues,
+    All,
+    default: Unset,
+    inherits_automatically: false,
+
+    identifiers:
+    "initial" => Initial,
+    "unset" => Unset,
+);
+
+// https://drafts.fxtf.org/filter-effects-2/#BackdropFilterProperty
+make_property!(
+    ComputedValues,
+    BackdropFilter,
+    default: FilterValueList::default(),
+    inherits_automatically: false,
+    newtype_parse: FilterValueList,
+);
+
 // https://www.w3.org/TR/SVG/text.html#BaselineShiftProperty
 make_property!(
     ComputedValues,
@@ -29,13 +55,17 @@ make_property!(
                 let font_size = v.font_size().0.value();
                 let parent = v.baseline_shift();
 
-                match (self.0.unit, parent.0.unit) {
-                    (LengthUnit::Percent, _) => {
-                        BaselineShift(Length::<Both>::new(self.0.length * font_size.length + parent.0.length, font_size.unit))
-                    }
+                // Percentages refer to the element's own font-size, which has
+                // already been computed to an absolute length at this point.
+                let shift = if self.0.unit == LengthUnit::Percent {
+                    Length::<Both>::new(self.0.length * font_size.length, font_size.unit)
+                } else {
+                    self.0
+                };
 
+                match (shift.unit, parent.0.unit) {
                     (x, y) if x == y || parent.0.length == 0.0 => {
-                        BaselineShift(Length::<Both>::new(self.0.length + parent.0.length, self.0.unit))
+                        BaselineShift(Length::<Both>::new(shift.length + parent.0.length, shift.unit))
                     }
 
                     _ => {
@@ -73,6 +103,15 @@ make_property!(
     }
 );
 
+impl ToCss for BaselineShift {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        self.0.to_css(dest)
+    }
+}
+
 // https://www.w3.org/TR/SVG/masking.html#ClipPathProperty
 make_property!(
     ComputedValues,
@@ -110,6 +149,19 @@ make_property!(
 );
 
 // https://www.w3.org/TR/SVG11/painting.html#ColorInterpolationProperty
+make_property!(
+    ComputedValues,
+    ColorInterpolation,
+    default: Srgb,
+    inherits_automatically: true,
+
+    identifiers:
+    "auto" => Auto,
+    "sRGB" => Srgb,
+    "linearRGB" => LinearRgb,
+);
+
+// https://www.w3.org/TR/SVG11/painting.html#ColorInterpolationFiltersProperty
 make_property!(
     ComputedValues,
     ColorInterpolationFilters,
@@ -203,13 +255,13 @@ make_property!(
     "evenodd" => EvenOdd,
 );
 
-// https://www.w3.org/TR/SVG/filters.html#FilterProperty
+// https://www.w3.org/TR/filter-effects/#FilterProperty
 make_property!(
     ComputedValues,
     Filter,
-    default: IRI::None,
+    default: FilterValueList::default(),
     inherits_automatically: false,
-    newtype_parse: IRI,
+    newtype_parse: FilterValueList,
 );
 
 // https://www.w3.org/TR/SVG/filters.html#FloodColorProperty
@@ -258,6 +310,50 @@ make_property!(
     }
 );
 
+// https://www.w3.org/TR/css-fonts-3/#font-size-adjust-prop
+make_property!(
+    ComputedValues,
+    FontSizeAdjust,
+    default: None,
+    inherits_automatically: true,
+    newtype: Option<f64>,
+    parse_impl: {
+        impl Parse for FontSizeAdjust {
+            fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<FontSizeAdjust, ParseError<'i>> {
+                if parser
+                    .try_parse(|p| p.expect_ident_matching("none"))
+                    .is_ok()
+                {
+                    return Ok(FontSizeAdjust(None));
+                }
+
+                let loc = parser.current_source_location();
+                let aspect = f64::parse(parser)?;
+
+                if aspect > 0.0 {
+                    Ok(FontSizeAdjust(Some(aspect)))
+                } else {
+                    Err(loc.new_custom_error(ValueErrorKind::value_error(
+                        "font-size-adjust must be a positive number",
+                    )))
+                }
+            }
+        }
+    },
+);
+
+impl ToCss for FontSizeAdjust {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        match self.0 {
+            None => dest.write_str("none"),
+            Some(aspect) => serialize_number(aspect, dest),
+        }
+    }
+}
+
 // https://www.w3.org/TR/SVG/text.html#FontStretchProperty
 make_property!(
     ComputedValues,
@@ -395,6 +491,7 @@ make_property!(
 );
 
 // https://www.w3.org/TR/SVG/masking.html#OverflowProperty
+// https://www.w3.org/TR/css-overflow-3/#valdef-overflow-clip
 make_property!(
     ComputedValues,
     Overflow,
@@ -404,6 +501,7 @@ make_property!(
     identifiers:
     "visible" => Visible,
     "hidden" => Hidden,
+    "clip" => Clip,
     "scroll" => Scroll,
     "auto" => Auto,
 );
@@ -499,9 +597,34 @@ make_property!(
     StrokeMiterlimit,
     default: 4f64,
     inherits_automatically: true,
-    newtype_parse: f64,
+    newtype: f64,
+    parse_impl: {
+        impl Parse for StrokeMiterlimit {
+            fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<StrokeMiterlimit, ParseError<'i>> {
+                let loc = parser.current_source_location();
+                let limit = f64::parse(parser)?;
+
+                if limit >= 1.0 {
+                    Ok(StrokeMiterlimit(limit))
+                } else {
+                    Err(loc.new_custom_error(ValueErrorKind::value_error(
+                        "stroke-miterlimit must be greater than or equal to 1",
+                    )))
+                }
+            }
+        }
+    },
 );
 
+impl ToCss for StrokeMiterlimit {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        serialize_number(self.0, dest)
+    }
+}
+
 // https://www.w3.org/TR/SVG/painting.html#StrokeOpacityProperty
 make_property!(
     ComputedValues,
@@ -578,6 +701,70 @@ make_property!(
     }
 );
 
+impl ToCss for TextDecoration {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        let decorations = [
+            (self.overline, "overline"),
+            (self.underline, "underline"),
+            (self.strike, "line-through"),
+        ];
+
+        let mut any = false;
+
+        for &(_, name) in decorations.iter().filter(|&&(set, _)| set) {
+            if any {
+                dest.write_str(" ")?;
+            }
+
+            dest.write_str(name)?;
+            any = true;
+        }
+
+        if !any {
+            dest.write_str("none")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn parses_stroke_miterlimit() {
+    assert_eq!(
+        StrokeMiterlimit::parse_str("1").unwrap(),
+        StrokeMiterlimit(1.0)
+    );
+    assert_eq!(
+        StrokeMiterlimit::parse_str("10.5").unwrap(),
+        StrokeMiterlimit(10.5)
+    );
+
+    assert!(StrokeMiterlimit::parse_str("0.5").is_err());
+    assert!(StrokeMiterlimit::parse_str("0").is_err());
+    assert!(StrokeMiterlimit::parse_str("-4").is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn parses_font_size_adjust() {
+    assert_eq!(
+        FontSizeAdjust::parse_str("none").unwrap(),
+        FontSizeAdjust(None)
+    );
+    assert_eq!(
+        FontSizeAdjust::parse_str("0.5").unwrap(),
+        FontSizeAdjust(Some(0.5))
+    );
+
+    assert!(FontSizeAdjust::parse_str("0").is_err());
+    assert!(FontSizeAdjust::parse_str("-1").is_err());
+    assert!(FontSizeAdjust::parse_str("auto").is_err());
+}
+
 #[cfg(test)]
 #[test]
 fn parses_text_decoration() {
@@ -669,14 +856,20 @@ make_property!(
     "collapse" => Collapse,
 );
 
-// https://www.w3.org/TR/SVG/text.html#WritingModeProperty
+// https://www.w3.org/TR/css-writing-modes-3/#block-flow
+//
+// The values from SVG 1.1 are also accepted:
+// https://www.w3.org/TR/SVG11/text.html#WritingModeProperty
 make_property!(
     ComputedValues,
     WritingMode,
-    default: LrTb,
+    default: HorizontalTb,
     inherits_automatically: true,
 
     identifiers:
+    "horizontal-tb" => HorizontalTb,
+    "vertical-rl" => VerticalRl,
+    "vertical-lr" => VerticalLr,
     "lr" => Lr,
     "lr-tb" => LrTb,
     "rl" => Rl,
@@ -688,7 +881,10 @@ make_property!(
 impl WritingMode {
     pub fn is_vertical(self) -> bool {
         match self {
-            WritingMode::Tb | WritingMode::TbRl => true,
+            WritingMode::VerticalRl
+            | WritingMode::VerticalLr
+            | WritingMode::Tb
+            | WritingMode::TbRl => true,
             _ => false,
         }
     }
@@ -711,6 +907,33 @@ make_property!(
     },
 );
 
+impl ToCss for XmlLang {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        cssparser::serialize_identifier(&self.0, dest)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn parses_writing_mode() {
+    assert_eq!(WritingMode::default(), WritingMode::HorizontalTb);
+
+    for &(s, vertical) in &[
+        ("horizontal-tb", false),
+        ("vertical-rl", true),
+        ("vertical-lr", true),
+        ("lr-tb", false),
+        ("tb-rl", true),
+    ] {
+        assert_eq!(WritingMode::parse_str(s).unwrap().is_vertical(), vertical);
+    }
+
+    assert!(WritingMode::parse_str("sideways-rl").is_err());
+}
+
 #[cfg(test)]
 #[test]
 fn parses_xml_lang() {
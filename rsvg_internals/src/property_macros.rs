@@ -27,7 +27,12 @@ pub trait Property<T> {
 /// variants.  It will generate an `impl Default for StrokeLinejoin`
 /// with the provided `default:` value.  Finally, it will generate an
 /// `impl Parse for StrokeLinejoin`, from `parsers::Parse`, with
-/// `type Err = ValueErrorKind`.
+/// `type Err = ValueErrorKind`, and an `impl ToCss` that writes the
+/// identifiers back.
+///
+/// The `newtype_parse` forms also generate an `impl ToCss` which delegates
+/// to the wrapped type.  The forms with a `parse_impl` need to implement
+/// `ToCss` by hand.
 #[macro_export]
 macro_rules! make_property {
     ($computed_values_type: ty,
@@ -54,6 +59,14 @@ macro_rules! make_property {
                 )?)
             }
         }
+
+        impl ::cssparser::ToCss for $name {
+            fn to_css<W>(&self, dest: &mut W) -> ::std::fmt::Result where W: ::std::fmt::Write {
+                dest.write_str(match *self {
+                    $($name::$variant => $str_prop,)+
+                })
+            }
+        }
     };
 
     ($computed_values_type: ty,
@@ -73,6 +86,12 @@ macro_rules! make_property {
                 Ok($name(<$type as crate::parsers::Parse>::parse(parser)?))
             }
         }
+
+        impl ::cssparser::ToCss for $name {
+            fn to_css<W>(&self, dest: &mut W) -> ::std::fmt::Result where W: ::std::fmt::Write {
+                ::cssparser::ToCss::to_css(&self.0, dest)
+            }
+        }
     };
 
     ($computed_values_type: ty,
@@ -93,6 +112,12 @@ macro_rules! make_property {
                 Ok($name(<$type as crate::parsers::Parse>::parse(parser)?))
             }
         }
+
+        impl ::cssparser::ToCss for $name {
+            fn to_css<W>(&self, dest: &mut W) -> ::std::fmt::Result where W: ::std::fmt::Write {
+                ::cssparser::ToCss::to_css(&self.0, dest)
+            }
+        }
     };
 
     ($computed_values_type: ty,
@@ -177,7 +202,7 @@ mod tests {
     use super::*;
 
     use crate::parsers::Parse;
-    use cssparser::RGBA;
+    use cssparser::{ToCss, RGBA};
 
     #[test]
     fn check_identifiers_property() {
@@ -197,6 +222,7 @@ mod tests {
         assert_eq!(<Foo as Property<()>>::inherits_automatically(), true);
         assert!(<Foo as Parse>::parse_str("blargh").is_err());
         assert_eq!(<Foo as Parse>::parse_str("bar"), Ok(Foo::Bar));
+        assert_eq!(Foo::Baz.to_css_string(), "baz");
     }
 
     #[test]
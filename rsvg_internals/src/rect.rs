@@ -23,6 +23,13 @@ mod rect {
         }
     }
 
+    /// A rectangle given by its top-left corner `(x0, y0)` and its bottom-right
+    /// corner `(x1, y1)`.
+    ///
+    /// The intervals are half-open: `x0` and `y0` are inclusive, while `x1` and `y1`
+    /// are exclusive.  For an `IRect` of pixel coordinates, the last column of pixels
+    /// is `x1 - 1` and the last row is `y1 - 1`; this is what `x_range()`,
+    /// `y_range()`, and `contains()` assume.
     #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
     pub struct Rect<T> {
         pub x0: T,
@@ -92,6 +99,10 @@ mod rect {
             }
         }
 
+        /// Returns the area common to both rectangles, or `None` if they do not overlap.
+        ///
+        /// Rectangles that only touch at an edge do not overlap, since their
+        /// right and bottom edges are exclusive.
         #[inline]
         pub fn intersection(&self, rect: &Self) -> Option<Self> {
             let (x0, y0, x1, y1) = (
@@ -108,6 +119,7 @@ mod rect {
             }
         }
 
+        /// Returns the smallest rectangle that contains both rectangles.
         #[inline]
         pub fn union(&self, rect: &Self) -> Self {
             Self {
@@ -229,3 +241,61 @@ impl From<IRect> for cairo::Rectangle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn irect_bounds_are_half_open() {
+        let r = IRect::new(1, 2, 4, 6);
+
+        assert_eq!(r.size(), (3, 4));
+        assert_eq!(r.x_range().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(r.contains(1, 2));
+        assert!(r.contains(3, 5));
+        assert!(!r.contains(4, 5));
+        assert!(!r.contains(3, 6));
+    }
+
+    #[test]
+    fn intersects_irects() {
+        let a = IRect::new(0, 0, 10, 10);
+
+        assert_eq!(
+            a.intersection(&IRect::new(5, -5, 15, 5)),
+            Some(IRect::new(5, 0, 10, 5))
+        );
+        assert_eq!(
+            a.intersection(&IRect::new(2, 3, 4, 5)),
+            Some(IRect::new(2, 3, 4, 5))
+        );
+
+        // Touching edges share no pixels
+        assert_eq!(a.intersection(&IRect::new(10, 0, 20, 10)), None);
+        assert_eq!(a.intersection(&IRect::new(0, 10, 10, 20)), None);
+
+        assert_eq!(a.intersection(&IRect::new(20, 20, 30, 30)), None);
+    }
+
+    #[test]
+    fn unites_irects() {
+        let a = IRect::new(0, 0, 10, 10);
+
+        assert_eq!(
+            a.union(&IRect::new(5, -5, 15, 5)),
+            IRect::new(0, -5, 15, 10)
+        );
+        assert_eq!(a.union(&IRect::new(2, 3, 4, 5)), a);
+        assert_eq!(
+            a.union(&IRect::new(20, 20, 30, 30)),
+            IRect::new(0, 0, 30, 30)
+        );
+    }
+
+    #[test]
+    fn converts_rect_to_covering_irect() {
+        let r = Rect::new(0.5, -0.5, 9.5, 10.0);
+        assert_eq!(IRect::from(r), IRect::new(0, -1, 10, 10));
+    }
+}
@@ -0,0 +1,234 @@
+//! Serialization of a loaded document back to SVG XML.
+//!
+//! This walks the tree of nodes and writes out each element with the attributes
+//! that it had in the original XML, and each `Chars` with its text.  Things that
+//! are not part of the tree, like stylesheets from `xml-stylesheet` processing
+//! instructions or user stylesheets, are not written out.
+
+use markup5ever::{namespace_url, ns, Namespace, QualName};
+
+use crate::node::{Node, NodeBorrow};
+
+/// Serializes the tree starting at `root` as an SVG document.
+///
+/// The elements must have kept their attributes; see `LoadOptions::keep_attributes`.
+pub fn to_svg_string(root: &Node) -> String {
+    let mut prefixes = Vec::new();
+    collect_prefixes(root, &mut prefixes);
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    serialize_node(root, &prefixes, true, &mut out);
+    out.push('\n');
+
+    out
+}
+
+/// Finds the prefix to use for each namespace other than SVG's, so that
+/// they can be declared in the toplevel element.
+///
+/// Names keep the prefix they had in the XML where possible.  If two namespaces
+/// used the same prefix in different parts of the document, or a namespace was
+/// the default one for some elements, a new prefix is made up for it.
+fn collect_prefixes(node: &Node, prefixes: &mut Vec<(Namespace, String)>) {
+    if node.is_element() {
+        let elt = node.borrow_element();
+
+        let names = Some(elt.element_name())
+            .into_iter()
+            .chain(elt.attributes().iter().map(|(name, _)| name));
+
+        for name in names {
+            if needs_declaration(&name.ns) && !prefixes.iter().any(|(ns, _)| *ns == name.ns) {
+                let prefix = unused_prefix(preferred_prefix(name), prefixes);
+                prefixes.push((name.ns.clone(), prefix));
+            }
+        }
+    }
+
+    for child in node.children() {
+        collect_prefixes(&child, prefixes);
+    }
+}
+
+fn needs_declaration(ns: &Namespace) -> bool {
+    *ns != ns!() && *ns != ns!(svg) && *ns != ns!(xml)
+}
+
+fn preferred_prefix(name: &QualName) -> Option<String> {
+    if name.ns == ns!(xlink) {
+        Some("xlink".to_string())
+    } else {
+        name.prefix.as_ref().map(|p| p.to_string())
+    }
+}
+
+fn unused_prefix(preferred: Option<String>, prefixes: &[(Namespace, String)]) -> String {
+    let is_unused = |prefix: &str| {
+        // "xml" is bound to its namespace already, and "xmlns" cannot be declared.
+        prefix != "xml" && prefix != "xmlns" && !prefixes.iter().any(|(_, p)| p == prefix)
+    };
+
+    if let Some(prefix) = preferred {
+        if is_unused(&prefix) {
+            return prefix;
+        }
+    }
+
+    (1..)
+        .map(|n| format!("ns{}", n))
+        .find(|prefix| is_unused(prefix))
+        .unwrap()
+}
+
+fn element_name_to_string(name: &QualName, prefixes: &[(Namespace, String)]) -> String {
+    // SVG is the default namespace, so SVG elements need no prefix.
+    if name.ns == ns!(svg) {
+        name.local.to_string()
+    } else {
+        qual_name_to_string(name, prefixes)
+    }
+}
+
+fn qual_name_to_string(name: &QualName, prefixes: &[(Namespace, String)]) -> String {
+    if name.ns == ns!(xml) {
+        format!("xml:{}", name.local)
+    } else if let Some((_, prefix)) = prefixes.iter().find(|(ns, _)| *ns == name.ns) {
+        format!("{}:{}", prefix, name.local)
+    } else {
+        name.local.to_string()
+    }
+}
+
+fn serialize_node(node: &Node, prefixes: &[(Namespace, String)], is_root: bool, out: &mut String) {
+    if node.is_chars() {
+        escape_text(&node.borrow_chars().get_string(), out);
+        return;
+    }
+
+    let elt = node.borrow_element();
+    let name = element_name_to_string(elt.element_name(), prefixes);
+
+    out.push('<');
+    out.push_str(&name);
+
+    if is_root {
+        push_attribute("xmlns", &ns!(svg), out);
+
+        for (ns, prefix) in prefixes {
+            push_attribute(&format!("xmlns:{}", prefix), ns, out);
+        }
+    } else if elt.element_name().ns == ns!() {
+        // Undo the default namespace for elements that are not in any namespace.
+        push_attribute("xmlns", "", out);
+    }
+
+    for (attr, value) in elt.attributes() {
+        push_attribute(&qual_name_to_string(attr, prefixes), value, out);
+    }
+
+    if node.has_children() {
+        out.push('>');
+
+        for child in node.children() {
+            serialize_node(&child, prefixes, false, out);
+        }
+
+        out.push_str("</");
+        out.push_str(&name);
+        out.push('>');
+    } else {
+        out.push_str("/>");
+    }
+}
+
+fn push_attribute(name: &str, value: &str, out: &mut String) {
+    out.push(' ');
+    out.push_str(name);
+    out.push_str("=\"");
+    escape_attribute_value(value, out);
+    out.push('"');
+}
+
+fn escape_text(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn escape_attribute_value(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '"' => out.push_str("&quot;"),
+            // Use character references for whitespace that the XML parser
+            // would otherwise normalize to spaces.
+            '\t' => out.push_str("&#9;"),
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use markup5ever::local_name;
+
+    #[test]
+    fn escapes_text() {
+        let mut out = String::new();
+        escape_text("a < b && c > d \"e\"", &mut out);
+        assert_eq!(out, "a &lt; b &amp;&amp; c &gt; d \"e\"");
+    }
+
+    #[test]
+    fn escapes_attribute_values() {
+        let mut out = String::new();
+        escape_attribute_value("<\"a\" & b>\n\tc", &mut out);
+        assert_eq!(out, "&lt;&quot;a&quot; &amp; b>&#10;&#9;c");
+    }
+
+    #[test]
+    fn writes_names_with_prefixes() {
+        let foo = Namespace::from("http://example.com");
+        let prefixes = vec![
+            (ns!(xlink), "xlink".to_string()),
+            (foo.clone(), "ns1".to_string()),
+        ];
+
+        let rect = QualName::new(None, ns!(svg), local_name!("rect"));
+        assert_eq!(element_name_to_string(&rect, &prefixes), "rect");
+
+        let href = QualName::new(None, ns!(xlink), local_name!("href"));
+        assert_eq!(qual_name_to_string(&href, &prefixes), "xlink:href");
+
+        let space = QualName::new(None, ns!(xml), local_name!("space"));
+        assert_eq!(qual_name_to_string(&space, &prefixes), "xml:space");
+
+        let width = QualName::new(None, ns!(), local_name!("width"));
+        assert_eq!(qual_name_to_string(&width, &prefixes), "width");
+
+        let foreign = QualName::new(None, foo, "foo".into());
+        assert_eq!(qual_name_to_string(&foreign, &prefixes), "ns1:foo");
+    }
+
+    #[test]
+    fn makes_up_prefixes_that_are_not_in_use() {
+        let prefixes = vec![
+            (Namespace::from("http://example.com/a"), "a".to_string()),
+            (Namespace::from("http://example.com/b"), "ns1".to_string()),
+        ];
+
+        assert_eq!(unused_prefix(Some("b".to_string()), &prefixes), "b");
+        assert_eq!(unused_prefix(Some("a".to_string()), &prefixes), "ns2");
+        assert_eq!(unused_prefix(Some("xml".to_string()), &prefixes), "ns2");
+        assert_eq!(unused_prefix(None, &prefixes), "ns2");
+    }
+}
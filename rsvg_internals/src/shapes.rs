@@ -17,7 +17,7 @@ use crate::path_builder::{LargeArc, Path as SvgPath, PathBuilder, Sweep};
 use crate::path_parser;
 use crate::properties::ComputedValues;
 use crate::property_bag::PropertyBag;
-use cssparser::{Parser, Token};
+use cssparser::{Parser, ParserInput, Token};
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum Markers {
@@ -28,11 +28,16 @@ pub enum Markers {
 pub struct Shape {
     path: Rc<SvgPath>,
     markers: Markers,
+    path_length: Option<f64>,
 }
 
 impl Shape {
-    fn new(path: Rc<SvgPath>, markers: Markers) -> Shape {
-        Shape { path, markers }
+    fn new(path: Rc<SvgPath>, markers: Markers, path_length: Option<f64>) -> Shape {
+        Shape {
+            path,
+            markers,
+            path_length,
+        }
     }
 
     fn draw(
@@ -49,15 +54,30 @@ impl Shape {
             acquired_nodes,
             values,
             self.markers,
+            self.path_length,
             clipping,
         )
     }
 }
 
+/// Validates the `pathLength` attribute of shapes.
+///
+/// https://www.w3.org/TR/SVG2/paths.html#PathLengthAttribute
+fn check_path_length(length: f64) -> Result<f64, ValueErrorKind> {
+    if length < 0.0 {
+        Err(ValueErrorKind::value_error("pathLength can't be negative"))
+    } else {
+        Ok(length)
+    }
+}
+
 fn make_ellipse(cx: f64, cy: f64, rx: f64, ry: f64) -> SvgPath {
     let mut builder = PathBuilder::new();
 
-    // Per the spec, rx and ry must be nonnegative
+    // Per the spec, rx and ry must be nonnegative, and a value of zero for either of
+    // them disables rendering of the element; it is not drawn as a line or a point.
+    //
+    // https://www.w3.org/TR/SVG2/shapes.html#EllipseElement
     if rx <= 0.0 || ry <= 0.0 {
         return builder.into_path();
     }
@@ -113,20 +133,29 @@ fn make_ellipse(cx: f64, cy: f64, rx: f64, ry: f64) -> SvgPath {
 #[derive(Default)]
 pub struct Path {
     path: Option<Rc<SvgPath>>,
+    path_length: Option<f64>,
 }
 
 impl SetAttributes for Path {
     fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
         for (attr, value) in pbag.iter() {
-            if attr.expanded() == expanded_name!("", "d") {
-                let mut builder = PathBuilder::new();
-                if let Err(e) = path_parser::parse_path_into_builder(value, &mut builder) {
-                    // FIXME: we don't propagate errors upstream, but creating a partial
-                    // path is OK per the spec
-
-                    rsvg_log!("could not parse path: {}", e);
+            match attr.expanded() {
+                expanded_name!("", "d") => {
+                    let mut builder = PathBuilder::new();
+                    if let Err(e) = path_parser::parse_path_into_builder(value, &mut builder) {
+                        // FIXME: we don't propagate errors upstream, but creating a partial
+                        // path is OK per the spec
+
+                        rsvg_log!(Warn, "could not parse path: {}", e);
+                    }
+                    self.path = Some(Rc::new(builder.into_path()));
                 }
-                self.path = Some(Rc::new(builder.into_path()));
+                expanded_name!("", "pathLength") => {
+                    self.path_length = attr
+                        .parse_and_validate(value, check_path_length)
+                        .map(Some)?
+                }
+                _ => (),
             }
         }
 
@@ -134,6 +163,13 @@ impl SetAttributes for Path {
     }
 }
 
+impl Path {
+    /// Returns the path from the `d` attribute, if there is one.
+    pub fn get_path(&self) -> Option<Rc<SvgPath>> {
+        self.path.clone()
+    }
+}
+
 impl Draw for Path {
     fn draw(
         &self,
@@ -145,7 +181,7 @@ impl Draw for Path {
     ) -> Result<BoundingBox, RenderingError> {
         if let Some(path) = self.path.as_ref() {
             let values = cascaded.get();
-            Shape::new(path.clone(), Markers::Yes).draw(
+            Shape::new(path.clone(), Markers::Yes, self.path_length).draw(
                 node,
                 acquired_nodes,
                 values,
@@ -169,31 +205,57 @@ impl Deref for Points {
     }
 }
 
+impl Points {
+    /// Parses the `points` attribute of polyline and polygon elements.
+    ///
+    /// Per the spec, an error in the list is not fatal: the points up to the last
+    /// complete pair before the error get rendered.
+    fn parse_partial(s: &str) -> Points {
+        let mut input = ParserInput::new(s);
+        let mut parser = Parser::new(&mut input);
+
+        let mut v = Vec::new();
+
+        if let Err(e) = parse_points_into(&mut parser, &mut v) {
+            rsvg_log!(Warn, "could not parse points: {:?}", e);
+        }
+
+        Points(v)
+    }
+}
+
 // Parse a list-of-points as for polyline and polygon elements
 // https://www.w3.org/TR/SVG/shapes.html#PointsBNF
 impl Parse for Points {
     fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<Points, ParseError<'i>> {
         let mut v = Vec::new();
+        parse_points_into(parser, &mut v)?;
+        Ok(Points(v))
+    }
+}
 
-        loop {
-            let x = f64::parse(parser)?;
-            optional_comma(parser);
-            let y = f64::parse(parser)?;
-
-            v.push((x, y));
+fn parse_points_into<'i>(
+    parser: &mut Parser<'i, '_>,
+    v: &mut Vec<(f64, f64)>,
+) -> Result<(), ParseError<'i>> {
+    loop {
+        let x = f64::parse(parser)?;
+        optional_comma(parser);
+        let y = f64::parse(parser)?;
 
-            if parser.is_exhausted() {
-                break;
-            }
+        v.push((x, y));
 
-            match parser.next_including_whitespace() {
-                Ok(&Token::WhiteSpace(_)) => (),
-                _ => optional_comma(parser),
-            }
+        if parser.is_exhausted() {
+            break;
         }
 
-        Ok(Points(v))
+        match parser.next_including_whitespace() {
+            Ok(&Token::WhiteSpace(_)) => (),
+            _ => optional_comma(parser),
+        }
     }
+
+    Ok(())
 }
 
 fn make_poly(points: Option<&Points>, closed: bool) -> SvgPath {
@@ -219,13 +281,20 @@ fn make_poly(points: Option<&Points>, closed: bool) -> SvgPath {
 #[derive(Default)]
 pub struct Polygon {
     points: Option<Points>,
+    path_length: Option<f64>,
 }
 
 impl SetAttributes for Polygon {
     fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
         for (attr, value) in pbag.iter() {
-            if attr.expanded() == expanded_name!("", "points") {
-                self.points = attr.parse(value).map(Some)?;
+            match attr.expanded() {
+                expanded_name!("", "points") => self.points = Some(Points::parse_partial(value)),
+                expanded_name!("", "pathLength") => {
+                    self.path_length = attr
+                        .parse_and_validate(value, check_path_length)
+                        .map(Some)?
+                }
+                _ => (),
             }
         }
 
@@ -243,26 +312,32 @@ impl Draw for Polygon {
         clipping: bool,
     ) -> Result<BoundingBox, RenderingError> {
         let values = cascaded.get();
-        Shape::new(Rc::new(make_poly(self.points.as_ref(), true)), Markers::Yes).draw(
-            node,
-            acquired_nodes,
-            values,
-            draw_ctx,
-            clipping,
+        Shape::new(
+            Rc::new(make_poly(self.points.as_ref(), true)),
+            Markers::Yes,
+            self.path_length,
         )
+        .draw(node, acquired_nodes, values, draw_ctx, clipping)
     }
 }
 
 #[derive(Default)]
 pub struct Polyline {
     points: Option<Points>,
+    path_length: Option<f64>,
 }
 
 impl SetAttributes for Polyline {
     fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
         for (attr, value) in pbag.iter() {
-            if attr.expanded() == expanded_name!("", "points") {
-                self.points = attr.parse(value).map(Some)?;
+            match attr.expanded() {
+                expanded_name!("", "points") => self.points = Some(Points::parse_partial(value)),
+                expanded_name!("", "pathLength") => {
+                    self.path_length = attr
+                        .parse_and_validate(value, check_path_length)
+                        .map(Some)?
+                }
+                _ => (),
             }
         }
 
@@ -283,6 +358,7 @@ impl Draw for Polyline {
         Shape::new(
             Rc::new(make_poly(self.points.as_ref(), false)),
             Markers::Yes,
+            self.path_length,
         )
         .draw(node, acquired_nodes, values, draw_ctx, clipping)
     }
@@ -294,6 +370,7 @@ pub struct Line {
     y1: Length<Vertical>,
     x2: Length<Horizontal>,
     y2: Length<Vertical>,
+    path_length: Option<f64>,
 }
 
 impl SetAttributes for Line {
@@ -304,6 +381,11 @@ impl SetAttributes for Line {
                 expanded_name!("", "y1") => self.y1 = attr.parse(value)?,
                 expanded_name!("", "x2") => self.x2 = attr.parse(value)?,
                 expanded_name!("", "y2") => self.y2 = attr.parse(value)?,
+                expanded_name!("", "pathLength") => {
+                    self.path_length = attr
+                        .parse_and_validate(value, check_path_length)
+                        .map(Some)?
+                }
                 _ => (),
             }
         }
@@ -322,13 +404,12 @@ impl Draw for Line {
         clipping: bool,
     ) -> Result<BoundingBox, RenderingError> {
         let values = cascaded.get();
-        Shape::new(Rc::new(self.make_path(values, draw_ctx)), Markers::Yes).draw(
-            node,
-            acquired_nodes,
-            values,
-            draw_ctx,
-            clipping,
+        Shape::new(
+            Rc::new(self.make_path(values, draw_ctx)),
+            Markers::Yes,
+            self.path_length,
         )
+        .draw(node, acquired_nodes, values, draw_ctx, clipping)
     }
 }
 
@@ -360,6 +441,8 @@ pub struct Rect {
     // Radiuses for rounded corners
     rx: Option<Length<Horizontal>>,
     ry: Option<Length<Vertical>>,
+
+    path_length: Option<f64>,
 }
 
 impl SetAttributes for Rect {
@@ -386,6 +469,11 @@ impl SetAttributes for Rect {
                         .parse_and_validate(value, Length::<Vertical>::check_nonnegative)
                         .map(Some)?
                 }
+                expanded_name!("", "pathLength") => {
+                    self.path_length = attr
+                        .parse_and_validate(value, check_path_length)
+                        .map(Some)?
+                }
                 _ => (),
             }
         }
@@ -404,13 +492,12 @@ impl Draw for Rect {
         clipping: bool,
     ) -> Result<BoundingBox, RenderingError> {
         let values = cascaded.get();
-        Shape::new(Rc::new(self.make_path(values, draw_ctx)), Markers::No).draw(
-            node,
-            acquired_nodes,
-            values,
-            draw_ctx,
-            clipping,
+        Shape::new(
+            Rc::new(self.make_path(values, draw_ctx)),
+            Markers::No,
+            self.path_length,
         )
+        .draw(node, acquired_nodes, values, draw_ctx, clipping)
     }
 }
 
@@ -592,6 +679,7 @@ pub struct Circle {
     cx: Length<Horizontal>,
     cy: Length<Vertical>,
     r: Length<Both>,
+    path_length: Option<f64>,
 }
 
 impl SetAttributes for Circle {
@@ -603,6 +691,11 @@ impl SetAttributes for Circle {
                 expanded_name!("", "r") => {
                     self.r = attr.parse_and_validate(value, Length::<Both>::check_nonnegative)?
                 }
+                expanded_name!("", "pathLength") => {
+                    self.path_length = attr
+                        .parse_and_validate(value, check_path_length)
+                        .map(Some)?
+                }
                 _ => (),
             }
         }
@@ -621,13 +714,12 @@ impl Draw for Circle {
         clipping: bool,
     ) -> Result<BoundingBox, RenderingError> {
         let values = cascaded.get();
-        Shape::new(Rc::new(self.make_path(values, draw_ctx)), Markers::No).draw(
-            node,
-            acquired_nodes,
-            values,
-            draw_ctx,
-            clipping,
+        Shape::new(
+            Rc::new(self.make_path(values, draw_ctx)),
+            Markers::No,
+            self.path_length,
         )
+        .draw(node, acquired_nodes, values, draw_ctx, clipping)
     }
 }
 
@@ -649,6 +741,7 @@ pub struct Ellipse {
     cy: Length<Vertical>,
     rx: Length<Horizontal>,
     ry: Length<Vertical>,
+    path_length: Option<f64>,
 }
 
 impl SetAttributes for Ellipse {
@@ -665,6 +758,11 @@ impl SetAttributes for Ellipse {
                     self.ry =
                         attr.parse_and_validate(value, Length::<Vertical>::check_nonnegative)?
                 }
+                expanded_name!("", "pathLength") => {
+                    self.path_length = attr
+                        .parse_and_validate(value, check_path_length)
+                        .map(Some)?
+                }
                 _ => (),
             }
         }
@@ -683,13 +781,12 @@ impl Draw for Ellipse {
         clipping: bool,
     ) -> Result<BoundingBox, RenderingError> {
         let values = cascaded.get();
-        Shape::new(Rc::new(self.make_path(values, draw_ctx)), Markers::No).draw(
-            node,
-            acquired_nodes,
-            values,
-            draw_ctx,
-            clipping,
+        Shape::new(
+            Rc::new(self.make_path(values, draw_ctx)),
+            Markers::No,
+            self.path_length,
         )
+        .draw(node, acquired_nodes, values, draw_ctx, clipping)
     }
 }
 
@@ -740,4 +837,25 @@ mod tests {
         assert!(Points::parse_str("-1-2-3-4").is_err());
         assert!(Points::parse_str("1 2-3,-4").is_err());
     }
+
+    #[test]
+    fn keeps_points_before_error() {
+        assert_eq!(Points::parse_partial(""), Points(vec![]));
+        assert_eq!(
+            Points::parse_partial("1,2 3,4 5,6"),
+            Points(vec![(1.0, 2.0), (3.0, 4.0), (5.0, 6.0)])
+        );
+        assert_eq!(
+            Points::parse_partial("1,2 3,4 5,BAD 7,8"),
+            Points(vec![(1.0, 2.0), (3.0, 4.0)])
+        );
+        assert_eq!(
+            Points::parse_partial("1,2 3,BAD 5,6"),
+            Points(vec![(1.0, 2.0)])
+        );
+        assert_eq!(
+            Points::parse_partial("1,2 3,4 5"),
+            Points(vec![(1.0, 2.0), (3.0, 4.0)])
+        );
+    }
 }
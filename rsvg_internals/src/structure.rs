@@ -232,6 +232,8 @@ impl Draw for Svg {
 
         let has_parent = node.parent().is_some();
 
+        // Nested svg elements clip to their viewport unless overflow is
+        // visible; auto, scroll and hidden all clip.
         let clip_mode = if !values.is_overflow() && has_parent {
             Some(ClipMode::ClipToViewport)
         } else {
@@ -265,10 +267,14 @@ impl Draw for Svg {
         };
 
         draw_ctx.with_discrete_layer(node, acquired_nodes, values, clipping, &mut |an, dc| {
-            let _params =
-                dc.push_new_viewport(vbox, viewport, self.preserve_aspect_ratio, clip_mode);
-
-            node.draw_children(an, cascaded, dc, clipping)
+            // A width, height, or viewBox size of zero disables rendering of the element.
+            if let Some(_params) =
+                dc.push_new_viewport(vbox, viewport, self.preserve_aspect_ratio, clip_mode)
+            {
+                node.draw_children(an, cascaded, dc, clipping)
+            } else {
+                Ok(dc.empty_bbox())
+            }
         })
     }
 }
@@ -283,7 +289,16 @@ pub struct Use {
 }
 
 impl Use {
-    pub fn get_rect(&self, values: &ComputedValues, params: &ViewParams) -> Rect {
+    /// Computes the viewport for the referenced element.
+    ///
+    /// If the `<use>` references a `<symbol>`, pass it in `symbol` so that its
+    /// `width` and `height` can be used when the `<use>` does not specify them.
+    pub fn get_rect(
+        &self,
+        symbol: Option<&Symbol>,
+        values: &ComputedValues,
+        params: &ViewParams,
+    ) -> Rect {
         let x = self.x.normalize(values, &params);
         let y = self.y.normalize(values, &params);
 
@@ -291,13 +306,19 @@ impl Use {
         // [...] use values of '100%' for these attributes.
         // From https://www.w3.org/TR/SVG/struct.html#UseElement in
         // "If the ‘use’ element references a ‘symbol’ element"
+        //
+        // SVG2 lets the symbol specify its own width and height, which
+        // are used instead of 100% if the use element doesn't override them.
+        // https://www.w3.org/TR/SVG2/struct.html#SymbolNotes
 
         let w = self
             .w
+            .or_else(|| symbol.and_then(|s| s.width))
             .unwrap_or_else(|| Length::<Horizontal>::parse_str("100%").unwrap())
             .normalize(values, &params);
         let h = self
             .h
+            .or_else(|| symbol.and_then(|s| s.height))
             .unwrap_or_else(|| Length::<Vertical>::parse_str("100%").unwrap())
             .normalize(values, &params);
 
@@ -349,6 +370,8 @@ impl Draw for Use {
 pub struct Symbol {
     preserve_aspect_ratio: AspectRatio,
     vbox: Option<ViewBox>,
+    width: Option<Length<Horizontal>>,
+    height: Option<Length<Vertical>>,
 }
 
 impl Symbol {
@@ -369,6 +392,16 @@ impl SetAttributes for Symbol {
                     self.preserve_aspect_ratio = attr.parse(value)?
                 }
                 expanded_name!("", "viewBox") => self.vbox = attr.parse(value).map(Some)?,
+                expanded_name!("", "width") => {
+                    self.width = attr
+                        .parse_and_validate(value, Length::<Horizontal>::check_nonnegative)
+                        .map(Some)?
+                }
+                expanded_name!("", "height") => {
+                    self.height = attr
+                        .parse_and_validate(value, Length::<Vertical>::check_nonnegative)
+                        .map(Some)?
+                }
                 _ => (),
             }
         }
@@ -480,20 +513,52 @@ impl SetAttributes for Mask {
 
 impl Draw for Mask {}
 
+/// The `<a>` element.
 #[derive(Default)]
 pub struct Link {
     link: Option<String>,
+    target: Option<String>,
+    title: Option<String>,
+}
+
+impl Link {
+    /// The link's URI, if it is present and not empty.
+    pub fn link(&self) -> Option<&str> {
+        match self.link.as_ref() {
+            Some(l) if !l.is_empty() => Some(l),
+            _ => None,
+        }
+    }
+
+    /// The `target` attribute, i.e. where the linked resource should be opened.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_ref().map(String::as_str)
+    }
+
+    /// The `xlink:title` attribute.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_ref().map(String::as_str)
+    }
 }
 
 impl SetAttributes for Link {
     fn set_attributes(&mut self, pbag: &PropertyBag<'_>) -> ElementResult {
+        let mut href = None;
+        let mut xlink_href = None;
+
         for (attr, value) in pbag.iter() {
             match attr.expanded() {
-                expanded_name!(xlink "href") => self.link = Some(value.to_owned()),
+                expanded_name!("", "href") => href = Some(value.to_owned()),
+                expanded_name!(xlink "href") => xlink_href = Some(value.to_owned()),
+                expanded_name!("", "target") => self.target = Some(value.to_owned()),
+                expanded_name!(xlink "title") => self.title = Some(value.to_owned()),
                 _ => (),
             }
         }
 
+        // SVG 2 says that href takes precedence over xlink:href
+        self.link = href.or(xlink_href);
+
         Ok(())
     }
 }
@@ -510,13 +575,23 @@ impl Draw for Link {
         let cascaded = CascadedValues::new(cascaded, node);
         let values = cascaded.get();
 
-        draw_ctx.with_discrete_layer(node, acquired_nodes, values, clipping, &mut |an, dc| {
-            match self.link.as_ref() {
-                Some(l) if !l.is_empty() => {
+        let bbox = draw_ctx.with_discrete_layer(
+            node,
+            acquired_nodes,
+            values,
+            clipping,
+            &mut |an, dc| match self.link() {
+                Some(l) => {
                     dc.with_link_tag(l, &mut |dc| node.draw_children(an, &cascaded, dc, clipping))
                 }
-                _ => node.draw_children(an, &cascaded, dc, clipping),
-            }
-        })
+                None => node.draw_children(an, &cascaded, dc, clipping),
+            },
+        )?;
+
+        if draw_ctx.is_measuring() && self.link().is_some() {
+            draw_ctx.record_link_bbox(node, &bbox);
+        }
+
+        Ok(bbox)
     }
 }
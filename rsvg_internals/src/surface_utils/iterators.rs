@@ -1,4 +1,7 @@
-//! Pixel iterators for `SharedImageSurface`.
+//! Pixel iterators for `SharedImageSurface`, and for the data of `ExclusiveImageSurface`.
+use std::convert::TryFrom;
+use std::mem;
+
 use crate::rect::IRect;
 use crate::util::clamp;
 
@@ -26,6 +29,20 @@ pub struct PixelRectangle<'a> {
     y: i32,
 }
 
+/// Iterator over mutable pixels of the image data of a surface.
+///
+/// This yields each pixel as the four bytes of a `u32` in native endianness, as
+/// stored by Cairo for the `ARgb32` format.  Use `Pixel::from_u32()` and
+/// `Pixel::to_u32()` to convert them.
+#[derive(Debug)]
+pub struct PixelsMut<'a> {
+    data: &'a mut [u8],
+    stride: usize,
+    bounds: IRect,
+    x: u32,
+    y: u32,
+}
+
 impl<'a> Pixels<'a> {
     /// Creates an iterator over the image surface pixels
     #[inline]
@@ -105,6 +122,33 @@ impl<'a> PixelRectangle<'a> {
     }
 }
 
+impl<'a> PixelsMut<'a> {
+    /// Creates an iterator over the pixels in `data`, constrained within the given bounds.
+    ///
+    /// The `data` and `stride` are those of an `ARgb32` image surface, as passed to the
+    /// closure in `ExclusiveImageSurface::modify()`.
+    #[inline]
+    pub fn within(data: &'a mut [u8], stride: usize, bounds: IRect) -> Self {
+        // Sanity checks.
+        assert!(bounds.x0 >= 0);
+        assert!(bounds.x1 >= bounds.x0);
+        assert!(bounds.x1 as usize * 4 <= stride);
+        assert!(bounds.y0 >= 0);
+        assert!(bounds.y1 >= bounds.y0);
+        assert!(bounds.y1 as usize * stride <= data.len());
+
+        let start = bounds.y0 as usize * stride + bounds.x0 as usize * 4;
+
+        Self {
+            data: &mut data[start..],
+            stride,
+            bounds,
+            x: bounds.x0 as u32,
+            y: bounds.y0 as u32,
+        }
+    }
+}
+
 impl<'a> Iterator for Pixels<'a> {
     type Item = (u32, u32, Pixel);
 
@@ -134,6 +178,41 @@ impl<'a> Iterator for Pixels<'a> {
     }
 }
 
+impl<'a> Iterator for PixelsMut<'a> {
+    type Item = (u32, u32, &'a mut [u8; 4]);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // This means we hit the end on the last iteration.
+        if self.x == self.bounds.x1 as u32 || self.y == self.bounds.y1 as u32 {
+            return None;
+        }
+
+        let data = mem::replace(&mut self.data, &mut []);
+        let (pixel, rest) = data.split_at_mut(4);
+
+        let rv = Some((self.x, self.y, <&mut [u8; 4]>::try_from(pixel).unwrap()));
+
+        if self.x + 1 == self.bounds.x1 as u32 {
+            self.x = self.bounds.x0 as u32;
+            self.y += 1;
+
+            // Skip to the start of the bounds in the next row
+            let skip = self.stride - self.bounds.width() as usize * 4;
+            self.data = if rest.len() > skip {
+                &mut rest[skip..]
+            } else {
+                &mut []
+            };
+        } else {
+            self.x += 1;
+            self.data = rest;
+        }
+
+        rv
+    }
+}
+
 impl<'a> Iterator for PixelRectangle<'a> {
     type Item = (i32, i32, Pixel);
 
@@ -194,7 +273,8 @@ impl<'a> Iterator for PixelRectangle<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::surface_utils::shared_surface::SurfaceType;
+    use crate::surface_utils::shared_surface::{ExclusiveImageSurface, SurfaceType};
+    use crate::surface_utils::ImageSurfaceDataExt;
 
     #[test]
     fn pixels_count() {
@@ -231,6 +311,67 @@ mod tests {
         assert_eq!(Pixels::within(&surface, bounds).count(), 0);
     }
 
+    #[test]
+    fn pixels_mut_visits_pixels_within_bounds() {
+        const WIDTH: i32 = 8;
+        const HEIGHT: i32 = 4;
+
+        let mut surface = ExclusiveImageSurface::new(WIDTH, HEIGHT, SurfaceType::SRgb).unwrap();
+
+        let pixel = Pixel {
+            r: 0x10,
+            g: 0x20,
+            b: 0x30,
+            a: 0xff,
+        };
+
+        let bounds = IRect::new(2, 1, 5, 3);
+
+        surface.modify(&mut |data, stride| {
+            let mut count = 0;
+
+            for (x, y, p) in PixelsMut::within(data, stride, bounds) {
+                assert!(bounds.contains(x as i32, y as i32));
+                *p = pixel.to_u32().to_ne_bytes();
+                count += 1;
+            }
+
+            assert_eq!(count, 6);
+        });
+
+        let surface = surface.share().unwrap();
+
+        for (x, y, p) in Pixels::new(&surface) {
+            if bounds.contains(x as i32, y as i32) {
+                assert_eq!(p, pixel);
+            } else {
+                assert_eq!(p.a, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn pixels_mut_reads_existing_values() {
+        let mut surface = ExclusiveImageSurface::new(4, 4, SurfaceType::SRgb).unwrap();
+
+        let pixel = Pixel {
+            r: 0x40,
+            g: 0x80,
+            b: 0xc0,
+            a: 0xff,
+        };
+
+        surface.modify(&mut |data, stride| {
+            data.set_pixel(stride, pixel, 3, 2);
+
+            let (_, _, p) = PixelsMut::within(data, stride, IRect::from_size(4, 4))
+                .find(|&(x, y, _)| (x, y) == (3, 2))
+                .unwrap();
+
+            assert_eq!(Pixel::from_u32(u32::from_ne_bytes(*p)), pixel);
+        });
+    }
+
     #[test]
     fn pixel_rectangle() {
         const WIDTH: i32 = 32;
@@ -33,22 +33,23 @@ pub trait ImageSurfaceDataExt: DerefMut<Target = [u8]> {
     /// Sets the pixel at the given coordinates. Assumes the `ARgb32` format.
     #[inline]
     fn set_pixel(&mut self, stride: usize, pixel: Pixel, x: u32, y: u32) {
-        let value = pixel.to_u32();
-        unsafe {
-            *(&mut self[y as usize * stride + x as usize * 4] as *mut u8 as *mut u32) = value;
-        }
+        let offset = y as usize * stride + x as usize * 4;
+        self[offset..offset + 4].copy_from_slice(&pixel.to_u32().to_ne_bytes());
     }
 }
 
 impl Pixel {
     /// Returns an unpremultiplied value of this pixel.
+    ///
+    /// Components are rounded half-up.  Invalid pixels, with a color component larger
+    /// than the alpha, get their components clamped to 255.
     #[inline]
     pub fn unpremultiply(self) -> Self {
         if self.a == 0 {
             self
         } else {
             let alpha = f64::from(self.a) / 255.0;
-            let unpremultiply = |x| ((f64::from(x) / alpha) + 0.5) as u8;
+            let unpremultiply = |x| ((f64::from(x) / alpha) + 0.5).min(255.0) as u8;
 
             Self {
                 r: unpremultiply(self.r),
@@ -60,6 +61,8 @@ impl Pixel {
     }
 
     /// Returns a premultiplied value of this pixel.
+    ///
+    /// Components are rounded half-up.
     #[inline]
     pub fn premultiply(self) -> Self {
         let alpha = f64::from(self.a) / 255.0;
@@ -149,3 +152,72 @@ impl Pixel {
 
 impl<'a> ImageSurfaceDataExt for cairo::ImageSurfaceData<'a> {}
 impl<'a> ImageSurfaceDataExt for &'a mut [u8] {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn premultiplied_pixels_round_trip() {
+        for a in 0..=255u8 {
+            for c in 0..=a {
+                let pixel = Pixel {
+                    r: c,
+                    g: c,
+                    b: c,
+                    a,
+                };
+                let round_trip = pixel.unpremultiply().premultiply();
+
+                let diff = pixel.diff(&round_trip);
+                assert!(
+                    diff.r <= 1 && diff.g <= 1 && diff.b <= 1 && diff.a == 0,
+                    "{:?} became {:?}",
+                    pixel,
+                    round_trip
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unpremultiply_rounds_half_up() {
+        // 1 / (2 / 255) = 127.5
+        let pixel = Pixel {
+            r: 1,
+            g: 0,
+            b: 2,
+            a: 2,
+        };
+
+        assert_eq!(
+            pixel.unpremultiply(),
+            Pixel {
+                r: 128,
+                g: 0,
+                b: 255,
+                a: 2
+            }
+        );
+    }
+
+    #[test]
+    fn unpremultiply_clamps_invalid_pixels() {
+        let pixel = Pixel {
+            r: 200,
+            g: 100,
+            b: 0,
+            a: 100,
+        };
+
+        assert_eq!(
+            pixel.unpremultiply(),
+            Pixel {
+                r: 255,
+                g: 255,
+                b: 0,
+                a: 100
+            }
+        );
+    }
+}
@@ -494,6 +494,31 @@ impl ImageSurface<Shared> {
         SharedImageSurface::wrap(output_surface, self.surface_type)
     }
 
+    /// Returns a surface with the color values pre-multiplied by alpha.
+    ///
+    /// This is the inverse of `unpremultiply()`, for surfaces that store unpremultiplied
+    /// pixels.
+    pub fn premultiply(&self, bounds: IRect) -> Result<SharedImageSurface, cairo::Status> {
+        // Premultiplication doesn't affect the alpha channel.
+        if self.is_alpha_only() {
+            return Ok(self.clone());
+        }
+
+        let mut output_surface =
+            cairo::ImageSurface::create(cairo::Format::ARgb32, self.width, self.height)?;
+
+        let stride = output_surface.get_stride() as usize;
+        {
+            let mut data = output_surface.get_data().unwrap();
+
+            for (x, y, pixel) in Pixels::within(self, bounds) {
+                data.set_pixel(stride, pixel.premultiply(), x, y);
+            }
+        }
+
+        SharedImageSurface::wrap(output_surface, self.surface_type)
+    }
+
     /// Converts the surface to the linear sRGB color space.
     #[inline]
     pub fn to_linear_rgb(&self, bounds: IRect) -> Result<SharedImageSurface, cairo::Status> {
@@ -941,29 +966,65 @@ impl ImageSurface<Shared> {
         SharedImageSurface::wrap(output_surface, self.surface_type)
     }
 
-    /// Fills the with a specified color.
+    /// Fills the bounds with a specified color.
+    ///
+    /// The contents of `self` are not used; it only provides the surface type, and
+    /// the maximum size of the output.  Filter primitives look up the pixels of their
+    /// inputs in the coordinate space of `self`, but only within the filter effects
+    /// `region`, so the output only extends to the bottom-right corner of the region
+    /// instead of having the full size of `self`.
     #[inline]
     pub fn flood(
         &self,
         bounds: IRect,
+        region: IRect,
         color: cssparser::RGBA,
         opacity: UnitInterval,
     ) -> Result<SharedImageSurface, cairo::Status> {
-        let output_surface =
-            cairo::ImageSurface::create(cairo::Format::ARgb32, self.width, self.height)?;
+        let width = region.x1.min(self.width).max(1);
+        let height = region.y1.min(self.height).max(1);
 
-        if opacity.0 > 0.0 {
-            let cr = cairo::Context::new(&output_surface);
-            let r = cairo::Rectangle::from(bounds);
-            cr.rectangle(r.x, r.y, r.width, r.height);
-            cr.clip();
+        let output_surface = cairo::ImageSurface::create(cairo::Format::ARgb32, width, height)?;
+
+        let bounds = bounds.intersection(&IRect::from_size(width, height));
+
+        if let Some(bounds) = bounds {
+            if opacity.0 > 0.0 {
+                let cr = cairo::Context::new(&output_surface);
+                cr.set_operator(cairo::Operator::Source);
+                cr.set_source_rgba(
+                    f64::from(color.red) / 255f64,
+                    f64::from(color.green) / 255f64,
+                    f64::from(color.blue) / 255f64,
+                    opacity.0,
+                );
+
+                // Filling a pixel-aligned rectangle lets Cairo write the
+                // pixels directly, instead of going through a clip mask.
+                let r = cairo::Rectangle::from(bounds);
+                cr.rectangle(r.x, r.y, r.width, r.height);
+                cr.fill();
+            }
+        }
 
-            cr.set_source_rgba(
-                f64::from(color.red) / 255f64,
-                f64::from(color.green) / 255f64,
-                f64::from(color.blue) / 255f64,
-                opacity.0,
-            );
+        SharedImageSurface::wrap(output_surface, self.surface_type)
+    }
+
+    /// Returns a surface of the given size with the contents of `self`.
+    ///
+    /// This is used to bring surfaces that only cover part of the source graphic,
+    /// like the output of `flood()`, back to its full size.
+    #[inline]
+    pub fn with_size(&self, width: i32, height: i32) -> Result<SharedImageSurface, cairo::Status> {
+        if self.width == width && self.height == height {
+            return Ok(self.clone());
+        }
+
+        let output_surface = cairo::ImageSurface::create(cairo::Format::ARgb32, width, height)?;
+
+        {
+            let cr = cairo::Context::new(&output_surface);
+            self.set_as_source_surface(&cr, 0.0, 0.0);
             cr.paint();
         }
 
@@ -981,12 +1042,15 @@ impl ImageSurface<Shared> {
         let output_surface =
             cairo::ImageSurface::create(cairo::Format::ARgb32, self.width, self.height)?;
 
-        // output_bounds contains all pixels within bounds,
-        // for which (x - ox) and (y - oy) also lie within bounds.
-        if let Some(output_bounds) = bounds
-            .translate((dx as i32, dy as i32))
-            .intersection(&bounds)
-        {
+        // output_bounds contains all points within bounds,
+        // for which (x - dx) and (y - dy) also lie within bounds.
+        //
+        // The offsets can be fractional; in that case Cairo interpolates
+        // the source pixels, and the clip blends the partially covered
+        // pixels at the edges.
+        let bounds = Rect::from(bounds);
+
+        if let Some(output_bounds) = bounds.translate((dx, dy)).intersection(&bounds) {
             let cr = cairo::Context::new(&output_surface);
             let r = cairo::Rectangle::from(output_bounds);
             cr.rectangle(r.x, r.y, r.width, r.height);
@@ -1123,8 +1187,11 @@ impl ImageSurface<Shared> {
     /// Each pixel of the resulting image is computed using the following formula:
     /// `res = k1*i1*i2 + k2*i1 + k3*i2 + k4`
     ///
+    /// `k4` can produce colors even if both inputs are alpha-only, so the output is
+    /// of the given `surface_type`.
+    ///
     /// # Panics
-    /// Panics if the two surface types are not compatible.
+    /// Panics if the surface types of the inputs and the output are not compatible.
     #[inline]
     pub fn compose_arithmetic(
         &self,
@@ -1134,12 +1201,16 @@ impl ImageSurface<Shared> {
         k2: f64,
         k3: f64,
         k4: f64,
+        surface_type: SurfaceType,
     ) -> Result<SharedImageSurface, cairo::Status> {
-        let mut output_surface = ExclusiveImageSurface::new(
-            self.width,
-            self.height,
-            self.surface_type.combine(other.surface_type),
-        )?;
+        assert_eq!(
+            self.surface_type
+                .combine(other.surface_type)
+                .combine(surface_type),
+            surface_type
+        );
+
+        let mut output_surface = ExclusiveImageSurface::new(self.width, self.height, surface_type)?;
 
         composite_arithmetic(self, other, &mut output_surface, bounds, k1, k2, k3, k4);
 
@@ -1301,4 +1372,163 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn offset_supports_fractional_amounts() {
+        const SIDE: i32 = 8;
+
+        let bounds = IRect::from_size(SIDE, SIDE);
+
+        let mut surface = ExclusiveImageSurface::new(SIDE, SIDE, SurfaceType::SRgb).unwrap();
+
+        // An opaque black column at x = 2.
+        surface
+            .draw(&mut |cr| {
+                cr.rectangle(2.0, 0.0, 1.0, f64::from(SIDE));
+                cr.fill();
+                Ok(())
+            })
+            .unwrap();
+
+        let surface = surface.share().unwrap();
+
+        let whole = surface.offset(bounds, 1.0, 0.0).unwrap();
+        assert_eq!(whole.get_pixel(2, 4).a, 0);
+        assert_eq!(whole.get_pixel(3, 4).a, 255);
+
+        let unmoved = surface.offset(bounds, 0.0, 0.0).unwrap();
+        let half = surface.offset(bounds, 0.5, 0.0).unwrap();
+
+        // The column gets spread over two pixels.
+        assert_ne!(half.get_pixel(2, 4), unmoved.get_pixel(2, 4));
+        assert!(half.get_pixel(2, 4).a > 0 && half.get_pixel(2, 4).a < 255);
+        assert!(half.get_pixel(3, 4).a > 0 && half.get_pixel(3, 4).a < 255);
+
+        // Nothing is written to the left of the offset bounds.
+        assert_eq!(half.get_pixel(0, 4).a, 0);
+    }
+
+    #[test]
+    fn tiles_input_subregion() {
+        const SIDE: i32 = 100;
+
+        let tile_bounds = IRect::new(20, 20, 30, 30);
+
+        let mut surface = ExclusiveImageSurface::new(SIDE, SIDE, SurfaceType::SRgb).unwrap();
+
+        // A 10×10 tile with a single opaque pixel in its top-left corner.
+        surface
+            .draw(&mut |cr| {
+                cr.rectangle(20.0, 20.0, 1.0, 1.0);
+                cr.fill();
+                Ok(())
+            })
+            .unwrap();
+
+        let surface = surface.share().unwrap();
+
+        let tile = surface.tile(tile_bounds).unwrap();
+        assert_eq!(tile.width(), 10);
+        assert_eq!(tile.height(), 10);
+
+        let output = surface
+            .paint_image_tiled(
+                IRect::from_size(SIDE, SIDE),
+                &tile,
+                tile_bounds.x0,
+                tile_bounds.y0,
+            )
+            .unwrap();
+
+        assert_eq!(output.get_pixel(0, 0).a, 255);
+        assert_eq!(output.get_pixel(10, 0), output.get_pixel(0, 0));
+        assert_eq!(output.get_pixel(90, 90), output.get_pixel(0, 0));
+        assert_eq!(output.get_pixel(1, 0).a, 0);
+        assert_eq!(output.get_pixel(11, 0), output.get_pixel(1, 0));
+    }
+
+    #[test]
+    fn premultiply_undoes_unpremultiply() {
+        const SIDE: i32 = 16;
+
+        let bounds = IRect::from_size(SIDE, SIDE);
+
+        let mut surface = ExclusiveImageSurface::new(SIDE, SIDE, SurfaceType::SRgb).unwrap();
+
+        surface.modify(&mut |data, stride| {
+            for y in 0..SIDE as u32 {
+                for x in 0..SIDE as u32 {
+                    let a = (y * 16 + x) as u8;
+                    let pixel = Pixel {
+                        r: a,
+                        g: a / 2,
+                        b: a / 3,
+                        a,
+                    };
+
+                    data.set_pixel(stride, pixel, x, y);
+                }
+            }
+        });
+
+        let surface = surface.share().unwrap();
+        let round_trip = surface
+            .unpremultiply(bounds)
+            .unwrap()
+            .premultiply(bounds)
+            .unwrap();
+
+        for (x, y, pixel) in Pixels::new(&surface) {
+            let diff = pixel.diff(&round_trip.get_pixel(x, y));
+            assert!(diff.r <= 1 && diff.g <= 1 && diff.b <= 1 && diff.a == 0);
+        }
+    }
+
+    #[test]
+    fn flood_only_writes_bounds_within_surface() {
+        const SIDE: i32 = 32;
+
+        let surface = SharedImageSurface::empty(SIDE, SIDE, SurfaceType::SRgb).unwrap();
+        let red = cssparser::RGBA::new(255, 0, 0, 255);
+
+        // The region and the bounds extend past the right edge of the surface.
+        let region = IRect::new(4, 4, SIDE + 4, 20);
+        let bounds = IRect::new(8, 8, SIDE + 8, 16);
+        let flooded = surface
+            .flood(bounds, region, red, UnitInterval(1.0))
+            .unwrap();
+
+        assert_eq!(flooded.width(), SIDE);
+        assert_eq!(flooded.height(), 20);
+
+        for (x, y, pixel) in Pixels::new(&flooded) {
+            let expected = if bounds.contains(x as i32, y as i32) {
+                Pixel {
+                    r: 255,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                }
+            } else {
+                Pixel {
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 0,
+                }
+            };
+
+            assert_eq!(pixel, expected);
+        }
+
+        let transparent = surface
+            .flood(bounds, region, red, UnitInterval(0.0))
+            .unwrap();
+        assert!(Pixels::new(&transparent).all(|(_, _, pixel)| pixel.a == 0));
+
+        let padded = flooded.with_size(SIDE, SIDE).unwrap();
+        assert_eq!(padded.height(), SIDE);
+        assert_eq!(padded.get_pixel(8, 8), flooded.get_pixel(8, 8));
+        assert_eq!(padded.get_pixel(8, 24).a, 0);
+    }
 }
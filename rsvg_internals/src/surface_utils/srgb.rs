@@ -24,6 +24,31 @@ pub fn unlinearize(c: u8) -> u8 {
     UNLINEARIZE[usize::from(c)]
 }
 
+/// Converts an sRGB color value in the `[0.0, 1.0]` range to linear sRGB.
+///
+/// This is the non-tabulated version of `linearize()`, for callers that need
+/// more precision than 8 bits, like gradient color stops.
+#[inline]
+pub fn linearize_f64(c: f64) -> f64 {
+    if c <= (12.92 * 0.0031308) {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear sRGB color value in the `[0.0, 1.0]` range to normal sRGB.
+///
+/// This is the non-tabulated version of `unlinearize()`.
+#[inline]
+pub fn unlinearize_f64(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1f64 / 2.4) - 0.055
+    }
+}
+
 /// Processing loop of `map_unpremultiplied_components`. Extracted (and public) for benchmarking.
 #[inline]
 pub fn map_unpremultiplied_components_loop<F: Fn(u8) -> u8>(
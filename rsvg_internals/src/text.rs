@@ -3,23 +3,26 @@
 use markup5ever::{expanded_name, local_name, namespace_url, ns};
 use pango::FontMapExt;
 use std::cell::RefCell;
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
 
 use crate::allowed_url::Fragment;
 use crate::bbox::BoundingBox;
 use crate::document::AcquiredNodes;
-use crate::drawing_ctx::DrawingCtx;
+use crate::drawing_ctx::{DrawingCtx, RenderMode};
 use crate::element::{Draw, Element, ElementResult, SetAttributes};
 use crate::error::*;
 use crate::float_eq_cairo::ApproxEqCairo;
-use crate::font_props::FontWeightSpec;
+use crate::font_props::{FontWeightSpec, LetterSpacingSpec};
 use crate::length::*;
 use crate::node::{CascadedValues, Node, NodeBorrow};
+use crate::paint_server::PaintServer;
 use crate::parsers::ParseValue;
 use crate::properties::ComputedValues;
 use crate::property_bag::PropertyBag;
 use crate::property_defs::{
-    Direction, FontStretch, FontStyle, FontVariant, TextAnchor, TextRendering, UnicodeBidi,
-    WritingMode, XmlLang, XmlSpace,
+    Direction, Display, FontSizeAdjust, FontStretch, FontStyle, FontVariant, LetterSpacing,
+    TextAnchor, TextRendering, UnicodeBidi, WritingMode, XmlLang, XmlSpace,
 };
 use crate::rect::Rect;
 use crate::space::{xml_space_normalize, NormalizeDefault, XmlSpaceNormalize};
@@ -290,7 +293,7 @@ impl PositionedSpan {
 
             let cr = dc.get_cairo_context();
             cr.set_antialias(cairo::Antialias::from(self.values.text_rendering()));
-            dc.setup_cr_for_stroke(&cr, &self.values);
+            dc.setup_cr_for_stroke(&cr, &self.values, 1.0);
             cr.move_to(self.rendered_position.0, self.rendered_position.1);
 
             let rotation = gravity.to_rotation();
@@ -300,7 +303,9 @@ impl PositionedSpan {
 
             let current_color = self.values.color().0;
 
-            let res = if !clipping {
+            let painting = !clipping && dc.render_mode() == RenderMode::Rasterize;
+
+            let res = if painting {
                 dc.set_source_paint_server(
                     acquired_nodes,
                     &self.values.fill().0,
@@ -320,9 +325,11 @@ impl PositionedSpan {
             };
 
             if res.is_ok() {
-                let mut need_layout_path = clipping;
+                // The stroke's ink rect is needed even when not painting.
+                let mut need_layout_path =
+                    clipping || (!painting && self.values.stroke().0 != PaintServer::None);
 
-                let res = if !clipping {
+                let res = if painting {
                     dc.set_source_paint_server(
                         acquired_nodes,
                         &self.values.stroke().0,
@@ -350,7 +357,13 @@ impl PositionedSpan {
                         let ib = BoundingBox::new()
                             .with_transform(transform)
                             .with_ink_rect(r);
-                        cr.stroke();
+
+                        if painting {
+                            cr.stroke();
+                        } else {
+                            cr.new_path();
+                        }
+
                         bbox.insert(&ib);
                     }
                 }
@@ -378,9 +391,11 @@ impl PositionedSpan {
         let pango_scale = f64::from(pango::SCALE);
 
         let (x, y, w, h) = if gravity_is_vertical(gravity) {
+            // The layout is rotated 90 degrees clockwise when drawn (see draw()),
+            // so its x axis goes down the page and its y axis goes to the left.
             (
-                x + (ink_x - ink_height) / pango_scale,
-                y + ink_y / pango_scale,
+                x - (ink_y + ink_height) / pango_scale,
+                y + ink_x / pango_scale,
                 ink_height / pango_scale,
                 ink_width / pango_scale,
             )
@@ -437,9 +452,16 @@ fn children_to_chunks(
         } else {
             assert!(child.is_element());
 
+            let cascaded = CascadedValues::new(cascaded, &child);
+
+            // Sub-elements with display:none generate no text at all,
+            // so they must not take up space in the layout either.
+            if cascaded.get().display() == Display::None {
+                continue;
+            }
+
             match *child.borrow_element() {
                 Element::TSpan(ref tspan) => {
-                    let cascaded = CascadedValues::new(cascaded, &child);
                     tspan.to_chunks(
                         &child,
                         acquired_nodes,
@@ -451,7 +473,6 @@ fn children_to_chunks(
                 }
 
                 Element::TRef(ref tref) => {
-                    let cascaded = CascadedValues::new(cascaded, &child);
                     tref.to_chunks(&child, acquired_nodes, &cascaded, chunks, depth + 1);
                 }
 
@@ -482,17 +503,27 @@ fn children_to_chunks(
 /// When rendering a text element, it will take care of concatenating the strings
 /// in its `Chars` children as appropriate, depending on the
 /// `xml:space="preserve"` attribute.  A `Chars` stores the characters verbatim
-/// as they come out of the XML parser, after ensuring that they are valid UTF-8.
+/// as they come out of the XML parser, after ensuring that they are valid UTF-8,
+/// and normalized to Unicode NFC unless that was turned off in the `LoadOptions`.
 
 pub struct Chars {
     string: RefCell<String>,
-    space_normalized: RefCell<Option<String>>,
+
+    /// Whether to normalize the string to Unicode NFC as it gets appended.
+    normalize_unicode: bool,
+
+    /// Cache of the normalized string, along with the `xml:space` value used to compute it.
+    ///
+    /// The same `Chars` can be rendered with different values of `xml:space`, since
+    /// it is inherited and the text may be instanced from `<use>` elements.
+    space_normalized: RefCell<Option<(XmlSpace, String)>>,
 }
 
 impl Chars {
-    pub fn new() -> Chars {
+    pub fn new(normalize_unicode: bool) -> Chars {
         Chars {
             string: RefCell::new(String::new()),
+            normalize_unicode,
             space_normalized: RefCell::new(None),
         }
     }
@@ -502,15 +533,39 @@ impl Chars {
     }
 
     pub fn append(&self, s: &str) {
-        self.string.borrow_mut().push_str(s);
+        let mut string = self.string.borrow_mut();
+
+        if self.normalize_unicode {
+            // The XML parser may split a base character from its combining marks,
+            // so renormalize from the last starter of the text we already have.
+            // Everything before it is in NFC already and cannot change.
+            let start = string
+                .char_indices()
+                .rev()
+                .find(|&(_, c)| canonical_combining_class(c) == 0)
+                .map_or(0, |(i, _)| i);
+
+            let mut tail = string.split_off(start);
+            tail.push_str(s);
+
+            if is_nfc(&tail) {
+                string.push_str(&tail);
+            } else {
+                string.extend(tail.nfc());
+            }
+        } else {
+            string.push_str(s);
+        }
+
         *self.space_normalized.borrow_mut() = None;
     }
 
     fn ensure_normalized_string(&self, node: &Node, values: &ComputedValues) {
         let mut normalized = self.space_normalized.borrow_mut();
+        let xml_space = values.xml_space();
 
-        if (*normalized).is_none() {
-            let mode = match values.xml_space() {
+        if normalized.as_ref().map_or(true, |(s, _)| *s != xml_space) {
+            let mode = match xml_space {
                 XmlSpace::Default => XmlSpaceNormalize::Default(NormalizeDefault {
                     has_element_before: node.previous_sibling().is_some(),
                     has_element_after: node.next_sibling().is_some(),
@@ -519,7 +574,7 @@ impl Chars {
                 XmlSpace::Preserve => XmlSpaceNormalize::Preserve,
             };
 
-            *normalized = Some(xml_space_normalize(mode, &self.string.borrow()));
+            *normalized = Some((xml_space, xml_space_normalize(mode, &self.string.borrow())));
         }
     }
 
@@ -534,7 +589,7 @@ impl Chars {
         self.ensure_normalized_string(node, values);
 
         Span::new(
-            self.space_normalized.borrow().as_ref().unwrap(),
+            &self.space_normalized.borrow().as_ref().unwrap().1,
             values.clone(),
             dx,
             dy,
@@ -692,6 +747,7 @@ impl TRef {
             extract_chars_children_to_chunks_recursively(chunks, &c, values, depth);
         } else {
             rsvg_log!(
+                Warn,
                 "element {} references a nonexistent text source \"{}\"",
                 node,
                 link,
@@ -786,6 +842,31 @@ impl SetAttributes for TSpan {
 
 impl Draw for TSpan {}
 
+/// Computes the font size for `font-size-adjust`.
+///
+/// The font that Pango selects for `font_desc` is scaled so that its x-height
+/// becomes `aspect * font_size`.
+fn adjust_font_size(
+    pango_context: &pango::Context,
+    font_desc: &pango::FontDescription,
+    font_size: f64,
+    aspect: f64,
+) -> f64 {
+    // Pango doesn't give us the x-height in the font metrics, so measure an "x".
+    let layout = pango::Layout::new(pango_context);
+    layout.set_font_description(Some(font_desc));
+    layout.set_text("x");
+
+    let (ink, _) = layout.get_extents();
+    let x_height = f64::from(ink.height) / f64::from(pango::SCALE);
+
+    if x_height > 0.0 {
+        font_size * aspect * font_size / x_height
+    } else {
+        font_size
+    }
+}
+
 fn to_pango_units(v: f64) -> i32 {
     (v * f64::from(pango::SCALE) + 0.5) as i32
 }
@@ -799,14 +880,25 @@ impl<'a> From<&'a XmlLang> for pango::Language {
 impl From<TextRendering> for cairo::Antialias {
     fn from(tr: TextRendering) -> cairo::Antialias {
         match tr {
-            TextRendering::Auto
-            | TextRendering::OptimizeLegibility
-            | TextRendering::GeometricPrecision => cairo::Antialias::Default,
+            TextRendering::Auto => cairo::Antialias::Default,
+            TextRendering::OptimizeLegibility => cairo::Antialias::Gray,
+            TextRendering::GeometricPrecision => cairo::Antialias::Subpixel,
             TextRendering::OptimizeSpeed => cairo::Antialias::None,
         }
     }
 }
 
+impl From<TextRendering> for cairo::HintStyle {
+    fn from(tr: TextRendering) -> cairo::HintStyle {
+        match tr {
+            TextRendering::Auto => cairo::HintStyle::Default,
+            TextRendering::OptimizeLegibility => cairo::HintStyle::Slight,
+            TextRendering::GeometricPrecision => cairo::HintStyle::None,
+            TextRendering::OptimizeSpeed => cairo::HintStyle::Full,
+        }
+    }
+}
+
 impl From<FontStyle> for pango::Style {
     fn from(s: FontStyle) -> pango::Style {
         match s {
@@ -885,26 +977,29 @@ impl From<Direction> for pango::Alignment {
 impl From<WritingMode> for pango::Direction {
     fn from(m: WritingMode) -> pango::Direction {
         match m {
-            WritingMode::LrTb | WritingMode::Lr | WritingMode::Tb | WritingMode::TbRl => {
-                pango::Direction::Ltr
-            }
             WritingMode::RlTb | WritingMode::Rl => pango::Direction::Rtl,
+            _ => pango::Direction::Ltr,
         }
     }
 }
 
 impl From<WritingMode> for pango::Gravity {
     fn from(m: WritingMode) -> pango::Gravity {
-        match m {
-            WritingMode::Tb | WritingMode::TbRl => pango::Gravity::East,
-            WritingMode::LrTb | WritingMode::Lr | WritingMode::RlTb | WritingMode::Rl => {
-                pango::Gravity::South
-            }
+        // vertical-rl and vertical-lr only differ in the direction in which lines
+        // are stacked, and SVG text is always a single line.
+        if m.is_vertical() {
+            pango::Gravity::East
+        } else {
+            pango::Gravity::South
         }
     }
 }
 
-fn get_pango_context(cr: &cairo::Context, is_testing: bool) -> pango::Context {
+fn get_pango_context(
+    cr: &cairo::Context,
+    text_rendering: TextRendering,
+    is_testing: bool,
+) -> pango::Context {
     let font_map = pangocairo::FontMap::get_default().unwrap();
     let context = font_map.create_context().unwrap();
     pangocairo::functions::update_context(&cr, &context);
@@ -927,12 +1022,25 @@ fn get_pango_context(cr: &cairo::Context, is_testing: bool) -> pango::Context {
     // code.
     pangocairo::functions::context_set_resolution(&context, 72.0);
 
-    if is_testing {
+    if is_testing || text_rendering != TextRendering::Auto {
         let mut options = cairo::FontOptions::new();
 
-        options.set_antialias(cairo::Antialias::Gray);
-        options.set_hint_style(cairo::HintStyle::Full);
-        options.set_hint_metrics(cairo::HintMetrics::On);
+        if is_testing {
+            options.set_antialias(cairo::Antialias::Gray);
+            options.set_hint_style(cairo::HintStyle::Full);
+            options.set_hint_metrics(cairo::HintMetrics::On);
+        }
+
+        // An explicit text-rendering overrides the defaults, even when testing,
+        // so that its effect can be checked in the reference tests.
+        if text_rendering != TextRendering::Auto {
+            options.set_antialias(cairo::Antialias::from(text_rendering));
+            options.set_hint_style(cairo::HintStyle::from(text_rendering));
+
+            if text_rendering == TextRendering::GeometricPrecision {
+                options.set_hint_metrics(cairo::HintMetrics::Off);
+            }
+        }
 
         pangocairo::functions::context_set_font_options(&context, Some(&options));
     }
@@ -940,13 +1048,44 @@ fn get_pango_context(cr: &cairo::Context, is_testing: bool) -> pango::Context {
     context
 }
 
+/// Metrics of a run of text, in whole user-space units.
+///
+/// The `ascent` is the distance from the top of the logical extents to the
+/// baseline, and the `descent` the distance from the baseline to the bottom,
+/// so `ascent + descent == height`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextMetrics {
+    pub width: f64,
+    pub height: f64,
+    pub ascent: f64,
+    pub descent: f64,
+}
+
+/// Measures `text` as it would be laid out with the given `values`.
+pub fn measure_text(draw_ctx: &DrawingCtx, text: &str, values: &ComputedValues) -> TextMetrics {
+    let layout = create_pango_layout(draw_ctx, values, text);
+
+    let (_, logical) = layout.get_pixel_extents();
+
+    // Round the baseline to whole units in the same way as the pixel extents.
+    let baseline = (layout.get_baseline() + pango::SCALE / 2) / pango::SCALE;
+    let ascent = baseline - logical.y;
+
+    TextMetrics {
+        width: f64::from(logical.width),
+        height: f64::from(logical.height),
+        ascent: f64::from(ascent),
+        descent: f64::from(logical.height - ascent),
+    }
+}
+
 fn create_pango_layout(
     draw_ctx: &DrawingCtx,
     values: &ComputedValues,
     text: &str,
 ) -> pango::Layout {
     let cr = draw_ctx.get_cairo_context();
-    let pango_context = get_pango_context(&cr, draw_ctx.is_testing());
+    let pango_context = get_pango_context(&cr, values.text_rendering(), draw_ctx.is_testing());
 
     // See the construction of the XmlLang property
     // We use "" there as the default value; this means that the language is not set.
@@ -980,9 +1119,13 @@ fn create_pango_layout(
 
     let params = draw_ctx.get_view_params();
 
-    font_desc.set_size(to_pango_units(
-        values.font_size().0.normalize(values, &params),
-    ));
+    let font_size = values.font_size().0.normalize(values, &params);
+    font_desc.set_size(to_pango_units(font_size));
+
+    if let FontSizeAdjust(Some(aspect)) = values.font_size_adjust() {
+        let adjusted = adjust_font_size(&pango_context, &font_desc, font_size, aspect);
+        font_desc.set_size(to_pango_units(adjusted));
+    }
 
     let layout = pango::Layout::new(&pango_context);
     layout.set_auto_dir(false);
@@ -990,12 +1133,16 @@ fn create_pango_layout(
 
     let attr_list = pango::AttrList::new();
 
-    attr_list.insert(
-        pango::Attribute::new_letter_spacing(to_pango_units(
-            values.letter_spacing().0.normalize(values, &params),
-        ))
-        .unwrap(),
-    );
+    // For letter-spacing: normal, don't give Pango a spacing at all, so that it
+    // can use the font's kerning as-is.
+    if let LetterSpacing(LetterSpacingSpec::Value(spacing)) = values.letter_spacing() {
+        attr_list.insert(
+            pango::Attribute::new_letter_spacing(to_pango_units(
+                spacing.normalize(values, &params),
+            ))
+            .unwrap(),
+        );
+    }
 
     if values.text_decoration().underline {
         attr_list.insert(pango::Attribute::new_underline(pango::Underline::Single).unwrap());
@@ -1007,7 +1154,154 @@ fn create_pango_layout(
 
     layout.set_attributes(Some(&attr_list));
     layout.set_alignment(pango::Alignment::from(values.direction()));
-    layout.set_text(text);
+    layout.set_text(&wrap_in_bidi_control_chars(
+        text,
+        values.unicode_bidi(),
+        values.direction(),
+    ));
 
     layout
 }
+
+/// Wraps `text` in the Unicode control characters for `unicode-bidi` and `direction`.
+///
+/// Setting the base direction of the Pango context is not enough, since
+/// strongly-directional characters would still be laid out in their own
+/// direction.  The embedding and override characters make the bidi algorithm
+/// behave as requested.
+///
+/// https://www.w3.org/TR/css-writing-modes-3/#unicode-bidi
+fn wrap_in_bidi_control_chars(text: &str, bidi: UnicodeBidi, direction: Direction) -> String {
+    const LRE: char = '\u{202a}';
+    const RLE: char = '\u{202b}';
+    const PDF: char = '\u{202c}';
+    const LRO: char = '\u{202d}';
+    const RLO: char = '\u{202e}';
+
+    let start = match (bidi, direction) {
+        (UnicodeBidi::Normal, _) => return text.to_string(),
+        (UnicodeBidi::Embed, Direction::Ltr) => LRE,
+        (UnicodeBidi::Embed, Direction::Rtl) => RLE,
+        (UnicodeBidi::Override, Direction::Ltr) => LRO,
+        (UnicodeBidi::Override, Direction::Rtl) => RLO,
+    };
+
+    let mut s = String::with_capacity(text.len() + 2 * start.len_utf8());
+    s.push(start);
+    s.push_str(text);
+    s.push(PDF);
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::dpi::Dpi;
+    use crate::parsers::Parse;
+    use crate::properties::{ParsedProperty, SpecifiedValue, SpecifiedValues};
+    use crate::property_defs::FontSize;
+
+    #[test]
+    fn chars_are_normalized_to_nfc() {
+        let chars = Chars::new(true);
+        chars.append("cafe\u{301} n\u{303}o");
+        assert_eq!(chars.get_string(), "caf\u{e9} \u{f1}o");
+    }
+
+    #[test]
+    fn chars_normalize_combining_marks_split_across_appends() {
+        let chars = Chars::new(true);
+        chars.append("e");
+        chars.append("\u{301}");
+        assert_eq!(chars.get_string(), "\u{e9}");
+    }
+
+    #[test]
+    fn chars_only_renormalize_from_the_last_starter() {
+        let chars = Chars::new(true);
+        chars.append("cafe");
+        chars.append("\u{327}");
+        chars.append("\u{301} \u{1100}");
+        chars.append("\u{1161}\u{11a8}");
+        assert_eq!(chars.get_string(), "caf\u{229}\u{301} \u{ac01}");
+    }
+
+    #[test]
+    fn chars_can_keep_original_normalization_form() {
+        let chars = Chars::new(false);
+        chars.append("e\u{301}");
+        assert_eq!(chars.get_string(), "e\u{301}");
+    }
+
+    fn measure(text: &str, font_size: &str) -> TextMetrics {
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+        let cr = cairo::Context::new(&surface);
+
+        let draw_ctx = DrawingCtx::new(
+            None,
+            &cr,
+            Rect::from_size(100.0, 100.0),
+            Dpi::new(96.0, 96.0),
+            false,
+            true,
+            RenderMode::Rasterize,
+        );
+
+        let mut specified = SpecifiedValues::default();
+        specified.set_parsed_property(&ParsedProperty::FontSize(SpecifiedValue::Specified(
+            FontSize::parse_str(font_size).unwrap(),
+        )));
+
+        let mut values = ComputedValues::default();
+        specified.to_computed_values(&mut values);
+
+        measure_text(&draw_ctx, text, &values)
+    }
+
+    fn assert_within_5_percent(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= expected * 0.05,
+            "{} is not within 5% of {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn measured_width_is_proportional_to_text_length() {
+        let short = measure("mm", "20px");
+        let long = measure("mmmm", "20px");
+
+        assert!(short.width > 0.0);
+        assert_within_5_percent(long.width, 2.0 * short.width);
+    }
+
+    #[test]
+    fn measured_width_is_proportional_to_font_size() {
+        let small = measure("Hello", "20px");
+        let large = measure("Hello", "40px");
+
+        assert!(small.width > 0.0);
+        assert_within_5_percent(large.width, 2.0 * small.width);
+        assert_within_5_percent(large.ascent, 2.0 * small.ascent);
+    }
+
+    #[test]
+    fn ascent_and_descent_add_up_to_height() {
+        let metrics = measure("Hello", "20px");
+
+        assert!(metrics.ascent > 0.0);
+        assert!(metrics.descent > 0.0);
+        assert_eq!(metrics.ascent + metrics.descent, metrics.height);
+    }
+
+    #[test]
+    fn ascent_does_not_depend_on_text_length() {
+        let short = measure("H", "20px");
+        let long = measure("HHHH", "20px");
+
+        assert!(short.ascent > 0.0);
+        assert_within_5_percent(long.ascent, short.ascent);
+    }
+}
@@ -1,9 +1,10 @@
 //! Type for values in the [0.0, 1.0] range.
 
-use cssparser::Parser;
+use cssparser::{Parser, ToCss};
+use std::fmt;
 
 use crate::error::*;
-use crate::parsers::Parse;
+use crate::parsers::{serialize_number, Parse};
 use crate::util;
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd)]
@@ -22,6 +23,15 @@ impl Parse for UnitInterval {
     }
 }
 
+impl ToCss for UnitInterval {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        serialize_number(self.0, dest)
+    }
+}
+
 impl From<UnitInterval> for u8 {
     fn from(val: UnitInterval) -> u8 {
         let UnitInterval(x) = val;
@@ -12,11 +12,13 @@ use std::collections::HashMap;
 use std::rc::{Rc, Weak};
 use std::str;
 use std::string::ToString;
+use url::Url;
 use xml5ever::tendril::format_tendril;
 use xml5ever::tokenizer::{TagKind, Token, TokenSink, XmlTokenizer, XmlTokenizerOpts};
 
 use crate::allowed_url::AllowedUrl;
 use crate::document::{Document, DocumentBuilder};
+use crate::element::Element;
 use crate::error::LoadingError;
 use crate::io::{self, get_input_stream_for_loading};
 use crate::limits::MAX_LOADED_ELEMENTS;
@@ -102,6 +104,10 @@ struct XmlStateInner {
     context_stack: Vec<Context>,
     current_node: Option<Node>,
 
+    /// Base URI from `xml:base` for each element being created, parallel to the
+    /// element nesting; `None` means the document's base URL is in effect.
+    xml_base_stack: Vec<Option<Url>>,
+
     entities: HashMap<String, XmlEntityPtr>,
 }
 
@@ -128,6 +134,29 @@ impl XmlStateInner {
         // We can unwrap since the stack is never empty
         self.context_stack.last().unwrap().clone()
     }
+
+    /// Computes the base URI for an element from its `xml:base` attribute, if any,
+    /// and the base URI of its parent.
+    ///
+    /// https://www.w3.org/TR/xmlbase/
+    fn xml_base(&self, pbag: &PropertyBag) -> Option<Url> {
+        let parent_base = self.xml_base_stack.last().and_then(|b| b.clone());
+
+        for (attr, value) in pbag.iter() {
+            if attr.expanded() == expanded_name!(xml "base") {
+                let base = parent_base
+                    .as_ref()
+                    .or_else(|| self.document_builder.as_ref().unwrap().base_url());
+
+                match Url::options().base_url(base).parse(value) {
+                    Ok(url) => return Some(url),
+                    Err(e) => rsvg_log!(Warn, "ignoring invalid xml:base \"{}\": {}", value, e),
+                }
+            }
+        }
+
+        parent_base
+    }
 }
 
 impl XmlState {
@@ -139,6 +168,7 @@ impl XmlState {
                 num_loaded_elements: 0,
                 context_stack: vec![Context::Start],
                 current_node: None,
+                xml_base_stack: Vec::new(),
                 entities: HashMap::new(),
             }),
 
@@ -276,12 +306,16 @@ impl XmlState {
                     .is_err()
                 {
                     rsvg_log!(
+                        Warn,
                         "invalid xml-stylesheet {} in XML processing instruction",
                         href
                     );
                 }
             } else {
-                rsvg_log!("xml-stylesheet processing instruction does not have href; ignoring");
+                rsvg_log!(
+                    Warn,
+                    "xml-stylesheet processing instruction does not have href; ignoring"
+                );
             }
         } else {
             self.error(LoadingError::XmlParseError(String::from(
@@ -319,13 +353,25 @@ impl XmlState {
         } else {
             let mut inner = self.inner.borrow_mut();
 
+            let xml_base = inner.xml_base(pbag);
+
             let parent = inner.current_node.clone();
-            let node = inner
+            let mut node = inner
                 .document_builder
                 .as_mut()
                 .unwrap()
                 .append_element(name, pbag, parent);
+
+            if let Some(ref url) = xml_base {
+                match *node.borrow_element_mut() {
+                    Element::Image(ref mut image) => image.element_impl.set_xml_base(url.clone()),
+                    Element::FeImage(ref mut image) => image.element_impl.set_xml_base(url.clone()),
+                    _ => (),
+                }
+            }
+
             inner.current_node = Some(node);
+            inner.xml_base_stack.push(xml_base);
 
             if name.expanded() == expanded_name!(svg "style") {
                 Context::Style
@@ -339,6 +385,7 @@ impl XmlState {
         let mut inner = self.inner.borrow_mut();
         let node = inner.current_node.take().unwrap();
         inner.current_node = node.parent();
+        inner.xml_base_stack.pop();
     }
 
     fn element_creation_characters(&self, text: &str) {
@@ -361,6 +408,17 @@ impl XmlState {
         let mut inner = self.inner.borrow_mut();
         let current_node = inner.current_node.as_ref().unwrap();
 
+        // A <style> with an unsupported type attribute was set in error, and a
+        // diagnostic was already emitted for it; its contents must be ignored.
+        if current_node.borrow_element().is_in_error() {
+            rsvg_log!(
+                Debug,
+                "(ignoring style element {} because it is in error)",
+                current_node
+            );
+            return;
+        }
+
         let style_type = borrow_element_as!(current_node, Style)
             .style_type()
             .unwrap_or(StyleType::TextCss);
@@ -486,7 +544,7 @@ impl XmlState {
                 .map_err(|e| {
                     // FIXME: should AlloweUrlError::HrefParseError be a fatal error,
                     // not a resource error?
-                    rsvg_log!("could not acquire \"{}\": {}", href, e);
+                    rsvg_log!(Warn, "could not acquire \"{}\": {}", href, e);
                     AcquireError::ResourceError
                 })?;
 
@@ -517,7 +575,7 @@ impl XmlState {
 
     fn acquire_text(&self, aurl: &AllowedUrl, encoding: Option<&str>) -> Result<(), AcquireError> {
         let binary = io::acquire_data(aurl, None).map_err(|e| {
-            rsvg_log!("could not acquire \"{}\": {}", aurl, e);
+            rsvg_log!(Warn, "could not acquire \"{}\": {}", aurl, e);
             AcquireError::ResourceError
         })?;
 